@@ -181,6 +181,8 @@ fn configure_validation(builder: Builder) -> Builder {
             ("SearchPointGroups.group_size", "range(min = 1)"),
             ("SearchPointGroups.limit", "range(min = 1)"),
             ("SearchPointGroups.timeout", "custom = \"crate::grpc::validate::validate_u64_range_min_1\""),
+            ("SearchPointGroups.order_by", ""),
+            ("OrderBy.key", "length(min = 1)"),
             ("SearchParams.quantization", ""),
             ("QuantizationSearchParams.oversampling", "custom = \"crate::grpc::validate::validate_f64_range_min_1\""),
             ("ScrollPoints.collection_name", "length(min = 1, max = 255)"),
@@ -204,6 +206,7 @@ fn configure_validation(builder: Builder) -> Builder {
             ("RecommendPointGroups.timeout", "custom = \"crate::grpc::validate::validate_u64_range_min_1\""),
             ("RecommendPointGroups.positive_vectors", ""),
             ("RecommendPointGroups.negative_vectors", ""),
+            ("RecommendPointGroups.order_by", ""),
             ("DiscoverPoints.collection_name", "length(min = 1, max = 255)"),
             ("DiscoverPoints.filter", ""),
             ("DiscoverPoints.params", ""),
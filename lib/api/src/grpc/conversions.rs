@@ -519,6 +519,16 @@ impl From<segment::data_types::groups::GroupId> for GroupId {
             segment::data_types::groups::GroupId::NumberI64(n) => Self {
                 kind: Some(crate::grpc::qdrant::group_id::Kind::IntegerValue(n)),
             },
+            segment::data_types::groups::GroupId::Bool(b) => Self {
+                kind: Some(crate::grpc::qdrant::group_id::Kind::BoolValue(b)),
+            },
+            segment::data_types::groups::GroupId::Composite(keys) => Self {
+                kind: Some(crate::grpc::qdrant::group_id::Kind::CompositeValue(
+                    crate::grpc::qdrant::CompositeGroupId {
+                        values: keys.into_iter().map(Into::into).collect(),
+                    },
+                )),
+            },
         }
     }
 }
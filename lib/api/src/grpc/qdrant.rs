@@ -3685,6 +3685,27 @@ pub struct WithLookup {
     #[prost(message, optional, tag = "3")]
     pub with_vectors: ::core::option::Option<WithVectorsSelector>,
 }
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WithGroupStats {
+    /// Numeric payload field to sum over the hits of each group
+    #[prost(string, optional, tag = "1")]
+    pub sum: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(validator::Validate)]
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OrderBy {
+    /// Payload field to order by, numbers and strings are ordered by their value. Hits without the field come last
+    #[prost(string, tag = "1")]
+    #[validate(length(min = 1))]
+    pub key: ::prost::alloc::string::String,
+    /// Direction of the order, ascending by default
+    #[prost(enumeration = "Direction", optional, tag = "2")]
+    pub direction: ::core::option::Option<i32>,
+}
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -3744,6 +3765,28 @@ pub struct SearchPointGroups {
     pub shard_key_selector: ::core::option::Option<ShardKeySelector>,
     #[prost(message, optional, tag = "16")]
     pub sparse_indices: ::core::option::Option<SparseIndices>,
+    /// Further payload fields to group by along with group_by. Points are grouped by combinations of values of all the fields, the group id is a composite of the values in the order of the fields
+    #[prost(string, repeated, tag = "17")]
+    pub additional_group_by: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Compute statistics of the hits of each group
+    #[prost(message, optional, tag = "18")]
+    pub with_stats: ::core::option::Option<WithGroupStats>,
+    /// Amount of groups to skip, to page through the groups. Groups of the same best score are ordered by their id
+    #[prost(uint32, optional, tag = "19")]
+    pub offset: ::core::option::Option<u32>,
+    /// Order hits within each group by a payload field instead of their score
+    #[prost(message, optional, tag = "20")]
+    #[validate]
+    pub order_by: ::core::option::Option<OrderBy>,
+    /// Collect points without a value of the group_by field, or with a null value, into a group with the `__missing__` id instead of leaving them out
+    #[prost(bool, optional, tag = "21")]
+    pub with_missing: ::core::option::Option<bool>,
+    /// Options for specifying how to use the group id to lookup points in each of multiple other collections, the points are returned in lookups. Can't be set along with with_lookup
+    #[prost(message, repeated, tag = "22")]
+    pub with_lookups: ::prost::alloc::vec::Vec<WithLookup>,
+    /// Keep only as many groups and hits as requested while grouping, instead of every group found by the search. Uses less memory when grouping by a field with many distinct values, but hits found before their group is kept are dropped, with_stats only covers the kept hits, and order_by only orders them
+    #[prost(bool, optional, tag = "23")]
+    pub approximate: ::core::option::Option<bool>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -3950,6 +3993,28 @@ pub struct RecommendPointGroups {
     /// Specify in which shards to look for the points, if not specified - look in all shards
     #[prost(message, optional, tag = "21")]
     pub shard_key_selector: ::core::option::Option<ShardKeySelector>,
+    /// Further payload fields to group by along with group_by. Points are grouped by combinations of values of all the fields, the group id is a composite of the values in the order of the fields
+    #[prost(string, repeated, tag = "22")]
+    pub additional_group_by: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Compute statistics of the hits of each group
+    #[prost(message, optional, tag = "23")]
+    pub with_stats: ::core::option::Option<WithGroupStats>,
+    /// Amount of groups to skip, to page through the groups. Groups of the same best score are ordered by their id
+    #[prost(uint32, optional, tag = "24")]
+    pub offset: ::core::option::Option<u32>,
+    /// Order hits within each group by a payload field instead of their score
+    #[prost(message, optional, tag = "25")]
+    #[validate]
+    pub order_by: ::core::option::Option<OrderBy>,
+    /// Collect points without a value of the group_by field, or with a null value, into a group with the `__missing__` id instead of leaving them out
+    #[prost(bool, optional, tag = "26")]
+    pub with_missing: ::core::option::Option<bool>,
+    /// Options for specifying how to use the group id to lookup points in each of multiple other collections, the points are returned in lookups. Can't be set along with with_lookup
+    #[prost(message, repeated, tag = "27")]
+    pub with_lookups: ::prost::alloc::vec::Vec<WithLookup>,
+    /// Keep only as many groups and hits as requested while grouping, instead of every group found by the search. Uses less memory when grouping by a field with many distinct values, but hits found before their group is kept are dropped, with_stats only covers the kept hits, and order_by only orders them
+    #[prost(bool, optional, tag = "28")]
+    pub approximate: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -4284,7 +4349,7 @@ pub struct ScoredPoint {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GroupId {
-    #[prost(oneof = "group_id::Kind", tags = "1, 2, 3")]
+    #[prost(oneof = "group_id::Kind", tags = "1, 2, 3, 4, 5")]
     pub kind: ::core::option::Option<group_id::Kind>,
 }
 /// Nested message and enum types in `GroupId`.
@@ -4302,11 +4367,45 @@ pub mod group_id {
         /// Represents a string value.
         #[prost(string, tag = "3")]
         StringValue(::prost::alloc::string::String),
+        /// Represents a boolean value.
+        #[prost(bool, tag = "4")]
+        BoolValue(bool),
+        /// Represents values of multiple group_by fields.
+        #[prost(message, tag = "5")]
+        CompositeValue(super::CompositeGroupId),
     }
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CompositeGroupId {
+    /// Values of each group_by field, in the order of the fields
+    #[prost(message, repeated, tag = "1")]
+    pub values: ::prost::alloc::vec::Vec<GroupId>,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupStats {
+    /// Number of hits of the group found by the search, there may be more of them than the returned hits
+    #[prost(uint64, tag = "1")]
+    pub hit_count: u64,
+    /// Lowest score of the hits
+    #[prost(float, tag = "2")]
+    pub min_score: f32,
+    /// Highest score of the hits
+    #[prost(float, tag = "3")]
+    pub max_score: f32,
+    /// Average score of the hits
+    #[prost(float, tag = "4")]
+    pub avg_score: f32,
+    /// Sum of the numeric values of the summed payload field over the hits
+    #[prost(double, optional, tag = "5")]
+    pub sum: ::core::option::Option<f64>,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PointGroup {
     /// Group id
     #[prost(message, optional, tag = "1")]
@@ -4317,6 +4416,15 @@ pub struct PointGroup {
     /// Point(s) from the lookup collection that matches the group id
     #[prost(message, optional, tag = "3")]
     pub lookup: ::core::option::Option<RetrievedPoint>,
+    /// Statistics of the hits of the group, if requested
+    #[prost(message, optional, tag = "4")]
+    pub stats: ::core::option::Option<GroupStats>,
+    /// Points from each of the lookup collections that match the group id, by the name of the collection
+    #[prost(map = "string, message", tag = "5")]
+    pub lookups: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        RetrievedPoint,
+    >,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -4861,6 +4969,33 @@ impl FieldType {
         }
     }
 }
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Direction {
+    Asc = 0,
+    Desc = 1,
+}
+impl Direction {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Direction::Asc => "Asc",
+            Direction::Desc => "Desc",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Asc" => Some(Self::Asc),
+            "Desc" => Some(Self::Desc),
+            _ => None,
+        }
+    }
+}
 /// How to use positive and negative vectors to find the results, default is `AverageVector`:
 #[derive(serde::Serialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
@@ -11294,6 +11429,29 @@ pub struct HealthCheckReply {
     #[prost(string, tag = "2")]
     pub version: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetLoggerRequest {}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateLoggerRequest {
+    /// Partial logger configuration as JSON object, e.g. `{"log_level": "DEBUG"}`
+    #[prost(string, tag = "1")]
+    pub config: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LoggerReply {
+    /// Logger configuration as JSON object
+    #[prost(string, tag = "1")]
+    pub config: ::prost::alloc::string::String,
+    /// Time spent to process
+    #[prost(double, tag = "2")]
+    pub time: f64,
+}
 /// Generated client implementations.
 pub mod qdrant_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -11403,6 +11561,52 @@ pub mod qdrant_client {
             req.extensions_mut().insert(GrpcMethod::new("qdrant.Qdrant", "HealthCheck"));
             self.inner.unary(req, path, codec).await
         }
+        ///
+        /// Get configuration of the logger
+        pub async fn get_logger(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetLoggerRequest>,
+        ) -> std::result::Result<tonic::Response<super::LoggerReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Qdrant/GetLogger",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("qdrant.Qdrant", "GetLogger"));
+            self.inner.unary(req, path, codec).await
+        }
+        ///
+        /// Update configuration of the logger, only the fields which are set are applied
+        pub async fn update_logger(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateLoggerRequest>,
+        ) -> std::result::Result<tonic::Response<super::LoggerReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Qdrant/UpdateLogger",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("qdrant.Qdrant", "UpdateLogger"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -11419,6 +11623,18 @@ pub mod qdrant_server {
             tonic::Response<super::HealthCheckReply>,
             tonic::Status,
         >;
+        ///
+        /// Get configuration of the logger
+        async fn get_logger(
+            &self,
+            request: tonic::Request<super::GetLoggerRequest>,
+        ) -> std::result::Result<tonic::Response<super::LoggerReply>, tonic::Status>;
+        ///
+        /// Update configuration of the logger, only the fields which are set are applied
+        async fn update_logger(
+            &self,
+            request: tonic::Request<super::UpdateLoggerRequest>,
+        ) -> std::result::Result<tonic::Response<super::LoggerReply>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct QdrantServer<T: Qdrant> {
@@ -11545,6 +11761,98 @@ pub mod qdrant_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.Qdrant/GetLogger" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetLoggerSvc<T: Qdrant>(pub Arc<T>);
+                    impl<
+                        T: Qdrant,
+                    > tonic::server::UnaryService<super::GetLoggerRequest>
+                    for GetLoggerSvc<T> {
+                        type Response = super::LoggerReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetLoggerRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Qdrant>::get_logger(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetLoggerSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/qdrant.Qdrant/UpdateLogger" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateLoggerSvc<T: Qdrant>(pub Arc<T>);
+                    impl<
+                        T: Qdrant,
+                    > tonic::server::UnaryService<super::UpdateLoggerRequest>
+                    for UpdateLoggerSvc<T> {
+                        type Response = super::LoggerReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateLoggerRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Qdrant>::update_logger(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateLoggerSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -0,0 +1,62 @@
+//! Id of the API request being served.
+//!
+//! The id is taken from the `x-request-id` header of the request, or generated if there is
+//! none, and kept in a task local while the request is served. Internal gRPC requests to other
+//! peers carry it in their metadata, see [`crate::grpc::transport_channel_pool::AddTimeout`],
+//! so the logs of all peers can be correlated by it.
+
+use std::future::Future;
+
+use tokio::task::futures::TaskLocalFuture;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longer ids from clients are replaced with generated ones
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Serve `future` as the request with `request_id`
+pub fn scope<F: Future>(request_id: String, future: F) -> TaskLocalFuture<String, F> {
+    REQUEST_ID.scope(request_id, future)
+}
+
+/// Id of the request served by the current task
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Id from the header of the request if it's valid, a new one otherwise
+pub fn from_header(header: Option<&[u8]>) -> String {
+    header
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .filter(|id| is_valid(id))
+        .map_or_else(generate, str::to_string)
+}
+
+fn generate() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Ids are written into logs and headers as is, only allow visible ASCII characters
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_id() {
+        assert_eq!(from_header(Some(b"abc-123")), "abc-123");
+        assert_ne!(from_header(Some(b"with space")), "with space");
+        assert_eq!(from_header(None).len(), 36);
+
+        assert_eq!(current(), None);
+        let id = scope("abc".to_string(), async { current() }).await;
+        assert_eq!(id.as_deref(), Some("abc"));
+    }
+}
@@ -14,6 +14,7 @@ use crate::grpc::dynamic_channel_pool::DynamicChannelPool;
 use crate::grpc::dynamic_pool::CountedItem;
 use crate::grpc::qdrant::qdrant_client::QdrantClient;
 use crate::grpc::qdrant::HealthCheckRequest;
+use crate::grpc::request_id;
 
 /// Maximum lifetime of a gRPC channel.
 ///
@@ -73,6 +74,7 @@ enum RequestFailure {
 }
 
 /// Intercepts gRPC requests and adds a default timeout if it wasn't already set.
+/// Also passes on the id of the API request being served, see [`request_id`].
 pub struct AddTimeout {
     default_timeout: Duration,
 }
@@ -88,6 +90,13 @@ impl Interceptor for AddTimeout {
         if request.metadata().get("grpc-timeout").is_none() {
             request.set_timeout(self.default_timeout);
         }
+        if let Some(request_id) = request_id::current() {
+            if let Ok(request_id) = request_id.parse() {
+                request
+                    .metadata_mut()
+                    .insert(request_id::REQUEST_ID_HEADER, request_id);
+            }
+        }
         Ok(request)
     }
 }
@@ -85,6 +85,7 @@ fn batch_search_bench(c: &mut Criterion) {
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
+        search_defaults: None,
     };
 
     let shared_config = Arc::new(RwLock::new(collection_config));
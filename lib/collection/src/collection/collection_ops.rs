@@ -5,6 +5,7 @@ use futures::{future, TryStreamExt as _};
 use segment::types::QuantizationConfig;
 
 use super::Collection;
+use crate::config::SearchDefaults;
 use crate::operations::config_diff::*;
 use crate::operations::shard_selector_internal::ShardSelectorInternal;
 use crate::operations::types::*;
@@ -153,6 +154,23 @@ impl Collection {
         Ok(())
     }
 
+    /// Updates search defaults:
+    /// Saves new params on disk
+    ///
+    /// Fields not specified in the diff keep their current values.
+    pub async fn update_search_defaults_from_diff(
+        &self,
+        search_defaults_diff: SearchDefaults,
+    ) -> CollectionResult<()> {
+        {
+            let mut config = self.collection_config.write().await;
+            let search_defaults = config.search_defaults.unwrap_or_default();
+            config.search_defaults = Some(search_defaults_diff.update(&search_defaults)?);
+        }
+        self.collection_config.read().await.save(&self.path)?;
+        Ok(())
+    }
+
     /// Handle replica changes
     ///
     /// add and remove replicas from replica set
@@ -6,6 +6,7 @@ mod shard_transfer;
 mod sharding_keys;
 mod snapshots;
 mod state_management;
+mod update_batcher;
 
 use std::collections::HashSet;
 use std::ops::Deref;
@@ -20,6 +21,7 @@ use tokio::runtime::Handle;
 use tokio::sync::{Mutex, RwLock, RwLockWriteGuard};
 
 use crate::collection::payload_index_schema::PayloadIndexSchema;
+use crate::collection::update_batcher::UpdateBatcher;
 use crate::collection_state::{ShardInfo, State};
 use crate::common::is_ready::IsReady;
 use crate::config::CollectionConfig;
@@ -62,6 +64,8 @@ pub struct Collection {
     // Lock is acquired for read on update operation and can be acquired for write externally,
     // which will block all update operations until the lock is released.
     updates_lock: RwLock<()>,
+    // Pending upserts to apply together, if update batching is enabled.
+    update_batcher: UpdateBatcher,
     // Update runtime handle.
     update_runtime: Handle,
     // Search runtime handle.
@@ -144,6 +148,7 @@ impl Collection {
             init_time: start_time.elapsed(),
             is_initialized: Arc::new(Default::default()),
             updates_lock: RwLock::new(()),
+            update_batcher: Default::default(),
             update_runtime: update_runtime.unwrap_or_else(Handle::current),
             search_runtime: search_runtime.unwrap_or_else(Handle::current),
         })
@@ -238,6 +243,7 @@ impl Collection {
             init_time: start_time.elapsed(),
             is_initialized: Arc::new(Default::default()),
             updates_lock: RwLock::new(()),
+            update_batcher: Default::default(),
             update_runtime: update_runtime.unwrap_or_else(Handle::current),
             search_runtime: search_runtime.unwrap_or_else(Handle::current),
         }
@@ -266,6 +272,38 @@ impl Collection {
         true
     }
 
+    /// Check version of a stored collection without loading it.
+    ///
+    /// Returns `true` if the collection is migrated to the current version on load.
+    pub fn check_stored_version(path: &Path) -> CollectionResult<bool> {
+        let stored_version: Version = CollectionVersion::load(path)?.parse().map_err(|err| {
+            CollectionError::service_error(format!("Can't parse stored collection version: {err}"))
+        })?;
+        let app_version: Version = CollectionVersion::current()
+            .parse()
+            .expect("Failed to parse current collection version as semver");
+
+        if stored_version > app_version {
+            return Err(CollectionError::service_error(format!(
+                "Collection version {stored_version} is greater than application version {app_version}"
+            )));
+        }
+        if stored_version == app_version {
+            return Ok(false);
+        }
+        if !Self::can_upgrade_storage(&stored_version, &app_version) {
+            return Err(CollectionError::service_error(format!(
+                "Cannot upgrade version {stored_version} to {app_version}"
+            )));
+        }
+        Ok(true)
+    }
+
+    /// Time it took to load the collection
+    pub fn init_time(&self) -> Duration {
+        self.init_time
+    }
+
     pub fn name(&self) -> String {
         self.id.clone()
     }
@@ -594,6 +632,29 @@ impl Collection {
         self.updates_lock.write().await
     }
 
+    /// Persist WAL and segments of all local shards to disk
+    ///
+    /// Updates are blocked during the flush, so persisted state includes every acknowledged operation.
+    pub async fn flush(&self) -> CollectionResult<()> {
+        let _updates_guard = self.lock_updates().await;
+        let shards_holder = self.shards_holder.read().await;
+        for shard in shards_holder.all_shards() {
+            shard.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Flush all shards, and drop applied operations from their WAL, so they are not replayed
+    /// when the collection is loaded again
+    pub async fn checkpoint(&self) -> CollectionResult<()> {
+        let _updates_guard = self.lock_updates().await;
+        let shards_holder = self.shards_holder.read().await;
+        for shard in shards_holder.all_shards() {
+            shard.checkpoint().await?;
+        }
+        Ok(())
+    }
+
     pub fn wait_collection_initiated(&self, timeout: Duration) -> bool {
         self.is_initialized.await_ready_for_timeout(timeout)
     }
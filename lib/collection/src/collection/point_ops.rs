@@ -7,7 +7,7 @@ use validator::Validate as _;
 
 use super::Collection;
 use crate::operations::consistency_params::ReadConsistency;
-use crate::operations::point_ops::WriteOrdering;
+use crate::operations::point_ops::{PointInsertOperationsInternal, PointOperations, WriteOrdering};
 use crate::operations::shard_selector_internal::ShardSelectorInternal;
 use crate::operations::types::*;
 use crate::operations::CollectionUpdateOperations;
@@ -88,6 +88,31 @@ impl Collection {
         shard_keys_selection: Option<ShardKey>,
     ) -> CollectionResult<UpdateResult> {
         operation.validate()?;
+
+        // Only plain upserts are batched. Their vectors are checked before merging, so an
+        // invalid upsert doesn't fail the others.
+        if let Some(batching) = self.shared_storage_config.update_batching {
+            if shard_keys_selection.is_none() && matches!(ordering, WriteOrdering::Weak) {
+                if let CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                    PointInsertOperationsInternal::PointsList(points),
+                )) = operation
+                {
+                    return self.update_batched(points, wait, batching).await;
+                }
+            }
+        }
+
+        self.update_from_client_unbatched(operation, wait, ordering, shard_keys_selection)
+            .await
+    }
+
+    pub(super) async fn update_from_client_unbatched(
+        &self,
+        operation: CollectionUpdateOperations,
+        wait: bool,
+        ordering: WriteOrdering,
+        shard_keys_selection: Option<ShardKey>,
+    ) -> CollectionResult<UpdateResult> {
         let _update_lock = self.updates_lock.read().await;
 
         let mut results = {
@@ -117,6 +117,22 @@ impl Collection {
         }
     }
 
+    /// Fill parameters omitted by search requests with search defaults of the collection
+    async fn apply_search_defaults(
+        &self,
+        mut request: CoreSearchRequestBatch,
+        timeout: Option<Duration>,
+    ) -> (CoreSearchRequestBatch, Option<Duration>) {
+        let Some(search_defaults) = self.collection_config.read().await.search_defaults else {
+            return (request, timeout);
+        };
+
+        for search in &mut request.searches {
+            search_defaults.apply(search);
+        }
+        (request, timeout.or_else(|| search_defaults.timeout()))
+    }
+
     async fn do_core_search_batch(
         &self,
         request: CoreSearchRequestBatch,
@@ -124,6 +140,7 @@ impl Collection {
         shard_selection: &ShardSelectorInternal,
         timeout: Option<Duration>,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        let (request, timeout) = self.apply_search_defaults(request, timeout).await;
         let request = Arc::new(request);
 
         // query all shards concurrently
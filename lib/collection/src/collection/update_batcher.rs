@@ -0,0 +1,195 @@
+//! Coalescing of upserts arriving in a burst.
+//!
+//! Upserts of points lists arriving within a short window are merged into a single operation,
+//! so a burst of small writes costs one WAL append and one pass over segments. Vectors of each
+//! upsert are checked against the collection config before merging, so an invalid upsert fails
+//! alone. Every caller of a merged operation receives its result, and a batch is discarded
+//! once all of its callers are gone, as a single cancelled upsert would be.
+//!
+//! With the collection write queue of the service enabled, updates of a collection arrive one
+//! at a time, so there would be nothing to merge: batching is disabled by the service then.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+use segment::common::check_named_vectors;
+use segment::types::{PayloadStorageType, SegmentConfig};
+use tokio::sync::watch;
+
+use super::Collection;
+use crate::operations::point_ops::{
+    PointInsertOperationsInternal, PointOperations, PointStruct, WriteOrdering,
+};
+use crate::operations::shared_storage_config::UpdateBatchingConfig;
+use crate::operations::types::{CollectionError, CollectionResult, UpdateResult};
+use crate::operations::CollectionUpdateOperations;
+
+type BatchResult = Option<CollectionResult<UpdateResult>>;
+
+#[derive(Default)]
+pub(super) struct UpdateBatcher {
+    pending: Mutex<Option<PendingBatch>>,
+    next_batch_id: AtomicU64,
+}
+
+struct PendingBatch {
+    id: u64,
+    points: Vec<PointStruct>,
+    operations: usize,
+    wait: bool,
+    result: watch::Sender<BatchResult>,
+}
+
+/// Membership of a caller in a pending batch, the batch is discarded once all tickets are dropped
+struct BatchTicket<'a> {
+    batcher: &'a UpdateBatcher,
+    batch_id: u64,
+    is_full: bool,
+    result: watch::Receiver<BatchResult>,
+}
+
+impl UpdateBatcher {
+    /// Add `points` to the pending batch, or start a new batch if there is none
+    fn add(&self, points: Vec<PointStruct>, wait: bool, max_operations: usize) -> BatchTicket<'_> {
+        let mut pending = self.pending.lock();
+        let batch = pending.get_or_insert_with(|| PendingBatch {
+            id: self.next_batch_id.fetch_add(1, Ordering::Relaxed),
+            points: Vec::new(),
+            operations: 0,
+            wait: false,
+            result: watch::channel(None).0,
+        });
+
+        batch.points.extend(points);
+        batch.operations += 1;
+        batch.wait |= wait;
+
+        BatchTicket {
+            batcher: self,
+            batch_id: batch.id,
+            is_full: batch.operations >= max_operations,
+            result: batch.result.subscribe(),
+        }
+    }
+
+    /// Take the batch of the `ticket` for applying, `None` if it is taken already
+    fn take(&self, ticket: &BatchTicket) -> Option<PendingBatch> {
+        let mut pending = self.pending.lock();
+        if pending.as_ref()?.id == ticket.batch_id {
+            pending.take()
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for BatchTicket<'_> {
+    fn drop(&mut self) {
+        let mut pending = self.batcher.pending.lock();
+        // The receiver of this ticket is still alive, so the last one counts as one
+        let is_abandoned = pending.as_ref().map_or(false, |batch| {
+            batch.id == self.batch_id && batch.result.receiver_count() == 1
+        });
+        if is_abandoned {
+            pending.take();
+        }
+    }
+}
+
+impl Collection {
+    /// Apply upsert of `points` together with other upserts arriving within the batching window
+    pub(super) async fn update_batched(
+        &self,
+        points: Vec<PointStruct>,
+        wait: bool,
+        config: UpdateBatchingConfig,
+    ) -> CollectionResult<UpdateResult> {
+        self.check_batched_points(&points).await?;
+        let mut ticket = self.update_batcher.add(points, wait, config.max_operations);
+
+        // Wait for more upserts, unless the batch gets full and is applied by another caller
+        if !ticket.is_full {
+            let applied =
+                tokio::time::timeout(config.window, ticket.result.wait_for(Option::is_some)).await;
+            if let Ok(result) = applied {
+                return batch_result(result);
+            }
+        }
+
+        // First caller to get here applies the batch, even if the one who started it is gone
+        if let Some(batch) = self.update_batcher.take(&ticket) {
+            let operation =
+                CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                    PointInsertOperationsInternal::PointsList(batch.points),
+                ));
+            let result = self
+                .update_from_client_unbatched(operation, batch.wait, WriteOrdering::Weak, None)
+                .await;
+            let _ = batch.result.send(Some(result.clone()));
+            return result;
+        }
+
+        batch_result(ticket.result.wait_for(Option::is_some).await)
+    }
+
+    /// Check vectors of `points` the same way segments do on upsert, so they can't fail the
+    /// batch they are merged into
+    async fn check_batched_points(&self, points: &[PointStruct]) -> CollectionResult<()> {
+        let segment_config = {
+            let config = self.collection_config.read().await;
+            SegmentConfig {
+                vector_data: config.params.into_base_vector_data()?,
+                sparse_vector_data: config.params.into_sparse_vector_data()?,
+                payload_storage_type: PayloadStorageType::InMemory,
+            }
+        };
+        for point in points {
+            check_named_vectors(&point.get_vectors(), &segment_config)?;
+        }
+        Ok(())
+    }
+}
+
+fn batch_result(
+    result: Result<watch::Ref<'_, BatchResult>, watch::error::RecvError>,
+) -> CollectionResult<UpdateResult> {
+    match result {
+        Ok(result) => result.clone().unwrap(), // `wait_for` returns only once result is set
+        Err(_) => Err(CollectionError::service_error(
+            "Batched update was cancelled before it was applied",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abandoned_batch_is_discarded() {
+        let batcher = UpdateBatcher::default();
+        let point = || PointStruct {
+            id: 1.into(),
+            vector: vec![1.0].into(),
+            payload: None,
+        };
+
+        let first = batcher.add(vec![point()], false, 10);
+        let second = batcher.add(vec![point()], false, 10);
+        assert_eq!(first.batch_id, second.batch_id);
+
+        // The batch is pending as long as one of its callers waits for it
+        drop(first);
+        assert!(batcher.pending.lock().is_some());
+        drop(second);
+        assert!(batcher.pending.lock().is_none());
+
+        // Applied batches are not affected
+        let ticket = batcher.add(vec![point()], false, 10);
+        let batch = batcher.take(&ticket).unwrap();
+        let next = batcher.add(vec![point()], false, 10);
+        drop(ticket);
+        assert_eq!(batcher.pending.lock().as_ref().unwrap().id, next.batch_id);
+        assert_eq!(batch.points.len(), 1);
+    }
+}
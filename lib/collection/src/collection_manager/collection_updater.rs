@@ -42,6 +42,10 @@ impl CollectionUpdater {
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "wal_apply", level = "debug", skip_all)
+    )]
     pub fn update(
         segments: &RwLock<SegmentHolder>,
         op_num: SeqNumberType,
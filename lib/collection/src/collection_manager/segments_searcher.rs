@@ -416,6 +416,10 @@ fn effective_limit(limit: usize, ef_limit: usize, poisson_sampling: usize) -> us
 /// Collection Result of:
 /// * Vector of ScoredPoints for each request in the batch
 /// * Vector of boolean indicating if the segment have further points to search
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "segment_search", level = "debug", skip_all)
+)]
 fn search_in_segment(
     segment: LockedSegment,
     request: Arc<CoreSearchRequestBatch>,
@@ -3,16 +3,18 @@ use std::fs::File;
 use std::io::{Read, Write};
 use std::num::NonZeroU32;
 use std::path::Path;
+use std::time::Duration;
 
 use atomicwrites::AtomicFile;
 use atomicwrites::OverwriteBehavior::AllowOverwrite;
+use merge::Merge;
 use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use segment::data_types::vectors::DEFAULT_VECTOR_NAME;
 use segment::index::sparse_index::sparse_index_config::{SparseIndexConfig, SparseIndexType};
 use segment::types::{
-    Distance, HnswConfig, Indexes, QuantizationConfig, SparseVectorDataConfig, VectorDataConfig,
-    VectorStorageType,
+    Distance, HnswConfig, Indexes, QuantizationConfig, ScoreType, SearchParams,
+    SparseVectorDataConfig, VectorDataConfig, VectorStorageType,
 };
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -20,8 +22,8 @@ use wal::WalOptions;
 
 use crate::operations::config_diff::{DiffConfig, QuantizationConfigDiff};
 use crate::operations::types::{
-    CollectionError, CollectionResult, SparseVectorParams, SparseVectorsConfig, VectorParams,
-    VectorParamsDiff, VectorsConfig, VectorsConfigDiff,
+    CollectionError, CollectionResult, CoreSearchRequest, SparseVectorParams, SparseVectorsConfig,
+    VectorParams, VectorParamsDiff, VectorsConfig, VectorsConfigDiff,
 };
 use crate::operations::validation;
 use crate::optimizers_builder::OptimizersConfig;
@@ -149,8 +151,81 @@ pub struct CollectionConfig {
     pub wal_config: WalConfig,
     #[serde(default)]
     pub quantization_config: Option<QuantizationConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate]
+    pub search_defaults: Option<SearchDefaults>,
 }
 
+/// Search parameters used for requests to the collection which don't specify them
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Validate, Clone, Copy, Merge)]
+#[serde(rename_all = "snake_case")]
+pub struct SearchDefaults {
+    /// Size of the beam in a beam-search, if not specified in search params of the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1))]
+    pub hnsw_ef: Option<usize>,
+    /// Search without approximation, if the request has no search params
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exact: Option<bool>,
+    /// Minimal score of returned points, if not specified in the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_threshold: Option<ScoreType>,
+    /// Timeout of search requests in seconds, if not specified in the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1))]
+    pub timeout: Option<u64>,
+}
+
+impl SearchDefaults {
+    /// Fill parameters omitted by the `request` with defaults
+    pub fn apply(&self, request: &mut CoreSearchRequest) {
+        if request.score_threshold.is_none() {
+            request.score_threshold = self.score_threshold;
+        }
+
+        match &mut request.params {
+            Some(params) => {
+                if params.hnsw_ef.is_none() {
+                    params.hnsw_ef = self.hnsw_ef;
+                }
+            }
+            None if self.hnsw_ef.is_some() || self.exact.is_some() => {
+                request.params = Some(SearchParams {
+                    hnsw_ef: self.hnsw_ef,
+                    exact: self.exact.unwrap_or_default(),
+                    ..Default::default()
+                });
+            }
+            None => {}
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_secs)
+    }
+}
+
+impl std::hash::Hash for SearchDefaults {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.hnsw_ef.hash(state);
+        self.exact.hash(state);
+        self.score_threshold.map(f32::to_le_bytes).hash(state);
+        self.timeout.hash(state);
+    }
+}
+
+impl PartialEq for SearchDefaults {
+    fn eq(&self, other: &Self) -> bool {
+        self.hnsw_ef == other.hnsw_ef
+            && self.exact == other.exact
+            && self.score_threshold.map(f32::to_le_bytes)
+                == other.score_threshold.map(f32::to_le_bytes)
+            && self.timeout == other.timeout
+    }
+}
+
+impl Eq for SearchDefaults {}
+
 impl CollectionConfig {
     pub fn save(&self, path: &Path) -> CollectionResult<()> {
         let config_path = path.join(COLLECTION_CONFIG_FILE);
@@ -1,31 +1,49 @@
+use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
 use common::types::ScoreType;
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 use segment::data_types::groups::GroupId;
 use segment::spaces::tools::{peek_top_largest_iterable, peek_top_smallest_iterable};
-use segment::types::{ExtendedPointId, Order, PayloadContainer, PointIdType, ScoredPoint};
+use segment::types::{ExtendedPointId, Order, Payload, PayloadContainer, PointIdType, ScoredPoint};
 use serde_json::Value;
 
 use super::types::AggregatorError::{self, *};
-use super::types::Group;
+use super::types::{Group, MISSING_GROUP_ID};
+use crate::operations::types::{Direction, GroupStats, OrderBy, WithGroupStats};
 
 type Hits = HashMap<PointIdType, ScoredPoint>;
 pub(super) struct GroupsAggregator {
     groups: HashMap<GroupId, Hits>,
     max_group_size: usize,
-    grouped_by: String,
+    grouped_by: Vec<String>,
     max_groups: usize,
     full_groups: HashSet<GroupId>,
     group_best_scores: HashMap<GroupId, ScoreType>,
     all_ids: HashSet<ExtendedPointId>,
     order: Order,
+    with_stats: Option<WithGroupStats>,
+    order_by: Option<OrderBy>,
+    with_missing: bool,
+    /// Keep at most `max_groups` groups of at most `max_group_size` hits, see `with_approximate`
+    approximate: bool,
+    /// Ranks of the best scores of the groups with their keys, the worst group on top. Entries of
+    /// groups whose best score improved since, or which were evicted, are stale and skipped.
+    worst_groups: BinaryHeap<(OrderedFloat<ScoreType>, GroupId)>,
+    /// Number of groups evicted, and of hits dropped, to keep within the bounds
+    spilled_groups: usize,
+    spilled_hits: usize,
 }
 
 impl GroupsAggregator {
-    pub(super) fn new(groups: usize, group_size: usize, grouped_by: String, order: Order) -> Self {
+    pub(super) fn new(
+        groups: usize,
+        group_size: usize,
+        grouped_by: Vec<String>,
+        order: Order,
+    ) -> Self {
         Self {
             groups: HashMap::with_capacity(groups),
             max_group_size: group_size,
@@ -35,41 +53,92 @@ impl GroupsAggregator {
             group_best_scores: HashMap::with_capacity(groups),
             all_ids: HashSet::with_capacity(groups * group_size),
             order,
+            with_stats: None,
+            order_by: None,
+            with_missing: false,
+            approximate: false,
+            worst_groups: BinaryHeap::new(),
+            spilled_groups: 0,
+            spilled_hits: 0,
         }
     }
 
+    /// Compute statistics of the groups when distilling them
+    pub(super) fn with_stats(mut self, with_stats: Option<WithGroupStats>) -> Self {
+        self.with_stats = with_stats;
+        self
+    }
+
+    /// Order hits within the groups by a payload field instead of their score when distilling them
+    pub(super) fn with_order_by(mut self, order_by: Option<OrderBy>) -> Self {
+        self.order_by = order_by;
+        self
+    }
+
+    /// Put points without a value of a group_by field into the group of missing values, instead
+    /// of leaving them out
+    pub(super) fn with_missing(mut self, with_missing: bool) -> Self {
+        self.with_missing = with_missing;
+        self
+    }
+
+    /// Keep only as many groups and hits as requested, instead of every group found by the search.
+    ///
+    /// Points are mostly found in the order of their score, so groups found later rarely replace
+    /// the best groups and the best groups are the same. But hits found before their group was
+    /// kept are dropped, statistics only cover the kept hits, and hits ordered by a payload field
+    /// are chosen among the best scored ones.
+    pub(super) fn with_approximate(mut self, approximate: bool) -> Self {
+        self.approximate = approximate;
+        self
+    }
+
+    /// Keys of the groups of the point, assumes that the point has the group_by fields.
+    fn group_keys(&self, point: &ScoredPoint) -> Result<Vec<GroupId>, AggregatorError> {
+        payload_group_keys(point.payload.as_ref(), &self.grouped_by, self.with_missing)
+    }
+
     /// Adds a point to the group that corresponds based on the group_by field, assumes that the point has the group_by field
     fn add_point(&mut self, point: ScoredPoint) -> Result<(), AggregatorError> {
-        // extract all values from the group_by field
-        let payload_values: Vec<_> = point
-            .payload
-            .as_ref()
-            .map(|p| {
-                p.get_value(&self.grouped_by)
-                    .values()
-                    .into_iter()
-                    .flat_map(|v| match v {
-                        Value::Array(arr) => arr.iter().collect(),
-                        _ => vec![v],
-                    })
-                    .collect()
-            })
-            .ok_or(KeyNotFound)?;
-
-        let group_keys = payload_values
-            .into_iter()
-            .map(GroupId::try_from)
-            .collect::<Result<Vec<GroupId>, ()>>()
-            .map_err(|_| BadKeyType)?;
-
-        let unique_group_keys: Vec<_> = group_keys.into_iter().unique().collect();
+        let unique_group_keys = self.group_keys(&point)?;
 
         for group_key in unique_group_keys {
+            if self.approximate
+                && !self.groups.contains_key(&group_key)
+                && !self.make_room(&group_key, point.score)
+            {
+                self.spilled_hits += 1;
+                continue;
+            }
+
             let group = self
                 .groups
                 .entry(group_key.clone())
                 .or_insert_with(|| HashMap::with_capacity(self.max_group_size));
 
+            // Full groups only keep their best hits when approximating
+            if self.approximate
+                && group.len() >= self.max_group_size
+                && !group.contains_key(&point.id)
+            {
+                self.spilled_hits += 1;
+                let worst_hit = group
+                    .values()
+                    .max_by_key(|hit| rank(self.order, hit.score))
+                    .map(|hit| (hit.id, hit.score));
+                match worst_hit {
+                    Some((id, score))
+                        if rank(self.order, point.score) < rank(self.order, score) =>
+                    {
+                        group.remove(&id);
+                    }
+                    _ => {
+                        self.all_ids.insert(point.id);
+                        continue;
+                    }
+                }
+            }
+
             let entry = group.entry(point.id);
 
             // if the point is already in the group, check if it has newer version
@@ -90,7 +159,9 @@ impl GroupsAggregator {
             }
 
             // Insert score if better than the group best score
-            self.group_best_scores
+            let previous_best_score = self.group_best_scores.get(&group_key).copied();
+            let best_score = *self
+                .group_best_scores
                 .entry(group_key.clone())
                 .and_modify(|e| {
                     *e = match self.order {
@@ -99,15 +170,56 @@ impl GroupsAggregator {
                     }
                 })
                 .or_insert(point.score);
+
+            if self.approximate && previous_best_score != Some(best_score) {
+                self.worst_groups
+                    .push((rank(self.order, best_score), group_key));
+            }
         }
         Ok(())
     }
 
+    /// Evict the worst group if there are already `max_groups` groups, unless the new group with
+    /// a hit of `score` would be worse. Returns whether there is room for the new group.
+    fn make_room(&mut self, key: &GroupId, score: ScoreType) -> bool {
+        if self.groups.len() < self.max_groups {
+            return true;
+        }
+
+        while let Some((worst_rank, worst_key)) = self.worst_groups.peek() {
+            let worst_rank = *worst_rank;
+            let is_stale = self
+                .group_best_scores
+                .get(worst_key)
+                .map_or(true, |best_score| {
+                    rank(self.order, *best_score) != worst_rank
+                });
+            if is_stale {
+                self.worst_groups.pop();
+                continue;
+            }
+
+            if (rank(self.order, score), key) >= (worst_rank, worst_key) {
+                return false;
+            }
+
+            let (_, worst_key) = self.worst_groups.pop().unwrap();
+            if let Some(group) = self.groups.remove(&worst_key) {
+                self.spilled_hits += group.len();
+            }
+            self.group_best_scores.remove(&worst_key);
+            self.full_groups.remove(&worst_key);
+            self.spilled_groups += 1;
+            return true;
+        }
+        true
+    }
+
     /// Adds multiple points to the group that they corresponds based on the group_by field, assumes that the points always have the grouped_by field, else it just ignores them
     pub(super) fn add_points(&mut self, points: &[ScoredPoint]) {
         for point in points {
             match self.add_point(point.to_owned()) {
-                Ok(()) | Err(KeyNotFound | BadKeyType) => continue, // ignore points that don't have the group_by field
+                Ok(()) | Err(KeyNotFound | UnsupportedKey(_)) => continue, // ignore points that don't have the group_by field
             }
         }
     }
@@ -117,14 +229,14 @@ impl GroupsAggregator {
         self.groups.len()
     }
 
-    /// Return `max_groups` number of keys of the groups with the best score
+    /// Return `max_groups` number of keys of the groups with the best score.
+    ///
+    /// Groups of the same score are ordered by their keys, so that the same groups are returned
+    /// for the same points, and pages of groups don't overlap.
     fn best_group_keys(&self) -> impl Iterator<Item = &GroupId> {
         self.group_best_scores
             .iter()
-            .sorted_by_key(|(_, score)| match self.order {
-                Order::LargeBetter => -OrderedFloat(**score),
-                Order::SmallBetter => OrderedFloat(**score),
-            })
+            .sorted_by_key(|(key, score)| (rank(self.order, **score), *key))
             .take(self.max_groups)
             .map(|(k, _)| k)
     }
@@ -157,23 +269,39 @@ impl GroupsAggregator {
 
     /// Returns the best groups sorted by their best hit. The hits are sorted too.
     pub(super) fn distill(mut self) -> Vec<Group> {
+        if self.spilled_groups > 0 || self.spilled_hits > 0 {
+            log::debug!(
+                "Approximate grouping evicted {} groups and dropped {} hits",
+                self.spilled_groups,
+                self.spilled_hits,
+            );
+        }
+
         let best_groups: Vec<_> = self.best_group_keys().cloned().collect();
         let mut groups = Vec::with_capacity(best_groups.len());
 
         for group_key in best_groups {
             let mut group = self.groups.remove(&group_key).unwrap();
+            let stats = self
+                .with_stats
+                .as_ref()
+                .map(|with_stats| group_stats(&group, with_stats));
             let scored_points_iter = group.drain().map(|(_, hit)| hit);
-            let hits = match self.order {
-                Order::LargeBetter => {
+            let hits = match (&self.order_by, self.order) {
+                (Some(order_by), order) => {
+                    ordered_hits(scored_points_iter, order_by, order, self.max_group_size)
+                }
+                (None, Order::LargeBetter) => {
                     peek_top_largest_iterable(scored_points_iter, self.max_group_size)
                 }
-                Order::SmallBetter => {
+                (None, Order::SmallBetter) => {
                     peek_top_smallest_iterable(scored_points_iter, self.max_group_size)
                 }
             };
             groups.push(Group {
                 hits,
                 key: group_key,
+                stats,
             });
         }
 
@@ -181,10 +309,160 @@ impl GroupsAggregator {
     }
 }
 
+/// Rank of the score, lower ranks are better
+fn rank(order: Order, score: ScoreType) -> OrderedFloat<ScoreType> {
+    match order {
+        Order::LargeBetter => -OrderedFloat(score),
+        Order::SmallBetter => OrderedFloat(score),
+    }
+}
+
+/// Keys of the groups of a point with the payload, grouped by the `grouped_by` fields.
+///
+/// With multiple group_by fields, the point is in a group for each combination of their values
+pub(super) fn payload_group_keys(
+    payload: Option<&Payload>,
+    grouped_by: &[String],
+    with_missing: bool,
+) -> Result<Vec<GroupId>, AggregatorError> {
+    let payload = match payload {
+        Some(payload) => payload,
+        None if with_missing => return Ok(vec![missing_key(grouped_by)]),
+        None => return Err(KeyNotFound),
+    };
+
+    let field_keys = grouped_by
+        .iter()
+        .map(|path| {
+            let values = payload_values(payload, path);
+            if with_missing && values.iter().all(|value| value.is_null()) {
+                return Ok(vec![GroupId::from(MISSING_GROUP_ID)]);
+            }
+            let group_keys = values
+                .into_iter()
+                .map(GroupId::try_from)
+                .collect::<Result<Vec<GroupId>, _>>()
+                .map_err(UnsupportedKey)?;
+            Ok(group_keys.into_iter().unique().collect::<Vec<_>>())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match <[_; 1]>::try_from(field_keys) {
+        // Keys of a single field are its values themselves
+        Ok([group_keys]) => Ok(group_keys),
+        Err(field_keys) => Ok(field_keys
+            .into_iter()
+            .multi_cartesian_product()
+            .map(GroupId::Composite)
+            .collect()),
+    }
+}
+
+/// Key of the group of points without any of the group_by fields
+fn missing_key(grouped_by: &[String]) -> GroupId {
+    match grouped_by {
+        [_] => GroupId::from(MISSING_GROUP_ID),
+        paths => GroupId::Composite(vec![GroupId::from(MISSING_GROUP_ID); paths.len()]),
+    }
+}
+
+/// All values of the payload field, with values of arrays flattened
+fn payload_values<'a>(payload: &'a Payload, path: &str) -> Vec<&'a Value> {
+    payload
+        .get_value(path)
+        .values()
+        .into_iter()
+        .flat_map(|v| match v {
+            Value::Array(arr) => arr.iter().collect(),
+            _ => vec![v],
+        })
+        .collect()
+}
+
+/// Value of a payload field to order hits by, numbers come before strings
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum OrderValue {
+    Number(OrderedFloat<f64>),
+    String(String),
+}
+
+impl OrderValue {
+    /// Value of the hit to order it by. Of multiple values, the first one in the direction is
+    /// used, e.g. the smallest one in ascending order.
+    fn of(hit: &ScoredPoint, path: &str, direction: Direction) -> Option<Self> {
+        let values = payload_values(hit.payload.as_ref()?, path)
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::Number(number) => number.as_f64().map(|n| Self::Number(OrderedFloat(n))),
+                Value::String(string) => Some(Self::String(string.clone())),
+                _ => None,
+            });
+        match direction {
+            Direction::Asc => values.min(),
+            Direction::Desc => values.max(),
+        }
+    }
+}
+
+/// Best `limit` hits ordered by the payload field, hits without it come last. Hits of the same
+/// value are ordered by their score, and then by their id.
+fn ordered_hits(
+    hits: impl Iterator<Item = ScoredPoint>,
+    order_by: &OrderBy,
+    order: Order,
+    limit: usize,
+) -> Vec<ScoredPoint> {
+    let direction = order_by.direction.unwrap_or_default();
+    hits.map(|hit| (OrderValue::of(&hit, &order_by.key, direction), hit))
+        .sorted_by(|(a_value, a), (b_value, b)| {
+            let by_value = match (a_value, b_value) {
+                (Some(a_value), Some(b_value)) => match direction {
+                    Direction::Asc => a_value.cmp(b_value),
+                    Direction::Desc => b_value.cmp(a_value),
+                },
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            let by_score = match order {
+                Order::LargeBetter => OrderedFloat(b.score).cmp(&OrderedFloat(a.score)),
+                Order::SmallBetter => OrderedFloat(a.score).cmp(&OrderedFloat(b.score)),
+            };
+            by_value.then(by_score).then_with(|| a.id.cmp(&b.id))
+        })
+        .take(limit)
+        .map(|(_, hit)| hit)
+        .collect()
+}
+
+/// Statistics of all hits of the group, assumes that the group is not empty
+fn group_stats(hits: &Hits, with_stats: &WithGroupStats) -> GroupStats {
+    let scores = hits.values().map(|hit| hit.score);
+    let min_score = scores.clone().fold(ScoreType::INFINITY, ScoreType::min);
+    let max_score = scores.clone().fold(ScoreType::NEG_INFINITY, ScoreType::max);
+    let avg_score = scores.sum::<ScoreType>() / hits.len() as ScoreType;
+
+    let sum = with_stats.sum.as_ref().map(|path| {
+        hits.values()
+            .filter_map(|hit| hit.payload.as_ref())
+            .flat_map(|payload| payload_values(payload, path))
+            .filter_map(Value::as_f64)
+            .sum::<f64>()
+    });
+
+    GroupStats {
+        hit_count: hits.len(),
+        min_score,
+        max_score,
+        avg_score,
+        sum,
+    }
+}
+
 #[cfg(test)]
 mod unit_tests {
 
-    use segment::types::Payload;
+    use segment::data_types::groups::GroupIdError;
     use serde_json::json;
 
     use super::*;
@@ -219,7 +497,8 @@ mod unit_tests {
             point(3, 0.75, json!("b")),
         ];
 
-        let mut aggregator = GroupsAggregator::new(3, 2, "docId".to_string(), Order::LargeBetter);
+        let mut aggregator =
+            GroupsAggregator::new(3, 2, vec!["docId".to_string()], Order::LargeBetter);
         for point in scored_points {
             aggregator.add_point(point).unwrap();
         }
@@ -237,6 +516,276 @@ mod unit_tests {
         assert_eq!(result[1].hits[1].id, 3.into());
     }
 
+    #[test]
+    fn test_group_by_nested_paths() {
+        let nested_point = |idx: u64, score: ScoreType, payload: Value| ScoredPoint {
+            payload: Some(Payload::from(payload)),
+            ..empty_point(idx, score)
+        };
+        let group_keys = |group_by: &str, points: Vec<ScoredPoint>| {
+            let mut aggregator =
+                GroupsAggregator::new(10, 10, vec![group_by.to_string()], Order::LargeBetter);
+            aggregator.add_points(&points);
+            aggregator
+                .distill()
+                .into_iter()
+                .map(|group| (group.key, group.hits.len()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let points = vec![
+            nested_point(1, 0.9, json!({"metadata": {"author": {"id": "a"}}})),
+            nested_point(2, 0.8, json!({"metadata": {"author": {"id": "a"}}})),
+            nested_point(3, 0.7, json!({"metadata": {"author": {"id": 3}}})),
+            nested_point(4, 0.6, json!({"metadata": {"author": "b"}})),
+        ];
+        assert_eq!(
+            group_keys("metadata.author.id", points),
+            HashMap::from([(GroupId::from("a"), 2), (GroupId::from(3u64), 1)]),
+        );
+
+        let points = vec![
+            nested_point(1, 0.9, json!({"authors": [{"id": "a"}, {"id": "b"}]})),
+            nested_point(2, 0.8, json!({"authors": [{"id": "b"}, {"name": "c"}]})),
+        ];
+        assert_eq!(
+            group_keys("authors[].id", points.clone()),
+            HashMap::from([(GroupId::from("a"), 1), (GroupId::from("b"), 2)]),
+        );
+        assert_eq!(
+            group_keys("authors[0].id", points),
+            HashMap::from([(GroupId::from("a"), 1), (GroupId::from("b"), 1)]),
+        );
+    }
+
+    #[test]
+    fn test_group_by_multiple_fields() {
+        let doc_point = |idx: u64, score: ScoreType, payload: Value| ScoredPoint {
+            payload: Some(Payload::from(payload)),
+            ..empty_point(idx, score)
+        };
+        let points = vec![
+            doc_point(1, 0.9, json!({"docId": "a", "lang": "en"})),
+            doc_point(2, 0.8, json!({"docId": "a", "lang": ["en", "de"]})),
+            doc_point(3, 0.7, json!({"docId": 3, "lang": "en"})),
+            doc_point(4, 0.6, json!({"docId": "b"})),
+        ];
+
+        let mut aggregator = GroupsAggregator::new(
+            10,
+            10,
+            vec!["docId".to_string(), "lang".to_string()],
+            Order::LargeBetter,
+        );
+        aggregator.add_points(&points);
+
+        let composite = |doc_id: GroupId, lang: &str| GroupId::Composite(vec![doc_id, lang.into()]);
+        let groups: HashMap<_, _> = aggregator
+            .distill()
+            .into_iter()
+            .map(|group| (group.key, group.hits.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            HashMap::from([
+                (composite("a".into(), "en"), 2),
+                (composite("a".into(), "de"), 1),
+                (composite(3u64.into(), "en"), 1),
+            ]),
+        );
+
+        assert_eq!(
+            serde_json::to_value(composite("a".into(), "en")).unwrap(),
+            json!(["a", "en"]),
+        );
+    }
+
+    #[test]
+    fn test_ties_ordered_by_key() {
+        let points = vec![
+            point(1, 0.5, json!("c")),
+            point(2, 0.5, json!("a")),
+            point(3, 0.9, json!("d")),
+            point(4, 0.5, json!("b")),
+        ];
+
+        let mut aggregator =
+            GroupsAggregator::new(3, 1, vec!["docId".to_string()], Order::LargeBetter);
+        aggregator.add_points(&points);
+
+        let keys: Vec<_> = aggregator
+            .distill()
+            .into_iter()
+            .map(|group| group.key)
+            .collect();
+        assert_eq!(keys, ["d", "a", "b"].map(GroupId::from));
+    }
+
+    #[test]
+    fn test_group_stats() {
+        let priced_point = |idx: u64, score: ScoreType, price: Value| ScoredPoint {
+            payload: Some(Payload::from(json!({"docId": "a", "price": price}))),
+            ..empty_point(idx, score)
+        };
+        let points = vec![
+            priced_point(1, 0.75, json!(10)),
+            priced_point(2, 0.5, json!([1.5, 2.5])),
+            priced_point(3, 0.25, json!("free")),
+        ];
+
+        let mut aggregator =
+            GroupsAggregator::new(1, 2, vec!["docId".to_string()], Order::LargeBetter).with_stats(
+                Some(WithGroupStats {
+                    sum: Some("price".to_string()),
+                }),
+            );
+        aggregator.add_points(&points);
+
+        let groups = aggregator.distill();
+        assert_eq!(groups[0].hits.len(), 2);
+        // Statistics cover all hits found for the group
+        assert_eq!(
+            groups[0].stats,
+            Some(GroupStats {
+                hit_count: 3,
+                min_score: 0.25,
+                max_score: 0.75,
+                avg_score: 0.5,
+                sum: Some(14.0),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_order_by() {
+        let dated_point = |idx: u64, score: ScoreType, published_at: Value| ScoredPoint {
+            payload: Some(Payload::from(
+                json!({"docId": "a", "published_at": published_at}),
+            )),
+            ..empty_point(idx, score)
+        };
+        let points = vec![
+            dated_point(1, 0.9, json!("2023-01-01")),
+            dated_point(2, 0.8, json!("2023-03-01")),
+            dated_point(3, 0.7, json!(null)),
+            dated_point(4, 0.6, json!(["2022-01-01", "2023-02-01"])),
+            dated_point(5, 0.5, json!("2023-03-01")),
+        ];
+        let ordered_ids = |direction: Option<Direction>, group_size: usize| {
+            let mut aggregator =
+                GroupsAggregator::new(1, group_size, vec!["docId".to_string()], Order::LargeBetter)
+                    .with_order_by(Some(OrderBy {
+                        key: "published_at".to_string(),
+                        direction,
+                    }));
+            aggregator.add_points(&points);
+            aggregator.distill()[0]
+                .hits
+                .iter()
+                .map(|hit| hit.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ordered_ids(None, 5),
+            [4, 1, 2, 5, 3].map(ExtendedPointId::from)
+        );
+        // Hits of the same value are ordered by score, hits without the value come last
+        assert_eq!(
+            ordered_ids(Some(Direction::Desc), 5),
+            [2, 5, 4, 1, 3].map(ExtendedPointId::from),
+        );
+        // The best hits by the field are returned, not the best by score
+        assert_eq!(
+            ordered_ids(Some(Direction::Desc), 2),
+            [2, 5].map(ExtendedPointId::from)
+        );
+    }
+
+    #[test]
+    fn test_with_missing() {
+        let points = vec![
+            point(1, 0.9, json!("a")),
+            point(2, 0.8, json!(null)),
+            point(3, 0.7, json!([])),
+            empty_point(4, 0.6),
+            ScoredPoint {
+                payload: Some(Payload::from(json!({"lang": "en"}))),
+                ..empty_point(5, 0.5)
+            },
+        ];
+        let groups = |with_missing: bool| {
+            let mut aggregator =
+                GroupsAggregator::new(10, 10, vec!["docId".to_string()], Order::LargeBetter)
+                    .with_missing(with_missing);
+            aggregator.add_points(&points);
+            aggregator
+                .distill()
+                .into_iter()
+                .map(|group| (group.key, group.hits.len()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert_eq!(groups(false), HashMap::from([(GroupId::from("a"), 1)]));
+        assert_eq!(
+            groups(true),
+            HashMap::from([
+                (GroupId::from("a"), 1),
+                (GroupId::from(MISSING_GROUP_ID), 4),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_approximate() {
+        let points = vec![
+            point(1, 0.9, json!("a")),
+            point(2, 0.8, json!("b")),
+            point(3, 0.7, json!("c")),
+            point(4, 0.95, json!("c")),
+            point(5, 0.6, json!("a")),
+            point(6, 0.5, json!("a")),
+            point(7, 0.99, json!("a")),
+        ];
+        let groups = |aggregator: GroupsAggregator| {
+            aggregator
+                .distill()
+                .into_iter()
+                .map(|group| (group.key, group.hits.iter().map(|hit| hit.id).collect_vec()))
+                .collect_vec()
+        };
+
+        let mut aggregator =
+            GroupsAggregator::new(2, 2, vec!["docId".to_string()], Order::LargeBetter)
+                .with_approximate(true);
+        for point in points.clone() {
+            aggregator.add_point(point).unwrap();
+            assert!(aggregator.len() <= 2);
+            assert!(aggregator.groups.values().all(|group| group.len() <= 2));
+        }
+        assert_eq!(aggregator.spilled_groups, 1);
+        assert_eq!(aggregator.spilled_hits, 4);
+
+        let mut exact = GroupsAggregator::new(2, 2, vec!["docId".to_string()], Order::LargeBetter);
+        exact.add_points(&points);
+        assert_eq!(
+            groups(exact),
+            [
+                (GroupId::from("a"), vec![ExtendedPointId::from(7), 1.into()]),
+                (GroupId::from("c"), vec![ExtendedPointId::from(4), 3.into()]),
+            ],
+        );
+        // The same groups as without the bounds, but hits found before their group was kept are
+        // dropped
+        assert_eq!(
+            groups(aggregator),
+            [
+                (GroupId::from("a"), vec![ExtendedPointId::from(7), 1.into()]),
+                (GroupId::from("c"), vec![ExtendedPointId::from(4)]),
+            ],
+        );
+    }
+
     struct Case {
         point: ScoredPoint,
         key: Value,
@@ -265,7 +814,8 @@ mod unit_tests {
 
     #[test]
     fn it_adds_single_points() {
-        let mut aggregator = GroupsAggregator::new(4, 3, "docId".to_string(), Order::LargeBetter);
+        let mut aggregator =
+            GroupsAggregator::new(4, 3, vec!["docId".to_string()], Order::LargeBetter);
 
         // cases
         #[rustfmt::skip]
@@ -284,7 +834,7 @@ mod unit_tests {
             Case::new(json!("a"), 8, 4, Ok(()), point(104, 0.35, json!("a"))), // small score 'a'
             Case::new(json!("a"), 9, 4, Ok(()), point(105, 0.36, json!("a"))), // small score 'a'
             Case::new(json!("b"), 3, 4, Ok(()), point(7, 1.0, json!("b"))),
-            Case::new(json!("false"), 0, 4, Err(BadKeyType), point(8, 1.0, json!(false))),
+            Case::new(json!("1.5"), 0, 4, Err(UnsupportedKey(GroupIdError::Float)), point(8, 1.0, json!(1.5))),
             Case::new(json!("none"), 0, 4, Err(KeyNotFound), empty_point(9, 1.0)),
             Case::new(json!(3), 2, 4, Ok(()), point(10, 0.6, json!(3))),
             Case::new(json!(3), 3, 4, Ok(()), point(11, 0.1, json!(3))),
@@ -368,7 +918,8 @@ mod unit_tests {
 
     #[test]
     fn test_aggregate_less_groups() {
-        let mut aggregator = GroupsAggregator::new(3, 2, "docId".to_string(), Order::LargeBetter);
+        let mut aggregator =
+            GroupsAggregator::new(3, 2, vec!["docId".to_string()], Order::LargeBetter);
 
         // cases
         [
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::time::Duration;
 
 use futures::Future;
@@ -76,6 +77,25 @@ where
     /// Does the actual grouping
     async fn run(self) -> CollectionResult<Vec<PointGroup>> {
         let with_lookup = self.group_by.with_lookup.clone();
+        let with_lookups = self.group_by.with_lookups.clone();
+
+        if (with_lookup.is_some() || !with_lookups.is_empty()) && self.group_by.group_by.len() > 1 {
+            return Err(CollectionError::bad_request(
+                "with_lookup is not supported when grouping by multiple fields".to_string(),
+            ));
+        }
+
+        // Lookups are returned by the name of their collection
+        if let Some(duplicate) = with_lookups
+            .iter()
+            .map(|lookup| &lookup.collection_name)
+            .duplicates()
+            .next()
+        {
+            return Err(CollectionError::bad_request(format!(
+                "Collection {duplicate} is looked up more than once",
+            )));
+        }
 
         let core_group_by = self
             .group_by
@@ -100,14 +120,13 @@ where
             let mut lookups = {
                 let pseudo_ids = groups
                     .iter()
-                    .map(|group| group.id.clone())
-                    .map_into()
+                    .filter_map(|group| PseudoId::try_from(group.id.clone()).ok())
                     .collect();
 
                 lookup_ids(
                     lookup,
                     pseudo_ids,
-                    self.collection_by_name,
+                    self.collection_by_name.clone(),
                     self.read_consistency,
                     &self.shard_selection,
                 )
@@ -116,10 +135,42 @@ where
 
             // Put the lookups in their respective groups
             groups.iter_mut().for_each(|group| {
-                group.lookup = lookups.remove(&PseudoId::from(group.id.clone()));
+                group.lookup = PseudoId::try_from(group.id.clone())
+                    .ok()
+                    .and_then(|pseudo_id| lookups.remove(&pseudo_id));
             });
         }
 
+        if !with_lookups.is_empty() {
+            let pseudo_ids: Vec<_> = groups
+                .iter()
+                .filter_map(|group| PseudoId::try_from(group.id.clone()).ok())
+                .collect();
+
+            for lookup in with_lookups {
+                let collection_name = lookup.collection_name.clone();
+                let mut lookups = lookup_ids(
+                    lookup,
+                    pseudo_ids.clone(),
+                    self.collection_by_name.clone(),
+                    self.read_consistency,
+                    &self.shard_selection,
+                )
+                .await?;
+
+                // Put the lookups in their respective groups, under the name of the collection
+                for group in groups.iter_mut() {
+                    let group_lookups = group.lookups.get_or_insert_with(HashMap::new);
+                    let record = PseudoId::try_from(group.id.clone())
+                        .ok()
+                        .and_then(|pseudo_id| lookups.remove(&pseudo_id));
+                    if let Some(record) = record {
+                        group_lookups.insert(collection_name.clone(), record);
+                    }
+                }
+            }
+        }
+
         Ok(groups)
     }
 }
@@ -0,0 +1,163 @@
+//! Estimation of the number of groups a group request would find, see [`count_groups`].
+//!
+//! Points with the group_by fields are scrolled through, keeping a uniformly random sample of
+//! their values. The number of distinct values is extrapolated from how often values repeat
+//! within the sample, which holds at most `sample` points however many are scrolled.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+use segment::data_types::groups::GroupId;
+use segment::types::{Condition, Filter, WithPayloadInterface, WithVector};
+
+use super::aggregator::payload_group_keys;
+use super::group_by::payload_selector_path;
+use crate::collection::Collection;
+use crate::operations::consistency_params::ReadConsistency;
+use crate::operations::shard_selector_internal::ShardSelectorInternal;
+use crate::operations::types::{
+    CollectionResult, GroupsCountRequestInternal, GroupsCountResult, ScrollRequestInternal,
+};
+
+const DEFAULT_SAMPLE: usize = 1000;
+
+/// Estimate the number of distinct values of the group_by fields among points which satisfy
+/// the filter of the request
+pub async fn count_groups(
+    request: GroupsCountRequestInternal,
+    collection: &Collection,
+    read_consistency: Option<ReadConsistency>,
+    shard_selection: &ShardSelectorInternal,
+) -> CollectionResult<GroupsCountResult> {
+    let group_by = request.group_by.into_fields();
+    let sample = request.sample.unwrap_or(DEFAULT_SAMPLE);
+
+    // Points without the key are not grouped
+    let key_not_empty = Filter {
+        must_not: Some(
+            group_by
+                .iter()
+                .map(|path| Condition::IsEmpty(path.clone().into()))
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    let filter = request.filter.unwrap_or_default().merge(&key_not_empty);
+
+    let with_payload = WithPayloadInterface::Fields(
+        group_by
+            .iter()
+            .map(|path| payload_selector_path(path))
+            .collect(),
+    );
+
+    // Reservoir sampling: the first `sample` points fill the sample, after which the n-th point
+    // replaces a random one with probability `sample / n`, so that the ids don't bias the sample
+    let mut rng = rand::thread_rng();
+    let mut reservoir: Vec<Vec<GroupId>> = Vec::with_capacity(sample);
+    let mut total = 0;
+    let mut offset = None;
+    loop {
+        let scroll = collection
+            .scroll_by(
+                ScrollRequestInternal {
+                    offset,
+                    limit: Some(sample),
+                    filter: Some(filter.clone()),
+                    with_payload: Some(with_payload.clone()),
+                    with_vector: WithVector::Bool(false),
+                },
+                read_consistency,
+                shard_selection,
+            )
+            .await?;
+
+        for point in scroll.points {
+            total += 1;
+            let slot = if reservoir.len() < sample {
+                reservoir.len()
+            } else {
+                rng.gen_range(0..total)
+            };
+            if slot >= sample {
+                continue;
+            }
+
+            // Values which can't be group keys are skipped by group requests as well
+            let keys =
+                payload_group_keys(point.payload.as_ref(), &group_by, false).unwrap_or_default();
+            if slot == reservoir.len() {
+                reservoir.push(keys);
+            } else {
+                reservoir[slot] = keys;
+            }
+        }
+
+        offset = scroll.next_page_offset;
+        if offset.is_none() {
+            break;
+        }
+    }
+
+    let mut frequencies: HashMap<GroupId, usize> = HashMap::new();
+    for key in reservoir.iter().flatten() {
+        *frequencies.entry(key.clone()).or_default() += 1;
+    }
+
+    let sampled = reservoir.len();
+    Ok(GroupsCountResult {
+        count: estimate_distinct(&frequencies, sampled, total),
+        // All points with the key were sampled
+        exact: total <= sampled,
+    })
+}
+
+/// Guaranteed-error estimator (GEE) of the number of distinct values among `total` points, from
+/// the `frequencies` of the values in a sample of `sampled` points.
+///
+/// Values seen more than once are likely to be all the frequent values, while each value seen
+/// once stands for `sqrt(total / sampled)` values, as it may be one of many rare values.
+fn estimate_distinct(frequencies: &HashMap<GroupId, usize>, sampled: usize, total: usize) -> usize {
+    let seen = frequencies.len();
+    if sampled == 0 || total <= sampled {
+        return seen;
+    }
+
+    let singletons = frequencies.values().filter(|&&count| count == 1).count();
+    let scale = (total as f64 / sampled as f64).sqrt();
+    (scale * singletons as f64).round() as usize + (seen - singletons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_distinct() {
+        let frequencies = |counts: &[usize]| {
+            counts
+                .iter()
+                .enumerate()
+                .map(|(value, &count)| (GroupId::from(value as u64), count))
+                .collect::<HashMap<_, _>>()
+        };
+
+        // Every value repeats, likely all of them were seen
+        assert_eq!(estimate_distinct(&frequencies(&[5; 20]), 100, 10_000), 20);
+
+        // Every value is unique, scaled by the square root of the sampling fraction
+        assert_eq!(
+            estimate_distinct(&frequencies(&[1; 100]), 100, 10_000),
+            1_000
+        );
+
+        // Only the unique values are scaled
+        let mut counts = vec![1; 10];
+        counts.extend([3; 30]);
+        assert_eq!(estimate_distinct(&frequencies(&counts), 100, 400), 50);
+
+        // The whole collection was sampled
+        assert_eq!(estimate_distinct(&frequencies(&[1; 100]), 100, 100), 100);
+        assert_eq!(estimate_distinct(&frequencies(&[]), 0, 100), 0);
+    }
+}
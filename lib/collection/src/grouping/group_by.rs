@@ -4,21 +4,23 @@ use std::time::Duration;
 
 use itertools::Itertools;
 use segment::types::{
-    AnyVariants, Condition, FieldCondition, Filter, Match, ScoredPoint, WithPayloadInterface,
+    AnyVariants, Condition, FieldCondition, Filter, Match, ScoredPoint, ValueVariants,
+    WithPayloadInterface,
 };
 use serde_json::Value;
 use tokio::sync::RwLockReadGuard;
 
 use super::aggregator::GroupsAggregator;
-use super::types::CoreGroupRequest;
+use super::types::{CoreGroupRequest, MISSING_GROUP_ID};
 use crate::collection::Collection;
 use crate::common::fetch_vectors;
+use crate::lookup::types::WithLookupInterface;
 use crate::lookup::WithLookup;
 use crate::operations::consistency_params::ReadConsistency;
 use crate::operations::shard_selector_internal::ShardSelectorInternal;
 use crate::operations::types::{
-    BaseGroupRequest, CollectionResult, PointGroup, RecommendGroupsRequestInternal,
-    RecommendRequestInternal, SearchGroupsRequestInternal, SearchRequestInternal,
+    BaseGroupRequest, CollectionResult, OrderBy, PointGroup, RecommendGroupsRequestInternal,
+    RecommendRequestInternal, SearchGroupsRequestInternal, SearchRequestInternal, WithGroupStats,
 };
 use crate::recommendations::recommend_into_core_search;
 
@@ -36,8 +38,8 @@ pub struct GroupRequest {
     /// Request to use (search or recommend)
     pub source: SourceRequest,
 
-    /// Path to the field to group by
-    pub group_by: String,
+    /// Paths to the fields to group by, groups of multiple fields have composite keys
+    pub group_by: Vec<String>,
 
     /// Limit of points to return per group
     pub group_size: usize,
@@ -45,8 +47,26 @@ pub struct GroupRequest {
     /// Limit of groups to return
     pub limit: usize,
 
+    /// Amount of best groups to skip
+    pub offset: usize,
+
     /// Options for specifying how to use the group id to lookup points in another collection
     pub with_lookup: Option<WithLookup>,
+
+    /// Lookups of points in each of multiple collections using the group id
+    pub with_lookups: Vec<WithLookup>,
+
+    /// Statistics to compute for each group
+    pub with_stats: Option<WithGroupStats>,
+
+    /// Order of hits within each group, by score if not specified
+    pub order_by: Option<OrderBy>,
+
+    /// Collect points without a value of the group_by field into their own group
+    pub with_missing: bool,
+
+    /// Keep only as many groups and hits as requested while grouping
+    pub approximate: bool,
 }
 
 impl GroupRequest {
@@ -61,10 +81,16 @@ impl GroupRequest {
         };
         Self {
             source,
-            group_by,
+            group_by: vec![group_by],
             group_size,
             limit,
+            offset: 0,
             with_lookup: None,
+            with_lookups: vec![],
+            with_stats: None,
+            order_by: None,
+            with_missing: false,
+            approximate: false,
         }
     }
 
@@ -99,16 +125,34 @@ impl GroupRequest {
             group_by: self.group_by,
             group_size: self.group_size,
             limit: self.limit,
+            offset: self.offset,
             with_lookup: self.with_lookup,
+            with_lookups: self.with_lookups,
+            with_stats: self.with_stats,
+            order_by: self.order_by,
+            with_missing: self.with_missing,
+            approximate: self.approximate,
         })
     }
 }
 
 impl CoreGroupRequest {
-    /// Make `group_by` field selector work with as `with_payload`.
-    fn group_by_to_payload_selector(&self, group_by: &str) -> WithPayloadInterface {
-        let group_by = group_by.strip_suffix("[]").unwrap_or(group_by).to_owned();
-        WithPayloadInterface::Fields(vec![group_by])
+    /// Make `group_by` field selector work with as `with_payload`, along with the summed field
+    /// of the statistics and the field to order hits by.
+    fn group_by_to_payload_selector(&self, group_by: &[String]) -> WithPayloadInterface {
+        let summed = self
+            .with_stats
+            .as_ref()
+            .and_then(|stats| stats.sum.as_ref());
+        let ordered_by = self.order_by.as_ref().map(|order_by| &order_by.key);
+        WithPayloadInterface::Fields(
+            group_by
+                .iter()
+                .chain(summed)
+                .chain(ordered_by)
+                .map(|path| payload_selector_path(path))
+                .collect(),
+        )
     }
 
     async fn r#do(
@@ -120,10 +164,21 @@ impl CoreGroupRequest {
     ) -> CollectionResult<Vec<ScoredPoint>> {
         let mut request = self.source.clone();
 
-        request.limit = self.limit * self.group_size;
-
-        let key_not_empty = Filter::new_must_not(Condition::IsEmpty(self.group_by.clone().into()));
-        request.filter = Some(request.filter.unwrap_or_default().merge(&key_not_empty));
+        request.limit = (self.offset + self.limit) * self.group_size;
+
+        // Points without the key are only grouped if they are collected into their own group
+        if !self.with_missing {
+            let key_not_empty = Filter {
+                must_not: Some(
+                    self.group_by
+                        .iter()
+                        .map(|path| Condition::IsEmpty(path.clone().into()))
+                        .collect(),
+                ),
+                ..Default::default()
+            };
+            request.filter = Some(request.filter.unwrap_or_default().merge(&key_not_empty));
+        }
 
         let with_group_by_payload = self.group_by_to_payload_selector(&self.group_by);
 
@@ -151,10 +206,19 @@ impl From<SearchGroupsRequestInternal> for GroupRequest {
                     group_by,
                     group_size,
                     limit,
+                    offset,
                     with_lookup: with_lookup_interface,
+                    with_stats,
+                    order_by,
+                    with_missing,
+                    approximate,
                 },
         } = request;
 
+        let (with_lookup, with_lookups) = with_lookup_interface
+            .map(WithLookupInterface::into_lookups)
+            .unwrap_or_default();
+
         let search = SearchRequestInternal {
             vector,
             filter,
@@ -168,10 +232,16 @@ impl From<SearchGroupsRequestInternal> for GroupRequest {
 
         GroupRequest {
             source: SourceRequest::Search(search),
-            group_by,
+            group_by: group_by.into_fields(),
             group_size: group_size as usize,
             limit: limit as usize,
-            with_lookup: with_lookup_interface.map(Into::into),
+            offset: offset.unwrap_or_default() as usize,
+            with_lookup,
+            with_lookups,
+            with_stats,
+            order_by,
+            with_missing: with_missing.unwrap_or_default(),
+            approximate: approximate.unwrap_or_default(),
         }
     }
 }
@@ -194,10 +264,19 @@ impl From<RecommendGroupsRequestInternal> for GroupRequest {
                     group_by,
                     group_size,
                     limit,
+                    offset,
                     with_lookup: with_lookup_interface,
+                    with_stats,
+                    order_by,
+                    with_missing,
+                    approximate,
                 },
         } = request;
 
+        let (with_lookup, with_lookups) = with_lookup_interface
+            .map(WithLookupInterface::into_lookups)
+            .unwrap_or_default();
+
         let recommend = RecommendRequestInternal {
             positive,
             negative,
@@ -215,10 +294,16 @@ impl From<RecommendGroupsRequestInternal> for GroupRequest {
 
         GroupRequest {
             source: SourceRequest::Recommend(recommend),
-            group_by,
+            group_by: group_by.into_fields(),
             group_size: group_size as usize,
             limit: limit as usize,
-            with_lookup: with_lookup_interface.map(Into::into),
+            offset: offset.unwrap_or_default() as usize,
+            with_lookup,
+            with_lookups,
+            with_stats,
+            order_by,
+            with_missing: with_missing.unwrap_or_default(),
+            approximate: approximate.unwrap_or_default(),
         }
     }
 }
@@ -238,12 +323,18 @@ pub async fn group_by(
         distance.distance_order()
     };
 
+    // Skipped groups are aggregated as well, to know which groups to skip
+    let groups_to_aggregate = request.offset + request.limit;
     let mut aggregator = GroupsAggregator::new(
-        request.limit,
+        groups_to_aggregate,
         request.group_size,
         request.group_by.clone(),
         score_ordering,
-    );
+    )
+    .with_stats(request.with_stats.clone())
+    .with_order_by(request.order_by.clone())
+    .with_missing(request.with_missing)
+    .with_approximate(request.approximate);
 
     // Try to complete amount of groups
     let mut needs_filling = true;
@@ -255,7 +346,7 @@ pub async fn group_by(
         // Construct filter to exclude already found groups
         let full_groups = aggregator.keys_of_filled_groups();
         if !full_groups.is_empty() {
-            let except_any = except_on(&request.group_by, full_groups);
+            let except_any = except_on(&request.group_by, full_groups, request.with_missing);
             if !except_any.is_empty() {
                 let exclude_groups = Filter {
                     must: Some(except_any),
@@ -301,7 +392,7 @@ pub async fn group_by(
         aggregator.add_points(&points);
 
         // TODO: should we break early if we have some amount of "enough" groups?
-        if aggregator.len_of_filled_best_groups() >= request.limit {
+        if aggregator.len_of_filled_best_groups() >= groups_to_aggregate {
             needs_filling = false;
             break;
         }
@@ -316,7 +407,7 @@ pub async fn group_by(
 
             // Construct filter to only include unsatisfied groups
             let unsatisfied_groups = aggregator.keys_of_unfilled_best_groups();
-            let match_any = match_on(&request.group_by, unsatisfied_groups);
+            let match_any = match_on(&request.group_by, unsatisfied_groups, request.with_missing);
             if !match_any.is_empty() {
                 let include_groups = Filter {
                     must: Some(match_any),
@@ -360,14 +451,18 @@ pub async fn group_by(
 
             aggregator.add_points(&points);
 
-            if aggregator.len_of_filled_best_groups() >= request.limit {
+            if aggregator.len_of_filled_best_groups() >= groups_to_aggregate {
                 break;
             }
         }
     }
 
     // extract best results
-    let mut groups = aggregator.distill();
+    let mut groups: Vec<_> = aggregator
+        .distill()
+        .into_iter()
+        .skip(request.offset)
+        .collect();
 
     // flatten results
     let bare_points = groups
@@ -401,17 +496,87 @@ pub async fn group_by(
     Ok(groups)
 }
 
+/// Path of the `group_by` field for a payload selector, which matches arrays as `[]` regardless
+/// of the index, e.g. `authors[0].id` selects `authors[].id`
+pub(super) fn payload_selector_path(group_by: &str) -> String {
+    let mut path = String::with_capacity(group_by.len());
+    let mut in_brackets = false;
+    for c in group_by.chars() {
+        match c {
+            '[' => in_brackets = true,
+            ']' => {
+                in_brackets = false;
+                path.push_str("[]");
+            }
+            _ if in_brackets => {}
+            c => path.push(c),
+        }
+    }
+
+    // Select the whole array at the end of the path
+    match path.strip_suffix("[]") {
+        Some(stripped) => stripped.to_owned(),
+        None => path,
+    }
+}
+
 /// Uses the set of values to create Match::Except's, if possible
-fn except_on(path: &str, values: Vec<Value>) -> Vec<Condition> {
-    values_to_any_variants(values)
+fn except_on(paths: &[String], values: Vec<Value>, with_missing: bool) -> Vec<Condition> {
+    let [path] = paths else {
+        // Exclude each combination of values at once
+        return values
+            .iter()
+            .filter_map(|value| composite_match(paths, value, with_missing))
+            .map(|filter| Condition::Filter(Filter::new_must_not(Condition::Filter(filter))))
+            .collect();
+    };
+    let (missing, values) = split_missing(values, with_missing);
+    let except: Vec<_> = values_to_any_variants(values)
         .into_iter()
         .map(|v| Condition::Field(FieldCondition::new_match(path, Match::new_except(v))))
-        .collect()
+        .collect();
+
+    // Points without the key don't match any `except`, keep them while their group isn't full
+    if missing {
+        if except.is_empty() {
+            return vec![Condition::Filter(Filter::new_must_not(is_empty(path)))];
+        }
+        return except;
+    }
+    if !with_missing || except.is_empty() {
+        return except;
+    }
+    vec![Condition::Filter(Filter {
+        should: Some(vec![
+            Condition::Filter(Filter {
+                must: Some(except),
+                ..Default::default()
+            }),
+            is_empty(path),
+        ]),
+        ..Default::default()
+    })]
 }
 
 /// Uses the set of values to create Match::Any's, if possible
-fn match_on(path: &str, values: Vec<Value>) -> Vec<Condition> {
-    values_to_any_variants(values)
+fn match_on(paths: &[String], values: Vec<Value>, with_missing: bool) -> Vec<Condition> {
+    let [path] = paths else {
+        // Match any of the combinations of values
+        let should: Vec<_> = values
+            .iter()
+            .filter_map(|value| composite_match(paths, value, with_missing))
+            .map(Condition::Filter)
+            .collect();
+        if should.is_empty() {
+            return vec![];
+        }
+        return vec![Condition::Filter(Filter {
+            should: Some(should),
+            ..Default::default()
+        })];
+    };
+    let (missing, values) = split_missing(values, with_missing);
+    let any: Vec<_> = values_to_any_variants(values)
         .into_iter()
         .map(|any_variants| {
             Condition::Field(FieldCondition::new_match(
@@ -419,9 +584,71 @@ fn match_on(path: &str, values: Vec<Value>) -> Vec<Condition> {
                 Match::new_any(any_variants),
             ))
         })
-        .collect()
+        .collect();
+
+    if !missing {
+        return any;
+    }
+    if any.is_empty() {
+        return vec![is_empty(path)];
+    }
+    vec![Condition::Filter(Filter {
+        should: Some(vec![
+            Condition::Filter(Filter {
+                must: Some(any),
+                ..Default::default()
+            }),
+            is_empty(path),
+        ]),
+        ..Default::default()
+    })]
 }
 
+/// Whether the values contain the id of the group of points without the key, and the other values
+fn split_missing(values: Vec<Value>, with_missing: bool) -> (bool, Vec<Value>) {
+    if !with_missing {
+        return (false, values);
+    }
+    let len = values.len();
+    let values: Vec<_> = values
+        .into_iter()
+        .filter(|value| value.as_str() != Some(MISSING_GROUP_ID))
+        .collect();
+    (values.len() < len, values)
+}
+
+fn is_empty(path: &str) -> Condition {
+    Condition::IsEmpty(path.to_string().into())
+}
+
+/// Filter matching the combination of values of a composite group key, one for each path
+fn composite_match(paths: &[String], value: &Value, with_missing: bool) -> Option<Filter> {
+    let values = value.as_array()?;
+    let must = paths
+        .iter()
+        .zip(values)
+        .map(|(path, value)| {
+            let value = match value {
+                Value::String(s) if with_missing && s == MISSING_GROUP_ID => {
+                    return Some(is_empty(path));
+                }
+                Value::String(s) => ValueVariants::Keyword(s.clone()),
+                Value::Bool(b) => ValueVariants::Bool(*b),
+                value => ValueVariants::Integer(value.as_i64()?),
+            };
+            Some(Condition::Field(FieldCondition::new_match(
+                path.as_str(),
+                Match::new_value(value),
+            )))
+        })
+        .collect::<Option<_>>()?;
+    Some(Filter {
+        must: Some(must),
+        ..Default::default()
+    })
+}
+
+/// Booleans can't be matched with `any`, groups with boolean keys are not filtered by the key
 fn values_to_any_variants(values: Vec<Value>) -> Vec<AnyVariants> {
     let mut any_variants = Vec::new();
 
@@ -452,8 +679,25 @@ mod tests {
     use segment::data_types::groups::GroupId;
     use segment::types::{Payload, ScoredPoint};
 
+    use super::payload_selector_path;
     use crate::grouping::types::Group;
 
+    #[test]
+    fn test_payload_selector_path() {
+        assert_eq!(payload_selector_path("docId"), "docId");
+        assert_eq!(payload_selector_path("docIds[]"), "docIds");
+        assert_eq!(
+            payload_selector_path("metadata.author.id"),
+            "metadata.author.id"
+        );
+        assert_eq!(payload_selector_path("authors[].id"), "authors[].id");
+        assert_eq!(payload_selector_path("authors[0].id"), "authors[].id");
+        assert_eq!(
+            payload_selector_path("authors[1].tags[0]"),
+            "authors[].tags"
+        );
+    }
+
     #[test]
     fn test_hydrated_from() {
         // arrange
@@ -507,6 +751,7 @@ mod tests {
             let group = Group {
                 key: GroupId::from(key),
                 hits: points.into_iter().collect(),
+                stats: None,
             };
             groups.push(group);
         });
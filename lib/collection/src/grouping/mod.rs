@@ -1,5 +1,6 @@
 mod aggregator;
 mod builder;
+pub mod count;
 pub mod group_by;
 mod types;
 
@@ -1,20 +1,26 @@
 use std::collections::HashMap;
 
-use segment::data_types::groups::GroupId;
+use segment::data_types::groups::{GroupId, GroupIdError};
 use segment::types::{PointIdType, ScoredPoint};
 
 use crate::lookup::WithLookup;
-use crate::operations::types::{CoreSearchRequest, PointGroup};
+use crate::operations::types::{
+    CoreSearchRequest, GroupStats, OrderBy, PointGroup, WithGroupStats,
+};
+
+/// Id of the group of points without a value of the group_by field, see `with_missing`
+pub(super) const MISSING_GROUP_ID: &str = "__missing__";
 
 #[derive(PartialEq, Debug)]
 pub(super) enum AggregatorError {
-    BadKeyType,
+    UnsupportedKey(GroupIdError),
     KeyNotFound,
 }
 #[derive(Debug, Clone)]
 pub(super) struct Group {
     pub hits: Vec<ScoredPoint>,
     pub key: GroupId,
+    pub stats: Option<GroupStats>,
 }
 
 impl Group {
@@ -34,6 +40,8 @@ impl From<Group> for PointGroup {
             hits: group.hits,
             id: group.key,
             lookup: None,
+            lookups: None,
+            stats: group.stats,
         }
     }
 }
@@ -43,8 +51,8 @@ pub struct CoreGroupRequest {
     /// Core request to use
     pub source: CoreSearchRequest,
 
-    /// Path to the field to group by
-    pub group_by: String,
+    /// Paths to the fields to group by, groups of multiple fields have composite keys
+    pub group_by: Vec<String>,
 
     /// Limit of points to return per group
     pub group_size: usize,
@@ -52,13 +60,31 @@ pub struct CoreGroupRequest {
     /// Limit of groups to return
     pub limit: usize,
 
+    /// Amount of best groups to skip
+    pub offset: usize,
+
     /// Options for specifying how to use the group id to lookup points in another collection
     pub with_lookup: Option<WithLookup>,
+
+    /// Lookups of points in each of multiple collections using the group id
+    pub with_lookups: Vec<WithLookup>,
+
+    /// Statistics to compute for each group
+    pub with_stats: Option<WithGroupStats>,
+
+    /// Order of hits within each group, by score if not specified
+    pub order_by: Option<OrderBy>,
+
+    /// Collect points without a value of the group_by field into their own group
+    pub with_missing: bool,
+
+    /// Keep only as many groups and hits as requested while grouping
+    pub approximate: bool,
 }
 
 #[cfg(test)]
 mod test {
-    use segment::data_types::groups::GroupId;
+    use segment::data_types::groups::{GroupId, GroupIdError};
 
     #[test]
     fn group_key_from_values() {
@@ -69,7 +95,7 @@ mod test {
         let string = GroupId::try_from(&json!("string")).unwrap();
         let int = GroupId::try_from(&json!(1)).unwrap();
 
-        assert!(GroupId::try_from(&json!(2.42)).is_err());
+        assert_eq!(GroupId::try_from(&json!(2.42)), Err(GroupIdError::Float));
 
         assert!(GroupId::try_from(&json!([5, 6, 7])).is_err());
         assert!(GroupId::try_from(&json!(["a", "b", "c"])).is_err());
@@ -77,8 +103,8 @@ mod test {
         assert_eq!(string, GroupId::String("string".to_string()));
         assert_eq!(int.as_u64().unwrap(), 1);
 
-        let bad_key = GroupId::try_from(&json!(true));
-        assert!(bad_key.is_err());
+        let boolean = GroupId::try_from(&json!(true));
+        assert_eq!(boolean, Ok(GroupId::Bool(true)));
 
         let empty_array = GroupId::try_from(&json!([]));
         assert!(empty_array.is_err());
@@ -87,12 +113,30 @@ mod test {
         assert!(empty_object.is_err());
 
         let null = GroupId::try_from(&serde_json::Value::Null);
-        assert!(null.is_err());
+        assert_eq!(null, Err(GroupIdError::Null));
 
         let nested_array = GroupId::try_from(&json!([[1, 2, 3], [4, 5, 6]]));
         assert!(nested_array.is_err());
 
         let nested_object = GroupId::try_from(&json!({"a": 1, "b": 2}));
-        assert!(nested_object.is_err());
+        assert_eq!(nested_object, Err(GroupIdError::Object));
+    }
+
+    #[test]
+    fn group_key_from_uuids() {
+        use serde_json::json;
+
+        let lowercase = "6ba7b810-9dad-11d1-80b4-00c04fd430c8";
+        let uppercase = GroupId::try_from(&json!("6BA7B810-9DAD-11D1-80B4-00C04FD430C8"));
+        assert_eq!(uppercase, Ok(GroupId::from(lowercase)));
+
+        // Only the hyphenated form is recognized as UUID
+        let simple = "6BA7B8109DAD11D180B400C04FD430C8";
+        assert_eq!(GroupId::try_from(&json!(simple)), Ok(GroupId::from(simple)));
+        let not_uuid = "6BA7B810-9DAD-11D1-80B4-00C04FD430CX";
+        assert_eq!(
+            GroupId::try_from(&json!(not_uuid)),
+            Ok(GroupId::from(not_uuid))
+        );
     }
 }
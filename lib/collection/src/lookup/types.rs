@@ -13,17 +13,23 @@ use super::WithLookup;
 pub enum WithLookupInterface {
     Collection(String),
     WithLookup(WithLookup),
+    Multiple(Vec<WithLookup>),
 }
 
-impl From<WithLookupInterface> for WithLookup {
-    fn from(with_lookup: WithLookupInterface) -> Self {
-        match with_lookup {
-            WithLookupInterface::Collection(collection_name) => Self {
-                collection_name,
-                with_payload: Some(true.into()),
-                with_vectors: Some(false.into()),
-            },
-            WithLookupInterface::WithLookup(with_lookup) => with_lookup,
+impl WithLookupInterface {
+    /// Lookup in a single collection, or lookups in each of multiple collections
+    pub fn into_lookups(self) -> (Option<WithLookup>, Vec<WithLookup>) {
+        match self {
+            WithLookupInterface::Collection(collection_name) => {
+                let with_lookup = WithLookup {
+                    collection_name,
+                    with_payload: Some(true.into()),
+                    with_vectors: Some(false.into()),
+                };
+                (Some(with_lookup), vec![])
+            }
+            WithLookupInterface::WithLookup(with_lookup) => (Some(with_lookup), vec![]),
+            WithLookupInterface::Multiple(with_lookups) => (None, with_lookups),
         }
     }
 }
@@ -34,6 +40,7 @@ pub enum PseudoId {
     String(String),
     NumberU64(u64),
     NumberI64(i64),
+    Bool(bool),
 }
 
 impl Display for PseudoId {
@@ -42,16 +49,21 @@ impl Display for PseudoId {
             PseudoId::String(s) => write!(f, "{}", s),
             PseudoId::NumberU64(n) => write!(f, "{}", n),
             PseudoId::NumberI64(n) => write!(f, "{}", n),
+            PseudoId::Bool(b) => write!(f, "{}", b),
         }
     }
 }
 
-impl From<GroupId> for PseudoId {
-    fn from(id: GroupId) -> Self {
+impl TryFrom<GroupId> for PseudoId {
+    type Error = ConversionError;
+
+    fn try_from(id: GroupId) -> Result<Self, Self::Error> {
         match id {
-            GroupId::String(s) => Self::String(s),
-            GroupId::NumberU64(n) => Self::NumberU64(n),
-            GroupId::NumberI64(n) => Self::NumberI64(n),
+            GroupId::String(s) => Ok(Self::String(s)),
+            GroupId::NumberU64(n) => Ok(Self::NumberU64(n)),
+            GroupId::NumberI64(n) => Ok(Self::NumberI64(n)),
+            GroupId::Bool(b) => Ok(Self::Bool(b)),
+            GroupId::Composite(_) => Err(ConversionError::CompositeError),
         }
     }
 }
@@ -62,6 +74,7 @@ impl From<PseudoId> for GroupId {
             PseudoId::String(s) => Self::String(s),
             PseudoId::NumberU64(n) => Self::NumberU64(n),
             PseudoId::NumberI64(n) => Self::NumberI64(n),
+            PseudoId::Bool(b) => Self::Bool(b),
         }
     }
 }
@@ -70,6 +83,10 @@ impl From<PseudoId> for GroupId {
 pub enum ConversionError {
     IntError(core::num::TryFromIntError),
     ParseError(uuid::Error),
+    /// Booleans can't be point IDs
+    BoolError,
+    /// Composite group ids can't be used to look up points
+    CompositeError,
 }
 
 impl TryFrom<PseudoId> for PointIdType {
@@ -84,6 +101,7 @@ impl TryFrom<PseudoId> for PointIdType {
             PseudoId::NumberI64(n) => Ok(PointIdType::NumId(
                 u64::try_from(n).map_err(ConversionError::IntError)?,
             )),
+            PseudoId::Bool(_) => Err(ConversionError::BoolError),
         }
     }
 }
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use validator::{Validate, ValidationErrors};
 
-use crate::config::{CollectionParams, WalConfig};
+use crate::config::{CollectionParams, SearchDefaults, WalConfig};
 use crate::operations::types::CollectionResult;
 use crate::optimizers_builder::OptimizersConfig;
 
@@ -197,6 +197,8 @@ impl DiffConfig<WalConfig> for WalConfigDiff {}
 
 impl DiffConfig<CollectionParams> for CollectionParamsDiff {}
 
+impl DiffConfig<SearchDefaults> for SearchDefaults {}
+
 impl From<HnswConfig> for HnswConfigDiff {
     fn from(config: HnswConfig) -> Self {
         HnswConfigDiff::from_full(&config).unwrap()
@@ -391,4 +393,17 @@ mod tests {
         let new_config = update.update(&base_config).unwrap();
         assert_eq!(new_config.wal_segments_ahead, 2)
     }
+
+    #[test]
+    fn test_search_defaults_update() {
+        let base_config = SearchDefaults {
+            hnsw_ef: Some(64),
+            score_threshold: Some(0.5),
+            ..Default::default()
+        };
+        let update: SearchDefaults = serde_json::from_str(r#"{ "hnsw_ef": 128 }"#).unwrap();
+        let new_config = update.update(&base_config).unwrap();
+        assert_eq!(new_config.hnsw_ef, Some(128));
+        assert_eq!(new_config.score_threshold, Some(0.5));
+    }
 }
@@ -22,10 +22,10 @@ use tonic::Status;
 
 use super::consistency_params::ReadConsistency;
 use super::types::{
-    BaseGroupRequest, ContextExamplePair, CoreSearchRequest, DiscoverRequestInternal, GroupsResult,
-    PointGroup, QueryEnum, RecommendExample, RecommendGroupsRequestInternal, RecommendStrategy,
-    SearchGroupsRequestInternal, SparseIndexParams, SparseVectorParams, VectorParamsDiff,
-    VectorsConfigDiff,
+    BaseGroupRequest, ContextExamplePair, CoreSearchRequest, Direction, DiscoverRequestInternal,
+    GroupByInterface, GroupStats, GroupsResult, OrderBy, PointGroup, QueryEnum, RecommendExample,
+    RecommendGroupsRequestInternal, RecommendStrategy, SearchGroupsRequestInternal,
+    SparseIndexParams, SparseVectorParams, VectorParamsDiff, VectorsConfigDiff, WithGroupStats,
 };
 use crate::config::{
     default_replication_factor, default_write_consistency_factor, CollectionConfig,
@@ -692,6 +692,7 @@ impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
                     None
                 }
             },
+            search_defaults: None,
         })
     }
 }
@@ -1194,6 +1195,38 @@ impl TryFrom<api::grpc::qdrant::SearchPoints> for SearchRequestInternal {
     }
 }
 
+/// Fields to group by of gRPC group requests, where further fields are listed separately
+fn group_by_interface(group_by: String, additional_group_by: Vec<String>) -> GroupByInterface {
+    if additional_group_by.is_empty() {
+        GroupByInterface::Field(group_by)
+    } else {
+        GroupByInterface::Fields(
+            std::iter::once(group_by)
+                .chain(additional_group_by)
+                .collect(),
+        )
+    }
+}
+
+/// Lookups of gRPC group requests, where lookups in multiple collections are listed separately
+fn with_lookup_interface(
+    with_lookup: Option<api::grpc::qdrant::WithLookup>,
+    with_lookups: Vec<api::grpc::qdrant::WithLookup>,
+) -> Result<Option<WithLookupInterface>, Status> {
+    match (with_lookup, with_lookups.is_empty()) {
+        (Some(_), false) => Err(Status::invalid_argument(
+            "Only one of with_lookup and with_lookups can be set",
+        )),
+        (with_lookup, true) => with_lookup.map(TryInto::try_into).transpose(),
+        (None, false) => Ok(Some(WithLookupInterface::Multiple(
+            with_lookups
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        ))),
+    }
+}
+
 impl TryFrom<api::grpc::qdrant::SearchPointGroups> for SearchGroupsRequestInternal {
     type Error = Status;
 
@@ -1234,10 +1267,15 @@ impl TryFrom<api::grpc::qdrant::SearchPointGroups> for SearchGroupsRequestIntern
             with_vector,
             score_threshold,
             group_request: BaseGroupRequest {
-                group_by: value.group_by,
+                group_by: group_by_interface(value.group_by, value.additional_group_by),
                 limit: value.limit,
                 group_size: value.group_size,
-                with_lookup: value.with_lookup.map(|l| l.try_into()).transpose()?,
+                offset: value.offset,
+                with_lookup: with_lookup_interface(value.with_lookup, value.with_lookups)?,
+                with_stats: value.with_stats.map(Into::into),
+                order_by: value.order_by.map(TryInto::try_into).transpose()?,
+                with_missing: value.with_missing,
+                approximate: value.approximate,
             },
         })
     }
@@ -1249,6 +1287,56 @@ impl From<PointGroup> for api::grpc::qdrant::PointGroup {
             hits: group.hits.into_iter().map_into().collect(),
             id: Some(group.id.into()),
             lookup: group.lookup.map(|record| record.into()),
+            lookups: group
+                .lookups
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(collection_name, record)| (collection_name, record.into()))
+                .collect(),
+            stats: group.stats.map(Into::into),
+        }
+    }
+}
+
+impl From<api::grpc::qdrant::WithGroupStats> for WithGroupStats {
+    fn from(value: api::grpc::qdrant::WithGroupStats) -> Self {
+        Self { sum: value.sum }
+    }
+}
+
+impl TryFrom<api::grpc::qdrant::OrderBy> for OrderBy {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::OrderBy) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: value.key,
+            direction: value.direction.map(Direction::try_from).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<i32> for Direction {
+    type Error = Status;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        let direction = api::grpc::qdrant::Direction::from_i32(value).ok_or_else(|| {
+            Status::invalid_argument(format!("Unknown order direction: {}", value))
+        })?;
+        Ok(match direction {
+            api::grpc::qdrant::Direction::Asc => Direction::Asc,
+            api::grpc::qdrant::Direction::Desc => Direction::Desc,
+        })
+    }
+}
+
+impl From<GroupStats> for api::grpc::qdrant::GroupStats {
+    fn from(stats: GroupStats) -> Self {
+        Self {
+            hit_count: stats.hit_count as u64,
+            min_score: stats.min_score,
+            max_score: stats.max_score,
+            avg_score: stats.avg_score,
+            sum: stats.sum,
         }
     }
 }
@@ -1453,10 +1541,15 @@ impl TryFrom<api::grpc::qdrant::RecommendPointGroups> for RecommendGroupsRequest
             with_vector,
             score_threshold,
             group_request: BaseGroupRequest {
-                group_by: value.group_by,
+                group_by: group_by_interface(value.group_by, value.additional_group_by),
                 limit: value.limit,
                 group_size: value.group_size,
-                with_lookup: value.with_lookup.map(|l| l.try_into()).transpose()?,
+                offset: value.offset,
+                with_lookup: with_lookup_interface(value.with_lookup, value.with_lookups)?,
+                with_stats: value.with_stats.map(Into::into),
+                order_by: value.order_by.map(TryInto::try_into).transpose()?,
+                with_missing: value.with_missing,
+                approximate: value.approximate,
             },
         })
     }
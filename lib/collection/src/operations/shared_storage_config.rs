@@ -21,6 +21,16 @@ pub struct SharedStorageConfig {
     pub search_timeout: Duration,
     pub update_concurrency: Option<NonZeroUsize>,
     pub is_distributed: bool,
+    pub update_batching: Option<UpdateBatchingConfig>,
+}
+
+/// Coalescing of upserts arriving in a burst into a single update
+#[derive(Clone, Copy, Debug)]
+pub struct UpdateBatchingConfig {
+    /// How long to wait for more upserts after the first one
+    pub window: Duration,
+    /// Apply the batch early once it has this many upserts
+    pub max_operations: usize,
 }
 
 impl Default for SharedStorageConfig {
@@ -33,6 +43,7 @@ impl Default for SharedStorageConfig {
             search_timeout: DEFAULT_SEARCH_TIMEOUT,
             update_concurrency: None,
             is_distributed: false,
+            update_batching: None,
         }
     }
 }
@@ -46,6 +57,7 @@ impl SharedStorageConfig {
         search_timeout: Option<Duration>,
         update_concurrency: Option<NonZeroUsize>,
         is_distributed: bool,
+        update_batching: Option<UpdateBatchingConfig>,
     ) -> Self {
         let update_queue_size = update_queue_size.unwrap_or(match node_type {
             NodeType::Normal => DEFAULT_UPDATE_QUEUE_SIZE,
@@ -59,6 +71,7 @@ impl SharedStorageConfig {
             search_timeout: search_timeout.unwrap_or(DEFAULT_SEARCH_TIMEOUT),
             update_concurrency,
             is_distributed,
+            update_batching,
         }
     }
 }
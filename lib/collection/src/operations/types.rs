@@ -236,14 +236,14 @@ pub struct RemoteShardInfo {
 
 /// `Acknowledged` - Request is saved to WAL and will be process in a queue.
 /// `Completed` - Request is completed, changes are actual.
-#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum UpdateStatus {
     Acknowledged,
     Completed,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct UpdateResult {
     /// Sequential number of the operation
@@ -805,6 +805,30 @@ pub struct PointGroup {
     /// Record that has been looked up using the group id
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lookup: Option<Record>,
+    /// Records that have been looked up using the group id, by the name of their collection.
+    /// Only present if `with_lookup` is a list of lookups.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lookups: Option<HashMap<String, Record>>,
+    /// Statistics of the hits of the group, if requested with `with_stats`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<GroupStats>,
+}
+
+/// Statistics of the hits of a group found by the search, there may be more of them than the
+/// returned hits
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+pub struct GroupStats {
+    /// Number of the hits
+    pub hit_count: usize,
+    /// Lowest score of the hits
+    pub min_score: ScoreType,
+    /// Highest score of the hits
+    pub max_score: ScoreType,
+    /// Average score of the hits
+    pub avg_score: ScoreType,
+    /// Sum of the numeric values of the `sum` payload field over the hits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sum: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -849,6 +873,44 @@ pub struct CountResult {
     pub count: usize,
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Validate)]
+#[serde(rename_all = "snake_case")]
+pub struct GroupsCountRequest {
+    #[serde(flatten)]
+    #[validate]
+    pub count_request: GroupsCountRequestInternal,
+    /// Specify in which shards to look for the points, if not specified - look in all shards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard_key: Option<ShardKeySelector>,
+}
+
+/// Groups Count Request
+/// Estimates the number of groups of points which satisfy the given filter, i.e. the number of
+/// distinct values of the group_by field. Useful to choose the limit of a group request.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Validate)]
+#[serde(rename_all = "snake_case")]
+pub struct GroupsCountRequestInternal {
+    /// Payload field to count the groups of, the same as `group_by` of group requests
+    #[validate(custom = "validate_group_by")]
+    pub group_by: GroupByInterface,
+    /// Look only for points which satisfies this conditions
+    #[validate]
+    pub filter: Option<Filter>,
+    /// Number of points to estimate the count from, sampled at random among the points with the
+    /// field. If there are no more points with the field, the count is exact. Default: 1000
+    #[validate(range(min = 1))]
+    pub sample: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GroupsCountResult {
+    /// Estimated number of groups
+    pub count: usize,
+    /// Whether all points with the field were read, so the count is exact
+    pub exact: bool,
+}
+
 #[derive(Error, Debug, Clone)]
 #[error("{0}")]
 pub enum CollectionError {
@@ -1685,13 +1747,57 @@ pub enum NodeType {
     Listener,
 }
 
+/// Payload field or list of payload fields to group by
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum GroupByInterface {
+    Field(String),
+    /// Groups by combinations of values of all the fields, the group id is a list of the
+    /// values in the order of the fields
+    Fields(Vec<String>),
+}
+
+impl GroupByInterface {
+    /// Fields to group by, a list of a single field groups the same way as the field itself
+    pub fn into_fields(self) -> Vec<String> {
+        match self {
+            GroupByInterface::Field(field) => vec![field],
+            GroupByInterface::Fields(fields) => fields,
+        }
+    }
+}
+
+impl From<String> for GroupByInterface {
+    fn from(field: String) -> Self {
+        GroupByInterface::Field(field)
+    }
+}
+
+fn validate_group_by(group_by: &GroupByInterface) -> Result<(), ValidationError> {
+    let fields = match group_by {
+        GroupByInterface::Field(field) => std::slice::from_ref(field),
+        GroupByInterface::Fields(fields) => fields.as_slice(),
+    };
+    if fields.is_empty() || fields.iter().any(|field| field.is_empty()) {
+        let mut err = ValidationError::new("length");
+        err.message = Some("must specify non-empty fields to group by".into());
+        err.add_param("min".into(), &1);
+        return Err(err);
+    }
+    Ok(())
+}
+
 #[derive(Validate, Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct BaseGroupRequest {
-    /// Payload field to group by, must be a string or number field.
+    /// Payload field to group by, must be a string, integer or boolean field.
     /// If the field contains more than 1 value, all values will be used for grouping.
     /// One point can be in multiple groups.
-    #[validate(length(min = 1))]
-    pub group_by: String,
+    /// Nested fields are addressed with dots, e.g. `metadata.author.id`, and fields of
+    /// objects in arrays with `[]`, e.g. `authors[].id`.
+    /// With a list of fields, points are grouped by combinations of their values, e.g.
+    /// `["document_id", "language"]`.
+    #[validate(custom = "validate_group_by")]
+    pub group_by: GroupByInterface,
 
     /// Maximum amount of points to return per group
     #[validate(range(min = 1))]
@@ -1701,8 +1807,66 @@ pub struct BaseGroupRequest {
     #[validate(range(min = 1))]
     pub limit: u32,
 
-    /// Look for points in another collection using the group ids
+    /// Amount of groups to skip, to page through the groups. Groups of the same best score are
+    /// ordered by their id, so pages are consistent as long as the collection doesn't change.
+    #[serde(default)]
+    pub offset: Option<u32>,
+
+    /// Look for points in another collection using the group ids.
+    /// With a list of lookups, points are looked up in each of the collections and returned in
+    /// `lookups`, e.g. to fetch the parent document along with its author.
     pub with_lookup: Option<WithLookupInterface>,
+
+    /// Compute statistics of the hits of each group
+    #[serde(default)]
+    pub with_stats: Option<WithGroupStats>,
+
+    /// Order hits within each group by a payload field instead of their score. Only the hits
+    /// found by the search are ordered, the best `group_size` of them are returned.
+    #[serde(default)]
+    #[validate]
+    pub order_by: Option<OrderBy>,
+
+    /// Collect points without a value of the group_by field, or with a null value, into a group
+    /// with the `"__missing__"` id instead of leaving them out. Default is `false`.
+    #[serde(default)]
+    pub with_missing: Option<bool>,
+
+    /// Keep only as many groups and hits as requested while grouping, instead of every group
+    /// found by the search. Uses less memory when grouping by a field with many distinct values,
+    /// but hits found before their group is kept are dropped, `with_stats` only covers the kept
+    /// hits, and `order_by` only orders them. Default is `false`.
+    #[serde(default)]
+    pub approximate: Option<bool>,
+}
+
+/// Order of hits by a payload field
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, Clone, PartialEq, Eq)]
+pub struct OrderBy {
+    /// Payload field to order by, numbers and strings are ordered by their value.
+    /// Hits without the field come last.
+    #[validate(length(min = 1))]
+    pub key: String,
+
+    /// Direction of the order, ascending by default
+    #[serde(default)]
+    pub direction: Option<Direction>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Statistics to compute for each group, in addition to the number and the scores of the hits
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WithGroupStats {
+    /// Numeric payload field to sum over the hits of each group
+    #[serde(default)]
+    pub sum: Option<String>,
 }
 
 impl From<SearchRequestInternal> for CoreSearchRequest {
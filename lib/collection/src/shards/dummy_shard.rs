@@ -41,6 +41,11 @@ impl DummyShard {
         self.dummy()
     }
 
+    /// Nothing to persist in a dummy shard
+    pub fn flush(&self) -> CollectionResult<()> {
+        Ok(())
+    }
+
     pub fn get_telemetry_data(&self) -> LocalShardTelemetry {
         LocalShardTelemetry {
             variant_name: Some("dummy shard".into()),
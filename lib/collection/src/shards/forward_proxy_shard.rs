@@ -150,6 +150,10 @@ impl ForwardProxyShard {
         self.wrapped_shard.on_optimizer_config_update().await
     }
 
+    pub fn flush(&self) -> CollectionResult<()> {
+        self.wrapped_shard.flush()
+    }
+
     pub fn get_telemetry_data(&self) -> LocalShardTelemetry {
         self.wrapped_shard.get_telemetry_data()
     }
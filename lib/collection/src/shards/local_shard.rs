@@ -1,7 +1,9 @@
+use std::cmp::min;
 use std::collections::{BTreeSet, HashMap};
 use std::mem::size_of;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::thread;
 
@@ -528,6 +530,45 @@ impl LocalShard {
         Ok(())
     }
 
+    /// Persist WAL and all segments to disk
+    ///
+    /// # Blocking
+    ///
+    /// Waits for segments being flushed in the background to finish.
+    pub fn flush(&self) -> CollectionResult<()> {
+        self.wal.lock().flush()?;
+        self.segments.read().flush_all(true)?;
+        Ok(())
+    }
+
+    /// Persist WAL and all segments to disk, and acknowledge persisted operations in WAL,
+    /// so they are not replayed when the shard is loaded again
+    ///
+    /// # Blocking
+    ///
+    /// Waits for segments being flushed in the background to finish.
+    pub async fn checkpoint(&self) -> CollectionResult<()> {
+        self.wal.lock().flush()?;
+        let confirmed_version = {
+            let segments = self.segments.read();
+            let flushed_version = segments.flush_all(true)?;
+            match segments.failed_operation.iter().min() {
+                None => flushed_version,
+                Some(&failed_operation) => min(failed_operation, flushed_version),
+            }
+        };
+
+        // Operations still needed by a queue proxy shard must be kept
+        let max_ack = self
+            .update_handler
+            .lock()
+            .await
+            .max_ack_version
+            .load(Ordering::Relaxed);
+        self.wal.lock().ack(confirmed_version.min(max_ack))?;
+        Ok(())
+    }
+
     /// Finishes ongoing update tasks
     pub async fn stop_gracefully(&self) {
         if let Err(err) = self.update_sender.load().send(UpdateSignal::Stop).await {
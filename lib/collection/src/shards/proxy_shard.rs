@@ -78,6 +78,10 @@ impl ProxyShard {
         self.wrapped_shard.on_optimizer_config_update().await
     }
 
+    pub fn flush(&self) -> CollectionResult<()> {
+        self.wrapped_shard.flush()
+    }
+
     pub async fn reinit_changelog(&self) -> CollectionResult<()> {
         // Blocks updates in the wrapped shard.
         let mut changed_points_guard = self.changed_points.write().await;
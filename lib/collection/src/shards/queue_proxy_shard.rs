@@ -103,6 +103,14 @@ impl QueueProxyShard {
             .await
     }
 
+    pub fn flush(&self) -> CollectionResult<()> {
+        self.inner
+            .as_ref()
+            .expect("Queue proxy has been finalized")
+            .wrapped_shard
+            .flush()
+    }
+
     pub fn get_telemetry_data(&self) -> LocalShardTelemetry {
         self.inner
             .as_ref()
@@ -672,6 +672,24 @@ impl ShardReplicaSet {
         }
     }
 
+    pub(crate) async fn flush(&self) -> CollectionResult<()> {
+        let read_local = self.local.read().await;
+        if let Some(shard) = &*read_local {
+            shard.flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) async fn checkpoint(&self) -> CollectionResult<()> {
+        let read_local = self.local.read().await;
+        if let Some(shard) = &*read_local {
+            shard.checkpoint().await
+        } else {
+            Ok(())
+        }
+    }
+
     /// Check if the are any locally disabled peers
     /// And if so, report them to the consensus
     pub fn sync_local_state<F>(&self, get_shard_transfers: F) -> CollectionResult<()>
@@ -425,6 +425,7 @@ mod tests {
             wal_config,
             hnsw_config: Default::default(),
             quantization_config: None,
+            search_defaults: None,
         };
 
         let shared_config = Arc::new(RwLock::new(config.clone()));
@@ -119,6 +119,24 @@ impl Shard {
         }
     }
 
+    pub fn flush(&self) -> CollectionResult<()> {
+        match self {
+            Shard::Local(local_shard) => local_shard.flush(),
+            Shard::Proxy(proxy_shard) => proxy_shard.flush(),
+            Shard::ForwardProxy(proxy_shard) => proxy_shard.flush(),
+            Shard::QueueProxy(proxy_shard) => proxy_shard.flush(),
+            Shard::Dummy(dummy_shard) => dummy_shard.flush(),
+        }
+    }
+
+    /// Flush the shard, and acknowledge flushed operations in the WAL of a local shard
+    pub async fn checkpoint(&self) -> CollectionResult<()> {
+        match self {
+            Shard::Local(local_shard) => local_shard.checkpoint().await,
+            shard => shard.flush(),
+        }
+    }
+
     pub fn is_update_in_progress(&self) -> bool {
         self.update_tracker()
             .map_or(false, UpdateTracker::is_update_in_progress)
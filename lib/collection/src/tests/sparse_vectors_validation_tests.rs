@@ -98,10 +98,15 @@ fn validate_error_sparse_vector_search_groups_request_internal() {
         with_vector: None,
         score_threshold: None,
         group_request: BaseGroupRequest {
-            group_by: "sparse".to_owned(),
+            group_by: "sparse".to_owned().into(),
             group_size: 5,
             limit: 5,
+            offset: None,
             with_lookup: None,
+            with_stats: None,
+            order_by: None,
+            with_missing: None,
+            approximate: None,
         },
     });
 }
@@ -42,6 +42,7 @@ fn create_collection_config() -> CollectionConfig {
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
+        search_defaults: None,
     }
 }
 
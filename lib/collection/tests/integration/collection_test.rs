@@ -1,9 +1,12 @@
 use std::collections::HashSet;
 use std::fs::File;
+use std::sync::Arc;
+use std::time::Duration;
 
 use collection::operations::payload_ops::{PayloadOps, SetPayloadOp};
 use collection::operations::point_ops::{Batch, PointOperations, PointStruct, WriteOrdering};
 use collection::operations::shard_selector_internal::ShardSelectorInternal;
+use collection::operations::shared_storage_config::{SharedStorageConfig, UpdateBatchingConfig};
 use collection::operations::types::{
     CountRequestInternal, PointRequestInternal, RecommendRequestInternal, ScrollRequestInternal,
     SearchRequestInternal, UpdateStatus,
@@ -18,7 +21,10 @@ use segment::types::{
 };
 use tempfile::Builder;
 
-use crate::common::{load_local_collection, simple_collection_fixture, N_SHARDS};
+use crate::common::{
+    load_local_collection, new_local_collection_with_storage_config, simple_collection_config,
+    simple_collection_fixture, N_SHARDS,
+};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_collection_updater() {
@@ -548,3 +554,54 @@ async fn test_collection_local_load_initializing_not_stuck() {
         }
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_batched_upserts_with_invalid_one() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let shared_storage_config = SharedStorageConfig {
+        update_batching: Some(UpdateBatchingConfig {
+            window: Duration::from_millis(100),
+            max_operations: 2,
+        }),
+        ..Default::default()
+    };
+    let collection = new_local_collection_with_storage_config(
+        "test".to_string(),
+        collection_dir.path(),
+        &collection_dir.path().join("snapshots"),
+        &simple_collection_config(1),
+        Arc::new(shared_storage_config),
+    )
+    .await
+    .unwrap();
+
+    let upsert = |id: u64, vector: Vec<f32>| {
+        CollectionUpdateOperations::PointOperation(PointOperations::from(vec![PointStruct {
+            id: id.into(),
+            vector: vector.into(),
+            payload: None,
+        }]))
+    };
+
+    // Second upsert has a vector of the wrong dimension
+    let (valid, invalid) = tokio::join!(
+        collection.update_from_client_simple(
+            upsert(1, vec![1.0, 0.0, 1.0, 1.0]),
+            true,
+            WriteOrdering::Weak,
+        ),
+        collection.update_from_client_simple(upsert(2, vec![1.0, 0.0]), true, WriteOrdering::Weak),
+    );
+    assert!(valid.is_ok(), "{valid:?}");
+    assert!(invalid.is_err());
+
+    let count_request = CountRequestInternal {
+        filter: None,
+        exact: true,
+    };
+    let count_res = collection
+        .count(count_request, None, &ShardSelectorInternal::All)
+        .await
+        .unwrap();
+    assert_eq!(count_res.count, 1);
+}
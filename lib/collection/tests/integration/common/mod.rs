@@ -6,6 +6,7 @@ use std::sync::Arc;
 
 use collection::collection::{Collection, RequestShardTransfer};
 use collection::config::{CollectionConfig, CollectionParams, WalConfig};
+use collection::operations::shared_storage_config::SharedStorageConfig;
 use collection::operations::types::{CollectionError, VectorParams};
 use collection::optimizers_builder::OptimizersConfig;
 use collection::shards::channel_service::ChannelService;
@@ -36,6 +37,23 @@ pub const TEST_OPTIMIZERS_CONFIG: OptimizersConfig = OptimizersConfig {
 #[cfg(test)]
 #[allow(dead_code)]
 pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32) -> Collection {
+    let collection_config = simple_collection_config(shard_number);
+    let snapshot_path = collection_path.join("snapshots");
+
+    // Default to a collection with all the shards local
+    new_local_collection(
+        "test".to_string(),
+        collection_path,
+        &snapshot_path,
+        &collection_config,
+    )
+    .await
+    .unwrap()
+}
+
+/// Config of a collection with a single 4-dimensional vector
+#[allow(dead_code)]
+pub fn simple_collection_config(shard_number: u32) -> CollectionConfig {
     let wal_config = WalConfig {
         wal_capacity_mb: 1,
         wal_segments_ahead: 0,
@@ -54,25 +72,14 @@ pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32
         ..CollectionParams::empty()
     };
 
-    let collection_config = CollectionConfig {
+    CollectionConfig {
         params: collection_params,
         optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
-    };
-
-    let snapshot_path = collection_path.join("snapshots");
-
-    // Default to a collection with all the shards local
-    new_local_collection(
-        "test".to_string(),
-        collection_path,
-        &snapshot_path,
-        &collection_config,
-    )
-    .await
-    .unwrap()
+        search_defaults: None,
+    }
 }
 
 pub fn dummy_on_replica_failure() -> ChangePeerState {
@@ -94,6 +101,19 @@ pub async fn new_local_collection(
     path: &Path,
     snapshots_path: &Path,
     config: &CollectionConfig,
+) -> Result<Collection, CollectionError> {
+    new_local_collection_with_storage_config(id, path, snapshots_path, config, Default::default())
+        .await
+}
+
+/// Collection with all the shards local, with the given storage config
+#[cfg(test)]
+pub async fn new_local_collection_with_storage_config(
+    id: CollectionId,
+    path: &Path,
+    snapshots_path: &Path,
+    config: &CollectionConfig,
+    shared_storage_config: Arc<SharedStorageConfig>,
 ) -> Result<Collection, CollectionError> {
     let collection = Collection::new(
         id,
@@ -101,7 +121,7 @@ pub async fn new_local_collection(
         path,
         snapshots_path,
         config,
-        Default::default(),
+        shared_storage_config,
         CollectionShardDistribution::all_local(Some(config.params.shard_number.into()), 0),
         ChannelService::new(REST_PORT),
         dummy_on_replica_failure(),
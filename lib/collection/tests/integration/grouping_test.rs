@@ -2,13 +2,14 @@ use collection::collection::Collection;
 use collection::grouping::group_by::{GroupRequest, SourceRequest};
 use collection::operations::point_ops::{Batch, WriteOrdering};
 use collection::operations::types::{
-    RecommendRequestInternal, SearchRequestInternal, UpdateStatus,
+    PointGroup, RecommendRequestInternal, SearchRequestInternal, UpdateStatus,
 };
 use collection::operations::CollectionUpdateOperations;
 use itertools::Itertools;
 use rand::distributions::Uniform;
 use rand::rngs::ThreadRng;
 use rand::Rng;
+use segment::data_types::groups::GroupId;
 use segment::data_types::vectors::DenseVector;
 use segment::types::{Filter, Payload, WithPayloadInterface, WithVector};
 use serde_json::json;
@@ -20,7 +21,11 @@ fn rand_dense_vector(rng: &mut ThreadRng, size: usize) -> DenseVector {
 }
 
 mod group_by {
+    use collection::grouping::count::count_groups;
     use collection::grouping::GroupBy;
+    use collection::operations::shard_selector_internal::ShardSelectorInternal;
+    use collection::operations::types::GroupsCountRequestInternal;
+    use segment::types::{Condition, FieldCondition, Match, ValueVariants};
 
     use super::*;
 
@@ -291,6 +296,170 @@ mod group_by {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn group_by_multiple_fields() {
+        let Resources { collection, .. } = setup(16, 8).await;
+
+        let mut group_by_request = GroupRequest::with_limit_from_request(
+            SourceRequest::Search(SearchRequestInternal {
+                vector: vec![0.5, 0.5, 0.5, 0.5].into(),
+                filter: None,
+                params: None,
+                limit: 4,
+                offset: None,
+                with_payload: Some(WithPayloadInterface::Bool(true)),
+                with_vector: None,
+                score_threshold: None,
+            }),
+            "docId".to_string(),
+            3,
+        );
+        group_by_request.group_by = vec!["docId".to_string(), "other_stuff".to_string()];
+
+        let group_by = GroupBy::new(group_by_request.clone(), &collection, |_| async {
+            unreachable!()
+        });
+
+        let result = group_by.execute().await;
+
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+
+        assert_eq!(result.len(), 4);
+
+        for group in result {
+            assert_eq!(group.hits.len(), group_by_request.group_size);
+
+            let payload = group.hits[0].payload.as_ref().unwrap();
+            let doc_id = payload.0["docId"].as_u64().unwrap();
+            assert_eq!(
+                group.id,
+                GroupId::Composite(vec![doc_id.into(), format!("{doc_id}foo").into()]),
+            );
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn with_offset() {
+        let resources = setup(16, 8).await;
+
+        let page = |offset: usize| {
+            let mut request = resources.request.clone();
+            request.offset = offset;
+            GroupBy::new(request, &resources.collection, |_| async { unreachable!() }).execute()
+        };
+
+        let first_page = page(0).await.unwrap();
+        let second_page = page(4).await.unwrap();
+        let both_pages = {
+            let mut request = resources.request.clone();
+            request.limit = 8;
+            GroupBy::new(request, &resources.collection, |_| async { unreachable!() })
+                .execute()
+                .await
+                .unwrap()
+        };
+
+        assert_eq!(second_page.len(), 4);
+        let ids = |groups: &[PointGroup]| groups.iter().map(|g| g.id.clone()).collect_vec();
+        assert_eq!(
+            [ids(&first_page), ids(&second_page)].concat(),
+            ids(&both_pages),
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn with_missing() {
+        let Resources {
+            mut request,
+            collection,
+        } = setup(4, 4).await;
+        let mut rng = rand::thread_rng();
+
+        let insert_points = CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: (100..104).map(|x: u64| x.into()).collect_vec(),
+                vectors: (0..4)
+                    .map(|_| rand_dense_vector(&mut rng, 4))
+                    .collect_vec()
+                    .into(),
+                payloads: (0..4)
+                    .map(|_| Some(Payload::from(json!({ "other_stuff": "nothing" }))))
+                    .collect_vec()
+                    .into(),
+            }
+            .into(),
+        );
+        collection
+            .update_from_client_simple(insert_points, true, WriteOrdering::default())
+            .await
+            .expect("insert failed");
+
+        request.limit = 5;
+        let without_missing =
+            GroupBy::new(request.clone(), &collection, |_| async { unreachable!() })
+                .execute()
+                .await
+                .unwrap();
+        assert_eq!(without_missing.len(), 4);
+
+        request.with_missing = true;
+        let result = GroupBy::new(request.clone(), &collection, |_| async { unreachable!() })
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 5);
+
+        let missing = result
+            .iter()
+            .find(|group| group.id == GroupId::from("__missing__"))
+            .unwrap();
+        assert_eq!(missing.hits.len(), request.group_size);
+        assert!(missing.hits.iter().all(|hit| hit.id >= 100.into()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn counting_groups() {
+        let Resources { collection, .. } = setup(16, 8).await;
+
+        let count = |sample, filter| {
+            count_groups(
+                GroupsCountRequestInternal {
+                    group_by: "docId".to_string().into(),
+                    filter,
+                    sample: Some(sample),
+                },
+                &collection,
+                None,
+                &ShardSelectorInternal::All,
+            )
+        };
+
+        let result = count(1000, None).await.unwrap();
+        assert_eq!(result.count, 16);
+        assert!(result.exact);
+
+        let result = count(
+            1000,
+            Some(Filter::new_must(Condition::Field(
+                FieldCondition::new_match(
+                    "docId".to_string(),
+                    Match::new_value(ValueVariants::Integer(3)),
+                ),
+            ))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.count, 1);
+        assert!(result.exact);
+
+        // Any 20 points are of at least 3 documents
+        let result = count(20, None).await.unwrap();
+        assert!(result.count >= 3);
+        assert!(!result.exact);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn zero_group_size() {
         let Resources { collection, .. } = setup(16, 8).await;
@@ -592,11 +761,74 @@ mod group_by_builder {
 
             let lookup = group.lookup.expect("lookup not found");
 
-            assert_eq!(PseudoId::from(group.id), PseudoId::from(lookup.id));
+            assert_eq!(
+                PseudoId::try_from(group.id).unwrap(),
+                PseudoId::from(lookup.id)
+            );
 
             let payload = lookup.payload.unwrap();
             let body = payload.0.get("body").unwrap().as_str().unwrap();
             assert_eq!(body, &format!("{} {BODY_TEXT}", lookup.id));
         }
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn group_by_with_multiple_lookups() {
+        let Resources {
+            mut request,
+            collection,
+            lookup_collection,
+            ..
+        } = setup(16, 8).await;
+
+        // Both collections are served by the same lookup collection
+        request.with_lookups = vec![
+            WithLookup {
+                collection_name: "docs".to_string(),
+                with_payload: Some(true.into()),
+                with_vectors: Some(false.into()),
+            },
+            WithLookup {
+                collection_name: "doc_vectors".to_string(),
+                with_payload: Some(false.into()),
+                with_vectors: Some(true.into()),
+            },
+        ];
+
+        let collection_by_name = |_: String| async { Some(lookup_collection.read().await) };
+
+        let result = GroupBy::new(request.clone(), &collection, collection_by_name)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(result.len(), request.limit);
+
+        for group in result {
+            assert!(group.lookup.is_none());
+            let lookups = group.lookups.expect("lookups not found");
+
+            let doc = &lookups["docs"];
+            assert_eq!(
+                PseudoId::try_from(group.id.clone()).unwrap(),
+                PseudoId::from(doc.id)
+            );
+            assert!(doc.payload.is_some());
+            assert!(doc.vector.is_none());
+
+            let doc_vectors = &lookups["doc_vectors"];
+            assert_eq!(
+                PseudoId::try_from(group.id).unwrap(),
+                PseudoId::from(doc_vectors.id)
+            );
+            assert!(doc_vectors.payload.is_none());
+            assert!(doc_vectors.vector.is_some());
+        }
+
+        request.with_lookups[1].collection_name = "docs".to_string();
+        let result = GroupBy::new(request, &collection, collection_by_name)
+            .execute()
+            .await;
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,249 @@
+//! Writes of large files with direct IO, bypassing the page cache.
+//!
+//! Optimizers write whole segments at once. Written pages stay in the page cache, and on an
+//! instance short of memory they evict pages of HNSW links and vectors which searches need.
+//! With direct IO enabled, see [`set_global`], files written through [`DirectWriter`] don't
+//! go through the page cache at all.
+//!
+//! Direct IO requires aligned offsets, sizes and memory, so data is collected in aligned
+//! buffers which are reused between writers.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+
+/// Alignment of offsets, sizes and memory of direct IO, the logical block size of most devices
+const ALIGNMENT: usize = 4096;
+
+/// Size of each buffer, a multiple of [`ALIGNMENT`]
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Maximum number of idle buffers kept for reuse
+const MAX_POOLED_BUFFERS: usize = 4;
+
+static DIRECT_IO: AtomicBool = AtomicBool::new(false);
+
+static BUFFER_POOL: Mutex<Vec<AlignedBuffer>> = Mutex::new(Vec::new());
+
+/// Write files of optimized segments with direct IO. Disabled by default.
+pub fn set_global(enabled: bool) {
+    DIRECT_IO.store(enabled, Ordering::Relaxed);
+}
+
+pub fn get_global() -> bool {
+    DIRECT_IO.load(Ordering::Relaxed)
+}
+
+struct AlignedBuffer {
+    data: Vec<u8>,
+    /// Offset of the aligned memory within `data`
+    start: usize,
+}
+
+impl AlignedBuffer {
+    /// Take a buffer from the pool, or allocate a new one
+    fn take() -> Self {
+        if let Some(buffer) = BUFFER_POOL.lock().pop() {
+            return buffer;
+        }
+
+        // Heap memory of a vector doesn't move, align within it
+        let data = vec![0; BUFFER_SIZE + ALIGNMENT];
+        let start = data.as_ptr().align_offset(ALIGNMENT);
+        Self { data, start }
+    }
+
+    fn release(self) {
+        let mut pool = BUFFER_POOL.lock();
+        if pool.len() < MAX_POOLED_BUFFERS {
+            pool.push(self);
+        }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.data[self.start..self.start + BUFFER_SIZE]
+    }
+}
+
+/// Writer of a file, with direct IO if enabled with [`set_global`] and supported by the file
+/// system. Otherwise it writes through the page cache like a buffered writer.
+///
+/// [`DirectWriter::finish`] must be called once all data is written, data still buffered is
+/// dropped along with the writer.
+pub struct DirectWriter {
+    file: File,
+    buffer: Option<AlignedBuffer>,
+    /// Number of bytes in the buffer
+    filled: usize,
+    /// Offset of the start of the buffer in the file, aligned
+    offset: u64,
+    direct: bool,
+}
+
+impl DirectWriter {
+    /// Create or truncate the file at `path`
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        let (file, direct) = open(path, &options)?;
+
+        Ok(Self {
+            file,
+            buffer: Some(AlignedBuffer::take()),
+            filled: 0,
+            offset: 0,
+            direct,
+        })
+    }
+
+    /// Append to the existing file at `path`
+    pub fn append(path: &Path) -> io::Result<Self> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true);
+        let (mut file, direct) = open(path, &options)?;
+
+        // Start at the last block of the file, with its data in the buffer
+        let len = file.metadata()?.len();
+        let offset = len - len % ALIGNMENT as u64;
+        let filled = (len - offset) as usize;
+
+        let mut buffer = AlignedBuffer::take();
+        if filled > 0 {
+            file.seek(SeekFrom::Start(offset))?;
+            let block = &mut buffer.as_mut_slice()[..ALIGNMENT];
+            let mut read = 0;
+            while read < filled {
+                match file.read(&mut block[read..])? {
+                    0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                    n => read += n,
+                }
+            }
+        }
+
+        Ok(Self {
+            file,
+            buffer: Some(buffer),
+            filled,
+            offset,
+            direct,
+        })
+    }
+
+    /// Whether the file is written with direct IO
+    pub fn is_direct(&self) -> bool {
+        self.direct
+    }
+
+    /// Write the rest of the data, set the length of the file and sync it to the disk
+    pub fn finish(mut self) -> io::Result<()> {
+        let len = self.offset + self.filled as u64;
+        // Direct IO writes whole blocks, the padding is truncated afterwards
+        let size = if self.direct {
+            self.filled.div_ceil(ALIGNMENT) * ALIGNMENT
+        } else {
+            self.filled
+        };
+
+        let mut buffer = self.buffer.take().expect("buffer is only taken by finish");
+        let data = buffer.as_mut_slice();
+        data[self.filled..size].fill(0);
+        self.file.seek(SeekFrom::Start(self.offset))?;
+        self.file.write_all(&data[..size])?;
+        buffer.release();
+
+        if self.direct {
+            self.file.set_len(len)?;
+        }
+        self.file.sync_data()
+    }
+
+    fn write_buffer(&mut self) -> io::Result<()> {
+        let buffer = self
+            .buffer
+            .as_mut()
+            .expect("buffer is only taken by finish");
+        self.file.seek(SeekFrom::Start(self.offset))?;
+        self.file.write_all(buffer.as_mut_slice())?;
+        self.offset += BUFFER_SIZE as u64;
+        self.filled = 0;
+        Ok(())
+    }
+}
+
+impl Write for DirectWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let buffer = self
+            .buffer
+            .as_mut()
+            .expect("buffer is only taken by finish");
+        let size = data.len().min(BUFFER_SIZE - self.filled);
+        buffer.as_mut_slice()[self.filled..self.filled + size].copy_from_slice(&data[..size]);
+        self.filled += size;
+
+        if self.filled == BUFFER_SIZE {
+            self.write_buffer()?;
+        }
+        Ok(size)
+    }
+
+    /// Data is only written in whole buffers, the rest is written by [`DirectWriter::finish`]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for DirectWriter {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            buffer.release();
+        }
+    }
+}
+
+/// Open the file with direct IO if enabled, returns whether it is
+fn open(path: &Path, options: &OpenOptions) -> io::Result<(File, bool)> {
+    #[cfg(target_os = "linux")]
+    if get_global() {
+        use std::os::unix::fs::OpenOptionsExt as _;
+
+        match options.clone().custom_flags(libc::O_DIRECT).open(path) {
+            Ok(file) => return Ok((file, true)),
+            // The file system doesn't support direct IO, e.g. tmpfs
+            Err(err) if err.kind() == io::ErrorKind::InvalidInput => {
+                log::debug!(
+                    "Direct IO is not supported for {path:?}, writing it through the page cache"
+                );
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok((options.open(path)?, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_direct_writer() {
+        let dir = tempfile::Builder::new().tempdir().unwrap();
+        let path = dir.path().join("data");
+        let data: Vec<u8> = (0..BUFFER_SIZE * 2 + 100).map(|i| i as u8).collect();
+
+        let mut writer = DirectWriter::create(&path).unwrap();
+        writer.write_all(&data[..BUFFER_SIZE + 10]).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), data[..BUFFER_SIZE + 10]);
+
+        let mut writer = DirectWriter::append(&path).unwrap();
+        writer.write_all(&data[BUFFER_SIZE + 10..]).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), data);
+    }
+}
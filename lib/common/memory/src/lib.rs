@@ -1,2 +1,5 @@
+pub mod direct_io;
 pub mod madvise;
+pub mod mlock;
 pub mod mmap_ops;
+pub mod mmap_registry;
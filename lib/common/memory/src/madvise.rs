@@ -2,42 +2,167 @@
 //! and [`memmap2::Advice`].
 
 use std::io;
+use std::ops::Range;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-/// Global [`Advice`] value, to trivially set [`Advice`] value
-/// used by all memmaps created by the `segment` crate.
+/// Global [`Advice`] values for each [`MmapKind`], to trivially set [`Advice`] values
+/// used by all memmaps created by the `segment` and `sparse` crates.
 ///
 /// See [`set_global`] and [`get_global`].
-static ADVICE: parking_lot::RwLock<Advice> = parking_lot::RwLock::new(Advice::Random);
+static ADVICE: parking_lot::RwLock<AdviceConfig> =
+    parking_lot::RwLock::new(AdviceConfig::all(Advice::Random));
 
-/// Set global [`Advice`] value.
+/// Set global [`Advice`] values.
 ///
 /// When the `segment` crate creates [`memmap2::Mmap`] or [`memmap2::MmapMut`]
 /// _for a memory-mapped, on-disk HNSW index or vector storage access_
 /// it will "advise" the created memmap with the current global [`Advice`] value
-/// (obtained with [`get_global`]).
+/// of its [`MmapKind`] (obtained with [`get_global`]).
 ///
-/// It is recommended to set the desired [`Advice`] value before calling any other function
-/// from the `segment` crate and not to change it afterwards.
+/// It is recommended to set the desired [`Advice`] values before calling any other function
+/// from the `segment` crate. Memory maps opened before are not advised again, unless
+/// [`crate::mmap_registry::readvise_all`] is called afterwards.
 ///
-/// The `segment` crate itself does not modify the global [`Advice`] value.
+/// The `segment` crate itself does not modify the global [`Advice`] values.
 ///
-/// The default global [`Advice`] value is [`Advice::Random`].
-pub fn set_global(advice: Advice) {
-    *ADVICE.write() = advice;
+/// The default global [`Advice`] value is [`Advice::Random`] for all kinds.
+pub fn set_global(config: AdviceConfig) {
+    *ADVICE.write() = config;
 }
 
-/// Get current global [`Advice`] value.
-pub fn get_global() -> Advice {
+/// Get current global [`Advice`] value for memmaps of `kind`.
+pub fn get_global(kind: MmapKind) -> Advice {
+    ADVICE.read().get(kind)
+}
+
+/// Get current global [`Advice`] values of all kinds.
+pub fn get_global_config() -> AdviceConfig {
     *ADVICE.read()
 }
 
+/// Kind of data in a memmap, each kind may be advised differently.
+///
+/// E.g. random access suits HNSW links, while sequential access suits full scans of vectors and
+/// rescoring with quantized vectors.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MmapKind {
+    /// Dense vectors
+    Vectors,
+    /// Links of the HNSW graph
+    HnswLinks,
+    /// Quantized vectors
+    Quantized,
+    /// Other files, e.g. flags of deleted vectors, always advised with the default value
+    Other,
+}
+
+/// [`Advice`] for each [`MmapKind`], from `storage.mmap_advice` of the settings.
+///
+/// Either a single value for all kinds, e.g. `random`, or a value per kind on top of the
+/// `default` one, e.g. `{default: random, vectors: sequential}`. Only access patterns are
+/// accepted, see [`Advice::is_access_pattern`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "AdviceConfigRepr")]
+pub struct AdviceConfig {
+    /// Advice for kinds which are not set
+    pub default: Advice,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vectors: Option<Advice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hnsw_links: Option<Advice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantized: Option<Advice>,
+}
+
+impl AdviceConfig {
+    /// Same `advice` for all kinds
+    pub const fn all(advice: Advice) -> Self {
+        Self {
+            default: advice,
+            vectors: None,
+            hnsw_links: None,
+            quantized: None,
+        }
+    }
+
+    pub fn get(&self, kind: MmapKind) -> Advice {
+        let advice = match kind {
+            MmapKind::Vectors => self.vectors,
+            MmapKind::HnswLinks => self.hnsw_links,
+            MmapKind::Quantized => self.quantized,
+            MmapKind::Other => None,
+        };
+        advice.unwrap_or(self.default)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AdviceConfigRepr {
+    All(Advice),
+    PerKind(AdviceConfigPerKind),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AdviceConfigPerKind {
+    #[serde(default = "default_advice")]
+    default: Advice,
+    vectors: Option<Advice>,
+    hnsw_links: Option<Advice>,
+    quantized: Option<Advice>,
+}
+
+const fn default_advice() -> Advice {
+    Advice::Random
+}
+
+impl TryFrom<AdviceConfigRepr> for AdviceConfig {
+    type Error = String;
+
+    fn try_from(repr: AdviceConfigRepr) -> Result<Self, Self::Error> {
+        let config = match repr {
+            AdviceConfigRepr::All(advice) => Self::all(advice),
+            AdviceConfigRepr::PerKind(AdviceConfigPerKind {
+                default,
+                vectors,
+                hnsw_links,
+                quantized,
+            }) => Self {
+                default,
+                vectors,
+                hnsw_links,
+                quantized,
+            },
+        };
+
+        let advices = [
+            Some(config.default),
+            config.vectors,
+            config.hnsw_links,
+            config.quantized,
+        ];
+        if let Some(advice) = advices
+            .into_iter()
+            .flatten()
+            .find(|a| !a.is_access_pattern())
+        {
+            return Err(format!(
+                "{advice:?} can't be used as mmap advice, use normal, random or sequential",
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
 /// Platform-independent version of [`memmap2::Advice`].
 /// See [`memmap2::Advice`] and [`madvise(2)`] man page.
 ///
 /// [`madvise(2)`]: https://man7.org/linux/man-pages/man2/madvise.2.html
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Advice {
     /// See [`memmap2::Advice::Normal`].
@@ -48,16 +173,61 @@ pub enum Advice {
 
     /// See [`memmap2::Advice::Sequential`].
     Sequential,
+
+    /// See [`memmap2::Advice::WillNeed`].
+    WillNeed,
+
+    /// See [`memmap2::UncheckedAdvice::DontNeed`].
+    ///
+    /// Pages of file mappings are dropped, and read from the file again on the next access.
+    DontNeed,
+
+    /// See [`memmap2::UncheckedAdvice::Free`]. Supported on Linux and macOS only.
+    ///
+    /// Applies to private anonymous mappings only, the OS rejects it for file mappings.
+    Free,
+
+    /// See [`memmap2::Advice::HugePage`]. Supported on Linux only.
+    HugePage,
+}
+
+impl Advice {
+    /// Whether the advice describes how memory maps will be accessed, rather than requests an
+    /// action on their pages. Only these can be used as global [`Advice`] values.
+    pub fn is_access_pattern(self) -> bool {
+        matches!(self, Advice::Normal | Advice::Random | Advice::Sequential)
+    }
 }
 
+/// [`memmap2`] advice, which is split into safe and unsafe ones
 #[cfg(unix)]
-impl From<Advice> for memmap2::Advice {
-    fn from(advice: Advice) -> Self {
-        match advice {
-            Advice::Normal => memmap2::Advice::Normal,
-            Advice::Random => memmap2::Advice::Random,
-            Advice::Sequential => memmap2::Advice::Sequential,
-        }
+enum Memmap2Advice {
+    Advice(memmap2::Advice),
+    Unchecked(memmap2::UncheckedAdvice),
+}
+
+#[cfg(unix)]
+impl Advice {
+    fn to_memmap2(self) -> io::Result<Memmap2Advice> {
+        let advice = match self {
+            Advice::Normal => Memmap2Advice::Advice(memmap2::Advice::Normal),
+            Advice::Random => Memmap2Advice::Advice(memmap2::Advice::Random),
+            Advice::Sequential => Memmap2Advice::Advice(memmap2::Advice::Sequential),
+            Advice::WillNeed => Memmap2Advice::Advice(memmap2::Advice::WillNeed),
+            Advice::DontNeed => Memmap2Advice::Unchecked(memmap2::UncheckedAdvice::DontNeed),
+            #[cfg(any(target_os = "linux", target_os = "macos", target_os = "ios"))]
+            Advice::Free => Memmap2Advice::Unchecked(memmap2::UncheckedAdvice::Free),
+            #[cfg(target_os = "linux")]
+            Advice::HugePage => Memmap2Advice::Advice(memmap2::Advice::HugePage),
+            #[allow(unreachable_patterns)] // All variants are supported on Linux
+            advice => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{advice:?} advice is not supported on this platform"),
+                ));
+            }
+        };
+        Ok(advice)
     }
 }
 
@@ -71,24 +241,200 @@ pub fn madvise(madviseable: &impl Madviseable, advice: Advice) -> io::Result<()>
 pub trait Madviseable {
     /// Advise OS how given memory map will be accessed. On non-Unix platforms this is a no-op.
     fn madvise(&self, advice: Advice) -> io::Result<()>;
+
+    /// Ask OS to start reading `range` of the memory map into the page cache in the background.
+    ///
+    /// Uses `MADV_WILLNEED` on Unix and `PrefetchVirtualMemory` on Windows, and returns without
+    /// waiting for the pages to be read.
+    fn prefetch(&self, range: Range<usize>) -> io::Result<()>;
+
+    /// Lock all pages of the memory map in RAM, so they are never evicted from the page cache.
+    ///
+    /// Uses `mlock` on Unix and `VirtualLock` on Windows. Fails if the pages don't fit the
+    /// limit of locked memory of the process, e.g. `RLIMIT_MEMLOCK`.
+    fn lock(&self) -> io::Result<()>;
+
+    /// Number of bytes of the memory map which are resident in RAM, out of all of them.
+    ///
+    /// Uses `mincore` on Unix, not supported on other platforms.
+    fn residency(&self) -> io::Result<Residency>;
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Residency {
+    pub resident_bytes: usize,
+    pub total_bytes: usize,
+}
+
+#[cfg(unix)]
+fn mincore(data: &[u8]) -> io::Result<Residency> {
+    if data.is_empty() {
+        return Ok(Residency::default());
+    }
+
+    // Safety: `sysconf` has no preconditions
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let pages = data.len().div_ceil(page_size);
+    let mut resident = vec![0; pages];
+    // Safety: `data` starts at a page boundary as a memory map, and `resident` has a byte for
+    // each of its pages
+    let res = unsafe {
+        libc::mincore(
+            data.as_ptr() as *mut libc::c_void,
+            data.len(),
+            resident.as_mut_ptr(),
+        )
+    };
+    if res != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let resident_pages = resident.iter().filter(|page| *page & 1 != 0).count();
+    Ok(Residency {
+        resident_bytes: (resident_pages * page_size).min(data.len()),
+        total_bytes: data.len(),
+    })
+}
+
+fn check_range(len: usize, range: &Range<usize>) -> io::Result<()> {
+    if range.start > range.end || range.end > len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("range {range:?} is out of bounds of memory map of {len} bytes"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn prefetch_virtual_memory(data: &[u8]) -> io::Result<()> {
+    use windows_sys::Win32::System::Memory::{PrefetchVirtualMemory, WIN32_MEMORY_RANGE_ENTRY};
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    if data.is_empty() {
+        return Ok(());
+    }
+
+    let entry = WIN32_MEMORY_RANGE_ENTRY {
+        VirtualAddress: data.as_ptr() as *mut _,
+        NumberOfBytes: data.len(),
+    };
+    // Safety: `entry` describes memory of a live memory map, which is only read by the call
+    let prefetched = unsafe { PrefetchVirtualMemory(GetCurrentProcess(), 1, &entry, 0) };
+    if prefetched == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn virtual_lock(data: &[u8]) -> io::Result<()> {
+    use windows_sys::Win32::System::Memory::VirtualLock;
+
+    if data.is_empty() {
+        return Ok(());
+    }
+
+    // Safety: `data` is memory of a live memory map, locking doesn't change its contents
+    let locked = unsafe { VirtualLock(data.as_ptr() as *const _, data.len()) };
+    if locked == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
 }
 
 impl Madviseable for memmap2::Mmap {
     fn madvise(&self, advice: Advice) -> io::Result<()> {
         #[cfg(unix)]
-        self.advise(advice.into())?;
+        match advice.to_memmap2()? {
+            Memmap2Advice::Advice(advice) => self.advise(advice)?,
+            // Safety: memory maps are only created for files, where pages dropped by `DontNeed`
+            // are read from the file again, and `Free` is rejected
+            Memmap2Advice::Unchecked(advice) => unsafe { self.unchecked_advise(advice)? },
+        }
         #[cfg(not(unix))]
         log::debug!("Ignore {advice:?} on this platform");
         Ok(())
     }
+
+    fn prefetch(&self, range: Range<usize>) -> io::Result<()> {
+        check_range(self.len(), &range)?;
+        #[cfg(unix)]
+        self.advise_range(memmap2::Advice::WillNeed, range.start, range.len())?;
+        #[cfg(windows)]
+        prefetch_virtual_memory(&self[range])?;
+        #[cfg(not(any(unix, windows)))]
+        log::debug!("Ignore prefetch of {range:?} on this platform");
+        Ok(())
+    }
+
+    fn lock(&self) -> io::Result<()> {
+        #[cfg(unix)]
+        return memmap2::Mmap::lock(self);
+        #[cfg(windows)]
+        return virtual_lock(self);
+        #[cfg(not(any(unix, windows)))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "locking memory maps is not supported on this platform",
+        ));
+    }
+
+    fn residency(&self) -> io::Result<Residency> {
+        #[cfg(unix)]
+        return mincore(self);
+        #[cfg(not(unix))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "residency of memory maps is not supported on this platform",
+        ));
+    }
 }
 
 impl Madviseable for memmap2::MmapMut {
     fn madvise(&self, advice: Advice) -> io::Result<()> {
         #[cfg(unix)]
-        self.advise(advice.into())?;
+        match advice.to_memmap2()? {
+            Memmap2Advice::Advice(advice) => self.advise(advice)?,
+            // Safety: memory maps are only created for shared file mappings, where dirty pages
+            // dropped by `DontNeed` stay in the page cache, and `Free` is rejected
+            Memmap2Advice::Unchecked(advice) => unsafe { self.unchecked_advise(advice)? },
+        }
         #[cfg(not(unix))]
         log::debug!("Ignore {advice:?} on this platform");
         Ok(())
     }
+
+    fn prefetch(&self, range: Range<usize>) -> io::Result<()> {
+        check_range(self.len(), &range)?;
+        #[cfg(unix)]
+        self.advise_range(memmap2::Advice::WillNeed, range.start, range.len())?;
+        #[cfg(windows)]
+        prefetch_virtual_memory(&self[range])?;
+        #[cfg(not(any(unix, windows)))]
+        log::debug!("Ignore prefetch of {range:?} on this platform");
+        Ok(())
+    }
+
+    fn lock(&self) -> io::Result<()> {
+        #[cfg(unix)]
+        return memmap2::MmapMut::lock(self);
+        #[cfg(windows)]
+        return virtual_lock(self);
+        #[cfg(not(any(unix, windows)))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "locking memory maps is not supported on this platform",
+        ));
+    }
+
+    fn residency(&self) -> io::Result<Residency> {
+        #[cfg(unix)]
+        return mincore(self);
+        #[cfg(not(unix))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "residency of memory maps is not supported on this platform",
+        ));
+    }
 }
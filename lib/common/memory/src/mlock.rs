@@ -0,0 +1,38 @@
+//! Locking of memory maps of hot index data in RAM, see `storage.mlock` of the settings.
+//!
+//! Pages of memory maps are evicted from the page cache under memory pressure, and every search
+//! touching them afterwards waits for the disk. Locking HNSW links and quantized vectors keeps
+//! the latency of searches predictable at the cost of memory which can't be reclaimed.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::madvise::Madviseable;
+
+static MLOCK: AtomicBool = AtomicBool::new(false);
+
+/// Lock memory maps of HNSW links and quantized vectors opened afterwards. Disabled by default.
+pub fn set_global(enabled: bool) {
+    MLOCK.store(enabled, Ordering::Relaxed);
+}
+
+pub fn get_global() -> bool {
+    MLOCK.load(Ordering::Relaxed)
+}
+
+/// Lock `mmap` of the file at `path` in RAM, if enabled with [`set_global`].
+///
+/// Failures are logged rather than returned, the memory map is still usable, its pages are
+/// just not protected from eviction.
+pub fn lock_if_enabled(mmap: &impl Madviseable, path: &Path) {
+    if !get_global() {
+        return;
+    }
+
+    if let Err(err) = mmap.lock() {
+        log::warn!(
+            "Failed to lock {} in memory, check the limit of locked memory (ulimit -l): {err}",
+            path.display(),
+        );
+    }
+}
@@ -8,7 +8,7 @@ use std::{io, mem, ops, time};
 use memmap2::{Mmap, MmapMut};
 
 use crate::madvise;
-use crate::madvise::Madviseable;
+use crate::madvise::{Madviseable, MmapKind};
 
 pub fn create_and_ensure_length(path: &Path, length: usize) -> io::Result<()> {
     let file = OpenOptions::new()
@@ -21,7 +21,7 @@ pub fn create_and_ensure_length(path: &Path, length: usize) -> io::Result<()> {
     Ok(())
 }
 
-pub fn open_read_mmap(path: &Path) -> io::Result<Mmap> {
+pub fn open_read_mmap(path: &Path, kind: MmapKind) -> io::Result<Mmap> {
     let file = OpenOptions::new()
         .read(true)
         .write(false)
@@ -30,12 +30,12 @@ pub fn open_read_mmap(path: &Path) -> io::Result<Mmap> {
         .open(path)?;
 
     let mmap = unsafe { Mmap::map(&file)? };
-    madvise::madvise(&mmap, madvise::get_global())?;
+    madvise::madvise(&mmap, madvise::get_global(kind))?;
 
     Ok(mmap)
 }
 
-pub fn open_write_mmap(path: &Path) -> io::Result<MmapMut> {
+pub fn open_write_mmap(path: &Path, kind: MmapKind) -> io::Result<MmapMut> {
     let file = OpenOptions::new()
         .read(true)
         .write(true)
@@ -43,7 +43,7 @@ pub fn open_write_mmap(path: &Path) -> io::Result<MmapMut> {
         .open(path)?;
 
     let mmap = unsafe { MmapMut::map_mut(&file)? };
-    madvise::madvise(&mmap, madvise::get_global())?;
+    madvise::madvise(&mmap, madvise::get_global(kind))?;
 
     Ok(mmap)
 }
@@ -67,6 +67,53 @@ impl PrefaultMmapPages {
     }
 }
 
+/// Load all pages of the file at `path` into the page cache, so the first reads of the file
+/// don't have to wait for the disk. Returns size of the file.
+///
+/// Uses `MADV_POPULATE_READ` where supported, and reads the whole file otherwise.
+pub fn populate_file_pages(path: &Path) -> io::Result<usize> {
+    let file = OpenOptions::new().read(true).open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    #[cfg(target_os = "linux")]
+    match mmap.advise(memmap2::Advice::PopulateRead) {
+        Ok(()) => return Ok(mmap.len()),
+        // Not supported by kernels older than 5.14
+        Err(err) => log::trace!("Failed to advise MADV_POPULATE_READ for {path:?}: {err}"),
+    }
+
+    prefault_mmap_pages(&mmap, Some(path));
+    Ok(mmap.len())
+}
+
+/// Ask OS to read the file at `path` into the page cache in the background, without waiting for
+/// it. Returns size of the file.
+pub fn prefetch_file_pages(path: &Path) -> io::Result<usize> {
+    let file = OpenOptions::new().read(true).open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+    mmap.prefetch(0..mmap.len())?;
+    Ok(mmap.len())
+}
+
+/// Drop pages of the file at `path` from the page cache, except for pages which are still mapped
+/// by some process or not written back yet. Supported on Linux only, elsewhere this is a no-op.
+pub fn drop_file_cache(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd as _;
+
+        let file = OpenOptions::new().read(true).open(path)?;
+        // Safety: `file` is open for the duration of the call
+        let res = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+        if res != 0 {
+            return Err(io::Error::from_raw_os_error(res));
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    log::debug!("Ignore dropping page cache of {path:?} on this platform");
+    Ok(())
+}
+
 fn prefault_mmap_pages<T>(mmap: &T, path: Option<&Path>)
 where
     T: Madviseable + ops::Deref<Target = [u8]>,
@@ -0,0 +1,128 @@
+//! Registry of live memory maps of large, read-mostly files.
+//!
+//! Memory maps are owned by the storages which opened them, the registry only keeps weak
+//! references along with the path and kind of each file. This allows to act on memory maps of
+//! a collection as a whole, e.g. to release their pages once the collection is idle, and to
+//! apply changes of the global advice to memory maps opened before, see [`readvise_all`].
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::madvise::{self, Madviseable, MmapKind};
+
+static MMAPS: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+struct Entry {
+    path: PathBuf,
+    kind: MmapKind,
+    mmap: Weak<dyn Madviseable + Send + Sync>,
+}
+
+/// Memory map in the registry, see [`mmaps_under`]
+pub struct RegisteredMmap {
+    pub path: PathBuf,
+    pub kind: MmapKind,
+    pub mmap: Arc<dyn Madviseable + Send + Sync>,
+}
+
+/// Add `mmap` of the file at `path` to the registry, until it is dropped
+pub fn register<M>(path: &Path, kind: MmapKind, mmap: &Arc<M>)
+where
+    M: Madviseable + Send + Sync + 'static,
+{
+    let mmap: Arc<dyn Madviseable + Send + Sync> = mmap.clone();
+    let mut mmaps = MMAPS.lock();
+    // Drop entries of memory maps dropped since
+    mmaps.retain(|entry| entry.mmap.strong_count() > 0);
+    mmaps.push(Entry {
+        path: path.to_path_buf(),
+        kind,
+        mmap: Arc::downgrade(&mmap),
+    });
+}
+
+/// Live memory maps of files within `dir`
+pub fn mmaps_under(dir: &Path) -> Vec<RegisteredMmap> {
+    MMAPS
+        .lock()
+        .iter()
+        .filter(|entry| entry.path.starts_with(dir))
+        .filter_map(|entry| {
+            Some(RegisteredMmap {
+                path: entry.path.clone(),
+                kind: entry.kind,
+                mmap: entry.mmap.upgrade()?,
+            })
+        })
+        .collect()
+}
+
+/// Advise all live memory maps with the current global advice of their kind, see
+/// [`madvise::get_global`]. Returns the number of advised memory maps.
+///
+/// [`madvise::set_global`] only applies to memory maps opened afterwards, call this after it to
+/// apply the new advice to the open ones as well.
+pub fn readvise_all() -> usize {
+    let config = madvise::get_global_config();
+    let mmaps: Vec<_> = MMAPS
+        .lock()
+        .iter()
+        .filter_map(|entry| Some((entry.path.clone(), entry.kind, entry.mmap.upgrade()?)))
+        .collect();
+
+    let mut advised = 0;
+    for (path, kind, mmap) in mmaps {
+        match mmap.madvise(config.get(kind)) {
+            Ok(()) => advised += 1,
+            Err(err) => log::warn!("Failed to advise {}: {err}", path.display()),
+        }
+    }
+    advised
+}
+
+/// Residency of memory maps of one kind, see [`residency_under`]
+#[derive(Clone, Debug, Serialize)]
+pub struct KindResidency {
+    pub kind: MmapKind,
+    pub files: usize,
+    pub resident_bytes: usize,
+    pub total_bytes: usize,
+}
+
+/// Bytes of live memory maps of files within `dir` resident in RAM, out of all of them, for
+/// each kind of the memory maps. Memory maps which can't be probed are skipped.
+pub fn residency_under(dir: &Path) -> Vec<KindResidency> {
+    let mut kinds: Vec<KindResidency> = vec![];
+
+    for registered in mmaps_under(dir) {
+        let residency = match registered.mmap.residency() {
+            Ok(residency) => residency,
+            Err(err) => {
+                log::debug!("Failed to probe {}: {err}", registered.path.display());
+                continue;
+            }
+        };
+
+        let index = match kinds.iter().position(|kind| kind.kind == registered.kind) {
+            Some(index) => index,
+            None => {
+                kinds.push(KindResidency {
+                    kind: registered.kind,
+                    files: 0,
+                    resident_bytes: 0,
+                    total_bytes: 0,
+                });
+                kinds.len() - 1
+            }
+        };
+        let kind = &mut kinds[index];
+        kind.files += 1;
+        kind.resident_bytes += residency.resident_bytes;
+        kind.total_bytes += residency.total_bytes;
+    }
+
+    kinds
+}
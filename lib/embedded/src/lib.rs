@@ -0,0 +1,492 @@
+//! In-process facade over the Qdrant engine.
+//!
+//! [`QdrantEmbedded`] exposes the same method names and gRPC request/response types as the
+//! official `qdrant-client` crate, but executes every call directly against the [`Dispatcher`]
+//! and [`TableOfContent`] living in the current process. Code written against it can later be
+//! switched to a remote Qdrant by replacing the facade with `QdrantClient`.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use api::grpc::qdrant::collections_server::Collections;
+use api::grpc::qdrant::points_server::Points;
+use api::grpc::qdrant::qdrant_server::Qdrant;
+use api::grpc::qdrant::{
+    shard_key, ClearPayloadPoints, CollectionOperationResponse, CountPoints, CountResponse,
+    CreateCollection, CreateFieldIndexCollection, DeleteCollection, DeleteFieldIndexCollection,
+    DeletePayloadPoints, DeletePoints, GetCollectionInfoRequest, GetCollectionInfoResponse,
+    GetPoints, GetResponse, HealthCheckReply, HealthCheckRequest, ListCollectionsRequest,
+    ListCollectionsResponse, PointId, PointStruct, PointsOperationResponse, PointsSelector,
+    ReadConsistency, RecommendBatchPoints, RecommendBatchResponse, RecommendPoints,
+    RecommendResponse, ScrollPoints, ScrollResponse, SearchBatchPoints, SearchBatchResponse,
+    SearchGroupsResponse, SearchPointGroups, SearchPoints, SearchResponse, SetPayloadPoints,
+    ShardKey, ShardKeySelector, UpdateCollection, UpsertPoints, WithPayloadSelector,
+    WithVectorsSelector, WriteOrdering,
+};
+use collection::shards::channel_service::ChannelService;
+use qdrant::common::helpers::{
+    create_general_purpose_runtime, create_search_runtime, create_update_runtime,
+};
+use qdrant::settings::Settings;
+use qdrant::tonic::api::collections_api::CollectionsService;
+use qdrant::tonic::api::points_api::PointsService;
+use qdrant::tonic::QdrantService;
+use storage::content_manager::consensus::persistent::Persistent;
+use storage::content_manager::toc::TableOfContent;
+use storage::dispatcher::Dispatcher;
+use tonic::Request;
+
+/// Qdrant engine embedded into the current process.
+///
+/// All calls are routed through the same service implementations as the gRPC API,
+/// so validation, timeouts and error semantics are identical to a remote instance.
+pub struct QdrantEmbedded {
+    dispatcher: Arc<Dispatcher>,
+    qdrant: QdrantService,
+    collections: CollectionsService,
+    points: PointsService,
+}
+
+impl QdrantEmbedded {
+    /// Wrap an already running [`Dispatcher`].
+    pub fn new(dispatcher: Arc<Dispatcher>) -> Self {
+        Self {
+            qdrant: QdrantService::default(),
+            collections: CollectionsService::new(dispatcher.clone()),
+            points: PointsService::new(dispatcher.clone()),
+            dispatcher,
+        }
+    }
+
+    /// Load storage described by `settings` and start a single-node engine.
+    ///
+    /// Mirrors the start-up sequence of the `main_lambda` binary, without any network listeners.
+    pub async fn from_settings(settings: &Settings) -> Result<Self> {
+        let persistent_consensus_state =
+            Persistent::load_or_init(&settings.storage.storage_path, true)?;
+
+        let search_runtime =
+            create_search_runtime(settings.storage.performance.max_search_threads)?;
+        let update_runtime =
+            create_update_runtime(settings.storage.performance.max_optimization_threads)?;
+        let general_runtime = create_general_purpose_runtime()?;
+
+        let toc = TableOfContent::new_sync(
+            &settings.storage,
+            search_runtime,
+            update_runtime,
+            general_runtime,
+            ChannelService::new(settings.service.http_port),
+            persistent_consensus_state.this_peer_id(),
+            None,
+        )
+        .await;
+
+        toc.clear_all_tmp_directories()?;
+
+        let dispatcher = Dispatcher::new(Arc::new(toc));
+        Ok(Self::new(Arc::new(dispatcher)))
+    }
+
+    /// Underlying dispatcher, for operations not covered by the facade.
+    pub fn dispatcher(&self) -> &Arc<Dispatcher> {
+        &self.dispatcher
+    }
+
+    pub async fn health_check(&self) -> Result<HealthCheckReply> {
+        let response = self
+            .qdrant
+            .health_check(Request::new(HealthCheckRequest {}))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // Collections
+
+    pub async fn list_collections(&self) -> Result<ListCollectionsResponse> {
+        let response = self
+            .collections
+            .list(Request::new(ListCollectionsRequest {}))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn has_collection(&self, collection_name: impl ToString) -> Result<bool> {
+        let collection_name = collection_name.to_string();
+        let response = self.list_collections().await?;
+        Ok(response
+            .collections
+            .into_iter()
+            .any(|collection| collection.name == collection_name))
+    }
+
+    pub async fn create_collection(
+        &self,
+        details: &CreateCollection,
+    ) -> Result<CollectionOperationResponse> {
+        let response = self
+            .collections
+            .create(Request::new(details.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn update_collection(
+        &self,
+        details: &UpdateCollection,
+    ) -> Result<CollectionOperationResponse> {
+        let response = self
+            .collections
+            .update(Request::new(details.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn delete_collection(
+        &self,
+        collection_name: impl ToString,
+    ) -> Result<CollectionOperationResponse> {
+        let request = DeleteCollection {
+            collection_name: collection_name.to_string(),
+            timeout: None,
+        };
+        let response = self.collections.delete(Request::new(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn collection_info(
+        &self,
+        collection_name: impl ToString,
+    ) -> Result<GetCollectionInfoResponse> {
+        let request = GetCollectionInfoRequest {
+            collection_name: collection_name.to_string(),
+        };
+        let response = self.collections.get(Request::new(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    // Points: updates
+
+    pub async fn upsert_points(
+        &self,
+        collection_name: impl ToString,
+        shard_key_selector: Option<Vec<shard_key::Key>>,
+        points: Vec<PointStruct>,
+        ordering: Option<WriteOrdering>,
+    ) -> Result<PointsOperationResponse> {
+        self._upsert_points(collection_name, shard_key_selector, points, false, ordering)
+            .await
+    }
+
+    pub async fn upsert_points_blocking(
+        &self,
+        collection_name: impl ToString,
+        shard_key_selector: Option<Vec<shard_key::Key>>,
+        points: Vec<PointStruct>,
+        ordering: Option<WriteOrdering>,
+    ) -> Result<PointsOperationResponse> {
+        self._upsert_points(collection_name, shard_key_selector, points, true, ordering)
+            .await
+    }
+
+    async fn _upsert_points(
+        &self,
+        collection_name: impl ToString,
+        shard_key_selector: Option<Vec<shard_key::Key>>,
+        points: Vec<PointStruct>,
+        wait: bool,
+        ordering: Option<WriteOrdering>,
+    ) -> Result<PointsOperationResponse> {
+        let request = UpsertPoints {
+            collection_name: collection_name.to_string(),
+            wait: Some(wait),
+            points,
+            ordering,
+            shard_key_selector: shard_key_selector.map(to_shard_key_selector),
+        };
+        let response = self.points.upsert(Request::new(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn delete_points(
+        &self,
+        collection_name: impl ToString,
+        shard_key_selector: Option<Vec<shard_key::Key>>,
+        points: &PointsSelector,
+        ordering: Option<WriteOrdering>,
+    ) -> Result<PointsOperationResponse> {
+        self._delete_points(collection_name, shard_key_selector, points, false, ordering)
+            .await
+    }
+
+    pub async fn delete_points_blocking(
+        &self,
+        collection_name: impl ToString,
+        shard_key_selector: Option<Vec<shard_key::Key>>,
+        points: &PointsSelector,
+        ordering: Option<WriteOrdering>,
+    ) -> Result<PointsOperationResponse> {
+        self._delete_points(collection_name, shard_key_selector, points, true, ordering)
+            .await
+    }
+
+    async fn _delete_points(
+        &self,
+        collection_name: impl ToString,
+        shard_key_selector: Option<Vec<shard_key::Key>>,
+        points: &PointsSelector,
+        wait: bool,
+        ordering: Option<WriteOrdering>,
+    ) -> Result<PointsOperationResponse> {
+        let request = DeletePoints {
+            collection_name: collection_name.to_string(),
+            wait: Some(wait),
+            points: Some(points.clone()),
+            ordering,
+            shard_key_selector: shard_key_selector.map(to_shard_key_selector),
+        };
+        let response = self.points.delete(Request::new(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn set_payload(&self, request: &SetPayloadPoints) -> Result<PointsOperationResponse> {
+        let response = self
+            .points
+            .set_payload(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn overwrite_payload(
+        &self,
+        request: &SetPayloadPoints,
+    ) -> Result<PointsOperationResponse> {
+        let response = self
+            .points
+            .overwrite_payload(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn delete_payload(
+        &self,
+        request: &DeletePayloadPoints,
+    ) -> Result<PointsOperationResponse> {
+        let response = self
+            .points
+            .delete_payload(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn clear_payload(
+        &self,
+        request: &ClearPayloadPoints,
+    ) -> Result<PointsOperationResponse> {
+        let response = self
+            .points
+            .clear_payload(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn create_field_index(
+        &self,
+        request: &CreateFieldIndexCollection,
+    ) -> Result<PointsOperationResponse> {
+        let response = self
+            .points
+            .create_field_index(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn delete_field_index(
+        &self,
+        request: &DeleteFieldIndexCollection,
+    ) -> Result<PointsOperationResponse> {
+        let response = self
+            .points
+            .delete_field_index(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // Points: reads
+
+    pub async fn get_points(
+        &self,
+        collection_name: impl ToString,
+        shard_key_selector: Option<Vec<shard_key::Key>>,
+        points: &[PointId],
+        with_vectors: Option<impl Into<WithVectorsSelector>>,
+        with_payload: Option<impl Into<WithPayloadSelector>>,
+        read_consistency: Option<ReadConsistency>,
+    ) -> Result<GetResponse> {
+        let request = GetPoints {
+            collection_name: collection_name.to_string(),
+            ids: points.to_vec(),
+            with_payload: with_payload.map(Into::into),
+            with_vectors: with_vectors.map(Into::into),
+            read_consistency,
+            shard_key_selector: shard_key_selector.map(to_shard_key_selector),
+        };
+        let response = self.points.get(Request::new(request)).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn search_points(&self, request: &SearchPoints) -> Result<SearchResponse> {
+        let response = self.points.search(Request::new(request.clone())).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn search_batch_points(
+        &self,
+        request: &SearchBatchPoints,
+    ) -> Result<SearchBatchResponse> {
+        let response = self
+            .points
+            .search_batch(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn search_groups(&self, request: &SearchPointGroups) -> Result<SearchGroupsResponse> {
+        let response = self
+            .points
+            .search_groups(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn scroll(&self, request: &ScrollPoints) -> Result<ScrollResponse> {
+        let response = self.points.scroll(Request::new(request.clone())).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn recommend(&self, request: &RecommendPoints) -> Result<RecommendResponse> {
+        let response = self.points.recommend(Request::new(request.clone())).await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn recommend_batch(
+        &self,
+        request: &RecommendBatchPoints,
+    ) -> Result<RecommendBatchResponse> {
+        let response = self
+            .points
+            .recommend_batch(Request::new(request.clone()))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn count(&self, request: &CountPoints) -> Result<CountResponse> {
+        let response = self.points.count(Request::new(request.clone())).await?;
+        Ok(response.into_inner())
+    }
+}
+
+fn to_shard_key_selector(keys: Vec<shard_key::Key>) -> ShardKeySelector {
+    ShardKeySelector {
+        shard_keys: keys
+            .into_iter()
+            .map(|key| ShardKey { key: Some(key) })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::grpc::qdrant::{
+        point_id, vectors, vectors_config, Distance, Vector, VectorParams, Vectors, VectorsConfig,
+    };
+    use tokio::runtime::Runtime;
+
+    use super::*;
+
+    fn point(id: u64, vector: Vec<f32>) -> PointStruct {
+        PointStruct {
+            id: Some(PointId {
+                point_id_options: Some(point_id::PointIdOptions::Num(id)),
+            }),
+            payload: Default::default(),
+            vectors: Some(Vectors {
+                vectors_options: Some(vectors::VectorsOptions::Vector(Vector {
+                    data: vector,
+                    indices: None,
+                })),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_upsert_and_search() {
+        let storage_dir = tempfile::Builder::new()
+            .prefix("storage")
+            .tempdir()
+            .unwrap();
+        let mut settings = Settings::new(None).unwrap();
+        settings.storage.storage_path = storage_dir.path().to_string_lossy().into_owned();
+        settings.storage.snapshots_path = storage_dir
+            .path()
+            .join("snapshots")
+            .to_string_lossy()
+            .into_owned();
+
+        let runtime = Runtime::new().unwrap();
+        let embedded = runtime
+            .block_on(QdrantEmbedded::from_settings(&settings))
+            .unwrap();
+
+        runtime.block_on(async {
+            embedded
+                .create_collection(&CreateCollection {
+                    collection_name: "docs".to_string(),
+                    vectors_config: Some(VectorsConfig {
+                        config: Some(vectors_config::Config::Params(VectorParams {
+                            size: 2,
+                            distance: Distance::Dot as i32,
+                            ..Default::default()
+                        })),
+                    }),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            assert!(embedded.has_collection("docs").await.unwrap());
+
+            let points = vec![point(1, vec![1.0, 0.0]), point(2, vec![0.0, 1.0])];
+            embedded
+                .upsert_points_blocking("docs", None, points, None)
+                .await
+                .unwrap();
+
+            let response = embedded
+                .search_points(&SearchPoints {
+                    collection_name: "docs".to_string(),
+                    vector: vec![0.2, 0.9],
+                    limit: 1,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            let ids: Vec<_> = response.result.into_iter().map(|hit| hit.id).collect();
+            assert_eq!(
+                ids,
+                [Some(PointId {
+                    point_id_options: Some(point_id::PointIdOptions::Num(2)),
+                })]
+            );
+
+            // Errors keep the status of the gRPC API
+            let err = embedded.collection_info("missing").await.unwrap_err();
+            assert_eq!(
+                err.downcast::<tonic::Status>().unwrap().code(),
+                tonic::Code::NotFound
+            );
+        });
+
+        // The engine owns runtimes, which must not be dropped from within `runtime`
+        drop(embedded);
+    }
+}
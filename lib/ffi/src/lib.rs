@@ -0,0 +1,580 @@
+//! C ABI around [`QdrantEmbedded`], for running Qdrant in-process from other languages.
+//!
+//! Requests and responses are exchanged as protobuf-encoded gRPC messages, addressed by their
+//! full gRPC method name (e.g. `/qdrant.Points/Search`). This lets any language with Qdrant's
+//! generated protobuf classes (such as the `qdrant_client.grpc` module of the Python client)
+//! talk to the embedded engine without the REST layer.
+//!
+//! Python example using `ctypes`:
+//!
+//! ```python
+//! import ctypes
+//! from qdrant_client import grpc
+//!
+//! class Buffer(ctypes.Structure):
+//!     _fields_ = [("data", ctypes.POINTER(ctypes.c_uint8)), ("len", ctypes.c_size_t)]
+//!
+//! lib = ctypes.CDLL("libqdrant_ffi.so")
+//! lib.qdrant_open.restype = ctypes.c_void_p
+//! error = Buffer()
+//! handle = lib.qdrant_open(b"/mnt/efs/config/config.yaml", ctypes.byref(error))
+//!
+//! request = grpc.SearchPoints(collection_name="docs", vector=[0.1, 0.2], limit=3)
+//! payload = request.SerializeToString()
+//! out = Buffer()
+//! method = b"/qdrant.Points/Search"
+//! code = lib.qdrant_call(ctypes.c_void_p(handle), method, payload, len(payload), ctypes.byref(out))
+//! data = ctypes.string_at(out.data, out.len)
+//! lib.qdrant_buffer_free(out)
+//! response = grpc.SearchResponse.FromString(data) if code == 0 else data.decode()
+//! ```
+
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use api::grpc::qdrant::{
+    ClearPayloadPoints, CountPoints, CreateCollection, CreateFieldIndexCollection,
+    DeleteCollection, DeleteFieldIndexCollection, DeletePayloadPoints, DeletePoints,
+    GetCollectionInfoRequest, GetPoints, RecommendBatchPoints, RecommendPoints, ScrollPoints,
+    SearchBatchPoints, SearchPointGroups, SearchPoints, SetPayloadPoints, ShardKeySelector,
+    UpdateCollection, UpsertPoints,
+};
+use prost::Message;
+use qdrant::settings::Settings;
+use qdrant_embedded::QdrantEmbedded;
+use tokio::runtime::Runtime;
+use tonic::{Code, Status};
+
+/// Opaque handle to an embedded Qdrant instance.
+pub struct QdrantHandle {
+    // Dropped first: the engine owns its own runtimes, which must not be dropped
+    // from within `runtime`
+    embedded: QdrantEmbedded,
+    runtime: Runtime,
+}
+
+/// Byte buffer allocated by this library. Must be released with [`qdrant_buffer_free`].
+#[repr(C)]
+pub struct QdrantBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl QdrantBuffer {
+    fn empty() -> Self {
+        Self {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let mut bytes = bytes.into_boxed_slice();
+        let buffer = Self {
+            data: bytes.as_mut_ptr(),
+            len: bytes.len(),
+        };
+        std::mem::forget(bytes);
+        buffer
+    }
+}
+
+/// Load the configuration at `config_path` and start an embedded Qdrant instance.
+///
+/// Returns null on failure, in which case `out_error` (if not null) receives
+/// a UTF-8 error message.
+///
+/// # Safety
+///
+/// `config_path` must be a valid nul-terminated string.
+/// `out_error` must be null or point to a writable [`QdrantBuffer`].
+#[no_mangle]
+pub unsafe extern "C" fn qdrant_open(
+    config_path: *const c_char,
+    out_error: *mut QdrantBuffer,
+) -> *mut QdrantHandle {
+    let (handle, error) = match open(config_path) {
+        Ok(handle) => (Box::into_raw(Box::new(handle)), QdrantBuffer::empty()),
+        Err(err) => (
+            ptr::null_mut(),
+            QdrantBuffer::from_vec(format!("{err:#}").into_bytes()),
+        ),
+    };
+    if out_error.is_null() {
+        qdrant_buffer_free(error);
+    } else {
+        *out_error = error;
+    }
+    handle
+}
+
+unsafe fn open(config_path: *const c_char) -> anyhow::Result<QdrantHandle> {
+    let config_path = if config_path.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(config_path).to_str()?.to_string())
+    };
+
+    let settings = Settings::new(config_path)?;
+
+    // The host process may already have its own logger installed
+    let logger_config = settings
+        .logger
+        .clone()
+        .with_top_level_directive(settings.log_level.clone());
+    if let Err(err) = qdrant::tracing::setup(logger_config) {
+        eprintln!("Logging is not initialized by embedded Qdrant: {err}");
+    }
+    settings.validate_and_warn();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("qdrant-ffi")
+        .build()?;
+    let embedded = runtime.block_on(QdrantEmbedded::from_settings(&settings))?;
+
+    Ok(QdrantHandle { embedded, runtime })
+}
+
+/// Execute gRPC `method` with a protobuf-encoded `request`.
+///
+/// Returns a gRPC status code, `0` meaning success. On success `out` receives the
+/// protobuf-encoded response, otherwise a UTF-8 error message. Null pointers are rejected with
+/// `INVALID_ARGUMENT`, with no message if `out` is the null one.
+///
+/// # Safety
+///
+/// `handle` must be null or come from [`qdrant_open`] and not be closed yet.
+/// `method` must be null or a valid nul-terminated string.
+/// `request` must point to `request_len` readable bytes, or be null if `request_len` is `0`.
+/// `out` must be null or point to a writable [`QdrantBuffer`].
+#[no_mangle]
+pub unsafe extern "C" fn qdrant_call(
+    handle: *const QdrantHandle,
+    method: *const c_char,
+    request: *const u8,
+    request_len: usize,
+    out: *mut QdrantBuffer,
+) -> i32 {
+    if out.is_null() {
+        return Code::InvalidArgument as i32;
+    }
+
+    let result = if handle.is_null() {
+        Err(Status::invalid_argument("Handle is null"))
+    } else if method.is_null() {
+        Err(Status::invalid_argument("Method name is null"))
+    } else if request.is_null() && request_len != 0 {
+        Err(Status::invalid_argument("Request is null"))
+    } else {
+        let handle = &*handle;
+        let request: &[u8] = if request_len == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(request, request_len)
+        };
+
+        match CStr::from_ptr(method).to_str() {
+            Ok(method) => handle
+                .runtime
+                .block_on(call(&handle.embedded, method, request)),
+            Err(err) => Err(Status::invalid_argument(format!(
+                "Method name is not valid UTF-8: {err}"
+            ))),
+        }
+    };
+
+    let (code, bytes) = match result {
+        Ok(bytes) => (Code::Ok, bytes),
+        Err(status) => (status.code(), status.message().as_bytes().to_vec()),
+    };
+    *out = QdrantBuffer::from_vec(bytes);
+    code as i32
+}
+
+/// Release a buffer returned by this library.
+///
+/// # Safety
+///
+/// `buffer` must have been produced by this library and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn qdrant_buffer_free(buffer: QdrantBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+/// Stop the embedded instance and release its resources.
+///
+/// # Safety
+///
+/// `handle` must come from [`qdrant_open`] and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn qdrant_close(handle: *mut QdrantHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+async fn call(embedded: &QdrantEmbedded, method: &str, request: &[u8]) -> Result<Vec<u8>, Status> {
+    match method {
+        "/qdrant.Qdrant/HealthCheck" => encode(embedded.health_check().await),
+
+        "/qdrant.Collections/List" => encode(embedded.list_collections().await),
+        "/qdrant.Collections/Get" => {
+            let request: GetCollectionInfoRequest = decode(request)?;
+            encode(embedded.collection_info(request.collection_name).await)
+        }
+        "/qdrant.Collections/Create" => {
+            let request: CreateCollection = decode(request)?;
+            encode(embedded.create_collection(&request).await)
+        }
+        "/qdrant.Collections/Update" => {
+            let request: UpdateCollection = decode(request)?;
+            encode(embedded.update_collection(&request).await)
+        }
+        "/qdrant.Collections/Delete" => {
+            let request: DeleteCollection = decode(request)?;
+            encode(embedded.delete_collection(request.collection_name).await)
+        }
+
+        "/qdrant.Points/Upsert" => {
+            let UpsertPoints {
+                collection_name,
+                wait,
+                points,
+                ordering,
+                shard_key_selector,
+            } = decode(request)?;
+            let shard_keys = shard_key_selector.map(shard_keys);
+            if wait.unwrap_or(false) {
+                encode(
+                    embedded
+                        .upsert_points_blocking(collection_name, shard_keys, points, ordering)
+                        .await,
+                )
+            } else {
+                encode(
+                    embedded
+                        .upsert_points(collection_name, shard_keys, points, ordering)
+                        .await,
+                )
+            }
+        }
+        "/qdrant.Points/Delete" => {
+            let DeletePoints {
+                collection_name,
+                wait,
+                points,
+                ordering,
+                shard_key_selector,
+            } = decode(request)?;
+            let points =
+                points.ok_or_else(|| Status::invalid_argument("points_selector is expected"))?;
+            let shard_keys = shard_key_selector.map(shard_keys);
+            if wait.unwrap_or(false) {
+                encode(
+                    embedded
+                        .delete_points_blocking(collection_name, shard_keys, &points, ordering)
+                        .await,
+                )
+            } else {
+                encode(
+                    embedded
+                        .delete_points(collection_name, shard_keys, &points, ordering)
+                        .await,
+                )
+            }
+        }
+        "/qdrant.Points/Get" => {
+            let GetPoints {
+                collection_name,
+                ids,
+                with_payload,
+                with_vectors,
+                read_consistency,
+                shard_key_selector,
+            } = decode(request)?;
+            encode(
+                embedded
+                    .get_points(
+                        collection_name,
+                        shard_key_selector.map(shard_keys),
+                        &ids,
+                        with_vectors,
+                        with_payload,
+                        read_consistency,
+                    )
+                    .await,
+            )
+        }
+        "/qdrant.Points/SetPayload" => {
+            let request: SetPayloadPoints = decode(request)?;
+            encode(embedded.set_payload(&request).await)
+        }
+        "/qdrant.Points/OverwritePayload" => {
+            let request: SetPayloadPoints = decode(request)?;
+            encode(embedded.overwrite_payload(&request).await)
+        }
+        "/qdrant.Points/DeletePayload" => {
+            let request: DeletePayloadPoints = decode(request)?;
+            encode(embedded.delete_payload(&request).await)
+        }
+        "/qdrant.Points/ClearPayload" => {
+            let request: ClearPayloadPoints = decode(request)?;
+            encode(embedded.clear_payload(&request).await)
+        }
+        "/qdrant.Points/CreateFieldIndex" => {
+            let request: CreateFieldIndexCollection = decode(request)?;
+            encode(embedded.create_field_index(&request).await)
+        }
+        "/qdrant.Points/DeleteFieldIndex" => {
+            let request: DeleteFieldIndexCollection = decode(request)?;
+            encode(embedded.delete_field_index(&request).await)
+        }
+        "/qdrant.Points/Search" => {
+            let request: SearchPoints = decode(request)?;
+            encode(embedded.search_points(&request).await)
+        }
+        "/qdrant.Points/SearchBatch" => {
+            let request: SearchBatchPoints = decode(request)?;
+            encode(embedded.search_batch_points(&request).await)
+        }
+        "/qdrant.Points/SearchGroups" => {
+            let request: SearchPointGroups = decode(request)?;
+            encode(embedded.search_groups(&request).await)
+        }
+        "/qdrant.Points/Scroll" => {
+            let request: ScrollPoints = decode(request)?;
+            encode(embedded.scroll(&request).await)
+        }
+        "/qdrant.Points/Recommend" => {
+            let request: RecommendPoints = decode(request)?;
+            encode(embedded.recommend(&request).await)
+        }
+        "/qdrant.Points/RecommendBatch" => {
+            let request: RecommendBatchPoints = decode(request)?;
+            encode(embedded.recommend_batch(&request).await)
+        }
+        "/qdrant.Points/Count" => {
+            let request: CountPoints = decode(request)?;
+            encode(embedded.count(&request).await)
+        }
+
+        _ => Err(Status::unimplemented(format!(
+            "Method {method} is not supported by embedded Qdrant"
+        ))),
+    }
+}
+
+fn decode<M: Message + Default>(request: &[u8]) -> Result<M, Status> {
+    M::decode(request)
+        .map_err(|err| Status::invalid_argument(format!("Failed to decode request: {err}")))
+}
+
+fn encode<M: Message>(response: anyhow::Result<M>) -> Result<Vec<u8>, Status> {
+    match response {
+        Ok(response) => Ok(response.encode_to_vec()),
+        Err(err) => match err.downcast::<Status>() {
+            Ok(status) => Err(status),
+            Err(err) => Err(Status::internal(format!("{err:#}"))),
+        },
+    }
+}
+
+fn shard_keys(selector: ShardKeySelector) -> Vec<api::grpc::qdrant::shard_key::Key> {
+    selector
+        .shard_keys
+        .into_iter()
+        .filter_map(|shard_key| shard_key.key)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use api::grpc::qdrant::{
+        point_id, vectors, vectors_config, Distance, PointId, PointStruct, SearchResponse, Vector,
+        VectorParams, Vectors, VectorsConfig,
+    };
+
+    use super::*;
+
+    /// Call `method` of `handle` the way a foreign caller would
+    unsafe fn call_raw(
+        handle: *const QdrantHandle,
+        method: &[u8],
+        request: &[u8],
+    ) -> (i32, Vec<u8>) {
+        let method = CString::new(method).unwrap();
+        let mut out = QdrantBuffer::empty();
+        let code = qdrant_call(
+            handle,
+            method.as_ptr(),
+            request.as_ptr(),
+            request.len(),
+            &mut out,
+        );
+        let bytes = std::slice::from_raw_parts(out.data, out.len).to_vec();
+        qdrant_buffer_free(out);
+        (code, bytes)
+    }
+
+    fn point(id: u64, vector: Vec<f32>) -> PointStruct {
+        PointStruct {
+            id: Some(PointId {
+                point_id_options: Some(point_id::PointIdOptions::Num(id)),
+            }),
+            payload: Default::default(),
+            vectors: Some(Vectors {
+                vectors_options: Some(vectors::VectorsOptions::Vector(Vector {
+                    data: vector,
+                    indices: None,
+                })),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_buffer_roundtrip() {
+        let buffer = QdrantBuffer::from_vec(b"payload".to_vec());
+        assert_eq!(buffer.len, 7);
+        let bytes = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+        assert_eq!(bytes, b"payload");
+        unsafe { qdrant_buffer_free(buffer) };
+    }
+
+    #[test]
+    fn test_error_status_passthrough() {
+        let status = Status::not_found("Collection `docs` not found");
+        let err = encode::<CountPoints>(Err(status.into())).unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+
+        let err = encode::<CountPoints>(Err(anyhow::anyhow!("boom"))).unwrap_err();
+        assert_eq!(err.code(), Code::Internal);
+    }
+
+    #[test]
+    fn test_null_pointers_are_rejected() {
+        let method = CString::new("/qdrant.Qdrant/HealthCheck").unwrap();
+        let mut out = QdrantBuffer::empty();
+        unsafe {
+            let code = qdrant_call(ptr::null(), method.as_ptr(), ptr::null(), 0, &mut out);
+            assert_eq!(code, Code::InvalidArgument as i32);
+            let message = std::slice::from_raw_parts(out.data, out.len);
+            assert_eq!(message, b"Handle is null");
+            qdrant_buffer_free(out);
+
+            // Nowhere to write the message to
+            let code = qdrant_call(
+                ptr::null(),
+                method.as_ptr(),
+                ptr::null(),
+                0,
+                ptr::null_mut(),
+            );
+            assert_eq!(code, Code::InvalidArgument as i32);
+
+            // Path is not valid UTF-8
+            let mut error = QdrantBuffer::empty();
+            let handle = qdrant_open(b"\xff\0".as_ptr().cast(), &mut error);
+            assert!(handle.is_null());
+            assert!(error.len > 0);
+            qdrant_buffer_free(error);
+
+            qdrant_close(ptr::null_mut());
+            qdrant_buffer_free(QdrantBuffer::empty());
+        }
+    }
+
+    #[test]
+    fn test_call_roundtrip() {
+        let dir = tempfile::Builder::new().prefix("ffi").tempdir().unwrap();
+        let config_path = dir.path().join("config.yaml");
+        std::fs::write(
+            &config_path,
+            format!(
+                "storage:\n  storage_path: {}\n  snapshots_path: {}\n",
+                dir.path().join("storage").display(),
+                dir.path().join("snapshots").display(),
+            ),
+        )
+        .unwrap();
+        let config_path = CString::new(config_path.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let handle = qdrant_open(config_path.as_ptr(), ptr::null_mut());
+            assert!(!handle.is_null());
+
+            let create = CreateCollection {
+                collection_name: "docs".to_string(),
+                vectors_config: Some(VectorsConfig {
+                    config: Some(vectors_config::Config::Params(VectorParams {
+                        size: 2,
+                        distance: Distance::Dot as i32,
+                        ..Default::default()
+                    })),
+                }),
+                ..Default::default()
+            };
+            let (code, _) = call_raw(
+                handle,
+                b"/qdrant.Collections/Create",
+                &create.encode_to_vec(),
+            );
+            assert_eq!(code, Code::Ok as i32);
+
+            let upsert = UpsertPoints {
+                collection_name: "docs".to_string(),
+                wait: Some(true),
+                points: vec![point(1, vec![1.0, 0.0]), point(2, vec![0.0, 1.0])],
+                ..Default::default()
+            };
+            let (code, _) = call_raw(handle, b"/qdrant.Points/Upsert", &upsert.encode_to_vec());
+            assert_eq!(code, Code::Ok as i32);
+
+            let search = SearchPoints {
+                collection_name: "docs".to_string(),
+                vector: vec![0.9, 0.2],
+                limit: 1,
+                ..Default::default()
+            };
+            let (code, response) =
+                call_raw(handle, b"/qdrant.Points/Search", &search.encode_to_vec());
+            assert_eq!(code, Code::Ok as i32);
+            let response = SearchResponse::decode(response.as_slice()).unwrap();
+            assert_eq!(response.result.len(), 1);
+            assert_eq!(
+                response.result[0].id,
+                Some(PointId {
+                    point_id_options: Some(point_id::PointIdOptions::Num(1)),
+                })
+            );
+
+            // Invalid calls are reported with a status and a message
+            let (code, message) = call_raw(handle, b"/qdrant.Points/Search", b"\xff\xff");
+            assert_eq!(code, Code::InvalidArgument as i32);
+            assert!(String::from_utf8(message)
+                .unwrap()
+                .contains("Failed to decode"));
+
+            let (code, _) = call_raw(handle, b"/qdrant.Points/Unknown", &[]);
+            assert_eq!(code, Code::Unimplemented as i32);
+
+            let (code, _) = call_raw(handle, b"\xff", &[]);
+            assert_eq!(code, Code::InvalidArgument as i32);
+
+            let get = GetCollectionInfoRequest {
+                collection_name: "missing".to_string(),
+            };
+            let (code, _) = call_raw(handle, b"/qdrant.Collections/Get", &get.encode_to_vec());
+            assert_eq!(code, Code::NotFound as i32);
+
+            qdrant_close(handle);
+        }
+    }
+}
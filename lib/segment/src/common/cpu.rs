@@ -1,15 +1,114 @@
+/// Lambda allocates CPU in proportion to memory, a full vCPU per this many megabytes
+const LAMBDA_MEMORY_PER_VCPU_MB: usize = 1769;
+
+/// Lambda allocates at most this many vCPUs, at the maximum memory size
+const LAMBDA_MAX_VCPUS: usize = 6;
+
 /// Try to read number of CPUs from environment variable `QDRANT_NUM_CPUS`.
-/// If it is not set, use `num_cpus::get()`.
+/// If it is not set, use `num_cpus::get()`, limited by the vCPU share derived from the memory
+/// size of the Lambda function, when running on Lambda, and by the CPU quota of the cgroup, when
+/// running in a container. `num_cpus::get()` reports CPUs of the host in Lambda, and ignores
+/// quotas below a single CPU.
 pub fn get_num_cpus() -> usize {
-    match std::env::var("QDRANT_NUM_CPUS") {
-        Ok(val) => {
-            let num_cpus = val.parse::<usize>().unwrap_or(0);
-            if num_cpus > 0 {
-                num_cpus
-            } else {
-                num_cpus::get()
-            }
-        }
-        Err(_) => num_cpus::get(),
+    let num_cpus = std::env::var("QDRANT_NUM_CPUS")
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(0);
+    if num_cpus > 0 {
+        return num_cpus;
+    }
+
+    let mut num_cpus = num_cpus::get();
+    if let Some(memory_size_mb) = lambda_memory_size_mb() {
+        num_cpus = num_cpus.min(lambda_vcpus(memory_size_mb));
+    }
+    if let Some(quota_cpus) = cgroup_quota_cpus() {
+        num_cpus = num_cpus.min(quota_cpus);
+    }
+    num_cpus
+}
+
+fn lambda_memory_size_mb() -> Option<usize> {
+    std::env::var("AWS_LAMBDA_FUNCTION_MEMORY_SIZE")
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// Number of vCPUs Lambda allocates for `memory_size_mb`, a fraction of vCPU counts as one
+fn lambda_vcpus(memory_size_mb: usize) -> usize {
+    memory_size_mb
+        .div_ceil(LAMBDA_MEMORY_PER_VCPU_MB)
+        .clamp(1, LAMBDA_MAX_VCPUS)
+}
+
+/// CPUs allowed by the CPU quota of the cgroup, if limited. Reads the cgroup mounted at the
+/// root, which is the cgroup of the container with cgroup namespaces.
+#[cfg(target_os = "linux")]
+fn cgroup_quota_cpus() -> Option<usize> {
+    let read = |path: &str| std::fs::read_to_string(path).ok();
+
+    // cgroup v2
+    if let Some(cpu_max) = read("/sys/fs/cgroup/cpu.max") {
+        return parse_cpu_max(&cpu_max);
+    }
+
+    // cgroup v1
+    ["/sys/fs/cgroup/cpu", "/sys/fs/cgroup/cpu,cpuacct"]
+        .into_iter()
+        .find_map(|dir| {
+            let quota_us = read(&format!("{dir}/cpu.cfs_quota_us"))?;
+            let period_us = read(&format!("{dir}/cpu.cfs_period_us"))?;
+            // Quota is -1 if not limited
+            quota_cpus(
+                quota_us.trim().parse().ok()?,
+                period_us.trim().parse().ok()?,
+            )
+        })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cgroup_quota_cpus() -> Option<usize> {
+    None
+}
+
+/// Parse `cpu.max` of cgroup v2, `$MAX $PERIOD` where `$MAX` is `max` if not limited
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cpu_max(cpu_max: &str) -> Option<usize> {
+    let mut values = cpu_max.split_whitespace();
+    let quota_us = values.next()?.parse().ok()?;
+    let period_us = values.next()?.parse().ok()?;
+    quota_cpus(quota_us, period_us)
+}
+
+/// Number of CPUs for a quota of `quota_us` per `period_us`, a fraction of CPU counts as one
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn quota_cpus(quota_us: i64, period_us: i64) -> Option<usize> {
+    if quota_us <= 0 || period_us <= 0 {
+        return None;
+    }
+    Some((quota_us as usize).div_ceil(period_us as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lambda_vcpus() {
+        assert_eq!(lambda_vcpus(128), 1);
+        assert_eq!(lambda_vcpus(1769), 1);
+        assert_eq!(lambda_vcpus(1770), 2);
+        assert_eq!(lambda_vcpus(3008), 2);
+        assert_eq!(lambda_vcpus(10240), 6);
+    }
+
+    #[test]
+    fn test_quota_cpus() {
+        assert_eq!(parse_cpu_max("max 100000\n"), None);
+        assert_eq!(parse_cpu_max("50000 100000\n"), Some(1));
+        assert_eq!(parse_cpu_max("250000 100000\n"), Some(3));
+        assert_eq!(quota_cpus(-1, 100000), None);
+        assert_eq!(quota_cpus(200000, 100000), Some(2));
     }
 }
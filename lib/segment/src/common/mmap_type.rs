@@ -473,6 +473,7 @@ mod tests {
     use std::fmt::Debug;
     use std::iter;
 
+    use memory::madvise::MmapKind;
     use memory::mmap_ops;
     use rand::rngs::StdRng;
     use rand::{Rng, SeedableRng};
@@ -501,7 +502,7 @@ mod tests {
     fn check_open_zero_type<T: Sized + PartialEq + Debug + 'static>(zero: T) {
         let bytes = mem::size_of::<T>();
         let tempfile = create_temp_mmap_file(bytes);
-        let mmap = mmap_ops::open_write_mmap(tempfile.path()).unwrap();
+        let mmap = mmap_ops::open_write_mmap(tempfile.path(), MmapKind::Other).unwrap();
 
         let mmap_type: MmapType<T> = unsafe { MmapType::from(mmap) };
         assert_eq!(mmap_type.deref(), &zero);
@@ -531,7 +532,7 @@ mod tests {
     fn check_open_zero_slice<T: Sized + PartialEq + Debug + 'static>(len: usize, zero: T) {
         let bytes = mem::size_of::<T>() * len;
         let tempfile = create_temp_mmap_file(bytes);
-        let mmap = mmap_ops::open_write_mmap(tempfile.path()).unwrap();
+        let mmap = mmap_ops::open_write_mmap(tempfile.path(), MmapKind::Other).unwrap();
 
         let mmap_slice: MmapSlice<T> = unsafe { MmapSlice::from(mmap) };
         assert_eq!(mmap_slice.len(), len);
@@ -565,7 +566,7 @@ mod tests {
 
         // Write random values from template into mmap
         {
-            let mmap = mmap_ops::open_write_mmap(tempfile.path()).unwrap();
+            let mmap = mmap_ops::open_write_mmap(tempfile.path(), MmapKind::Other).unwrap();
             let mut mmap_slice: MmapSlice<T> = unsafe { MmapSlice::from(mmap) };
             assert_eq!(mmap_slice.len(), len);
             mmap_slice.copy_from_slice(&template);
@@ -573,7 +574,7 @@ mod tests {
 
         // Reopen and assert values from template
         {
-            let mmap = mmap_ops::open_write_mmap(tempfile.path()).unwrap();
+            let mmap = mmap_ops::open_write_mmap(tempfile.path(), MmapKind::Other).unwrap();
             let mmap_slice: MmapSlice<T> = unsafe { MmapSlice::from(mmap) };
             assert_eq!(mmap_slice.as_ref(), template);
         }
@@ -595,7 +596,7 @@ mod tests {
         // Fill bitslice
         {
             let mut rng = StdRng::seed_from_u64(42);
-            let mmap = mmap_ops::open_write_mmap(tempfile.path()).unwrap();
+            let mmap = mmap_ops::open_write_mmap(tempfile.path(), MmapKind::Other).unwrap();
             let mut mmap_bitslice = MmapBitSlice::from(mmap, header_size);
             (0..bits).for_each(|i| mmap_bitslice.set(i, rng.gen()));
         }
@@ -603,7 +604,7 @@ mod tests {
         // Reopen and assert contents
         {
             let mut rng = StdRng::seed_from_u64(42);
-            let mmap = mmap_ops::open_write_mmap(tempfile.path()).unwrap();
+            let mmap = mmap_ops::open_write_mmap(tempfile.path(), MmapKind::Other).unwrap();
             let mmap_bitslice = MmapBitSlice::from(mmap, header_size);
             (0..bits).for_each(|i| assert_eq!(mmap_bitslice[i], rng.gen::<bool>()));
         }
@@ -613,14 +614,14 @@ mod tests {
     fn test_zero_sized_type() {
         {
             let tempfile = create_temp_mmap_file(0);
-            let mmap = mmap_ops::open_write_mmap(tempfile.path()).unwrap();
+            let mmap = mmap_ops::open_write_mmap(tempfile.path(), MmapKind::Other).unwrap();
             let result = unsafe { MmapType::<()>::try_from(mmap).unwrap() };
             assert_eq!(result.deref(), &());
         }
 
         {
             let tempfile = create_temp_mmap_file(0);
-            let mmap = mmap_ops::open_write_mmap(tempfile.path()).unwrap();
+            let mmap = mmap_ops::open_write_mmap(tempfile.path(), MmapKind::Other).unwrap();
             let result = unsafe { MmapSlice::<()>::try_from(mmap).unwrap() };
             assert_eq!(result.as_ref(), &[]);
             assert_alignment::<_, ()>(result.as_ref());
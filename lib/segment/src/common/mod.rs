@@ -10,6 +10,7 @@ pub mod rocksdb_wrapper;
 pub mod utils;
 pub mod vector_utils;
 pub mod version;
+pub mod yielding;
 
 use std::sync::atomic::AtomicBool;
 
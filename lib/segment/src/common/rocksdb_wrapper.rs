@@ -151,6 +151,31 @@ impl DatabaseColumnWrapper {
         Ok(result)
     }
 
+    /// Get values of all `keys` in a single batch, in the same order as `keys`
+    pub fn multi_get_pinned<T, F>(
+        &self,
+        keys: &[Vec<u8>],
+        mut f: F,
+    ) -> OperationResult<Vec<Option<T>>>
+    where
+        F: FnMut(&[u8]) -> T,
+    {
+        let db = self.database.read();
+        let cf_handle = self.get_column_family(&db)?;
+        db.batched_multi_get_cf(cf_handle, keys, false)
+            .into_iter()
+            .map(|value| {
+                value
+                    .map(|value| value.map(|value| f(&value)))
+                    .map_err(|err| {
+                        OperationError::service_error(format!(
+                            "RocksDB batched_multi_get_cf error: {err}"
+                        ))
+                    })
+            })
+            .collect()
+    }
+
     pub fn remove<K>(&self, key: K) -> OperationResult<()>
     where
         K: AsRef<[u8]>,
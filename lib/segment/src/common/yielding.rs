@@ -0,0 +1,131 @@
+//! Cooperative yielding of optimizations to requests.
+//!
+//! With a single vCPU, an optimization occupies the CPU for seconds at a time, and requests
+//! arriving meanwhile wait for it. Optimization loops check [`check_process_yield`] instead of
+//! [`check_process_stopped`], which pauses the optimization while any request holds a
+//! [`RequestGuard`]. Requests take guards only while enabled with [`set_yield_to_requests`].
+//!
+//! Optimizations also pause while IO of the storage is congested, see [`set_io_congested`],
+//! leaving the remaining throughput to requests.
+//!
+//! A pause lasts at most [`MAX_PAUSE`], and is followed by at least [`MIN_SLICE`] of work, so
+//! optimizations still progress under a steady stream of requests or lasting congestion.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::common::operation_error::{check_process_stopped, OperationResult};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+const MAX_PAUSE: Duration = Duration::from_millis(500);
+const MIN_SLICE: Duration = Duration::from_millis(50);
+
+/// Number of requests currently being served
+static ACTIVE_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Requests take a [`RequestGuard`], see [`set_yield_to_requests`]
+static YIELD_TO_REQUESTS: AtomicBool = AtomicBool::new(false);
+
+/// IO of the storage is congested, e.g. throughput of a network filesystem is throttled
+static IO_CONGESTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Time the optimization on this thread was last resumed without waiting for requests
+    static FORCED_RESUME: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Marks a request as being served, optimizations are paused until it is dropped
+pub struct RequestGuard(());
+
+impl RequestGuard {
+    pub fn new() -> Self {
+        ACTIVE_REQUESTS.fetch_add(1, Ordering::Relaxed);
+        Self(())
+    }
+}
+
+impl Default for RequestGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        ACTIVE_REQUESTS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Set whether optimizations yield to requests, applies to requests started afterwards
+pub fn set_yield_to_requests(enabled: bool) {
+    YIELD_TO_REQUESTS.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether requests should take a [`RequestGuard`]
+pub fn yields_to_requests() -> bool {
+    YIELD_TO_REQUESTS.load(Ordering::Relaxed)
+}
+
+/// Mark IO of the storage as congested, optimizations are paused until it is cleared
+pub fn set_io_congested(congested: bool) {
+    IO_CONGESTED.store(congested, Ordering::Relaxed);
+}
+
+fn should_yield() -> bool {
+    ACTIVE_REQUESTS.load(Ordering::Relaxed) > 0 || IO_CONGESTED.load(Ordering::Relaxed)
+}
+
+/// Wait while requests are being served or IO is congested, then check if the process is
+/// stopped.
+///
+/// Must only be called from optimization threads, never while serving a request.
+pub fn check_process_yield(stopped: &AtomicBool) -> OperationResult<()> {
+    yield_to_requests(stopped);
+    check_process_stopped(stopped)
+}
+
+fn yield_to_requests(stopped: &AtomicBool) {
+    if !should_yield() {
+        return;
+    }
+
+    let paused_at = Instant::now();
+    let recently_forced = FORCED_RESUME
+        .get()
+        .is_some_and(|resumed_at| paused_at.duration_since(resumed_at) < MIN_SLICE);
+    if recently_forced {
+        return;
+    }
+
+    while should_yield() && !stopped.load(Ordering::Relaxed) {
+        if paused_at.elapsed() >= MAX_PAUSE {
+            FORCED_RESUME.set(Some(Instant::now()));
+            return;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yield_to_requests() {
+        let guard = RequestGuard::new();
+        let waiter = thread::spawn(|| {
+            yield_to_requests(&AtomicBool::new(false));
+            Instant::now()
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        let released_at = Instant::now();
+        drop(guard);
+
+        // Optimization resumes only once the request is done
+        let resumed_at = waiter.join().unwrap();
+        assert!(resumed_at >= released_at);
+    }
+}
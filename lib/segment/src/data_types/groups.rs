@@ -1,14 +1,34 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use thiserror::Error;
+use uuid::Uuid;
 
 /// Value of the group_by key, shared across all the hits in the group
-#[derive(Debug, Serialize, Deserialize, JsonSchema, Eq, PartialEq, Clone, Hash)]
+#[derive(
+    Debug, Serialize, Deserialize, JsonSchema, Eq, PartialEq, Ord, PartialOrd, Clone, Hash,
+)]
 #[serde(untagged)]
 pub enum GroupId {
     String(String),
     NumberU64(u64),
     NumberI64(i64),
+    Bool(bool),
+    /// Values of each field of a group by multiple fields, in the order of the fields
+    Composite(Vec<GroupId>),
+}
+
+/// Reason a payload value can't be used as [`GroupId`]
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum GroupIdError {
+    #[error("floating point numbers can't be used as group keys")]
+    Float,
+    #[error("null can't be used as a group key")]
+    Null,
+    #[error("nested arrays can't be used as group keys")]
+    Array,
+    #[error("objects can't be used as group keys")]
+    Object,
 }
 
 impl From<u64> for GroupId {
@@ -35,35 +55,62 @@ impl From<&str> for GroupId {
     }
 }
 
+impl From<bool> for GroupId {
+    fn from(value: bool) -> Self {
+        GroupId::Bool(value)
+    }
+}
+
 impl From<GroupId> for serde_json::Value {
     fn from(key: GroupId) -> Self {
         match key {
             GroupId::String(s) => serde_json::Value::String(s),
             GroupId::NumberU64(n) => json!(n),
             GroupId::NumberI64(n) => json!(n),
+            GroupId::Bool(b) => serde_json::Value::Bool(b),
+            GroupId::Composite(keys) => keys.into_iter().map(serde_json::Value::from).collect(),
         }
     }
 }
 
 impl TryFrom<&serde_json::Value> for GroupId {
-    type Error = ();
+    type Error = GroupIdError;
 
-    /// Only allows Strings and Numbers to be converted into GroupId
+    /// Only allows Strings, integers and Booleans to be converted into GroupId.
+    ///
+    /// UUIDs are converted into their lowercase form, so different spellings of the same UUID
+    /// fall into the same group, and match IDs of points in lookups.
     fn try_from(value: &serde_json::Value) -> Result<Self, Self::Error> {
         match value {
-            serde_json::Value::String(s) => Ok(Self::String(s.to_string())),
+            serde_json::Value::String(s) => Ok(Self::String(normalize_uuid(s))),
             serde_json::Value::Number(n) => {
                 if let Some(n_u64) = n.as_u64() {
                     Ok(Self::NumberU64(n_u64))
                 } else if let Some(n_i64) = n.as_i64() {
                     Ok(Self::NumberI64(n_i64))
                 } else {
-                    Err(())
+                    Err(GroupIdError::Float)
                 }
             }
-            _ => Err(()),
+            serde_json::Value::Bool(b) => Ok(Self::Bool(*b)),
+            serde_json::Value::Null => Err(GroupIdError::Null),
+            serde_json::Value::Array(_) => Err(GroupIdError::Array),
+            serde_json::Value::Object(_) => Err(GroupIdError::Object),
+        }
+    }
+}
+
+/// Lowercase form of `s` if it is a UUID in the hyphenated form, `s` otherwise.
+///
+/// Other forms of UUIDs are kept as is, e.g. 32 hex digits are likely a hash rather than a UUID.
+fn normalize_uuid(s: &str) -> String {
+    const HYPHENATED_LEN: usize = 36;
+    if s.len() == HYPHENATED_LEN {
+        if let Ok(uuid) = Uuid::try_parse(s) {
+            return uuid.hyphenated().to_string();
         }
     }
+    s.to_string()
 }
 
 impl GroupId {
@@ -71,7 +118,7 @@ impl GroupId {
         match self {
             GroupId::NumberI64(id) => Some(*id),
             GroupId::NumberU64(id) => i64::try_from(*id).ok(),
-            GroupId::String(_) => None,
+            GroupId::String(_) | GroupId::Bool(_) | GroupId::Composite(_) => None,
         }
     }
 
@@ -79,7 +126,7 @@ impl GroupId {
         match self {
             GroupId::NumberI64(id) => u64::try_from(*id).ok(),
             GroupId::NumberU64(id) => Some(*id),
-            GroupId::String(_) => None,
+            GroupId::String(_) | GroupId::Bool(_) | GroupId::Composite(_) => None,
         }
     }
 }
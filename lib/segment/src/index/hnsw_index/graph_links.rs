@@ -1,5 +1,6 @@
 use std::cmp::max;
 use std::fs::OpenOptions;
+use std::io::Write as _;
 use std::mem::size_of;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
@@ -7,7 +8,8 @@ use std::sync::Arc;
 
 use common::types::PointOffsetType;
 use memmap2::{Mmap, MmapMut};
-use memory::{madvise, mmap_ops};
+use memory::direct_io::{self, DirectWriter};
+use memory::{madvise, mlock, mmap_ops, mmap_registry};
 
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::vector_utils::TrySetCapacityExact;
@@ -264,7 +266,12 @@ impl GraphLinksConverter {
     pub fn save_as(&mut self, path: &Path) -> OperationResult<()> {
         self.path = Some(path.to_path_buf());
         let temp_path = path.with_extension("tmp");
-        {
+        if direct_io::get_global() {
+            // Don't let links of a new segment evict pages of the others from the page cache
+            let mut writer = DirectWriter::create(&temp_path)?;
+            writer.write_all(&self.to_bytes())?;
+            writer.finish()?;
+        } else {
             let file = OpenOptions::new()
                 .read(true)
                 .write(true)
@@ -523,13 +530,17 @@ impl GraphLinks for GraphLinksMmap {
             .open(path)?;
 
         let mmap = unsafe { Mmap::map(&file)? };
-        madvise::madvise(&mmap, madvise::get_global())?;
+        madvise::madvise(&mmap, madvise::get_global(madvise::MmapKind::HnswLinks))?;
+        mlock::lock_if_enabled(&mmap, path);
 
         let header = GraphLinksFileHeader::deserialize_bytes_from(&mmap);
         let level_offsets = get_level_offsets(&mmap, &header).to_vec();
 
+        let mmap = Arc::new(mmap);
+        mmap_registry::register(path, madvise::MmapKind::HnswLinks, &mmap);
+
         Ok(Self {
-            mmap: Some(Arc::new(mmap)),
+            mmap: Some(mmap),
             header,
             level_offsets,
         })
@@ -14,10 +14,11 @@ use rayon::prelude::*;
 use rayon::ThreadPool;
 
 use super::graph_links::{GraphLinks, GraphLinksMmap};
-use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::operation_time_statistics::{
     OperationDurationsAggregator, ScopeDurationMeasurer,
 };
+use crate::common::yielding::check_process_yield;
 use crate::common::BYTES_IN_KB;
 use crate::data_types::vectors::{QueryVector, Vector, VectorRef};
 use crate::id_tracker::{IdTracker, IdTrackerSS};
@@ -201,7 +202,7 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
         }
 
         let insert_points = |block_point_id| {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
 
             let vector = vector_storage.get_vector(block_point_id);
             let vector = vector.as_vec_ref().into();
@@ -532,6 +533,8 @@ impl<TGraphLinks: GraphLinks> VectorIndex for HNSWIndex<TGraphLinks> {
                     } else {
                         &self.searches_telemetry.unfiltered_plain
                     });
+                    vector_storage
+                        .will_need(0..vector_storage.total_vector_count() as PointOffsetType);
                     vectors
                         .iter()
                         .map(|&vector| {
@@ -664,7 +667,7 @@ impl<TGraphLinks: GraphLinks> VectorIndex for HNSWIndex<TGraphLinks> {
             .build()?;
 
         for vector_id in id_tracker.iter_ids_excluding(deleted_bitslice) {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
             let level = graph_layers_builder.get_random_layer(&mut rng);
             graph_layers_builder.set_levels(vector_id, level);
         }
@@ -683,7 +686,7 @@ impl<TGraphLinks: GraphLinks> VectorIndex for HNSWIndex<TGraphLinks> {
             indexed_vectors = ids.len() + first_few_ids.len();
 
             let insert_point = |vector_id| {
-                check_process_stopped(stopped)?;
+                check_process_yield(stopped)?;
                 let vector = vector_storage.get_vector(vector_id);
                 let vector = vector.as_vec_ref().into();
                 let raw_scorer = if let Some(quantized_storage) = quantized_vectors.as_ref() {
@@ -745,7 +748,7 @@ impl<TGraphLinks: GraphLinks> VectorIndex for HNSWIndex<TGraphLinks> {
                 let min_block_size = indexing_threshold;
 
                 for payload_block in payload_index.payload_blocks(&field, min_block_size) {
-                    check_process_stopped(stopped)?;
+                    check_process_yield(stopped)?;
                     if payload_block.cardinality > max_block_size {
                         continue;
                     }
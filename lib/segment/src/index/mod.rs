@@ -1,7 +1,7 @@
 pub mod field_index;
 pub mod hnsw_index;
 mod key_encoding;
-mod payload_config;
+pub(crate) mod payload_config;
 mod payload_index_base;
 pub mod plain_payload_index;
 pub mod query_estimator;
@@ -70,6 +70,14 @@ pub trait PayloadIndex {
     /// Get payload for point
     fn payload(&self, point_id: PointOffsetType) -> OperationResult<Payload>;
 
+    /// Get payloads for multiple points, in the same order as `point_ids`
+    fn payloads(&self, point_ids: &[PointOffsetType]) -> OperationResult<Vec<Payload>> {
+        point_ids
+            .iter()
+            .map(|&point_id| self.payload(point_id))
+            .collect()
+    }
+
     /// Delete payload by key
     fn delete(
         &mut self,
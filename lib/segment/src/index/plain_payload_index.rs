@@ -27,7 +27,9 @@ use crate::types::{
     Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PayloadSchemaType,
     SearchParams,
 };
-use crate::vector_storage::{new_stoppable_raw_scorer, VectorStorageEnum};
+use crate::vector_storage::{
+    new_stoppable_raw_scorer, scan_range, VectorStorage, VectorStorageEnum,
+};
 
 /// Implementation of `PayloadIndex` which does not really indexes anything.
 ///
@@ -236,6 +238,9 @@ impl VectorIndex for PlainIndex {
                 let payload_index = self.payload_index.borrow();
                 let vector_storage = self.vector_storage.borrow();
                 let filtered_ids_vec = payload_index.query_points(filter);
+                if let Some(ids) = scan_range(&filtered_ids_vec) {
+                    vector_storage.will_need(ids);
+                }
                 vectors
                     .iter()
                     .map(|&vector| {
@@ -255,6 +260,7 @@ impl VectorIndex for PlainIndex {
                 let _timer = ScopeDurationMeasurer::new(&self.unfiltered_searches_telemetry);
                 let vector_storage = self.vector_storage.borrow();
                 let id_tracker = self.id_tracker.borrow();
+                vector_storage.will_need(0..vector_storage.total_vector_count() as PointOffsetType);
                 vectors
                     .iter()
                     .map(|&vector| {
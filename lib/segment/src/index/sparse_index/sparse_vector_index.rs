@@ -16,6 +16,7 @@ use super::indices_tracker::IndicesTracker;
 use super::sparse_index_config::SparseIndexType;
 use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
 use crate::common::operation_time_statistics::ScopeDurationMeasurer;
+use crate::common::yielding::check_process_yield;
 use crate::data_types::vectors::{QueryVector, VectorRef};
 use crate::id_tracker::IdTrackerSS;
 use crate::index::field_index::CardinalityEstimation;
@@ -113,7 +114,7 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
         let mut index_point_count: usize = 0;
         let mut indices_tracker = IndicesTracker::default();
         for id in borrowed_id_tracker.iter_ids_excluding(deleted_bitslice) {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
             // It is possible that the vector is not present in the storage in case of crash.
             // Because:
             // - the `id_tracker` is flushed before the `vector_storage`
@@ -489,6 +489,10 @@ impl PayloadIndex for StructPayloadIndex {
         self.payload.borrow().payload(point_id)
     }
 
+    fn payloads(&self, point_ids: &[PointOffsetType]) -> OperationResult<Vec<Payload>> {
+        self.payload.borrow().payloads(point_ids)
+    }
+
     fn delete(
         &mut self,
         point_id: PointOffsetType,
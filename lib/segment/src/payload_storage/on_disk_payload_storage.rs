@@ -89,6 +89,19 @@ impl PayloadStorage for OnDiskPayloadStorage {
         }
     }
 
+    /// Read payloads of all points with a single multi-get, instead of a lookup per point
+    fn payloads(&self, point_ids: &[PointOffsetType]) -> OperationResult<Vec<Payload>> {
+        let keys: Vec<_> = point_ids
+            .iter()
+            .map(|point_id| serde_cbor::to_vec(point_id).unwrap())
+            .collect();
+        self.db_wrapper
+            .multi_get_pinned(&keys, serde_cbor::from_slice::<Payload>)?
+            .into_iter()
+            .map(|payload| Ok(payload.transpose()?.unwrap_or_default()))
+            .collect()
+    }
+
     fn delete(
         &mut self,
         point_id: PointOffsetType,
@@ -63,6 +63,14 @@ impl PayloadStorage for PayloadStorageEnum {
         }
     }
 
+    fn payloads(&self, point_ids: &[PointOffsetType]) -> OperationResult<Vec<Payload>> {
+        match self {
+            PayloadStorageEnum::InMemoryPayloadStorage(s) => s.payloads(point_ids),
+            PayloadStorageEnum::SimplePayloadStorage(s) => s.payloads(point_ids),
+            PayloadStorageEnum::OnDiskPayloadStorage(s) => s.payloads(point_ids),
+        }
+    }
+
     fn delete(
         &mut self,
         point_id: PointOffsetType,
@@ -189,4 +197,25 @@ mod tests {
             eprintln!("res = {res:#?}");
         }
     }
+
+    #[test]
+    fn test_on_disk_storage_payloads() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+
+        let mut storage: PayloadStorageEnum = OnDiskPayloadStorage::open(db).unwrap().into();
+        for point_id in 0..10 {
+            let payload: Payload = serde_json::json!({ "num": point_id }).into();
+            storage.assign(point_id, &payload).unwrap();
+        }
+
+        let point_ids = [7, 2, 100, 5];
+        let payloads = storage.payloads(&point_ids).unwrap();
+
+        assert_eq!(payloads.len(), point_ids.len());
+        for (point_id, payload) in point_ids.iter().zip(&payloads) {
+            assert_eq!(payload, &storage.payload(*point_id).unwrap());
+        }
+        assert_eq!(payloads[2], Default::default());
+    }
 }
@@ -554,7 +554,7 @@ impl Segment {
         with_vector: &WithVector,
     ) -> OperationResult<Vec<ScoredPoint>> {
         let id_tracker = self.id_tracker.borrow();
-        internal_result
+        let points: Vec<_> = internal_result
             .iter()
             .filter_map(|&scored_point_offset| {
                 let point_offset = scored_point_offset.idx;
@@ -570,24 +570,41 @@ impl Segment {
                     }
                 }
             })
-            .map(|(point_id, scored_point_offset)| {
+            .collect();
+
+        // Fetch payloads of all points at once, storage on disk reads them in a single batch
+        let payloads: Vec<Option<Payload>> = if with_payload.enable {
+            let point_offsets: Vec<_> = points
+                .iter()
+                .map(|(_, scored_point_offset)| scored_point_offset.idx)
+                .collect();
+            self.payload_index
+                .borrow()
+                .payloads(&point_offsets)?
+                .into_iter()
+                .map(Some)
+                .collect()
+        } else {
+            vec![None; points.len()]
+        };
+
+        points
+            .into_iter()
+            .zip(payloads)
+            .map(|((point_id, scored_point_offset), payload)| {
                 let point_offset = scored_point_offset.idx;
                 let point_version = id_tracker.internal_version(point_offset).ok_or_else(|| {
                     OperationError::service_error(format!(
                         "Corrupter id_tracker, no version for point {point_id}"
                     ))
                 })?;
-                let payload = if with_payload.enable {
-                    let initial_payload = self.payload_by_offset(point_offset)?;
-                    let processed_payload = if let Some(i) = &with_payload.payload_selector {
+                let payload = payload.map(|initial_payload| {
+                    if let Some(i) = &with_payload.payload_selector {
                         i.process(initial_payload)
                     } else {
                         initial_payload
-                    };
-                    Some(processed_payload)
-                } else {
-                    None
-                };
+                    }
+                });
                 let vector = match with_vector {
                     WithVector::Bool(false) => None,
                     WithVector::Bool(true) => {
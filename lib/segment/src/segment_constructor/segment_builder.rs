@@ -5,7 +5,8 @@ use std::sync::atomic::AtomicBool;
 
 use super::get_vector_storage_path;
 use crate::common::error_logging::LogError;
-use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::common::yielding::check_process_yield;
 use crate::entry::entry_point::SegmentEntry;
 use crate::index::hnsw_index::max_rayon_threads;
 use crate::index::{PayloadIndex, VectorIndex};
@@ -45,6 +46,9 @@ impl SegmentBuilder {
     /// Update current segment builder with all (not deleted) vectors and payload form `other` segment
     /// Perform index building at the end of update
     ///
+    /// Points are copied in the order of their external ids, so segments rebuilt from the same
+    /// points keep the same layout of vector files, regardless of the order of insertion.
+    ///
     /// # Arguments
     ///
     /// * `other` - segment to add into construction
@@ -93,9 +97,14 @@ impl SegmentBuilder {
             ));
         }
 
+        let other_ids: Vec<_> = other_id_tracker
+            .iter_from(None)
+            .map(|(_, internal_id)| internal_id)
+            .collect();
+
         let mut new_internal_range = None;
         for (vector_name, vector_storage) in &mut vector_storages {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
             let other_vector_storage = other_vector_storages.get(vector_name).ok_or_else(|| {
                 OperationError::service_error(format!(
                     "Cannot update from other segment because if missing vector name {vector_name}"
@@ -103,7 +112,7 @@ impl SegmentBuilder {
             })?;
             let internal_range = vector_storage.update_from(
                 other_vector_storage,
-                &mut other_id_tracker.iter_ids(),
+                &mut other_ids.iter().copied(),
                 stopped,
             )?;
             match new_internal_range.clone() {
@@ -119,10 +128,10 @@ impl SegmentBuilder {
         }
 
         if let Some(new_internal_range) = new_internal_range {
-            let internal_id_iter = new_internal_range.zip(other_id_tracker.iter_ids());
+            let internal_id_iter = new_internal_range.zip(other_ids.iter().copied());
 
             for (new_internal_id, old_internal_id) in internal_id_iter {
-                check_process_stopped(stopped)?;
+                check_process_yield(stopped)?;
 
                 let external_id =
                     if let Some(external_id) = other_id_tracker.external_id(old_internal_id) {
@@ -189,7 +198,7 @@ impl SegmentBuilder {
 
             for (field, payload_schema) in &self.indexed_fields {
                 segment.create_field_index(segment.version(), field, Some(payload_schema))?;
-                check_process_stopped(stopped)?;
+                check_process_yield(stopped)?;
             }
 
             Self::update_quantization(&mut segment, stopped)?;
@@ -232,7 +241,7 @@ impl SegmentBuilder {
 
             if let Some(quantization) = config.quantization_config(vector_name) {
                 let segment_path = segment.current_path.as_path();
-                check_process_stopped(stopped)?;
+                check_process_yield(stopped)?;
 
                 let vector_storage_path = get_vector_storage_path(segment_path, vector_name);
 
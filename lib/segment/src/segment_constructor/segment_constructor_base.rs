@@ -19,6 +19,7 @@ use crate::id_tracker::simple_id_tracker::SimpleIdTracker;
 use crate::id_tracker::IdTracker;
 use crate::index::hnsw_index::graph_links::{GraphLinksMmap, GraphLinksRam};
 use crate::index::hnsw_index::hnsw::HNSWIndex;
+use crate::index::payload_config::PayloadConfig;
 use crate::index::plain_payload_index::PlainIndex;
 use crate::index::sparse_index::sparse_index_config::SparseIndexType;
 use crate::index::sparse_index::sparse_vector_index::SparseVectorIndex;
@@ -32,7 +33,9 @@ use crate::types::{
     VectorStorageType,
 };
 use crate::vector_storage::appendable_mmap_vector_storage::open_appendable_memmap_vector_storage;
-use crate::vector_storage::memmap_vector_storage::open_memmap_vector_storage;
+use crate::vector_storage::memmap_vector_storage::{
+    check_memmap_vector_storage, open_memmap_vector_storage,
+};
 use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
 use crate::vector_storage::simple_dense_vector_storage::open_simple_vector_storage;
 use crate::vector_storage::simple_sparse_vector_storage::open_simple_sparse_vector_storage;
@@ -292,8 +295,7 @@ pub fn load_segment(path: &Path) -> OperationResult<Option<Segment>> {
         return Ok(None);
     }
 
-    let stored_version: Version = SegmentVersion::load(path)?.parse()?;
-    let app_version: Version = SegmentVersion::current().parse()?;
+    let (stored_version, app_version) = segment_versions(path)?;
 
     if stored_version != app_version {
         info!("Migrating segment {} -> {}", stored_version, app_version,);
@@ -329,6 +331,84 @@ pub fn load_segment(path: &Path) -> OperationResult<Option<Segment>> {
     Ok(Some(segment))
 }
 
+/// Whether `load_segment` skips the stored segment, because it is marked as deleted or was
+/// not fully saved, e.g. a temporary segment of an optimization interrupted by a crash.
+pub fn segment_is_skipped(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("deleted")
+        || !SegmentVersion::check_exists(path)
+}
+
+/// Check that files of a stored segment are present and consistent, without loading it.
+///
+/// Returns the list of found problems, empty if the segment is expected to load.
+/// Segments which `load_segment` would skip, see [`segment_is_skipped`], are not checked.
+pub fn verify_segment(path: &Path) -> Vec<String> {
+    let mut problems = vec![];
+
+    if segment_is_skipped(path) {
+        return problems;
+    }
+
+    let (stored_version, app_version) = match segment_versions(path) {
+        Ok(versions) => versions,
+        Err(err) => {
+            problems.push(format!("can't read segment version: {err}"));
+            return problems;
+        }
+    };
+    if stored_version > app_version {
+        problems.push(format!(
+            "segment version {stored_version} is newer than application version {app_version}"
+        ));
+        return problems;
+    }
+    if stored_version != app_version {
+        // State of older segments is migrated on load, it is not in the current format yet
+        return problems;
+    }
+
+    let segment_state = match Segment::load_state(path) {
+        Ok(state) => state,
+        Err(err) => {
+            problems.push(err.to_string());
+            return problems;
+        }
+    };
+
+    for (vector_name, vector_config) in &segment_state.config.vector_data {
+        if vector_config.storage_type != VectorStorageType::Mmap {
+            continue;
+        }
+        let vector_storage_path = get_vector_storage_path(path, vector_name);
+        if let Err(err) = check_memmap_vector_storage(&vector_storage_path, vector_config.size) {
+            problems.push(err.to_string());
+        }
+    }
+
+    let payload_config_path = PayloadConfig::get_config_path(&path.join(PAYLOAD_INDEX_PATH));
+    if payload_config_path.exists() {
+        if let Err(err) = PayloadConfig::load(&payload_config_path) {
+            problems.push(format!("can't parse payload index config: {err}"));
+        }
+    }
+
+    problems
+}
+
+/// Whether a stored segment has an older version, so `load_segment` migrates it.
+pub fn segment_needs_migration(path: &Path) -> bool {
+    segment_versions(path).map_or(false, |(stored_version, app_version)| {
+        stored_version < app_version
+    })
+}
+
+/// Version of the stored segment, and the version of the application
+fn segment_versions(path: &Path) -> OperationResult<(Version, Version)> {
+    let stored_version: Version = SegmentVersion::load(path)?.parse()?;
+    let app_version: Version = SegmentVersion::current().parse()?;
+    Ok((stored_version, app_version))
+}
+
 /// Build segment instance using given configuration.
 /// Builder will generate folder for the segment and store all segment information inside it.
 ///
@@ -9,7 +9,8 @@ use bitvec::prelude::BitSlice;
 use common::types::PointOffsetType;
 
 use super::DenseVectorStorage;
-use crate::common::operation_error::{check_process_stopped, OperationResult};
+use crate::common::operation_error::OperationResult;
+use crate::common::yielding::check_process_yield;
 use crate::common::Flusher;
 use crate::data_types::named_vectors::CowVector;
 use crate::data_types::vectors::{VectorElementType, VectorRef};
@@ -126,7 +127,7 @@ impl VectorStorage for AppendableMmapVectorStorage {
     ) -> OperationResult<Range<PointOffsetType>> {
         let start_index = self.vectors.len() as PointOffsetType;
         for point_id in other_ids {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
             // Do not perform preprocessing - vectors should be already processed
             let other_deleted = other.is_deleted_vector(point_id);
             let other_vector = other.get_vector(point_id);
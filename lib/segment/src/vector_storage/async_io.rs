@@ -1,5 +1,8 @@
-use std::fs::File;
+use std::fs::{File, OpenOptions};
+use std::io;
 use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt as _;
+use std::path::Path;
 
 use common::types::PointOffsetType;
 use io_uring::{opcode, types, IoUring};
@@ -10,16 +13,24 @@ use crate::data_types::vectors::VectorElementType;
 
 const DISK_PARALLELISM: usize = 16; // TODO: benchmark it better, or make it configurable
 
+/// Alignment of offsets, sizes and memory of direct IO reads, the logical block size of most
+/// devices
+const DIRECT_IO_ALIGNMENT: usize = 4096;
+
 struct BufferMeta {
     /// Sequential index of the processing point
     pub index: usize,
     /// Id of the point that is currently being processed
     pub point_id: PointOffsetType,
+    /// Offset of the vector from the start of the read, non-zero for direct IO only
+    pub vector_offset: usize,
 }
 
 struct Buffer {
     /// Stores the buffer for the point vectors
     pub buffer: Vec<u8>,
+    /// Offset of the data within `buffer`, to align it for direct IO
+    pub start: usize,
     /// Stores the point ids that are currently being processed in each buffer.
     pub meta: Option<BufferMeta>,
 }
@@ -35,29 +46,96 @@ impl BufferStore {
             buffers: (0..num_buffers)
                 .map(|_| Buffer {
                     buffer: vec![0; buffer_raw_size],
+                    start: 0,
                     meta: None,
                 })
                 .collect(),
         }
     }
 
+    /// Buffers for direct IO reads of `raw_size` bytes at any offset
+    pub fn new_aligned(num_buffers: usize, raw_size: usize) -> Self {
+        // A read starts up to one block before the vector, and ends at the end of a block
+        let read_size = align_up(DIRECT_IO_ALIGNMENT - 1 + raw_size);
+        Self {
+            buffers: (0..num_buffers)
+                .map(|_| {
+                    // Heap memory of a vector doesn't move, align within it
+                    let buffer = vec![0; read_size + DIRECT_IO_ALIGNMENT];
+                    let start = buffer.as_ptr().align_offset(DIRECT_IO_ALIGNMENT);
+                    Buffer {
+                        buffer,
+                        start,
+                        meta: None,
+                    }
+                })
+                .collect(),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn new_empty() -> Self {
         Self { buffers: vec![] }
     }
 }
 
+fn align_up(size: usize) -> usize {
+    size.div_ceil(DIRECT_IO_ALIGNMENT) * DIRECT_IO_ALIGNMENT
+}
+
 pub struct UringReader {
     file: File,
     buffers: BufferStore,
     io_uring: Option<IoUring>,
     raw_size: usize,
     header_size: usize,
+    /// File is opened with `O_DIRECT`, reads bypass the page cache
+    direct_io: bool,
 }
 
 impl UringReader {
-    pub fn new(file: File, raw_size: usize, header_size: usize) -> OperationResult<Self> {
-        let buffers = BufferStore::new(DISK_PARALLELISM, raw_size);
+    /// Open the file at `path` for reading vectors of `raw_size` bytes after a header of
+    /// `header_size` bytes.
+    ///
+    /// With `direct_io` the file is read with `O_DIRECT`, bypassing the page cache, if the file
+    /// system supports it.
+    pub fn open(
+        path: &Path,
+        raw_size: usize,
+        header_size: usize,
+        direct_io: bool,
+    ) -> OperationResult<Self> {
+        let direct_file = if direct_io {
+            match OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_DIRECT)
+                .open(path)
+            {
+                Ok(file) => Some(file),
+                // The file system doesn't support direct IO, e.g. tmpfs
+                Err(err) if err.kind() == io::ErrorKind::InvalidInput => {
+                    log::warn!(
+                        "Direct IO is not supported for {}, reading it through the page cache",
+                        path.display(),
+                    );
+                    None
+                }
+                Err(err) => return Err(err.into()),
+            }
+        } else {
+            None
+        };
+
+        let direct_io = direct_file.is_some();
+        let file = match direct_file {
+            Some(file) => file,
+            None => File::open(path)?,
+        };
+        let buffers = if direct_io {
+            BufferStore::new_aligned(DISK_PARALLELISM, raw_size)
+        } else {
+            BufferStore::new(DISK_PARALLELISM, raw_size)
+        };
         let io_uring = IoUring::new(DISK_PARALLELISM as _)?;
 
         Ok(Self {
@@ -66,6 +144,7 @@ impl UringReader {
             io_uring: Some(io_uring),
             raw_size,
             header_size,
+            direct_io,
         })
     }
 
@@ -102,22 +181,30 @@ impl UringReader {
             // Assume there is at least one buffer available at this point
             let buffer_id = unused_buffer_ids.pop().unwrap();
 
-            self.buffers.buffers[buffer_id].meta = Some(BufferMeta {
+            let offset = self.header_size + self.raw_size * point as usize;
+            // Direct IO reads whole blocks
+            let (read_offset, read_size) = if self.direct_io {
+                let read_offset = offset - offset % DIRECT_IO_ALIGNMENT;
+                (read_offset, align_up(offset - read_offset + self.raw_size))
+            } else {
+                (offset, self.raw_size)
+            };
+
+            let buffer = &mut self.buffers.buffers[buffer_id];
+            buffer.meta = Some(BufferMeta {
                 index: idx,
                 point_id: point,
+                vector_offset: offset - read_offset,
             });
 
-            let buffer = &mut self.buffers.buffers[buffer_id].buffer;
-            let offset = self.header_size + self.raw_size * point as usize;
-
             let user_data = buffer_id;
 
             let read_e = opcode::Read::new(
                 types::Fd(self.file.as_raw_fd()),
-                buffer.as_mut_ptr(),
-                buffer.len() as _,
+                buffer.buffer[buffer.start..].as_mut_ptr(),
+                read_size as _,
             )
-            .offset(offset as _)
+            .offset(read_offset as _)
             .build()
             .user_data(user_data as _);
 
@@ -170,17 +257,22 @@ fn submit_and_read(
                 "io_uring operation failed with {} error",
                 result
             )));
-        } else if (result as usize) != raw_size {
+        }
+
+        let buffer_id = entry.user_data() as usize;
+        let buffer = &mut buffers.buffers[buffer_id];
+        let meta = buffer.meta.take().unwrap();
+        // Direct IO reads past the vector, up to the end of the block or of the file
+        if (result as usize) < meta.vector_offset + raw_size {
             return Err(OperationError::service_error(format!(
                 "io_uring operation returned {} bytes instead of {}",
-                result, raw_size
+                result,
+                meta.vector_offset + raw_size,
             )));
         }
 
-        let buffer_id = entry.user_data() as usize;
-        let meta = buffers.buffers[buffer_id].meta.take().unwrap();
-        let buffer = &buffers.buffers[buffer_id].buffer;
-        let vector = transmute_from_u8_to_slice(buffer);
+        let vector_start = buffer.start + meta.vector_offset;
+        let vector = transmute_from_u8_to_slice(&buffer.buffer[vector_start..][..raw_size]);
         callback(meta.index, meta.point_id, vector);
         unused_buffer_ids.push(buffer_id);
     }
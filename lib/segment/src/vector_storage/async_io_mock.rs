@@ -1,4 +1,4 @@
-use std::fs::File;
+use std::path::Path;
 
 use crate::common::operation_error::OperationResult;
 
@@ -8,7 +8,12 @@ pub struct UringReader;
 
 #[allow(dead_code)]
 impl UringReader {
-    pub fn new(_file: File, _raw_size: usize, _header_size: usize) -> OperationResult<Self> {
+    pub fn open(
+        _path: &Path,
+        _raw_size: usize,
+        _header_size: usize,
+        _direct_io: bool,
+    ) -> OperationResult<Self> {
         Ok(Self {})
     }
 }
@@ -5,6 +5,7 @@ use std::path::{Path, PathBuf};
 
 use common::types::PointOffsetType;
 use memmap2::MmapMut;
+use memory::madvise::MmapKind;
 use memory::mmap_ops::{create_and_ensure_length, open_write_mmap};
 use serde::{Deserialize, Serialize};
 
@@ -57,10 +58,10 @@ impl ChunkedMmapVectors {
                 let length = std::mem::size_of::<usize>() as u64;
                 create_and_ensure_length(&status_file, length as usize)?;
             }
-            let mmap = open_write_mmap(&status_file)?;
+            let mmap = open_write_mmap(&status_file, MmapKind::Other)?;
             Ok(mmap)
         } else {
-            let mmap = open_write_mmap(&status_file)?;
+            let mmap = open_write_mmap(&status_file, MmapKind::Other)?;
             Ok(mmap)
         }
     }
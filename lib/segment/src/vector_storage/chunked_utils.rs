@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use memory::madvise::MmapKind;
 use memory::mmap_ops::{create_and_ensure_length, open_write_mmap};
 
 use crate::common::mmap_type::MmapSlice;
@@ -48,7 +49,7 @@ pub fn read_mmaps(directory: &Path) -> OperationResult<Vec<MmapChunk>> {
                 directory.display(),
             ))
         })?;
-        let mmap = open_write_mmap(&mmap_file)?;
+        let mmap = open_write_mmap(&mmap_file, MmapKind::Vectors)?;
         let chunk = unsafe { MmapChunk::try_from(mmap)? };
         result.push(chunk);
     }
@@ -68,7 +69,7 @@ pub fn create_chunk(
 ) -> OperationResult<MmapChunk> {
     let chunk_file_path = chunk_name(directory, chunk_id);
     create_and_ensure_length(&chunk_file_path, chunk_length_bytes)?;
-    let mmap = open_write_mmap(&chunk_file_path)?;
+    let mmap = open_write_mmap(&chunk_file_path, MmapKind::Vectors)?;
     let chunk = unsafe { MmapChunk::try_from(mmap)? };
     Ok(chunk)
 }
@@ -2,6 +2,8 @@ use std::sync::atomic::{AtomicBool, Ordering};
 
 static ASYNC_SCORER: AtomicBool = AtomicBool::new(false);
 
+static ASYNC_SCORER_DIRECT_IO: AtomicBool = AtomicBool::new(false);
+
 pub fn set_async_scorer(async_scorer: bool) {
     ASYNC_SCORER.store(async_scorer, Ordering::Relaxed);
 }
@@ -9,3 +11,13 @@ pub fn set_async_scorer(async_scorer: bool) {
 pub fn get_async_scorer() -> bool {
     ASYNC_SCORER.load(Ordering::Relaxed)
 }
+
+/// Read vectors of the async scorer with direct IO, bypassing the page cache. Applies to vector
+/// storages opened afterwards.
+pub fn set_async_scorer_direct_io(direct_io: bool) {
+    ASYNC_SCORER_DIRECT_IO.store(direct_io, Ordering::Relaxed);
+}
+
+pub fn get_async_scorer_direct_io() -> bool {
+    ASYNC_SCORER_DIRECT_IO.load(Ordering::Relaxed)
+}
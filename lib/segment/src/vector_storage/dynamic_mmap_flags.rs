@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use bitvec::prelude::BitSlice;
 use memmap2::MmapMut;
+use memory::madvise::MmapKind;
 use memory::mmap_ops::{create_and_ensure_length, open_write_mmap};
 use parking_lot::Mutex;
 
@@ -68,10 +69,10 @@ fn ensure_status_file(directory: &Path) -> OperationResult<MmapMut> {
     if !status_file.exists() {
         let length = std::mem::size_of::<DynamicMmapStatus>();
         create_and_ensure_length(&status_file, length)?;
-        let mmap = open_write_mmap(&status_file)?;
+        let mmap = open_write_mmap(&status_file, MmapKind::Other)?;
         Ok(mmap)
     } else {
-        let mmap = open_write_mmap(&status_file)?;
+        let mmap = open_write_mmap(&status_file, MmapKind::Other)?;
         Ok(mmap)
     }
 }
@@ -131,7 +132,8 @@ impl DynamicMmapFlags {
         let capacity_bytes = mmap_capacity_bytes(num_flags);
         let mmap_path = Self::file_id_to_file(directory, new_file_id);
         create_and_ensure_length(&mmap_path, capacity_bytes)?;
-        let flags_mmap = open_write_mmap(&mmap_path).describe("Open mmap flags for writing")?;
+        let flags_mmap =
+            open_write_mmap(&mmap_path, MmapKind::Other).describe("Open mmap flags for writing")?;
         #[cfg(unix)]
         if let Err(err) = flags_mmap.advise(memmap2::Advice::WillNeed) {
             log::error!("Failed to advise MADV_WILLNEED for deleted flags: {}", err,);
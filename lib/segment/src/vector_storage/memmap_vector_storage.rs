@@ -1,5 +1,5 @@
-use std::fs::{create_dir_all, File, OpenOptions};
-use std::io::{self, Write};
+use std::fs::create_dir_all;
+use std::io::Write;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
@@ -8,10 +8,12 @@ use std::sync::Arc;
 use atomic_refcell::AtomicRefCell;
 use bitvec::prelude::BitSlice;
 use common::types::PointOffsetType;
+use memory::direct_io::DirectWriter;
 use memory::mmap_ops;
 
 use super::{DenseVectorStorage, VectorStorageEnum};
-use crate::common::operation_error::{check_process_stopped, OperationResult};
+use crate::common::operation_error::OperationResult;
+use crate::common::yielding::check_process_yield;
 use crate::common::Flusher;
 use crate::data_types::named_vectors::CowVector;
 use crate::data_types::vectors::{DenseVector, VectorElementType, VectorRef};
@@ -66,6 +68,11 @@ pub fn open_memmap_vector_storage_with_async_io(
     ))))
 }
 
+/// Check files of a mem-mapped vector storage without opening it.
+pub fn check_memmap_vector_storage(path: &Path, dim: usize) -> OperationResult<()> {
+    MmapVectors::check_vectors_file(&path.join(VECTORS_PATH), dim)
+}
+
 impl MemmapVectorStorage {
     pub fn prefault_mmap_pages(&self) -> Option<mmap_ops::PrefaultMmapPages> {
         Some(
@@ -135,10 +142,10 @@ impl VectorStorage for MemmapVectorStorage {
             .unwrap_or(get_async_scorer());
 
         // Extend vectors file, write other vectors into it
-        let mut vectors_file = open_append(&self.vectors_path)?;
+        let mut vectors_file = DirectWriter::append(&self.vectors_path)?;
         let mut deleted_ids = vec![];
         for id in other_ids {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
             let vector: DenseVector = other.get_vector(id).try_into()?;
             let raw_bites = mmap_ops::transmute_to_u8_slice(&vector);
             vectors_file.write_all(raw_bites)?;
@@ -149,8 +156,7 @@ impl VectorStorage for MemmapVectorStorage {
                 deleted_ids.push((start_index + id) as PointOffsetType);
             }
         }
-        vectors_file.flush()?;
-        drop(vectors_file);
+        vectors_file.finish()?;
 
         // Load store with updated files
         self.mmap_store.replace(MmapVectors::open(
@@ -166,7 +172,7 @@ impl VectorStorage for MemmapVectorStorage {
         // the exact number of vectors beforehand. When opening the store it is done automatically.
         let store = self.mmap_store.as_mut().unwrap();
         for id in deleted_ids {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
             store.delete(id);
         }
 
@@ -199,16 +205,10 @@ impl VectorStorage for MemmapVectorStorage {
     fn deleted_vector_bitslice(&self) -> &BitSlice {
         self.mmap_store.as_ref().unwrap().deleted_vector_bitslice()
     }
-}
 
-/// Open a file shortly for appending
-fn open_append<P: AsRef<Path>>(path: P) -> io::Result<File> {
-    OpenOptions::new()
-        .read(false)
-        .write(false)
-        .append(true)
-        .create(false)
-        .open(path)
+    fn will_need(&self, ids: Range<PointOffsetType>) {
+        self.mmap_store.as_ref().unwrap().will_need(ids)
+    }
 }
 
 #[cfg(test)]
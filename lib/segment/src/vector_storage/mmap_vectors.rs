@@ -1,24 +1,27 @@
 use std::fs::{File, OpenOptions};
-use std::io::Write;
+use std::io::{Read, Write};
 use std::mem::{self, size_of, transmute};
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 
 use bitvec::prelude::BitSlice;
 use common::types::PointOffsetType;
 use memmap2::Mmap;
-use memory::mmap_ops;
+use memory::madvise::MmapKind;
+use memory::{mmap_ops, mmap_registry};
 use parking_lot::Mutex;
 
 use crate::common::error_logging::LogError;
 use crate::common::mmap_type::MmapBitSlice;
-use crate::common::operation_error::OperationResult;
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
 #[cfg(target_os = "linux")]
 use crate::vector_storage::async_io::UringReader;
 #[cfg(not(target_os = "linux"))]
 use crate::vector_storage::async_io_mock::UringReader;
+use crate::vector_storage::common::get_async_scorer_direct_io;
 
 const HEADER_SIZE: usize = 4;
 const VECTORS_HEADER: &[u8; HEADER_SIZE] = b"data";
@@ -51,15 +54,18 @@ impl MmapVectors {
         // Allocate/open vectors mmap
         ensure_mmap_file_size(vectors_path, VECTORS_HEADER, None)
             .describe("Create mmap data file")?;
-        let mmap = mmap_ops::open_read_mmap(vectors_path).describe("Open mmap for reading")?;
+        let mmap = mmap_ops::open_read_mmap(vectors_path, MmapKind::Vectors)
+            .describe("Open mmap for reading")?;
         let num_vectors = (mmap.len() - HEADER_SIZE) / dim / size_of::<VectorElementType>();
+        let mmap = Arc::new(mmap);
+        mmap_registry::register(vectors_path, MmapKind::Vectors, &mmap);
 
         // Allocate/open deleted mmap
         let deleted_mmap_size = deleted_mmap_size(num_vectors);
         ensure_mmap_file_size(deleted_path, DELETED_HEADER, Some(deleted_mmap_size as u64))
             .describe("Create mmap deleted file")?;
-        let deleted_mmap =
-            mmap_ops::open_write_mmap(deleted_path).describe("Open mmap deleted for writing")?;
+        let deleted_mmap = mmap_ops::open_write_mmap(deleted_path, MmapKind::Other)
+            .describe("Open mmap deleted for writing")?;
 
         // Advise kernel that we'll need this page soon so the kernel can prepare
         #[cfg(unix)]
@@ -73,9 +79,13 @@ impl MmapVectors {
 
         let uring_reader = if with_async_io {
             // Keep file handle open for async IO
-            let vectors_file = File::open(vectors_path)?;
             let raw_size = dim * size_of::<VectorElementType>();
-            Some(UringReader::new(vectors_file, raw_size, HEADER_SIZE)?)
+            Some(UringReader::open(
+                vectors_path,
+                raw_size,
+                HEADER_SIZE,
+                get_async_scorer_direct_io(),
+            )?)
         } else {
             None
         };
@@ -83,13 +93,44 @@ impl MmapVectors {
         Ok(MmapVectors {
             dim,
             num_vectors,
-            mmap: mmap.into(),
+            mmap,
             uring_reader: Mutex::new(uring_reader),
             deleted,
             deleted_count,
         })
     }
 
+    /// Check that vectors file is present and fits whole vectors of dimension `dim`.
+    ///
+    /// Does not create or map the file, so it is safe to call before the storage is opened.
+    pub fn check_vectors_file(vectors_path: &Path, dim: usize) -> OperationResult<()> {
+        let mut file = File::open(vectors_path)?;
+        let file_len = file.metadata()?.len() as usize;
+
+        let mut header = [0u8; HEADER_SIZE];
+        if file_len < HEADER_SIZE || file.read_exact(&mut header).is_err() {
+            return Err(OperationError::service_error(format!(
+                "Vectors file {} is too short: {file_len} bytes",
+                vectors_path.display(),
+            )));
+        }
+        if &header != VECTORS_HEADER {
+            return Err(OperationError::service_error(format!(
+                "Vectors file {} has invalid header",
+                vectors_path.display(),
+            )));
+        }
+
+        let vector_size = dim * size_of::<VectorElementType>();
+        if (file_len - HEADER_SIZE) % vector_size != 0 {
+            return Err(OperationError::service_error(format!(
+                "Vectors file {} size {file_len} does not fit vectors of dimension {dim}",
+                vectors_path.display(),
+            )));
+        }
+        Ok(())
+    }
+
     pub fn has_async_reader(&self) -> bool {
         self.uring_reader.lock().is_some()
     }
@@ -117,6 +158,25 @@ impl MmapVectors {
         &arr[0..self.dim]
     }
 
+    /// Advise the kernel to read ahead vectors of the `ids` range
+    pub fn will_need(&self, ids: Range<PointOffsetType>) {
+        let end = ids.end.min(self.num_vectors as PointOffsetType);
+        let Some(offset) = self.data_offset(ids.start).filter(|_| ids.start < end) else {
+            return;
+        };
+        let len = (end - ids.start) as usize * self.raw_size();
+
+        #[cfg(unix)]
+        if let Err(err) = self
+            .mmap
+            .advise_range(memmap2::Advice::WillNeed, offset, len)
+        {
+            log::debug!("Failed to advise MADV_WILLNEED for vectors {ids:?}: {err}");
+        }
+        #[cfg(not(unix))]
+        log::debug!("Ignore read ahead of vectors {ids:?} at {offset}+{len} on this platform");
+    }
+
     /// Returns reference to vector data by key
     pub fn get_vector(&self, key: PointOffsetType) -> &[VectorElementType] {
         let offset = self.data_offset(key).unwrap();
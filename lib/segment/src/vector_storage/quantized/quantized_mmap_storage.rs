@@ -1,10 +1,11 @@
 use std::path::Path;
+use std::sync::Arc;
 
 use memmap2::{Mmap, MmapMut};
-use memory::madvise;
+use memory::{madvise, mlock, mmap_registry};
 
 pub struct QuantizedMmapStorage {
-    mmap: Mmap,
+    mmap: Arc<Mmap>,
 }
 
 pub struct QuantizedMmapStorageBuilder {
@@ -28,10 +29,13 @@ impl quantization::EncodedStorage for QuantizedMmapStorage {
             .create(false)
             .open(path)?;
         let mmap = unsafe { Mmap::map(&file)? };
-        madvise::madvise(&mmap, madvise::get_global())?;
+        madvise::madvise(&mmap, madvise::get_global(madvise::MmapKind::Quantized))?;
+        mlock::lock_if_enabled(&mmap, path);
 
         let expected_size = quantized_vector_size * vectors_count;
         if mmap.len() == expected_size {
+            let mmap = Arc::new(mmap);
+            mmap_registry::register(path, madvise::MmapKind::Quantized, &mmap);
             Ok(Self { mmap })
         } else {
             Err(std::io::Error::new(
@@ -54,7 +58,9 @@ impl quantization::EncodedStorageBuilder<QuantizedMmapStorage> for QuantizedMmap
     fn build(self) -> QuantizedMmapStorage {
         self.mmap.flush().unwrap();
         let mmap = self.mmap.make_read_only().unwrap(); // TODO: remove unwrap
-        QuantizedMmapStorage { mmap }
+        QuantizedMmapStorage {
+            mmap: Arc::new(mmap),
+        }
     }
 
     fn push_vector_data(&mut self, other: &[u8]) {
@@ -78,7 +84,7 @@ impl QuantizedMmapStorageBuilder {
             .open(path)?;
         file.set_len(encoded_storage_size as u64)?;
         let mmap = unsafe { MmapMut::map_mut(&file) }?;
-        madvise::madvise(&mmap, madvise::get_global())?;
+        madvise::madvise(&mmap, madvise::get_global(madvise::MmapKind::Quantized))?;
         Ok(Self {
             mmap,
             cursor_pos: 0,
@@ -14,8 +14,9 @@ use serde::{Deserialize, Serialize};
 use super::chunked_vectors::ChunkedVectors;
 use super::vector_storage_base::VectorStorage;
 use super::{DenseVectorStorage, VectorStorageEnum};
-use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::yielding::check_process_yield;
 use crate::common::Flusher;
 use crate::data_types::named_vectors::CowVector;
 use crate::data_types::vectors::{VectorElementType, VectorRef};
@@ -172,7 +173,7 @@ impl VectorStorage for SimpleDenseVectorStorage {
     ) -> OperationResult<Range<PointOffsetType>> {
         let start_index = self.vectors.len() as PointOffsetType;
         for point_id in other_ids {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
             // Do not perform preprocessing - vectors should be already processed
             let other_vector = other.get_vector(point_id);
             let other_vector = other_vector.as_vec_ref().try_into()?;
@@ -11,8 +11,9 @@ use serde::{Deserialize, Serialize};
 use sparse::common::sparse_vector::SparseVector;
 
 use super::SparseVectorStorage;
-use crate::common::operation_error::{check_process_stopped, OperationError, OperationResult};
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::yielding::check_process_yield;
 use crate::common::Flusher;
 use crate::data_types::named_vectors::CowVector;
 use crate::data_types::vectors::VectorRef;
@@ -199,7 +200,7 @@ impl VectorStorage for SimpleSparseVectorStorage {
     ) -> OperationResult<Range<PointOffsetType>> {
         let start_index = self.total_vector_count as PointOffsetType;
         for point_id in other_ids {
-            check_process_stopped(stopped)?;
+            check_process_yield(stopped)?;
             // Do not perform preprocessing - vectors should be already processed
             let other_vector = other.get_vector(point_id);
             let other_vector = other_vector.as_vec_ref().try_into()?;
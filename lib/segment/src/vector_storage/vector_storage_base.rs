@@ -94,6 +94,19 @@ pub trait VectorStorage {
     /// The size of this slice is not guaranteed. It may be smaller/larger than the number of
     /// vectors in this segment.
     fn deleted_vector_bitslice(&self) -> &BitSlice;
+
+    /// Hint that vectors of the `ids` range are about to be read, e.g. by a full scan
+    /// Blanket implementation does nothing - override for storages which read ahead from disk
+    fn will_need(&self, _ids: Range<PointOffsetType>) {}
+}
+
+/// Range to read ahead before scoring `ids`, if they are dense enough for reading the whole
+/// range ahead to be cheaper than faulting in vectors one by one
+pub fn scan_range(ids: &[PointOffsetType]) -> Option<Range<PointOffsetType>> {
+    let start = *ids.iter().min()?;
+    let end = *ids.iter().max()? + 1;
+    let is_dense = ((end - start) as usize) <= ids.len() * 2;
+    is_dense.then_some(start..end)
 }
 
 pub trait DenseVectorStorage: VectorStorage {
@@ -242,4 +255,26 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::SparseSimple(v) => v.deleted_vector_bitslice(),
         }
     }
+
+    fn will_need(&self, ids: Range<PointOffsetType>) {
+        match self {
+            VectorStorageEnum::DenseSimple(v) => v.will_need(ids),
+            VectorStorageEnum::Memmap(v) => v.will_need(ids),
+            VectorStorageEnum::AppendableMemmap(v) => v.will_need(ids),
+            VectorStorageEnum::SparseSimple(v) => v.will_need(ids),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_range() {
+        assert_eq!(scan_range(&[]), None);
+        assert_eq!(scan_range(&[7]), Some(7..8));
+        assert_eq!(scan_range(&[12, 10, 13, 15]), Some(10..16));
+        assert_eq!(scan_range(&[0, 100]), None);
+    }
 }
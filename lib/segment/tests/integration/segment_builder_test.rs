@@ -9,7 +9,7 @@ use segment::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
 use segment::entry::entry_point::SegmentEntry;
 use segment::segment::Segment;
 use segment::segment_constructor::segment_builder::SegmentBuilder;
-use segment::types::{Indexes, SegmentConfig, VectorDataConfig, VectorStorageType};
+use segment::types::{Indexes, PointIdType, SegmentConfig, VectorDataConfig, VectorStorageType};
 use tempfile::Builder;
 
 use crate::fixtures::segment::{build_segment_1, build_segment_2, empty_segment};
@@ -70,6 +70,44 @@ fn test_building_new_segment() {
     assert_eq!(merged_segment.point_version(3.into()), Some(100));
 }
 
+#[test]
+fn test_building_segment_in_id_order() {
+    let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+    let temp_dir = Builder::new().prefix("segment_temp_dir").tempdir().unwrap();
+
+    let stopped = AtomicBool::new(false);
+
+    let mut segment = empty_segment(dir.path());
+    for (version, id) in [5u64, 2, 4, 1, 3].into_iter().enumerate() {
+        let vector = [id as f32; 4];
+        segment
+            .upsert_point(version as u64, id.into(), only_default_vector(&vector))
+            .unwrap();
+    }
+
+    let mut builder =
+        SegmentBuilder::new(dir.path(), temp_dir.path(), &segment.segment_config).unwrap();
+    builder.update_from(&segment, &stopped).unwrap();
+    let built_segment: Segment = builder.build(&stopped).unwrap();
+
+    // Points are laid out by external id, regardless of insertion order
+    let id_tracker = built_segment.id_tracker.borrow();
+    let external_ids = id_tracker
+        .iter_ids()
+        .map(|internal_id| id_tracker.external_id(internal_id).unwrap())
+        .collect_vec();
+    assert_eq!(external_ids, (1..=5).map(PointIdType::NumId).collect_vec());
+
+    for external_id in external_ids {
+        assert_eq!(
+            built_segment
+                .vector(DEFAULT_VECTOR_NAME, external_id)
+                .unwrap(),
+            segment.vector(DEFAULT_VECTOR_NAME, external_id).unwrap(),
+        );
+    }
+}
+
 fn estimate_build_time(segment: &Segment, stop_delay_millis: u64) -> (u64, bool) {
     let stopped = Arc::new(AtomicBool::new(false));
 
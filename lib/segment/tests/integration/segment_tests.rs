@@ -1,17 +1,24 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 
 use itertools::Itertools;
 use segment::common::operation_error::OperationError;
+use segment::common::version::VERSION_FILE;
 use segment::data_types::named_vectors::NamedVectors;
 use segment::data_types::vectors::{
     only_default_vector, VectorRef, VectorStruct, DEFAULT_VECTOR_NAME,
 };
 use segment::entry::entry_point::SegmentEntry;
 use segment::fixtures::index_fixtures::random_vector;
-use segment::segment_constructor::load_segment;
+use segment::segment::SEGMENT_STATE_FILE;
 use segment::segment_constructor::simple_segment_constructor::build_simple_segment;
-use segment::types::{Condition, Distance, Filter, SearchParams, WithPayload};
+use segment::segment_constructor::{
+    build_segment, load_segment, segment_is_skipped, verify_segment,
+};
+use segment::types::{
+    Condition, Distance, Filter, Indexes, SearchParams, SegmentConfig, VectorDataConfig,
+    VectorStorageType, WithPayload,
+};
 use tempfile::Builder;
 
 use crate::fixtures::segment::{build_segment_1, build_segment_3};
@@ -238,6 +245,55 @@ fn skip_deleted_segment() {
     assert!(segment.is_none());
 }
 
+#[test]
+fn verify_incomplete_segment() {
+    let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+
+    let path = {
+        let mut segment = build_segment_1(dir.path());
+        segment.flush(true).unwrap();
+        segment.current_path.clone()
+    };
+
+    assert!(verify_segment(&path).is_empty());
+
+    std::fs::write(path.join(SEGMENT_STATE_FILE), "{").unwrap();
+    assert_eq!(verify_segment(&path).len(), 1);
+
+    // Skipped on load, like a segment of an interrupted optimization
+    std::fs::remove_file(path.join(VERSION_FILE)).unwrap();
+    assert!(segment_is_skipped(&path));
+    assert!(verify_segment(&path).is_empty());
+}
+
+#[test]
+fn verify_unversioned_segment() {
+    let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+
+    // Optimizers build their temporary segments without version file until they are complete
+    let config = SegmentConfig {
+        vector_data: HashMap::from([(
+            DEFAULT_VECTOR_NAME.to_owned(),
+            VectorDataConfig {
+                size: 4,
+                distance: Distance::Dot,
+                storage_type: VectorStorageType::Memory,
+                index: Indexes::Plain {},
+                quantization_config: None,
+            },
+        )]),
+        sparse_vector_data: Default::default(),
+        payload_storage_type: Default::default(),
+    };
+    let segment = build_segment(dir.path(), &config, false).unwrap();
+    let path = segment.current_path.clone();
+    drop(segment);
+
+    assert!(segment_is_skipped(&path));
+    assert!(verify_segment(&path).is_empty());
+    assert!(load_segment(&path).unwrap().is_none());
+}
+
 #[test]
 fn test_update_named_vector() {
     let num_points = 25;
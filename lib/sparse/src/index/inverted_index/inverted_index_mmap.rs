@@ -5,7 +5,7 @@ use std::sync::Arc;
 use common::types::PointOffsetType;
 use io::file_operations::{atomic_save_json, read_json};
 use memmap2::{Mmap, MmapMut};
-use memory::madvise;
+use memory::madvise::{self, MmapKind};
 use memory::mmap_ops::{
     create_and_ensure_length, open_read_mmap, open_write_mmap, transmute_from_u8,
     transmute_from_u8_to_slice, transmute_to_u8, transmute_to_u8_slice,
@@ -119,7 +119,7 @@ impl InvertedIndexMmap {
         let file_path = Self::index_file_path(path.as_ref());
         create_and_ensure_length(file_path.as_ref(), file_length)?;
 
-        let mut mmap = open_write_mmap(file_path.as_ref())?;
+        let mut mmap = open_write_mmap(file_path.as_ref(), MmapKind::Other)?;
         madvise::madvise(&mmap, madvise::Advice::Normal)?;
 
         // file index data
@@ -155,7 +155,7 @@ impl InvertedIndexMmap {
         let file_header: InvertedIndexFileHeader = read_json(&config_file_path)?;
         // read index data into mmap
         let file_path = Self::index_file_path(path.as_ref());
-        let mmap = open_read_mmap(file_path.as_ref())?;
+        let mmap = open_read_mmap(file_path.as_ref(), MmapKind::Other)?;
         madvise::madvise(&mmap, madvise::Advice::Normal)?;
         Ok(Self {
             path: path.as_ref().to_owned(),
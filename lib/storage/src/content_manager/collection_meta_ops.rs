@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use collection::config::{CollectionConfig, ShardingMethod};
+use collection::config::{CollectionConfig, SearchDefaults, ShardingMethod};
 use collection::operations::config_diff::{
     CollectionParamsDiff, HnswConfigDiff, OptimizersConfigDiff, QuantizationConfigDiff,
     WalConfigDiff,
@@ -164,6 +164,10 @@ pub struct CreateCollection {
     /// Sparse vector data config.
     #[validate]
     pub sparse_vectors: Option<BTreeMap<String, SparseVectorParams>>,
+    /// Search parameters used for requests which don't specify them.
+    #[serde(default)]
+    #[validate]
+    pub search_defaults: Option<SearchDefaults>,
 }
 
 /// Operation for creating new collection and (optionally) specify index params
@@ -221,6 +225,10 @@ pub struct UpdateCollection {
     /// Map of sparse vector data parameters to update for each sparse vector.
     #[validate]
     pub sparse_vectors: Option<SparseVectorsConfig>,
+    /// Search defaults to update. If none - it is left unchanged.
+    #[serde(default)]
+    #[validate]
+    pub search_defaults: Option<SearchDefaults>,
 }
 
 /// Operation for updating parameters of the existing collection
@@ -243,6 +251,7 @@ impl UpdateCollectionOperation {
                 optimizers_config: None,
                 quantization_config: None,
                 sparse_vectors: None,
+                search_defaults: None,
             },
             shard_replica_changes: None,
         }
@@ -383,6 +392,7 @@ impl From<CollectionConfig> for CreateCollection {
             init_from: None,
             quantization_config: value.quantization_config,
             sparse_vectors: value.params.sparse_vectors,
+            search_defaults: value.search_defaults,
         }
     }
 }
@@ -18,6 +18,7 @@ pub fn error_to_status(error: StorageError) -> tonic::Status {
         StorageError::BadRequest { .. } => tonic::Code::InvalidArgument,
         StorageError::Locked { .. } => tonic::Code::FailedPrecondition,
         StorageError::Timeout { .. } => tonic::Code::DeadlineExceeded,
+        StorageError::Forbidden { .. } => tonic::Code::PermissionDenied,
     };
     tonic::Status::new(error_code, format!("{error}"))
 }
@@ -55,6 +56,7 @@ impl TryFrom<api::grpc::qdrant::CreateCollection> for CollectionMetaOperations {
                     .sharding_method
                     .map(sharding_method_from_proto)
                     .transpose()?,
+                search_defaults: None,
             },
         )))
     }
@@ -84,6 +86,7 @@ impl TryFrom<api::grpc::qdrant::UpdateCollection> for CollectionMetaOperations {
                         config.map.into_iter().map(|(k, v)| (k, v.into())).collect(),
                     )
                 }),
+                search_defaults: None,
             },
         )))
     }
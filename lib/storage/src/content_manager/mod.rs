@@ -109,6 +109,7 @@ pub mod consensus_ops {
                     hnsw_config: None,
                     quantization_config: None,
                     sparse_vectors: None,
+                    search_defaults: None,
                 },
             );
             operation
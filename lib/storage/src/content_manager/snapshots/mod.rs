@@ -13,6 +13,7 @@ use tokio::io::AsyncWriteExt;
 
 use crate::content_manager::toc::FULL_SNAPSHOT_FILE_NAME;
 use crate::dispatcher::Dispatcher;
+use crate::rbac::{self, AccessMode};
 use crate::{StorageError, TableOfContent};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -28,6 +29,7 @@ pub async fn get_full_snapshot_path(
     toc: &TableOfContent,
     snapshot_name: &str,
 ) -> Result<PathBuf, StorageError> {
+    rbac::check_global(AccessMode::Read)?;
     let snapshot_path = Path::new(toc.snapshots_path()).join(snapshot_name);
     if !snapshot_path.exists() {
         return Err(StorageError::NotFound {
@@ -42,6 +44,8 @@ pub async fn do_delete_full_snapshot(
     snapshot_name: &str,
     wait: bool,
 ) -> Result<bool, StorageError> {
+    // Access of the request is not passed to the spawned task
+    rbac::check_global(AccessMode::Manage)?;
     let dispatcher = dispatcher.clone();
     let snapshot_name = snapshot_name.to_string();
     let task =
@@ -70,6 +74,9 @@ pub async fn do_delete_collection_snapshot(
     snapshot_name: &str,
     wait: bool,
 ) -> Result<bool, StorageError> {
+    // Access of the request is not passed to the spawned task
+    let real_collection_name = dispatcher.toc().resolve_alias(collection_name).await;
+    rbac::check_collection(collection_name, &real_collection_name, AccessMode::Manage)?;
     let dispatcher = dispatcher.clone();
     let collection_name = collection_name.to_string();
     let snapshot_name = snapshot_name.to_string();
@@ -100,6 +107,7 @@ async fn _do_delete_collection_snapshot(
 pub async fn do_list_full_snapshots(
     toc: &TableOfContent,
 ) -> Result<Vec<SnapshotDescription>, StorageError> {
+    rbac::check_global(AccessMode::Read)?;
     let snapshots_path = Path::new(toc.snapshots_path());
     Ok(list_snapshots_in_directory(snapshots_path).await?)
 }
@@ -108,6 +116,8 @@ pub async fn do_create_full_snapshot(
     dispatcher: &Dispatcher,
     wait: bool,
 ) -> Result<Option<SnapshotDescription>, StorageError> {
+    // Access of the request is not passed to the spawned task
+    rbac::check_global(AccessMode::Manage)?;
     let dispatcher = dispatcher.clone();
     let task = tokio::spawn(async move { _do_create_full_snapshot(&dispatcher).await });
     if wait {
@@ -11,6 +11,7 @@ use crate::content_manager::collection_meta_ops::{
 };
 use crate::content_manager::snapshots::download::download_snapshot;
 use crate::dispatcher::Dispatcher;
+use crate::rbac::{self, AccessMode};
 use crate::{StorageError, TableOfContent};
 
 pub async fn activate_shard(
@@ -52,6 +53,9 @@ pub async fn do_recover_from_snapshot(
     wait: bool,
     client: reqwest::Client,
 ) -> Result<bool, StorageError> {
+    // Access of the request is not passed to the spawned task
+    let real_collection_name = dispatcher.toc().resolve_alias(collection_name).await;
+    rbac::check_collection(collection_name, &real_collection_name, AccessMode::Manage)?;
     let dispatch = dispatcher.clone();
     let collection_name = collection_name.to_string();
     let recovery = tokio::spawn(async move {
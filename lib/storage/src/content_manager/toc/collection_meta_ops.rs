@@ -111,6 +111,7 @@ impl TableOfContent {
             optimizers_config,
             quantization_config,
             sparse_vectors,
+            search_defaults,
         } = operation.update_collection;
         let collection = self.get_collection(&operation.collection_name).await?;
         let mut recreate_optimizers = false;
@@ -141,6 +142,9 @@ impl TableOfContent {
             collection.update_sparse_vectors_from_other(&diff).await?;
             recreate_optimizers = true;
         }
+        if let Some(diff) = search_defaults {
+            collection.update_search_defaults_from_diff(diff).await?;
+        }
         if let Some(changes) = replica_changes {
             collection.handle_replica_changes(changes).await?;
         }
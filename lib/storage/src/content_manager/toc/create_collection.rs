@@ -46,6 +46,7 @@ impl TableOfContent {
             init_from,
             quantization_config,
             sparse_vectors,
+            search_defaults,
         } = operation;
 
         self.collections
@@ -157,6 +158,7 @@ impl TableOfContent {
             optimizer_config: optimizers_config,
             hnsw_config,
             quantization_config,
+            search_defaults,
         };
         let collection = Collection::new(
             collection_name.to_string(),
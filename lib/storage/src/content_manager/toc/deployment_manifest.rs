@@ -0,0 +1,104 @@
+//! Manifest describing a deployment of the storage.
+//!
+//! Optional `deployment.json` file in the storage root lists collections the deployment consists
+//! of, together with storage format and Qdrant versions able to serve it. Checking the manifest
+//! catches storage copied for a different deployment or built by an incompatible version.
+
+use std::fs;
+use std::path::Path;
+
+use collection::config::CollectionConfig;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use super::storage_verification::StorageIssue;
+use super::tenant_directories::collection_path;
+use super::COLLECTIONS_DIR;
+
+pub const DEPLOYMENT_MANIFEST_FILE: &str = "deployment.json";
+
+/// Version of the storage layout described by the manifest, which this build can serve
+pub const DEPLOYMENT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DeploymentManifest {
+    pub format_version: u32,
+    /// Requirement for the Qdrant version serving the deployment, e.g. `~1.7`
+    pub qdrant_version: String,
+    /// Prefix the storage is kept under in the remote copy
+    #[serde(default)]
+    pub storage_prefix: Option<String>,
+    /// Collections which must be present in the storage
+    pub collections: Vec<String>,
+}
+
+impl DeploymentManifest {
+    /// Load manifest from the storage root, `None` if there is no manifest
+    pub fn load(storage_path: &Path) -> Result<Option<Self>, String> {
+        let manifest_path = storage_path.join(DEPLOYMENT_MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+
+        let manifest = fs::read(&manifest_path).map_err(|err| format!("can't read: {err}"))?;
+        serde_json::from_slice(&manifest)
+            .map(Some)
+            .map_err(|err| format!("can't parse: {err}"))
+    }
+
+    /// Problems preventing `qdrant_version` from serving the deployment stored in `storage_path`
+    pub fn check(&self, storage_path: &Path, qdrant_version: &str) -> Vec<String> {
+        let mut problems = vec![];
+
+        if self.format_version != DEPLOYMENT_FORMAT_VERSION {
+            problems.push(format!(
+                "format version {} is not supported, expected {DEPLOYMENT_FORMAT_VERSION}",
+                self.format_version,
+            ));
+        }
+
+        match VersionReq::parse(&self.qdrant_version) {
+            Ok(requirement) => {
+                let version_matches = Version::parse(qdrant_version)
+                    .is_ok_and(|version| requirement.matches(&version));
+                if !version_matches {
+                    problems.push(format!(
+                        "requires Qdrant {requirement}, but this is Qdrant {qdrant_version}",
+                    ));
+                }
+            }
+            Err(err) => problems.push(format!(
+                "invalid Qdrant version requirement {}: {err}",
+                self.qdrant_version,
+            )),
+        }
+
+        let collections_path = storage_path.join(COLLECTIONS_DIR);
+        for collection in &self.collections {
+            if !CollectionConfig::check(&collection_path(&collections_path, collection)) {
+                problems.push(format!("collection {collection} is missing from storage"));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Check storage in `storage_path` against its deployment manifest, if there is one.
+pub fn verify_deployment(storage_path: &Path, qdrant_version: &str) -> Vec<StorageIssue> {
+    let manifest_path = storage_path.join(DEPLOYMENT_MANIFEST_FILE);
+
+    let problems = match DeploymentManifest::load(storage_path) {
+        Ok(Some(manifest)) => manifest.check(storage_path, qdrant_version),
+        Ok(None) => vec![],
+        Err(problem) => vec![problem],
+    };
+
+    problems
+        .into_iter()
+        .map(|problem| StorageIssue {
+            path: manifest_path.clone(),
+            problem,
+        })
+        .collect()
+}
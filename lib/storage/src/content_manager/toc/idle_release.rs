@@ -0,0 +1,149 @@
+//! Release of memory held by collections which are not used.
+//!
+//! Pages of memory maps stay resident after a search touched them, until the OS runs short of
+//! memory. An instance serving many collections may reach the Lambda memory limit this way,
+//! even though only a few of the collections are used at a time. Releasing pages of idle
+//! collections keeps it below the limit, at the cost of reading the pages from the disk again
+//! once the collection is used.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use memory::madvise::{Advice, MmapKind};
+use memory::{mlock, mmap_ops, mmap_registry};
+use parking_lot::Mutex;
+
+/// Time of the last use of each collection
+pub struct IdleTracker {
+    /// Collections which were not used since are considered used at this time
+    started: Instant,
+    accesses: Mutex<HashMap<String, Access>>,
+}
+
+struct Access {
+    at: Instant,
+    /// Memory of the collection was released since the access
+    released: bool,
+}
+
+impl IdleTracker {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            accesses: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a use of the collection
+    pub fn touch(&self, collection_name: &str) {
+        let mut accesses = self.accesses.lock();
+        let access = Access {
+            at: Instant::now(),
+            released: false,
+        };
+        // Don't allocate the key for every use
+        match accesses.get_mut(collection_name) {
+            Some(existing) => *existing = access,
+            None => {
+                accesses.insert(collection_name.to_string(), access);
+            }
+        }
+    }
+
+    /// Mark `collections` which were not used for `idle` as released and return them, along
+    /// with the time until the next one of the others becomes idle
+    pub fn take_idle(&self, collections: &[String], idle: Duration) -> (Vec<String>, Duration) {
+        let mut accesses = self.accesses.lock();
+        let mut idle_collections = vec![];
+        let mut wait = idle;
+
+        for collection_name in collections {
+            let access = accesses
+                .entry(collection_name.clone())
+                .or_insert_with(|| Access {
+                    at: self.started,
+                    released: false,
+                });
+            if access.released {
+                continue;
+            }
+
+            let remaining = idle.saturating_sub(access.at.elapsed());
+            if remaining.is_zero() {
+                access.released = true;
+                idle_collections.push(collection_name.clone());
+            } else {
+                wait = wait.min(remaining);
+            }
+        }
+
+        // Forget deleted collections
+        accesses.retain(|name, _| collections.contains(name));
+
+        (idle_collections, wait)
+    }
+}
+
+impl Default for IdleTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Drop resident pages of memory maps of the collection at `collection_path`, and drop the
+/// files from the page cache. Returns the number of released files.
+pub fn release_collection_memory(collection_path: &Path) -> usize {
+    let mut released = 0;
+
+    for registered in mmap_registry::mmaps_under(collection_path) {
+        // Locked memory can't be released, it is locked to stay resident
+        let locked = matches!(registered.kind, MmapKind::HnswLinks | MmapKind::Quantized);
+        if locked && mlock::get_global() {
+            continue;
+        }
+
+        // `Free` is rejected for file mappings, `DontNeed` is the only way to release them
+        if let Err(err) = registered.mmap.madvise(Advice::DontNeed) {
+            log::warn!("Failed to release {}: {err}", registered.path.display());
+            continue;
+        }
+        // Pages are only dropped from the page cache once no memory map references them
+        if let Err(err) = mmap_ops::drop_file_cache(&registered.path) {
+            log::debug!(
+                "Failed to drop {} from the page cache: {err}",
+                registered.path.display(),
+            );
+        }
+        released += 1;
+    }
+
+    released
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_tracker() {
+        let tracker = IdleTracker::new();
+        let collections = ["a".to_string(), "b".to_string()];
+
+        let (idle, wait) = tracker.take_idle(&collections, Duration::from_secs(60));
+        assert!(idle.is_empty());
+        assert!(wait <= Duration::from_secs(60));
+
+        // Not used since the start
+        let (idle, _) = tracker.take_idle(&collections, Duration::ZERO);
+        assert_eq!(idle, collections);
+
+        // Released collections are taken again only after being used
+        let (idle, _) = tracker.take_idle(&collections, Duration::ZERO);
+        assert!(idle.is_empty());
+
+        tracker.touch("b");
+        let (idle, _) = tracker.take_idle(&collections, Duration::ZERO);
+        assert_eq!(idle, ["b"]);
+    }
+}
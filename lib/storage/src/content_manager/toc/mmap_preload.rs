@@ -0,0 +1,115 @@
+//! Preloading of files read by every search into the page cache.
+//!
+//! Memory maps are populated lazily, so the first searches after a cold start wait for the disk
+//! on every page of HNSW links and quantized vectors they touch. Reading these files once
+//! during startup, while the CPU is not throttled yet, saves it for the first requests.
+//! Collections can also be warmed up on demand with `POST /collections/{name}/warmup`.
+
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use memory::mmap_ops;
+use segment::index::hnsw_index::graph_layers::HNSW_LINKS_FILE;
+use segment::vector_storage::quantized::quantized_vectors::QUANTIZED_DATA_PATH;
+
+use super::COLLECTIONS_DIR;
+
+/// Files read by every search, regardless of the query
+const HOT_FILES: &[&str] = &[HNSW_LINKS_FILE, QUANTIZED_DATA_PATH];
+
+#[derive(Debug, Clone, Default)]
+pub struct MmapPreloadReport {
+    pub files: usize,
+    pub bytes: usize,
+    pub elapsed: Duration,
+}
+
+/// Load HNSW links and quantized vectors of all collections in `storage_path` into the page
+/// cache. Files which can't be read are skipped, they are reported once the collection is used.
+pub fn preload_hot_files(storage_path: &Path) -> MmapPreloadReport {
+    let mut hot_files = vec![];
+    collect_hot_files(&storage_path.join(COLLECTIONS_DIR), &mut hot_files);
+    load_files(hot_files, true)
+}
+
+/// Load HNSW links and quantized vectors of the collection at `collection_path` into the page
+/// cache, to warm it up before routing traffic to the instance.
+///
+/// If `wait` is false, reading of the files is only requested, see
+/// [`memory::madvise::Madviseable::prefetch`], and the report holds the size of the files.
+pub fn warmup_collection(collection_path: &Path, wait: bool) -> MmapPreloadReport {
+    let mut hot_files = vec![];
+    collect_hot_files(collection_path, &mut hot_files);
+    load_files(hot_files, wait)
+}
+
+fn load_files(paths: Vec<PathBuf>, wait: bool) -> MmapPreloadReport {
+    let started = Instant::now();
+    let mut report = MmapPreloadReport::default();
+
+    for path in paths {
+        let loaded = if wait {
+            mmap_ops::populate_file_pages(&path)
+        } else {
+            mmap_ops::prefetch_file_pages(&path)
+        };
+        match loaded {
+            Ok(bytes) => {
+                report.files += 1;
+                report.bytes += bytes;
+            }
+            Err(err) => log::warn!("Failed to preload {}: {err}", path.display()),
+        }
+    }
+
+    report.elapsed = started.elapsed();
+    report
+}
+
+fn collect_hot_files(path: &Path, hot_files: &mut Vec<PathBuf>) {
+    let Ok(entries) = read_dir(path) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            collect_hot_files(&entry_path, hot_files);
+        } else if HOT_FILES
+            .iter()
+            .any(|hot_file| entry.file_name() == *hot_file)
+        {
+            hot_files.push(entry_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_preload_hot_files() {
+        let storage = tempfile::Builder::new().tempdir().unwrap();
+        let segment_path = storage
+            .path()
+            .join(COLLECTIONS_DIR)
+            .join("test/0/segments/segment");
+        let index_path = segment_path.join("vector_index");
+        fs::create_dir_all(&index_path).unwrap();
+
+        fs::write(index_path.join(HNSW_LINKS_FILE), vec![1u8; 100]).unwrap();
+        fs::write(segment_path.join("payload_index.json"), vec![1u8; 10]).unwrap();
+
+        let report = preload_hot_files(storage.path());
+        assert_eq!(report.files, 1);
+        assert_eq!(report.bytes, 100);
+
+        let report = warmup_collection(&storage.path().join(COLLECTIONS_DIR).join("test"), false);
+        assert_eq!(report.files, 1);
+        assert_eq!(report.bytes, 100);
+    }
+}
@@ -1,20 +1,27 @@
 mod collection_container;
 mod collection_meta_ops;
 mod create_collection;
+pub mod deployment_manifest;
+pub mod idle_release;
 mod locks;
+pub mod mmap_preload;
 mod point_ops;
+pub mod pre_migration_backup;
+pub mod runtime_state;
 mod snapshots;
+pub mod storage_verification;
 mod temp_directories;
+pub mod tenant_directories;
 pub mod transfer;
 
 use std::cmp::max;
 use std::collections::{HashMap, HashSet};
-use std::fs::{create_dir_all, read_dir};
+use std::fs::create_dir_all;
 use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use api::grpc::qdrant::qdrant_internal_client::QdrantInternalClient;
 use api::grpc::qdrant::WaitOnConsensusCommitRequest;
@@ -29,6 +36,7 @@ use collection::shards::shard::{PeerId, ShardId};
 use collection::telemetry::CollectionTelemetry;
 use futures::future::try_join_all;
 use futures::Future;
+use memory::mmap_registry::{self, KindResidency};
 use segment::common::cpu::get_num_cpus;
 use tokio::runtime::Runtime;
 use tokio::sync::{Mutex, RwLock, RwLockReadGuard, Semaphore};
@@ -36,6 +44,9 @@ use tonic::codegen::InterceptedService;
 use tonic::transport::Channel;
 use tonic::Status;
 
+use self::idle_release::IdleTracker;
+use self::mmap_preload::MmapPreloadReport;
+use self::runtime_state::RuntimeState;
 use self::transfer::ShardTransferDispatcher;
 use crate::content_manager::alias_mapping::AliasPersistence;
 use crate::content_manager::collection_meta_ops::CreateCollectionOperation;
@@ -43,6 +54,7 @@ use crate::content_manager::collections_ops::{Checker, Collections};
 use crate::content_manager::consensus::operation_sender::OperationSender;
 use crate::content_manager::errors::StorageError;
 use crate::content_manager::shard_distribution::ShardDistributionProposal;
+use crate::rbac::{self, AccessMode};
 use crate::types::{PeerAddressById, StorageConfig};
 use crate::ConsensusOperations;
 
@@ -77,6 +89,12 @@ pub struct TableOfContent {
     collection_create_lock: Mutex<()>,
     /// Dispatcher for shard transfer to access consensus.
     shard_transfer_dispatcher: parking_lot::Mutex<Option<ShardTransferDispatcher>>,
+    /// Time of the last update of points, to checkpoint collections once updates stop.
+    last_update: parking_lot::Mutex<Option<Instant>>,
+    /// Time of the last use of each collection, to release memory of idle collections.
+    idle_tracker: IdleTracker,
+    /// Collections found on start, saved for the next start if they are not changed.
+    runtime_state: RuntimeState,
 }
 
 impl TableOfContent {
@@ -98,15 +116,11 @@ impl TableOfContent {
             let temp_path = Path::new(path);
             create_dir_all(temp_path).expect("Can't create temporary files directory");
         }
-        let collection_paths =
-            read_dir(&collections_path).expect("Can't read Collections directory");
+        let (collection_paths, runtime_state) =
+            Self::collection_paths(Path::new(&storage_config.storage_path));
         let mut collections: HashMap<String, Collection> = Default::default();
         let is_distributed = consensus_proposal_sender.is_some();
-        for entry in collection_paths {
-            let collection_path = entry
-                .expect("Can't access of one of the collection files")
-                .path();
-
+        for (collection_name, collection_path) in collection_paths {
             if !CollectionConfig::check(&collection_path) {
                 log::warn!(
                     "Collection config is not found in the collection directory: {:?}, skipping",
@@ -115,12 +129,6 @@ impl TableOfContent {
                 continue;
             }
 
-            let collection_name = collection_path
-                .file_name()
-                .expect("Can't resolve a filename of one of the collection files")
-                .to_str()
-                .expect("A filename of one of the collection files is not a valid UTF-8")
-                .to_string();
             let collection_snapshots_path =
                 Self::collection_snapshots_path(&snapshots_path, &collection_name);
             create_dir_all(&collection_snapshots_path).unwrap_or_else(|e| {
@@ -194,6 +202,9 @@ impl TableOfContent {
             update_rate_limiter: rate_limiter,
             collection_create_lock: Default::default(),
             shard_transfer_dispatcher: Default::default(),
+            last_update: Default::default(),
+            idle_tracker: Default::default(),
+            runtime_state,
         }
     }
 
@@ -216,15 +227,11 @@ impl TableOfContent {
             let temp_path = Path::new(path);
             create_dir_all(temp_path).expect("Can't create temporary files directory");
         }
-        let collection_paths =
-            read_dir(&collections_path).expect("Can't read Collections directory");
+        let (collection_paths, runtime_state) =
+            Self::collection_paths(Path::new(&storage_config.storage_path));
         let mut collections: HashMap<String, Collection> = Default::default();
         let is_distributed = consensus_proposal_sender.is_some();
-        for entry in collection_paths {
-            let collection_path = entry
-                .expect("Can't access of one of the collection files")
-                .path();
-
+        for (collection_name, collection_path) in collection_paths {
             if !CollectionConfig::check(&collection_path) {
                 log::warn!(
                     "Collection config is not found in the collection directory: {:?}, skipping",
@@ -233,12 +240,6 @@ impl TableOfContent {
                 continue;
             }
 
-            let collection_name = collection_path
-                .file_name()
-                .expect("Can't resolve a filename of one of the collection files")
-                .to_str()
-                .expect("A filename of one of the collection files is not a valid UTF-8")
-                .to_string();
             let collection_snapshots_path =
                 Self::collection_snapshots_path(&snapshots_path, &collection_name);
             create_dir_all(&collection_snapshots_path).unwrap_or_else(|e| {
@@ -311,6 +312,9 @@ impl TableOfContent {
             update_rate_limiter: rate_limiter,
             collection_create_lock: Default::default(),
             shard_transfer_dispatcher: Default::default(),
+            last_update: Default::default(),
+            idle_tracker: Default::default(),
+            runtime_state,
         }
     }
 
@@ -337,6 +341,14 @@ impl TableOfContent {
             .collect()
     }
 
+    /// List of all collections, `None` if they are locked at the moment
+    ///
+    /// Doesn't block, can be called where awaiting is impossible, e.g. in a panic hook.
+    pub fn try_all_collections(&self) -> Option<Vec<String>> {
+        let collections = self.collections.try_read().ok()?;
+        Some(collections.keys().cloned().collect())
+    }
+
     pub async fn get_collection(
         &self,
         collection_name: &str,
@@ -347,6 +359,8 @@ impl TableOfContent {
             let alias_persistence = self.alias_persistence.read().await;
             Self::resolve_name(collection_name, &read_collection, &alias_persistence).await?
         };
+        rbac::check_collection(collection_name, &real_collection_name, AccessMode::Read)?;
+        self.idle_tracker.touch(&real_collection_name);
         // resolve_name already checked collection existence, unwrap is safe here
         Ok(RwLockReadGuard::map(read_collection, |collection| {
             collection.get(&real_collection_name).unwrap()
@@ -390,6 +404,15 @@ impl TableOfContent {
         Ok(resolved_name)
     }
 
+    /// Name of the collection the alias `name` points to, or `name` itself if it is not an alias
+    pub async fn resolve_alias(&self, name: &str) -> String {
+        self.alias_persistence
+            .read()
+            .await
+            .get(name)
+            .unwrap_or_else(|| name.to_string())
+    }
+
     /// List of all aliases for a given collection
     pub async fn collection_aliases(
         &self,
@@ -523,6 +546,99 @@ impl TableOfContent {
         result
     }
 
+    /// Time it took to load every collection
+    pub async fn collections_init_time(&self) -> Vec<(String, Duration)> {
+        let collections = self.collections.read().await;
+        collections
+            .iter()
+            .map(|(name, collection)| (name.clone(), collection.init_time()))
+            .collect()
+    }
+
+    /// Persist WAL and segments of all collections to disk
+    pub async fn flush_all_collections(&self) -> Result<(), StorageError> {
+        let collections = self.collections.read().await;
+        for collection in collections.values() {
+            collection.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Persist all collections to disk, and drop applied operations from their WAL, so they
+    /// are not replayed when the collections are loaded again
+    pub async fn checkpoint_all_collections(&self) -> Result<(), StorageError> {
+        let collections = self.collections.read().await;
+        for collection in collections.values() {
+            collection.checkpoint().await?;
+        }
+        Ok(())
+    }
+
+    /// Load HNSW links and quantized vectors of the collection into the page cache, see
+    /// [`mmap_preload::warmup_collection`]
+    pub async fn warmup_collection(
+        &self,
+        collection_name: &str,
+        wait: bool,
+    ) -> Result<MmapPreloadReport, StorageError> {
+        let collection_path = {
+            let collection = self.get_collection(collection_name).await?;
+            self.get_collection_path(&collection.name())
+        };
+        let report = tokio::task::spawn_blocking(move || {
+            mmap_preload::warmup_collection(&collection_path, wait)
+        })
+        .await?;
+        Ok(report)
+    }
+
+    /// Bytes of memory maps of the collection resident in RAM, for each kind of data. Doesn't
+    /// count as a use of the collection.
+    pub async fn collection_memory(
+        &self,
+        collection_name: &str,
+    ) -> Result<Vec<KindResidency>, StorageError> {
+        let real_collection_name = {
+            let collections = self.collections.read().await;
+            let alias_persistence = self.alias_persistence.read().await;
+            Self::resolve_name(collection_name, &collections, &alias_persistence).await?
+        };
+        let collection_path = self.get_collection_path(&real_collection_name);
+        let residency =
+            tokio::task::spawn_blocking(move || mmap_registry::residency_under(&collection_path))
+                .await?;
+        Ok(residency)
+    }
+
+    pub fn idle_tracker(&self) -> &IdleTracker {
+        &self.idle_tracker
+    }
+
+    /// Release resident memory of the collection, see
+    /// [`idle_release::release_collection_memory`]. Returns the number of released files.
+    pub async fn release_collection_memory(
+        &self,
+        collection_name: &str,
+    ) -> Result<usize, StorageError> {
+        // Not using `get_collection`, it would count as a use of the collection
+        self.collections
+            .read()
+            .await
+            .validate_collection_exists(collection_name)
+            .await?;
+        let collection_path = self.get_collection_path(collection_name);
+        let released = tokio::task::spawn_blocking(move || {
+            idle_release::release_collection_memory(&collection_path)
+        })
+        .await?;
+        Ok(released)
+    }
+
+    /// Time of the last update of points, `None` if there was none since start
+    pub fn last_update(&self) -> Option<Instant> {
+        *self.last_update.lock()
+    }
+
     /// Cancels all transfers where the source peer is the current peer.
     pub async fn cancel_outgoing_all_transfers(&self, reason: &str) -> Result<(), StorageError> {
         let collections = self.collections.read().await;
@@ -649,9 +765,32 @@ impl TableOfContent {
     }
 
     async fn create_collection_path(&self, collection_name: &str) -> Result<PathBuf, StorageError> {
+        let collections_path = Path::new(&self.storage_config.storage_path).join(COLLECTIONS_DIR);
+        if self.storage_config.tenant_directories {
+            let tenant_path = tenant_directories::tenant_path(&collections_path, collection_name);
+            if let Some((tenant_path, _)) = tenant_path {
+                if !collections_path.join(collection_name).exists() {
+                    tokio::fs::create_dir_all(&tenant_path)
+                        .await
+                        .map_err(|err| {
+                            StorageError::service_error(format!(
+                                "Can't create tenant directory for collection {collection_name}. Error: {err}"
+                            ))
+                        })?;
+                }
+            }
+        }
+
         let path = self.get_collection_path(collection_name);
 
         if path.exists() {
+            if tenant_directories::is_tenant_dir(&path)
+                && tenant_directories::has_collections(&path)
+            {
+                return Err(StorageError::bad_input(format!(
+                    "Can't create collection with name {collection_name}. The name is taken by the directory of a tenant",
+                )));
+            }
             if CollectionConfig::check(&path) {
                 return Err(StorageError::bad_input(format!(
                     "Can't create collection with name {collection_name}. Collection data already exists at {path}",
@@ -682,9 +821,10 @@ impl TableOfContent {
     }
 
     fn get_collection_path(&self, collection_name: &str) -> PathBuf {
-        Path::new(&self.storage_config.storage_path)
-            .join(COLLECTIONS_DIR)
-            .join(collection_name)
+        tenant_directories::collection_path(
+            &Path::new(&self.storage_config.storage_path).join(COLLECTIONS_DIR),
+            collection_name,
+        )
     }
 
     /// Wait until all other known peers reach the given commit
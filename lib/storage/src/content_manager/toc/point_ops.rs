@@ -1,8 +1,8 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use collection::collection::Collection;
 use collection::grouping::group_by::GroupRequest;
-use collection::grouping::GroupBy;
+use collection::grouping::{count, GroupBy};
 use collection::operations::consistency_params::ReadConsistency;
 use collection::operations::point_ops::WriteOrdering;
 use collection::operations::shard_selector_internal::ShardSelectorInternal;
@@ -14,6 +14,7 @@ use segment::types::{ScoredPoint, ShardKey};
 
 use super::TableOfContent;
 use crate::content_manager::errors::StorageError;
+use crate::rbac::{self, AccessMode};
 
 impl TableOfContent {
     /// Recommend points using positive and negative example from the request
@@ -131,6 +132,31 @@ impl TableOfContent {
             .map_err(|err| err.into())
     }
 
+    /// Estimate the number of groups of points in the collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `collection_name` - in what collection do we count
+    /// * `request` - [`GroupsCountRequestInternal`]
+    /// * `shard_selection` - which local shard to use
+    ///
+    /// # Result
+    ///
+    /// Estimated number of distinct values of the group_by field.
+    ///
+    pub async fn count_groups(
+        &self,
+        collection_name: &str,
+        request: GroupsCountRequestInternal,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: ShardSelectorInternal,
+    ) -> Result<GroupsCountResult, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        count::count_groups(request, &collection, read_consistency, &shard_selection)
+            .await
+            .map_err(|err| err.into())
+    }
+
     /// Return specific points by IDs
     ///
     /// # Arguments
@@ -278,6 +304,7 @@ impl TableOfContent {
         shard_selector: ShardSelectorInternal,
     ) -> Result<UpdateResult, StorageError> {
         let collection = self.get_collection(collection_name).await?;
+        rbac::check_collection(collection_name, &collection.name(), AccessMode::Write)?;
 
         // Ordered operation flow:
         //
@@ -316,6 +343,7 @@ impl TableOfContent {
         };
         if operation.is_write_operation() {
             self.check_write_lock()?;
+            self.last_update.lock().replace(Instant::now());
         }
         let res = match shard_selector {
             ShardSelectorInternal::Empty => {
@@ -0,0 +1,44 @@
+//! Backup of storage files, which are rewritten when stored data is migrated on load.
+//!
+//! Migration of collections and segments to the current version only rewrites their version
+//! and segment state files, so keeping copies of those is enough to start the previous binary
+//! on the same storage again.
+
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use segment::common::version::VERSION_FILE;
+use segment::segment::SEGMENT_STATE_FILE;
+
+pub const PRE_MIGRATION_DIR: &str = "pre-migration";
+
+const MIGRATED_FILES: &[&str] = &[VERSION_FILE, SEGMENT_STATE_FILE];
+
+/// Copy files of `paths`, which are rewritten by migration, into `pre-migration/` of the storage.
+///
+/// The backup keeps the layout relative to `storage_path`, so a rollback is done by copying
+/// it over the storage. Files already present in the backup are not overwritten, so it always
+/// holds the state before the first migration.
+///
+/// Returns path of the backup directory.
+pub fn backup_before_migration(storage_path: &Path, paths: &[PathBuf]) -> io::Result<PathBuf> {
+    let backup_path = storage_path.join(PRE_MIGRATION_DIR);
+
+    for path in paths {
+        let relative_path = path
+            .strip_prefix(storage_path)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let target_dir = backup_path.join(relative_path);
+        fs::create_dir_all(&target_dir)?;
+
+        for file_name in MIGRATED_FILES {
+            let source = path.join(file_name);
+            let target = target_dir.join(file_name);
+            if source.exists() && !target.exists() {
+                fs::copy(&source, &target)?;
+            }
+        }
+    }
+
+    Ok(backup_path)
+}
@@ -0,0 +1,248 @@
+//! Lightweight state of the storage, saved on clean shutdown for the next start.
+//!
+//! Lists collections of the storage, so the next start loads them without scanning the
+//! collections directory. The list is only used while the directory keeps the modification time
+//! it was taken at: collections created or deleted since, e.g. by another instance sharing the
+//! storage, change it, and the directory is scanned again. The same goes for directories of
+//! tenants, see [`super::tenant_directories`].
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use io::file_operations::{atomic_save_json, read_json};
+use serde::{Deserialize, Serialize};
+
+use super::tenant_directories::{collection_dirs, collection_path, tenant_dirs};
+use super::{TableOfContent, COLLECTIONS_DIR};
+use crate::content_manager::errors::StorageError;
+
+pub const RUNTIME_STATE_FILE: &str = "runtime_state.json";
+
+/// Changes of a directory this soon after its modification time may not change it, as some
+/// filesystems keep coarse timestamps
+const MODIFIED_TIME_GRANULARITY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RuntimeState {
+    /// Collections of the storage
+    pub collections: Vec<String>,
+    /// Modification time of the collections directory the list was taken at, `None` if it was
+    /// modified too recently to tell later changes apart
+    #[serde(default)]
+    pub collections_modified: Option<SystemTime>,
+    /// Modification times of the tenant directories the list was taken at
+    #[serde(default)]
+    pub tenants_modified: BTreeMap<String, SystemTime>,
+}
+
+impl RuntimeState {
+    /// Read the state from the storage root, `None` if there is no valid state
+    pub fn read(storage_path: &Path) -> Option<Self> {
+        let state_path = storage_path.join(RUNTIME_STATE_FILE);
+        if !state_path.exists() {
+            return None;
+        }
+
+        match read_json(&state_path) {
+            Ok(state) => Some(state),
+            Err(err) => {
+                log::warn!("Failed to read runtime state {state_path:?}: {err}");
+                None
+            }
+        }
+    }
+
+    /// Remove the state from the storage root, when the storage is changed before loading it
+    pub fn discard(storage_path: &Path) {
+        let state_path = storage_path.join(RUNTIME_STATE_FILE);
+        if state_path.exists() {
+            if let Err(err) = fs::remove_file(&state_path) {
+                log::warn!("Failed to remove runtime state {state_path:?}: {err}");
+            }
+        }
+    }
+
+    /// Scan the collections directory
+    fn scan(collections_path: &Path) -> Self {
+        let now = SystemTime::now();
+        let settled_modified_time = |path: &Path| {
+            modified_time(path).filter(|modified| {
+                now.duration_since(*modified)
+                    .map_or(false, |age| age >= MODIFIED_TIME_GRANULARITY)
+            })
+        };
+
+        // Taken before the scan, so that changes during the scan make the state stale
+        let mut collections_modified = settled_modified_time(collections_path);
+        let mut tenants_modified = BTreeMap::new();
+        for (tenant, tenant_path) in
+            tenant_dirs(collections_path).expect("Can't read Collections directory")
+        {
+            match settled_modified_time(&tenant_path) {
+                Some(modified) => {
+                    tenants_modified.insert(tenant, modified);
+                }
+                None => collections_modified = None,
+            }
+        }
+
+        let collections = collection_dirs(collections_path)
+            .expect("Can't read Collections directory")
+            .into_iter()
+            .map(|(collection, _)| collection)
+            .collect();
+
+        Self {
+            collections,
+            collections_modified,
+            tenants_modified,
+        }
+    }
+
+    /// Whether collections were neither created nor deleted since the state was taken
+    fn is_current(&self, collections_path: &Path) -> bool {
+        self.collections_modified.is_some()
+            && self.collections_modified == modified_time(collections_path)
+            && self.tenants_modified.iter().all(|(tenant, modified)| {
+                modified_time(&collections_path.join(tenant)) == Some(*modified)
+            })
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+impl TableOfContent {
+    /// Save state of the storage for the next start, must be the last thing before shutdown
+    ///
+    /// The state is only saved if collections were neither created nor deleted since the start.
+    /// Otherwise a state left by a previous run is stale, and the next start scans the
+    /// collections directory.
+    pub async fn save_runtime_state(&self) -> Result<(), StorageError> {
+        let storage_path = Path::new(self.storage_path());
+        if self
+            .runtime_state
+            .is_current(&storage_path.join(COLLECTIONS_DIR))
+        {
+            atomic_save_json(&storage_path.join(RUNTIME_STATE_FILE), &self.runtime_state)?;
+        }
+        Ok(())
+    }
+
+    /// Names and paths of the collections to load, from the runtime state saved on shutdown if it
+    /// is current, and the state they were found in
+    pub(super) fn collection_paths(storage_path: &Path) -> (Vec<(String, PathBuf)>, RuntimeState) {
+        let collections_path = storage_path.join(COLLECTIONS_DIR);
+
+        let state = match RuntimeState::read(storage_path) {
+            Some(state) if state.is_current(&collections_path) => {
+                log::debug!("Loading collections listed in runtime state");
+                state
+            }
+            Some(_) => {
+                log::debug!("Collections were created or deleted since runtime state was saved");
+                RuntimeState::scan(&collections_path)
+            }
+            None => RuntimeState::scan(&collections_path),
+        };
+
+        let collection_paths = state
+            .collections
+            .iter()
+            .map(|collection| {
+                let path = collection_path(&collections_path, collection);
+                (collection.clone(), path)
+            })
+            .collect();
+        (collection_paths, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loaded_collections(storage_path: &Path) -> Vec<String> {
+        let (_, state) = TableOfContent::collection_paths(storage_path);
+        let mut collections = state.collections;
+        collections.sort();
+        collections
+    }
+
+    #[test]
+    fn test_collections_are_listed_from_current_state() {
+        let storage_dir = tempfile::Builder::new()
+            .prefix("storage")
+            .tempdir()
+            .unwrap();
+        let collections_path = storage_dir.path().join(COLLECTIONS_DIR);
+        for collection in ["a", "b"] {
+            fs::create_dir_all(collections_path.join(collection)).unwrap();
+        }
+
+        // Just modified, later changes may not change the modification time
+        let (_, state) = TableOfContent::collection_paths(storage_dir.path());
+        assert!(state.collections_modified.is_none());
+        assert_eq!(loaded_collections(storage_dir.path()), ["a", "b"]);
+
+        // Listed collections are loaded without a scan
+        let modified = modified_time(&collections_path).unwrap();
+        let state = RuntimeState {
+            collections: vec!["a".to_string()],
+            collections_modified: Some(modified),
+            tenants_modified: BTreeMap::new(),
+        };
+        atomic_save_json(&storage_dir.path().join(RUNTIME_STATE_FILE), &state).unwrap();
+        assert_eq!(loaded_collections(storage_dir.path()), ["a"]);
+
+        // Stale state is ignored
+        let state = RuntimeState {
+            collections: vec!["a".to_string()],
+            collections_modified: Some(modified - Duration::from_secs(1)),
+            tenants_modified: BTreeMap::new(),
+        };
+        atomic_save_json(&storage_dir.path().join(RUNTIME_STATE_FILE), &state).unwrap();
+        assert_eq!(loaded_collections(storage_dir.path()), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_collections_of_tenants_are_listed() {
+        let storage_dir = tempfile::Builder::new()
+            .prefix("storage")
+            .tempdir()
+            .unwrap();
+        let collections_path = storage_dir.path().join(COLLECTIONS_DIR);
+        fs::create_dir_all(collections_path.join("acme/docs")).unwrap();
+        fs::create_dir_all(collections_path.join("shared")).unwrap();
+
+        let (mut collection_paths, _) = TableOfContent::collection_paths(storage_dir.path());
+        collection_paths.sort();
+        assert_eq!(
+            collection_paths,
+            [
+                ("acme.docs".to_string(), collections_path.join("acme/docs")),
+                ("shared".to_string(), collections_path.join("shared")),
+            ],
+        );
+
+        // Collections created in a tenant directory since the state make it stale
+        let state = RuntimeState {
+            collections: vec!["shared".to_string()],
+            collections_modified: modified_time(&collections_path),
+            tenants_modified: BTreeMap::from([(
+                "acme".to_string(),
+                modified_time(&collections_path.join("acme")).unwrap() - Duration::from_secs(1),
+            )]),
+        };
+        atomic_save_json(&storage_dir.path().join(RUNTIME_STATE_FILE), &state).unwrap();
+        assert_eq!(
+            loaded_collections(storage_dir.path()),
+            ["acme.docs", "shared"],
+        );
+    }
+}
@@ -9,6 +9,7 @@ use super::TableOfContent;
 use crate::content_manager::consensus::operation_sender::OperationSender;
 use crate::content_manager::consensus_ops::ConsensusOperations;
 use crate::content_manager::errors::StorageError;
+use crate::rbac::{self, AccessMode};
 
 impl TableOfContent {
     pub fn snapshots_path(&self) -> &str {
@@ -46,6 +47,8 @@ impl TableOfContent {
         &self,
         collection_name: &str,
     ) -> Result<SnapshotDescription, StorageError> {
+        let real_collection_name = self.resolve_alias(collection_name).await;
+        rbac::check_collection(collection_name, &real_collection_name, AccessMode::Manage)?;
         let collection = self.get_collection(collection_name).await?;
         // We want to use temp dir inside the temp_path (storage if not specified), because it is possible, that
         // snapshot directory is mounted as network share and multiple writes to it could be slow
@@ -0,0 +1,149 @@
+//! Verification of the storage directory before collections are loaded.
+//!
+//! Storage restored from a remote copy may be incomplete or truncated. Loading such storage
+//! fails deep inside segment loading. This pass only inspects files, so problems are reported
+//! up-front and all at once. Segments without version file are skipped on load, as they are
+//! left by optimizations interrupted by a crash, so they are reported but are not an issue.
+
+use std::fmt;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+use collection::collection::Collection;
+use collection::config::CollectionConfig;
+use segment::segment_constructor::{segment_is_skipped, segment_needs_migration, verify_segment};
+use serde::Serialize;
+
+use super::tenant_directories::is_tenant_dir;
+use super::COLLECTIONS_DIR;
+
+const SEGMENTS_DIR: &str = "segments";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageIssue {
+    pub path: PathBuf,
+    pub problem: String,
+}
+
+impl fmt::Display for StorageIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.problem)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StorageVerificationReport {
+    pub collections: usize,
+    pub shards: usize,
+    pub segments: usize,
+    pub issues: Vec<StorageIssue>,
+    /// Collection and segment directories in an older format, which are migrated on load
+    pub migrations: Vec<PathBuf>,
+    /// Segment directories which are skipped on load, e.g. temporary segments of an
+    /// interrupted optimization. Not an issue, the optimization starts over.
+    pub skipped: Vec<PathBuf>,
+}
+
+impl StorageVerificationReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn issue(&mut self, path: &Path, problem: impl Into<String>) {
+        self.issues.push(StorageIssue {
+            path: path.to_path_buf(),
+            problem: problem.into(),
+        });
+    }
+}
+
+/// Inspect collections stored in `storage_path` without loading them.
+pub fn verify_storage(storage_path: &Path) -> StorageVerificationReport {
+    let mut report = StorageVerificationReport::default();
+
+    let collections_path = storage_path.join(COLLECTIONS_DIR);
+    if !collections_path.exists() {
+        // Fresh storage, nothing to verify
+        return report;
+    }
+
+    let mut collection_paths = vec![];
+    for path in sub_directories(&collections_path, &mut report) {
+        if is_tenant_dir(&path) {
+            collection_paths.extend(sub_directories(&path, &mut report));
+        } else {
+            collection_paths.push(path);
+        }
+    }
+
+    for collection_path in collection_paths {
+        // Directories without config are skipped on load as well
+        if !CollectionConfig::check(&collection_path) {
+            continue;
+        }
+        report.collections += 1;
+
+        if let Err(err) = CollectionConfig::load(&collection_path) {
+            report.issue(
+                &collection_path,
+                format!("can't parse collection config: {err}"),
+            );
+            continue;
+        }
+
+        match Collection::check_stored_version(&collection_path) {
+            Ok(true) => report.migrations.push(collection_path.clone()),
+            Ok(false) => {}
+            Err(err) => {
+                report.issue(&collection_path, err.to_string());
+                continue;
+            }
+        }
+
+        for shard_path in sub_directories(&collection_path, &mut report) {
+            let segments_path = shard_path.join(SEGMENTS_DIR);
+            if !segments_path.is_dir() {
+                continue;
+            }
+            report.shards += 1;
+
+            for segment_path in sub_directories(&segments_path, &mut report) {
+                report.segments += 1;
+                if segment_is_skipped(&segment_path) {
+                    report.skipped.push(segment_path);
+                    continue;
+                }
+                let problems = verify_segment(&segment_path);
+                if problems.is_empty() && segment_needs_migration(&segment_path) {
+                    report.migrations.push(segment_path.clone());
+                }
+                for problem in problems {
+                    report.issue(&segment_path, problem);
+                }
+            }
+        }
+    }
+
+    report
+}
+
+fn sub_directories(path: &Path, report: &mut StorageVerificationReport) -> Vec<PathBuf> {
+    let entries = match read_dir(path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            report.issue(path, format!("can't read directory: {err}"));
+            return vec![];
+        }
+    };
+
+    let mut directories = vec![];
+    for entry in entries {
+        match entry {
+            Ok(entry) if entry.path().is_dir() => directories.push(entry.path()),
+            Ok(_) => {}
+            Err(err) => report.issue(path, format!("can't read directory entry: {err}")),
+        }
+    }
+    directories.sort();
+    directories
+}
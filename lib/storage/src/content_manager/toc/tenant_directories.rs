@@ -0,0 +1,157 @@
+//! Directories of tenants in the collections directory.
+//!
+//! Collections of tenants are named `<tenant>.<collection>`, see [`TENANT_SEPARATOR`]. With
+//! `tenant_directories` of the storage config, they are stored in a directory of their tenant,
+//! `collections/<tenant>/<collection>`, so the storage of a tenant can be copied or removed as
+//! a whole. A tenant directory is told apart from a collection by not having a collection config.
+
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use collection::config::CollectionConfig;
+
+/// Separates the tenant from the collection in names of collections of tenants.
+/// Not allowed in tenant ids, so a tenant can't address collections of another one.
+pub const TENANT_SEPARATOR: char = '.';
+
+/// Directory of the collection in `collections_path`.
+///
+/// A collection of a tenant is in the directory of the tenant if there is one, unless the
+/// collection was stored directly in `collections_path` before.
+pub fn collection_path(collections_path: &Path, collection_name: &str) -> PathBuf {
+    let path = collections_path.join(collection_name);
+    if path.exists() {
+        return path;
+    }
+    match tenant_path(collections_path, collection_name) {
+        Some((tenant_path, name)) if is_tenant_dir(&tenant_path) => tenant_path.join(name),
+        _ => path,
+    }
+}
+
+/// Directory of the tenant of the collection, and the name of the collection within it, `None`
+/// if the collection doesn't belong to a tenant
+pub fn tenant_path<'a>(
+    collections_path: &Path,
+    collection_name: &'a str,
+) -> Option<(PathBuf, &'a str)> {
+    let (tenant, name) = collection_name.split_once(TENANT_SEPARATOR)?;
+    (!tenant.is_empty() && !name.is_empty()).then(|| (collections_path.join(tenant), name))
+}
+
+/// Whether `path` in the collections directory is a directory of a tenant
+pub fn is_tenant_dir(path: &Path) -> bool {
+    let is_tenant_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map_or(false, |name| !name.contains(TENANT_SEPARATOR));
+    is_tenant_name && path.is_dir() && !CollectionConfig::check(path)
+}
+
+/// Whether the tenant directory at `path` holds any collection with a config
+pub fn has_collections(path: &Path) -> bool {
+    fs::read_dir(path).map_or(false, |entries| {
+        entries
+            .flatten()
+            .any(|entry| CollectionConfig::check(&entry.path()))
+    })
+}
+
+/// Tenant ids and directories of the tenants in `collections_path`
+pub fn tenant_dirs(collections_path: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    Ok(named_entries(collections_path)?
+        .into_iter()
+        .filter(|(_, path)| is_tenant_dir(path))
+        .collect())
+}
+
+/// Names and directories of the collections in `collections_path`, including collections in
+/// directories of tenants. Directories without a collection config are listed as well, except
+/// for the tenant ones.
+pub fn collection_dirs(collections_path: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut collections = vec![];
+    for (name, path) in named_entries(collections_path)? {
+        if is_tenant_dir(&path) {
+            for (collection, path) in named_entries(&path)? {
+                collections.push((format!("{name}{TENANT_SEPARATOR}{collection}"), path));
+            }
+        } else {
+            collections.push((name, path));
+        }
+    }
+    Ok(collections)
+}
+
+fn named_entries(path: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    fs::read_dir(path)?
+        .map(|entry| {
+            let entry = entry?;
+            let name = entry.file_name().into_string().map_err(|name| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{name:?} is not a valid UTF-8 name"),
+                )
+            })?;
+            Ok((name, entry.path()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_dirs() {
+        let storage = tempfile::Builder::new()
+            .prefix("collections")
+            .tempdir()
+            .unwrap();
+        let collections_path = storage.path();
+
+        // Collection stored before the tenant directory was created
+        fs::create_dir_all(collections_path.join("acme.old")).unwrap();
+        fs::write(collections_path.join("acme.old/config.json"), "{}").unwrap();
+        fs::create_dir_all(collections_path.join("acme/docs")).unwrap();
+        fs::create_dir_all(collections_path.join("shared")).unwrap();
+        fs::write(collections_path.join("shared/config.json"), "{}").unwrap();
+
+        let mut collections = collection_dirs(collections_path).unwrap();
+        collections.sort();
+        assert_eq!(
+            collections,
+            [
+                ("acme.docs".to_string(), collections_path.join("acme/docs")),
+                ("acme.old".to_string(), collections_path.join("acme.old")),
+                ("shared".to_string(), collections_path.join("shared")),
+            ],
+        );
+
+        assert_eq!(
+            collection_path(collections_path, "acme.docs"),
+            collections_path.join("acme/docs"),
+        );
+        assert_eq!(
+            collection_path(collections_path, "acme.old"),
+            collections_path.join("acme.old"),
+        );
+        assert_eq!(
+            collection_path(collections_path, "acme.new"),
+            collections_path.join("acme/new"),
+        );
+        assert_eq!(
+            collection_path(collections_path, "globex.docs"),
+            collections_path.join("globex.docs"),
+        );
+        assert_eq!(
+            tenant_dirs(collections_path).unwrap(),
+            [("acme".to_string(), collections_path.join("acme"))],
+        );
+        assert!(!has_collections(&collections_path.join("acme")));
+
+        assert_eq!(
+            collection_path(collections_path, "shared"),
+            collections_path.join("shared"),
+        );
+    }
+}
@@ -8,8 +8,8 @@ use common::defaults::CONSENSUS_META_OP_WAIT;
 
 use crate::content_manager::shard_distribution::ShardDistributionProposal;
 use crate::{
-    ClusterStatus, CollectionMetaOperations, ConsensusOperations, ConsensusStateRef, StorageError,
-    TableOfContent,
+    rbac, ClusterStatus, CollectionMetaOperations, ConsensusOperations, ConsensusStateRef,
+    StorageError, TableOfContent,
 };
 
 #[derive(Clone)]
@@ -48,6 +48,8 @@ impl Dispatcher {
         operation: CollectionMetaOperations,
         wait_timeout: Option<Duration>,
     ) -> Result<bool, StorageError> {
+        rbac::check_meta_operation(&operation, &self.toc).await?;
+
         // if distributed deployment is enabled
         if let Some(state) = self.consensus_state.as_ref() {
             // List of operations to await for collection to be operational
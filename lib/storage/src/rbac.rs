@@ -0,0 +1,199 @@
+//! Access of the API request being served, granted by the claims of its JWT.
+//!
+//! The access is kept in a task local while the request is served, see [`scope`]. The
+//! [`Dispatcher`](crate::dispatcher::Dispatcher) and the table of content check it before
+//! operations on collections, so REST and gRPC requests are restricted alike. Requests served
+//! without an access, e.g. authenticated with an API key, and background tasks are not
+//! restricted.
+//!
+//! Collections may be addressed by their aliases. Access to either the name in the request or
+//! the collection it resolves to is enough, so claims may list aliases as well as collections.
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use tokio::task::futures::TaskLocalFuture;
+
+use crate::content_manager::collection_meta_ops::CollectionMetaOperations;
+use crate::content_manager::errors::StorageError;
+use crate::content_manager::toc::TableOfContent;
+
+tokio::task_local! {
+    static ACCESS: Access;
+}
+
+/// Operations allowed by an access, each mode allows the operations of the previous ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum AccessMode {
+    /// Read points and information of collections
+    #[serde(rename = "r")]
+    Read,
+    /// Write points and payload indices
+    #[serde(rename = "rw")]
+    Write,
+    /// Create, change and delete collections, and their snapshots
+    #[serde(rename = "m")]
+    Manage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Access {
+    /// Access to all collections, and to the service itself, e.g. `"m"`
+    Global(AccessMode),
+    /// Access to the listed collections only, e.g. `[{"collection": "books", "access": "r"}]`
+    Collections(Vec<CollectionAccess>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CollectionAccess {
+    pub collection: String,
+    pub access: AccessMode,
+}
+
+impl Access {
+    pub fn check_global(&self, mode: AccessMode) -> Result<(), StorageError> {
+        match self {
+            Access::Global(granted) if *granted >= mode => Ok(()),
+            _ => Err(StorageError::forbidden(format!(
+                "Global {mode:?} access is required"
+            ))),
+        }
+    }
+
+    pub fn check_collection(
+        &self,
+        collection_name: &str,
+        mode: AccessMode,
+    ) -> Result<(), StorageError> {
+        let granted = match self {
+            Access::Global(granted) => Some(*granted),
+            Access::Collections(collections) => collections
+                .iter()
+                .filter(|access| access.collection == collection_name)
+                .map(|access| access.access)
+                .max(),
+        };
+        match granted {
+            Some(granted) if granted >= mode => Ok(()),
+            _ => Err(StorageError::forbidden(format!(
+                "{mode:?} access to collection {collection_name} is required"
+            ))),
+        }
+    }
+}
+
+/// Serve `future` as a request with `access`
+pub fn scope<F: Future>(access: Access, future: F) -> TaskLocalFuture<Access, F> {
+    ACCESS.scope(access, future)
+}
+
+/// Check that the request served by the current task may use the service with `mode`
+pub fn check_global(mode: AccessMode) -> Result<(), StorageError> {
+    ACCESS
+        .try_with(|access| access.check_global(mode))
+        .unwrap_or(Ok(()))
+}
+
+/// Check that the request served by the current task may use the collection with `mode`.
+///
+/// `collection_name` is the name in the request, `real_collection_name` the collection it
+/// resolves to, the same name unless it is an alias.
+pub fn check_collection(
+    collection_name: &str,
+    real_collection_name: &str,
+    mode: AccessMode,
+) -> Result<(), StorageError> {
+    ACCESS
+        .try_with(|access| {
+            access
+                .check_collection(collection_name, mode)
+                .or_else(|err| {
+                    if real_collection_name == collection_name {
+                        return Err(err);
+                    }
+                    access
+                        .check_collection(real_collection_name, mode)
+                        .map_err(|_| err)
+                })
+        })
+        .unwrap_or(Ok(()))
+}
+
+/// Check that the request served by the current task may perform the meta operation
+pub async fn check_meta_operation(
+    operation: &CollectionMetaOperations,
+    toc: &TableOfContent,
+) -> Result<(), StorageError> {
+    let (collection_name, mode) = match operation {
+        CollectionMetaOperations::CreateCollection(op) => (&op.collection_name, AccessMode::Manage),
+        CollectionMetaOperations::UpdateCollection(op) => (&op.collection_name, AccessMode::Manage),
+        CollectionMetaOperations::DeleteCollection(op) => (&op.0, AccessMode::Manage),
+        CollectionMetaOperations::TransferShard(collection_name, _) => {
+            (collection_name, AccessMode::Manage)
+        }
+        CollectionMetaOperations::SetShardReplicaState(op) => {
+            (&op.collection_name, AccessMode::Manage)
+        }
+        CollectionMetaOperations::CreateShardKey(op) => (&op.collection_name, AccessMode::Manage),
+        CollectionMetaOperations::DropShardKey(op) => (&op.collection_name, AccessMode::Manage),
+        CollectionMetaOperations::CreatePayloadIndex(op) => {
+            (&op.collection_name, AccessMode::Write)
+        }
+        CollectionMetaOperations::DropPayloadIndex(op) => (&op.collection_name, AccessMode::Write),
+        // Aliases may point to any collection, and may be renamed or deleted by their names alone
+        CollectionMetaOperations::ChangeAliases(_) => return check_global(AccessMode::Manage),
+        CollectionMetaOperations::Nop { .. } => return Ok(()),
+    };
+    let real_collection_name = toc.resolve_alias(collection_name).await;
+    check_collection(collection_name, &real_collection_name, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access() {
+        let access: Access = serde_json::from_str(
+            r#"[{"collection": "books", "access": "rw"}, {"collection": "authors", "access": "r"}]"#,
+        )
+        .unwrap();
+
+        futures::executor::block_on(scope(access, async {
+            assert!(check_collection("books", "books", AccessMode::Write).is_ok());
+            assert!(check_collection("books", "books", AccessMode::Manage).is_err());
+            assert!(check_collection("authors", "authors", AccessMode::Read).is_ok());
+            assert!(check_collection("authors", "authors", AccessMode::Write).is_err());
+            assert!(check_collection("movies", "movies", AccessMode::Read).is_err());
+            assert!(check_global(AccessMode::Read).is_err());
+        }));
+
+        let access: Access = serde_json::from_str(r#""r""#).unwrap();
+        assert_eq!(access, Access::Global(AccessMode::Read));
+        assert!(access.check_collection("movies", AccessMode::Read).is_ok());
+        assert!(access.check_global(AccessMode::Write).is_err());
+
+        // Not restricted outside of a request
+        assert!(check_global(AccessMode::Manage).is_ok());
+    }
+
+    #[test]
+    fn test_access_through_alias() {
+        let access: Access = serde_json::from_str(
+            r#"[{"collection": "books_v2", "access": "rw"}, {"collection": "authors", "access": "r"}]"#,
+        )
+        .unwrap();
+
+        futures::executor::block_on(scope(access, async {
+            // Claim of the collection an alias points to
+            assert!(check_collection("books", "books_v2", AccessMode::Write).is_ok());
+            assert!(check_collection("books", "books_v2", AccessMode::Manage).is_err());
+            // Claim of the alias
+            assert!(check_collection("authors", "authors_v3", AccessMode::Read).is_ok());
+            assert!(check_collection("authors", "authors_v3", AccessMode::Write).is_err());
+            // Neither the alias nor the collection is claimed
+            assert!(check_collection("movies", "movies_v1", AccessMode::Read).is_err());
+        }));
+    }
+}
@@ -4,7 +4,7 @@ use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use collection::config::WalConfig;
-use collection::operations::shared_storage_config::SharedStorageConfig;
+use collection::operations::shared_storage_config::{SharedStorageConfig, UpdateBatchingConfig};
 use collection::operations::types::NodeType;
 use collection::optimizers_builder::OptimizersConfig;
 use collection::shards::shard::PeerId;
@@ -27,8 +27,28 @@ pub struct PerformanceConfig {
     pub update_rate_limit: Option<usize>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub search_timeout_sec: Option<usize>,
+    /// Merge upserts arriving within this window into a single update, in milliseconds.
+    /// Disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_batch_window_ms: Option<u64>,
+    /// Apply merged upserts early once there are this many of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_batch_max_operations: Option<usize>,
+    /// Pause optimizations while requests are being served, so they don't compete for the CPU.
+    #[serde(default)]
+    pub optimizers_yield_to_requests: bool,
+    /// Pause optimizations while writes to the storage take longer than this, in milliseconds,
+    /// e.g. once burst credits of EFS are exhausted. Disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub io_pacing_latency_ms: Option<u64>,
+    /// Write vectors and HNSW links of optimized segments with direct IO, bypassing the page
+    /// cache, so they don't evict pages which searches need.
+    #[serde(default)]
+    pub optimizers_direct_io: bool,
 }
 
+const DEFAULT_UPDATE_BATCH_MAX_OPERATIONS: usize = 64;
+
 const fn default_max_optimization_threads() -> usize {
     1
 }
@@ -56,7 +76,7 @@ pub struct StorageConfig {
     #[validate]
     pub quantization: Option<QuantizationConfig>,
     #[serde(default = "default_mmap_advice")]
-    pub mmap_advice: madvise::Advice,
+    pub mmap_advice: madvise::AdviceConfig,
     #[serde(default)]
     pub node_type: NodeType,
     #[serde(default)]
@@ -65,6 +85,10 @@ pub struct StorageConfig {
     pub handle_collection_load_errors: bool,
     #[serde(default)]
     pub async_scorer: bool,
+    /// Read vectors of the async scorer with direct IO, bypassing the page cache, so scoring
+    /// doesn't depend on what else is cached. Linux only, requires `async_scorer`.
+    #[serde(default)]
+    pub async_scorer_direct_io: bool,
     /// If provided - qdrant will start in recovery mode, which means that it will not accept any new data.
     /// Only collection metadata will be available, and it will only process collection delete requests.
     /// Provided value will be used error message for unavailable requests.
@@ -72,6 +96,48 @@ pub struct StorageConfig {
     pub recovery_mode: Option<String>,
     #[serde(default)]
     pub update_concurrency: Option<NonZeroUsize>,
+    /// Load HNSW links and quantized vectors into the page cache on startup, after collections
+    /// are loaded, so the first searches don't wait for the disk.
+    #[serde(default)]
+    pub preload_on_startup: bool,
+    /// Checkpoint all collections once no points were updated for this many seconds, so
+    /// the next start doesn't replay their WAL. Disabled if not set.
+    #[serde(default)]
+    pub checkpoint_idle_sec: Option<u64>,
+    /// Lock memory maps of HNSW links and quantized vectors in RAM, so their pages are never
+    /// evicted. Requires a sufficient limit of locked memory of the process.
+    #[serde(default)]
+    pub mlock: bool,
+    /// Release resident pages of memory maps of collections which were not used for this many
+    /// seconds. Disabled if not set.
+    #[serde(default)]
+    pub release_idle_sec: Option<u64>,
+    /// Watch available memory and major page faults, and give memory back once they show
+    /// pressure. Disabled if not set.
+    #[serde(default)]
+    pub memory_pressure: Option<MemoryPressureConfig>,
+    /// Store new collections of tenants, `<tenant>.<collection>`, in a directory of their tenant,
+    /// `collections/<tenant>/<collection>`. Always enabled with tenancy of the service.
+    #[serde(default)]
+    pub tenant_directories: bool,
+}
+
+/// Thresholds of the memory pressure monitor, see `storage.memory_pressure` of the settings
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct MemoryPressureConfig {
+    /// Memory is under pressure once less than this is available, in megabytes
+    pub min_available_mb: usize,
+    /// Memory is under pressure once the process takes more major page faults per second.
+    /// Not considered if not set.
+    #[serde(default)]
+    pub max_major_faults_per_sec: Option<u64>,
+    /// Under pressure, release memory of collections which were not used for this many seconds
+    #[serde(default = "default_release_cold_sec")]
+    pub release_cold_sec: u64,
+}
+
+const fn default_release_cold_sec() -> u64 {
+    60
 }
 
 impl StorageConfig {
@@ -86,6 +152,15 @@ impl StorageConfig {
                 .map(|x| Duration::from_secs(x as u64)),
             self.update_concurrency,
             is_distributed,
+            self.performance
+                .update_batch_window_ms
+                .map(|window_ms| UpdateBatchingConfig {
+                    window: Duration::from_millis(window_ms),
+                    max_operations: self
+                        .performance
+                        .update_batch_max_operations
+                        .unwrap_or(DEFAULT_UPDATE_BATCH_MAX_OPERATIONS),
+                }),
         )
     }
 }
@@ -98,8 +173,8 @@ const fn default_on_disk_payload() -> bool {
     false
 }
 
-const fn default_mmap_advice() -> madvise::Advice {
-    madvise::Advice::Random
+const fn default_mmap_advice() -> madvise::AdviceConfig {
+    madvise::AdviceConfig::all(madvise::Advice::Random)
 }
 
 /// Information of a peer in the cluster
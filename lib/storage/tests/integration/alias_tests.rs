@@ -47,17 +47,29 @@ fn test_alias_operation() {
             max_optimization_threads: 1,
             update_rate_limit: None,
             search_timeout_sec: None,
+            update_batch_window_ms: None,
+            update_batch_max_operations: None,
+            optimizers_yield_to_requests: false,
+            io_pacing_latency_ms: None,
+            optimizers_direct_io: false,
         },
         hnsw_index: Default::default(),
         quantization: None,
-        mmap_advice: madvise::Advice::Random,
+        mmap_advice: madvise::AdviceConfig::all(madvise::Advice::Random),
         node_type: Default::default(),
         update_queue_size: Default::default(),
         handle_collection_load_errors: false,
         recovery_mode: None,
         async_scorer: false,
+        async_scorer_direct_io: false,
         update_concurrency: Some(NonZeroUsize::new(2).unwrap()),
         // update_concurrency: None,
+        preload_on_startup: false,
+        checkpoint_idle_sec: None,
+        mlock: false,
+        release_idle_sec: None,
+        memory_pressure: None,
+        tenant_directories: false,
     };
 
     let search_runtime = Runtime::new().unwrap();
@@ -106,6 +118,7 @@ fn test_alias_operation() {
                         init_from: None,
                         quantization_config: None,
                         sharding_method: None,
+                        search_defaults: None,
                     },
                 )),
                 None,
@@ -0,0 +1,33 @@
+use std::fs;
+
+use storage::content_manager::toc::deployment_manifest::{
+    verify_deployment, DEPLOYMENT_MANIFEST_FILE,
+};
+use tempfile::Builder;
+
+#[test]
+fn test_deployment_manifest_mismatch() {
+    let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+
+    // No manifest, nothing to check
+    assert!(verify_deployment(storage_dir.path(), "1.7.3").is_empty());
+
+    fs::write(
+        storage_dir.path().join(DEPLOYMENT_MANIFEST_FILE),
+        r#"{"format_version": 1, "qdrant_version": "~1.8", "collections": ["missing"]}"#,
+    )
+    .unwrap();
+
+    let issues = verify_deployment(storage_dir.path(), "1.7.3");
+    assert_eq!(issues.len(), 2, "{issues:?}");
+    assert!(issues[0].problem.contains("requires Qdrant"));
+    assert!(issues[1].problem.contains("collection missing"));
+
+    fs::write(
+        storage_dir.path().join(DEPLOYMENT_MANIFEST_FILE),
+        r#"{"format_version": 1, "qdrant_version": "~1.7", "collections": []}"#,
+    )
+    .unwrap();
+
+    assert!(verify_deployment(storage_dir.path(), "1.7.3").is_empty());
+}
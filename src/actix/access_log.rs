@@ -0,0 +1,106 @@
+use std::future::{ready, Ready};
+use std::time::Instant;
+
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use api::grpc::request_id::REQUEST_ID_HEADER;
+use futures_util::future::LocalBoxFuture;
+
+use crate::actix::api_key::request_api_key;
+use crate::common::auth::AuthKeys;
+use crate::tracing::access_log::AccessLogEntry;
+
+pub struct AccessLogTransform {
+    auth_keys: Option<AuthKeys>,
+}
+
+impl AccessLogTransform {
+    pub fn new(auth_keys: Option<AuthKeys>) -> Self {
+        Self { auth_keys }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLogTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AccessLogService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AccessLogService {
+            service,
+            auth_keys: self.auth_keys.clone(),
+        }))
+    }
+}
+
+pub struct AccessLogService<S> {
+    service: S,
+    auth_keys: Option<AuthKeys>,
+}
+
+impl<S, B> Service<ServiceRequest> for AccessLogService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let instant = Instant::now();
+        let method = request.method().to_string();
+        let path = request.path().to_string();
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|request_id| request_id.to_str().ok())
+            .map(|request_id| request_id.to_string());
+        let api_key = self
+            .auth_keys
+            .as_ref()
+            .and_then(|auth_keys| request_api_key(&request).and_then(|key| auth_keys.key_id(&key)));
+
+        let future = self.service.call(request);
+
+        Box::pin(async move {
+            let response = future.await;
+
+            let (status, bytes) = match &response {
+                Ok(response) => {
+                    let bytes = match response.response().body().size() {
+                        BodySize::Sized(size) => Some(size),
+                        BodySize::None | BodySize::Stream => None,
+                    };
+                    (response.status(), bytes)
+                }
+                Err(error) => (error.as_response_error().status_code(), None),
+            };
+
+            AccessLogEntry {
+                protocol: "http",
+                method: &method,
+                path: &path,
+                status: status.as_u16(),
+                duration: instant.elapsed(),
+                bytes,
+                api_key,
+                request_id: request_id.as_deref(),
+            }
+            .log();
+
+            response
+        })
+    }
+}
@@ -9,6 +9,7 @@ use parking_lot::Mutex;
 use crate::common::telemetry_ops::requests_telemetry::{
     ActixTelemetryCollector, ActixWorkerTelemetryCollector,
 };
+use crate::common::telemetry_ops::startup_telemetry;
 
 pub struct ActixTelemetryService<S> {
     service: S,
@@ -49,6 +50,7 @@ where
             telemetry_data
                 .lock()
                 .add_response(request_key, status, instant);
+            startup_telemetry::request_served();
             Ok(response)
         })
     }
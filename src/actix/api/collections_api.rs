@@ -1,10 +1,10 @@
 use std::time::Duration;
 
 use actix_web::rt::time::Instant;
-use actix_web::{delete, get, patch, post, put, web, Responder};
+use actix_web::{delete, get, patch, post, put, web, HttpMessage, HttpRequest, Responder};
 use actix_web_validator::{Json, Path, Query};
 use collection::operations::cluster_ops::ClusterOperations;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use storage::content_manager::collection_meta_ops::{
     ChangeAliasesOperation, CollectionMetaOperations, CreateCollection, CreateCollectionOperation,
     DeleteCollectionOperation, UpdateCollection, UpdateCollectionOperation,
@@ -16,6 +16,7 @@ use validator::Validate;
 use super::CollectionPath;
 use crate::actix::api::StrictCollectionPath;
 use crate::actix::helpers::process_response;
+use crate::actix::tenancy::Tenant;
 use crate::common::collections::*;
 
 #[derive(Debug, Deserialize, Validate)]
@@ -31,10 +32,19 @@ impl WaitTimeout {
 }
 
 #[get("/collections")]
-async fn get_collections(toc: web::Data<TableOfContent>) -> impl Responder {
+async fn get_collections(toc: web::Data<TableOfContent>, req: HttpRequest) -> impl Responder {
     let timing = Instant::now();
-    let response = Ok(do_list_collections(toc.get_ref()).await);
-    process_response(response, timing)
+    let mut response = do_list_collections(toc.get_ref()).await;
+    if let Some(tenant) = req.extensions().get::<Tenant>() {
+        response.collections.retain_mut(|collection| {
+            let Some(name) = tenant.strip_namespace(&collection.name) else {
+                return false;
+            };
+            collection.name = name.to_string();
+            true
+        });
+    }
+    process_response(Ok(response), timing)
 }
 
 #[get("/aliases")]
@@ -168,6 +178,47 @@ async fn update_collection_cluster(
     process_response(response, timing)
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct WarmupParams {
+    /// Wait until the files are read into the page cache, otherwise only request reading them
+    wait: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct WarmupResult {
+    /// Number of the files loaded
+    files: usize,
+    /// Total size of the files in bytes
+    bytes: usize,
+}
+
+#[post("/collections/{name}/warmup")]
+async fn warmup_collection(
+    toc: web::Data<TableOfContent>,
+    collection: Path<CollectionPath>,
+    Query(params): Query<WarmupParams>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let response = toc
+        .warmup_collection(&collection.name, params.wait.unwrap_or(false))
+        .await
+        .map(|report| WarmupResult {
+            files: report.files,
+            bytes: report.bytes,
+        });
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/memory")]
+async fn get_collection_memory(
+    toc: web::Data<TableOfContent>,
+    collection: Path<CollectionPath>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let response = toc.collection_memory(&collection.name).await;
+    process_response(response, timing)
+}
+
 // Configure services
 pub fn config_collections_api(cfg: &mut web::ServiceConfig) {
     cfg.service(get_collections)
@@ -179,7 +230,9 @@ pub fn config_collections_api(cfg: &mut web::ServiceConfig) {
         .service(get_collection_aliases)
         .service(update_aliases)
         .service(get_cluster_info)
-        .service(update_collection_cluster);
+        .service(update_collection_cluster)
+        .service(warmup_collection)
+        .service(get_collection_memory);
 }
 
 #[cfg(test)]
@@ -2,13 +2,13 @@ use actix_web::rt::time::Instant;
 use actix_web::{post, web, Responder};
 use actix_web_validator::{Json, Path, Query};
 use collection::operations::shard_selector_internal::ShardSelectorInternal;
-use collection::operations::types::CountRequest;
+use collection::operations::types::{CountRequest, GroupsCountRequest};
 use storage::content_manager::toc::TableOfContent;
 
 use super::CollectionPath;
 use crate::actix::api::read_params::ReadParams;
 use crate::actix::helpers::process_response;
-use crate::common::points::do_count_points;
+use crate::common::points::{do_count_point_groups, do_count_points};
 
 #[post("/collections/{name}/points/count")]
 async fn count_points(
@@ -41,3 +41,34 @@ async fn count_points(
 
     process_response(response, timing)
 }
+
+#[post("/collections/{name}/points/groups/count")]
+async fn count_point_groups(
+    toc: web::Data<TableOfContent>,
+    collection: Path<CollectionPath>,
+    request: Json<GroupsCountRequest>,
+    params: Query<ReadParams>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let GroupsCountRequest {
+        count_request,
+        shard_key,
+    } = request.into_inner();
+
+    let shard_selector = match shard_key {
+        None => ShardSelectorInternal::All,
+        Some(shard_keys) => ShardSelectorInternal::from(shard_keys),
+    };
+
+    let response = do_count_point_groups(
+        toc.get_ref(),
+        &collection.name,
+        count_request,
+        params.consistency,
+        shard_selector,
+    )
+    .await;
+
+    process_response(response, timing)
+}
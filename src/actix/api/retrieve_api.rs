@@ -13,6 +13,7 @@ use validator::Validate;
 use super::read_params::ReadParams;
 use super::CollectionPath;
 use crate::actix::helpers::process_response;
+use crate::actix::response_streaming::{process_streamed_response, ResponseStreaming};
 use crate::common::points::do_get_points;
 
 #[derive(Deserialize, Validate)]
@@ -119,6 +120,7 @@ async fn scroll_points(
     collection: Path<CollectionPath>,
     request: Json<ScrollRequest>,
     params: Query<ReadParams>,
+    streaming: web::Data<ResponseStreaming>,
 ) -> impl Responder {
     let timing = Instant::now();
 
@@ -141,5 +143,5 @@ async fn scroll_points(
         )
         .await;
 
-    process_response(response, timing)
+    process_streamed_response(response, timing, &streaming)
 }
@@ -1,5 +1,5 @@
 use actix_web::rt::time::Instant;
-use actix_web::{post, web, Responder};
+use actix_web::{post, web, HttpRequest, Responder};
 use actix_web_validator::{Json, Path, Query};
 use collection::operations::shard_selector_internal::ShardSelectorInternal;
 use collection::operations::types::{
@@ -9,7 +9,9 @@ use storage::content_manager::toc::TableOfContent;
 
 use super::read_params::ReadParams;
 use super::CollectionPath;
+use crate::actix::compact_scores;
 use crate::actix::helpers::process_response;
+use crate::actix::response_streaming::{process_streamed_response, ResponseStreaming};
 use crate::common::points::{
     do_core_search_points, do_search_batch_points, do_search_point_groups,
 };
@@ -20,8 +22,11 @@ async fn search_points(
     collection: Path<CollectionPath>,
     request: Json<SearchRequest>,
     params: Query<ReadParams>,
+    req: HttpRequest,
+    streaming: web::Data<ResponseStreaming>,
 ) -> impl Responder {
     let timing = Instant::now();
+    let compact = compact_scores::is_accepted(&req);
 
     let SearchRequest {
         search_request,
@@ -33,17 +38,26 @@ async fn search_points(
         Some(shard_keys) => shard_keys.into(),
     };
 
+    let mut core_request: CoreSearchRequest = search_request.into();
+    if compact {
+        compact_scores::strip_request(&mut core_request);
+    }
+
     let response = do_core_search_points(
         toc.get_ref(),
         &collection.name,
-        search_request.into(),
+        core_request,
         params.consistency,
         shard_selection,
         params.timeout(),
     )
     .await;
 
-    process_response(response, timing)
+    if compact {
+        compact_scores::process_search_response(response, timing)
+    } else {
+        process_streamed_response(response, timing, &streaming)
+    }
 }
 
 #[post("/collections/{name}/points/search/batch")]
@@ -52,8 +66,11 @@ async fn batch_search_points(
     collection: Path<CollectionPath>,
     request: Json<SearchRequestBatch>,
     params: Query<ReadParams>,
+    req: HttpRequest,
+    streaming: web::Data<ResponseStreaming>,
 ) -> impl Responder {
     let timing = Instant::now();
+    let compact = compact_scores::is_accepted(&req);
 
     let request = request.into_inner();
     let requests = request
@@ -68,7 +85,10 @@ async fn batch_search_points(
                 None => ShardSelectorInternal::All,
                 Some(shard_keys) => shard_keys.into(),
             };
-            let core_request: CoreSearchRequest = search_request.into();
+            let mut core_request: CoreSearchRequest = search_request.into();
+            if compact {
+                compact_scores::strip_request(&mut core_request);
+            }
 
             (core_request, shard_selection)
         })
@@ -83,7 +103,11 @@ async fn batch_search_points(
     )
     .await;
 
-    process_response(response, timing)
+    if compact {
+        compact_scores::process_batch_search_response(response, timing)
+    } else {
+        process_streamed_response(response, timing, &streaming)
+    }
 }
 
 #[post("/collections/{name}/points/search/groups")]
@@ -1,23 +1,32 @@
+use std::str::FromStr as _;
 use std::sync::Arc;
 
 use actix_web::http::header::ContentType;
 use actix_web::http::StatusCode;
 use actix_web::rt::time::Instant;
 use actix_web::web::Query;
-use actix_web::{get, post, web, HttpResponse, Responder};
+use actix_web::{get, patch, post, web, HttpResponse, Responder};
 use actix_web_validator::Json;
 use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use serde::{Deserialize, Serialize};
+use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
 use tokio::sync::Mutex;
 
 use crate::actix::helpers::process_response;
+use crate::actix::runtime_settings::{RuntimeSettingsDiff, RuntimeSettingsHandle};
 use crate::common::health;
 use crate::common::helpers::LocksOption;
 use crate::common::metrics::MetricsData;
 use crate::common::stacktrace::get_stack_trace;
 use crate::common::telemetry::TelemetryCollector;
+use crate::common::telemetry_ops::startup_telemetry::{
+    CollectionStartupTelemetry, StartupTelemetry,
+};
+use crate::tracing::{sink, LoggerConfigDiff, LoggerHandle};
+
+const DEFAULT_LOGS_TAIL_LINES: usize = 100;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
 pub struct TelemetryParam {
@@ -43,6 +52,37 @@ async fn telemetry(
     process_response(Ok(telemetry_data), timing)
 }
 
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct StartupTelemetryParam {
+    pub anonymize: Option<bool>,
+}
+
+#[get("/telemetry/startup")]
+async fn startup_telemetry(
+    toc: web::Data<TableOfContent>,
+    params: Query<StartupTelemetryParam>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let anonymize = params.anonymize.unwrap_or(false);
+    let collections = toc
+        .get_ref()
+        .collections_init_time()
+        .await
+        .into_iter()
+        .map(|(id, init_time)| CollectionStartupTelemetry {
+            id,
+            load_ms: init_time.as_millis() as u64,
+        })
+        .collect();
+    let startup_telemetry = StartupTelemetry::collect(collections);
+    let startup_telemetry = if anonymize {
+        startup_telemetry.anonymize()
+    } else {
+        startup_telemetry
+    };
+    process_response(Ok(startup_telemetry), timing)
+}
+
 #[derive(Deserialize, Serialize, JsonSchema)]
 pub struct MetricsParam {
     pub anonymize: Option<bool>,
@@ -92,6 +132,76 @@ async fn get_locks(toc: web::Data<TableOfContent>) -> impl Responder {
     process_response(Ok(result), timing)
 }
 
+#[get("/settings")]
+async fn get_settings(runtime_settings: web::Data<RuntimeSettingsHandle>) -> impl Responder {
+    let timing = Instant::now();
+    process_response(Ok(runtime_settings.get()), timing)
+}
+
+/// Apply a diff to the settings which can be changed at runtime, see [`RuntimeSettingsDiff`]
+#[patch("/settings")]
+async fn update_settings(
+    runtime_settings: web::Data<RuntimeSettingsHandle>,
+    web::Json(diff): web::Json<RuntimeSettingsDiff>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let result = runtime_settings.update(diff).await;
+    process_response(result, timing)
+}
+
+#[get("/logger")]
+async fn get_logger(logger_handle: web::Data<LoggerHandle>) -> impl Responder {
+    let timing = Instant::now();
+    process_response(Ok(logger_handle.get_config().await), timing)
+}
+
+/// Apply a diff to the configuration of the logger, only the fields which are set are changed
+#[patch("/logger")]
+async fn update_logger(
+    logger_handle: web::Data<LoggerHandle>,
+    web::Json(diff): web::Json<LoggerConfigDiff>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let result = logger_handle.update_config(diff).await.map_err(|err| {
+        StorageError::bad_request(format!("Failed to update logger config: {err:#}"))
+    });
+    process_response(result, timing)
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct LogsTailParam {
+    /// Number of the latest records to return, defaults to 100
+    pub lines: Option<usize>,
+    /// Only return records at this level or more severe, e.g. `warn`
+    pub level: Option<String>,
+}
+
+#[get("/logs/tail")]
+async fn logs_tail(
+    logger_handle: web::Data<LoggerHandle>,
+    params: Query<LogsTailParam>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let lines = params.lines.unwrap_or(DEFAULT_LOGS_TAIL_LINES);
+    let level = match params
+        .level
+        .as_deref()
+        .map(tracing::Level::from_str)
+        .transpose()
+    {
+        Ok(level) => level,
+        Err(err) => {
+            let err = StorageError::bad_request(format!("Invalid log level: {err}"));
+            return process_response::<()>(Err(err), timing);
+        }
+    };
+    let result = logger_handle
+        .tail(lines, level)
+        .await
+        .map_err(|err| StorageError::bad_request(err.to_string()));
+    process_response(result, timing)
+}
+
 #[get("/stacktrace")]
 async fn get_stacktrace() -> impl Responder {
     let timing = Instant::now();
@@ -104,9 +214,29 @@ async fn healthz() -> impl Responder {
     kubernetes_healthz().await
 }
 
+/// Liveness of the service, with log files which are currently unwritable.
+///
+/// Service stays alive while log files are unwritable, logs are written to stdout instead.
 #[get("/livez")]
 async fn livez() -> impl Responder {
-    kubernetes_healthz().await
+    let unhealthy_sinks: Vec<_> = sink::health()
+        .into_iter()
+        .filter(|sink| !sink.healthy)
+        .map(|sink| format!("{} ({})", sink.name, sink.log_file))
+        .collect();
+
+    let body = if unhealthy_sinks.is_empty() {
+        "healthz check passed".to_string()
+    } else {
+        format!(
+            "healthz check passed, logging to stdout instead of unwritable log files: {}",
+            unhealthy_sinks.join(", "),
+        )
+    };
+
+    HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .body(body)
 }
 
 #[get("/readyz")]
@@ -137,9 +267,15 @@ async fn kubernetes_healthz() -> impl Responder {
 // Configure services
 pub fn config_service_api(cfg: &mut web::ServiceConfig) {
     cfg.service(telemetry)
+        .service(startup_telemetry)
         .service(metrics)
         .service(put_locks)
         .service(get_locks)
+        .service(get_settings)
+        .service(update_settings)
+        .service(get_logger)
+        .service(update_logger)
+        .service(logs_tail)
         .service(get_stacktrace)
         .service(healthz)
         .service(livez)
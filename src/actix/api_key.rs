@@ -1,18 +1,22 @@
 use std::future::{ready, Ready};
+use std::rc::Rc;
 
 use actix_web::body::{BoxBody, EitherBody};
 use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
 use actix_web::http::header::Header;
 use actix_web::http::Method;
-use actix_web::{Error, HttpResponse};
+use actix_web::{Error, HttpMessage, HttpResponse};
 use actix_web_httpauth::headers::authorization::{Authorization, Bearer};
 use futures_util::future::LocalBoxFuture;
+use storage::rbac::{self, Access, AccessMode};
 
+use crate::actix::tenancy::Tenant;
 use crate::common::auth::AuthKeys;
 
-const READ_ONLY_POST_PATTERNS: [&str; 11] = [
+const READ_ONLY_POST_PATTERNS: [&str; 13] = [
     "/collections/{name}/points",
     "/collections/{name}/points/count",
+    "/collections/{name}/points/groups/count",
     "/collections/{name}/points/search",
     "/collections/{name}/points/scroll",
     "/collections/{name}/points/search/groups",
@@ -22,6 +26,7 @@ const READ_ONLY_POST_PATTERNS: [&str; 11] = [
     "/collections/{name}/points/recommend/batch",
     "/collections/{name}/points/discover",
     "/collections/{name}/points/discover/batch",
+    "/collections/{name}/warmup",
 ];
 
 pub struct ApiKey {
@@ -40,7 +45,8 @@ impl ApiKey {
 
 impl<S, B> Transform<S, ServiceRequest> for ApiKey
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<EitherBody<B, BoxBody>>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<EitherBody<B, BoxBody>>, Error = Error>
+        + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -54,7 +60,7 @@ where
         ready(Ok(ApiKeyMiddleware {
             auth_keys: self.auth_keys.clone(),
             whitelist: self.whitelist.clone(),
-            service,
+            service: Rc::new(service),
         }))
     }
 }
@@ -97,7 +103,7 @@ pub struct ApiKeyMiddleware<S> {
     auth_keys: Option<AuthKeys>,
     /// List of items whitelisted from authentication.
     whitelist: Vec<WhitelistItem>,
-    service: S,
+    service: Rc<S>,
 }
 
 impl<S> ApiKeyMiddleware<S> {
@@ -108,7 +114,8 @@ impl<S> ApiKeyMiddleware<S> {
 
 impl<S, B> Service<ServiceRequest> for ApiKeyMiddleware<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<EitherBody<B, BoxBody>>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<EitherBody<B, BoxBody>>, Error = Error>
+        + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -125,16 +132,16 @@ where
             return Box::pin(self.service.call(req));
         }
 
-        // Grab API key from request
-        let key =
-            // Request header
-            req.headers().get("api-key").and_then(|key| key.to_str().ok()).map(|key| key.to_string())
-                // Fall back to authentication header with bearer token
-                .or_else(|| {
-                    Authorization::<Bearer>::parse(&req).ok().map(|auth| auth.as_ref().token().into())
-                });
+        // Tenants are authenticated by their own API keys, see `Tenancy`
+        let is_tenant = req
+            .extensions()
+            .get::<Tenant>()
+            .map_or(false, |tenant| tenant.authenticated);
+        if is_tenant {
+            return Box::pin(self.service.call(req));
+        }
 
-        if let Some(key) = key {
+        if let Some(key) = request_api_key(&req) {
             let is_allowed = if let Some(ref auth_keys) = self.auth_keys {
                 auth_keys.can_write(&key) || (is_read_only(&req) && auth_keys.can_read(&key))
             } else {
@@ -145,17 +152,78 @@ where
             if is_allowed {
                 return Box::pin(self.service.call(req));
             }
+
+            let access = self
+                .auth_keys
+                .as_ref()
+                .and_then(|auth_keys| auth_keys.validate_jwt(&key));
+            if let Some(access) = access {
+                if is_allowed_with_access(&req, &access) {
+                    return Box::pin(rbac::scope(access, self.service.call(req)));
+                }
+            }
+
+            let iam_auth = self
+                .auth_keys
+                .as_ref()
+                .and_then(|auth_keys| auth_keys.iam_auth_for(&key));
+            if let Some(iam_auth) = iam_auth {
+                let service = self.service.clone();
+                return Box::pin(async move {
+                    match iam_auth.authenticate(&key).await {
+                        Some(access) if is_allowed_with_access(&req, &access) => {
+                            rbac::scope(access, service.call(req)).await
+                        }
+                        _ => Ok(forbidden(req)),
+                    }
+                });
+            }
         }
 
-        Box::pin(async {
-            Ok(req
-                .into_response(HttpResponse::Forbidden().body("Invalid api-key"))
-                .map_into_right_body())
+        Box::pin(async { Ok(forbidden(req)) })
+    }
+}
+
+fn forbidden<B>(req: ServiceRequest) -> ServiceResponse<EitherBody<B, BoxBody>> {
+    req.into_response(HttpResponse::Forbidden().body("Invalid api-key"))
+        .map_into_right_body()
+}
+
+/// Grab API key from request
+pub fn request_api_key(req: &ServiceRequest) -> Option<String> {
+    // Request header
+    req.headers()
+        .get("api-key")
+        .and_then(|key| key.to_str().ok())
+        .map(|key| key.to_string())
+        // Fall back to authentication header with bearer token
+        .or_else(|| {
+            Authorization::<Bearer>::parse(req)
+                .ok()
+                .map(|auth| auth.as_ref().token().into())
         })
+}
+
+/// Whether a request with the access of a JWT may reach the endpoint. Operations on collections
+/// are checked against the access by the storage, other endpoints require global access.
+fn is_allowed_with_access(req: &ServiceRequest, access: &Access) -> bool {
+    let is_collection_endpoint = match req.match_pattern() {
+        Some(pattern) => {
+            pattern.starts_with("/collections/{name}")
+                // Lists the collections of the access only
+                || (pattern == "/collections" && req.method() == Method::GET)
+        }
+        None => false,
+    };
+
+    match access {
+        Access::Global(AccessMode::Manage) => true,
+        Access::Global(_) => is_collection_endpoint || is_read_only(req),
+        Access::Collections(_) => is_collection_endpoint,
     }
 }
 
-fn is_read_only(req: &ServiceRequest) -> bool {
+pub fn is_read_only(req: &ServiceRequest) -> bool {
     match *req.method() {
         Method::GET => true,
         Method::POST => req
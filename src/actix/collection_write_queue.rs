@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::rt::time::timeout;
+use actix_web::{error, Error, HttpMessage};
+use futures_util::future::LocalBoxFuture;
+use parking_lot::Mutex;
+use storage::content_manager::errors::StorageError;
+use storage::content_manager::toc::TableOfContent;
+use tokio::sync::Semaphore;
+
+use crate::actix::api_key::is_read_only;
+use crate::actix::helpers::storage_into_actix_error;
+use crate::actix::tenancy::Tenant;
+use crate::settings::CollectionWriteQueueConfig;
+
+/// Writes to a single collection, one is applied at a time and the rest wait in line
+struct WriteQueue {
+    permit: Semaphore,
+    waiting: AtomicUsize,
+}
+
+/// Queues of writes to each collection, shared by all HTTP workers
+pub struct CollectionWriteQueues {
+    config: CollectionWriteQueueConfig,
+    queues: Mutex<HashMap<String, Arc<WriteQueue>>>,
+}
+
+impl CollectionWriteQueues {
+    pub fn new(config: CollectionWriteQueueConfig) -> Self {
+        Self {
+            config,
+            queues: Default::default(),
+        }
+    }
+
+    /// Queue of the collection, kept while the returned handle is used
+    fn queue(self: &Arc<Self>, collection_name: &str) -> QueueHandle {
+        let queue = self
+            .queues
+            .lock()
+            .entry(collection_name.to_string())
+            .or_insert_with(|| {
+                Arc::new(WriteQueue {
+                    permit: Semaphore::new(1),
+                    waiting: AtomicUsize::new(0),
+                })
+            })
+            .clone();
+        QueueHandle {
+            queues: self.clone(),
+            collection_name: collection_name.to_string(),
+            queue,
+        }
+    }
+}
+
+/// Queue of a collection used by a request. The queue is removed once no request uses it, so
+/// names of deleted or nonexistent collections don't pile up.
+struct QueueHandle {
+    queues: Arc<CollectionWriteQueues>,
+    collection_name: String,
+    queue: Arc<WriteQueue>,
+}
+
+impl Drop for QueueHandle {
+    fn drop(&mut self) {
+        // Handles are only created under the lock, no other request can get the queue meanwhile
+        let mut queues = self.queues.queues.lock();
+        // Referenced by the map and this handle only
+        if Arc::strong_count(&self.queue) == 2 {
+            queues.remove(&self.collection_name);
+        }
+    }
+}
+
+/// Serializes requests which may change a collection, so concurrent mutations of the same
+/// collection are not interleaved in its WAL. Requests through an alias share the queue of the
+/// collection the alias points to.
+///
+/// Requests to different collections and read-only requests are not affected. Requests are
+/// rejected with `429 Too Many Requests` if the queue of the collection is full, and with
+/// `408 Request Timeout` if they wait for their turn too long.
+pub struct CollectionWriteQueueTransform {
+    queues: Option<Arc<CollectionWriteQueues>>,
+    toc: Arc<TableOfContent>,
+}
+
+impl CollectionWriteQueueTransform {
+    pub fn new(queues: Option<Arc<CollectionWriteQueues>>, toc: Arc<TableOfContent>) -> Self {
+        Self { queues, toc }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CollectionWriteQueueTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = CollectionWriteQueueService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CollectionWriteQueueService {
+            service: Rc::new(service),
+            queues: self.queues.clone(),
+            toc: self.toc.clone(),
+        }))
+    }
+}
+
+pub struct CollectionWriteQueueService<S> {
+    service: Rc<S>,
+    queues: Option<Arc<CollectionWriteQueues>>,
+    toc: Arc<TableOfContent>,
+}
+
+impl<S, B> Service<ServiceRequest> for CollectionWriteQueueService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let Some(queues) = self.queues.clone() else {
+            return Box::pin(self.service.call(request));
+        };
+        if is_read_only(&request) {
+            return Box::pin(self.service.call(request));
+        }
+        let Some(collection_name) = stored_collection_name(&request) else {
+            return Box::pin(self.service.call(request));
+        };
+
+        let max_waiting = queues.config.max_queued;
+        let wait_timeout = Duration::from_millis(queues.config.timeout_ms);
+        let service = self.service.clone();
+        let toc = self.toc.clone();
+
+        Box::pin(async move {
+            let collection_name = toc.resolve_alias(&collection_name).await;
+            let handle = queues.queue(&collection_name);
+            let queue = &handle.queue;
+            let permit = match queue.permit.try_acquire() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    if queue.waiting.fetch_add(1, Ordering::SeqCst) >= max_waiting {
+                        queue.waiting.fetch_sub(1, Ordering::SeqCst);
+                        return Err(error::ErrorTooManyRequests(
+                            "Too many pending updates of the collection",
+                        ));
+                    }
+                    let permit = timeout(wait_timeout, queue.permit.acquire()).await;
+                    queue.waiting.fetch_sub(1, Ordering::SeqCst);
+
+                    match permit {
+                        Ok(permit) => permit.expect("semaphore of the write queue is never closed"),
+                        Err(_) => {
+                            return Err(storage_into_actix_error(StorageError::Timeout {
+                                description: format!(
+                                "Update waited for {:.3}s for preceding updates of the collection",
+                                wait_timeout.as_secs_f64(),
+                            ),
+                            }))
+                        }
+                    }
+                }
+            };
+
+            let response = service.call(request).await;
+            drop(permit);
+            drop(handle);
+            response
+        })
+    }
+}
+
+/// Name of the collection the request is addressed to, as stored in the table of content, so
+/// equally named collections of different tenants don't share a queue.
+/// `None` for requests to the whole service.
+fn stored_collection_name(request: &ServiceRequest) -> Option<String> {
+    let name = collection_name(request.path())?;
+    match request.extensions().get::<Tenant>() {
+        // Path is already namespaced if `Tenancy` ran before this middleware
+        Some(tenant) if tenant.strip_namespace(name).is_none() => Some(tenant.namespaced(name)),
+        _ => Some(name.to_string()),
+    }
+}
+
+/// Name of the collection in the path, `None` for requests to the whole service
+fn collection_name(path: &str) -> Option<&str> {
+    let name = path.strip_prefix("/collections/")?.split('/').next()?;
+    // Aliases are changed for all collections at once
+    (!name.is_empty() && name != "aliases").then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    #[test]
+    fn test_collection_name() {
+        assert_eq!(collection_name("/collections/test/points"), Some("test"));
+        assert_eq!(collection_name("/collections/test"), Some("test"));
+        assert_eq!(collection_name("/collections/aliases"), None);
+        assert_eq!(collection_name("/collections"), None);
+        assert_eq!(collection_name("/snapshots"), None);
+    }
+
+    #[test]
+    fn test_stored_collection_name() {
+        let request = TestRequest::put()
+            .uri("/collections/docs/points")
+            .to_srv_request();
+        assert_eq!(stored_collection_name(&request).as_deref(), Some("docs"));
+
+        // Collections of tenants are queued by their namespaced names, with or without
+        // `Tenancy` having rewritten the path
+        for (tenant, path) in [
+            ("acme", "/collections/acme.docs/points"),
+            ("acme", "/collections/docs/points"),
+            ("globex", "/collections/globex.docs/points"),
+        ] {
+            let request = TestRequest::put().uri(path).to_srv_request();
+            request.extensions_mut().insert(Tenant {
+                id: tenant.to_string(),
+                authenticated: true,
+            });
+            assert_eq!(
+                stored_collection_name(&request),
+                Some(format!("{tenant}.docs")),
+            );
+        }
+    }
+
+    #[test]
+    fn test_queue_removed_once_unused() {
+        let queues = Arc::new(CollectionWriteQueues::new(CollectionWriteQueueConfig {
+            max_queued: 1,
+            timeout_ms: 1000,
+        }));
+
+        let first = queues.queue("test");
+        let second = queues.queue("test");
+        assert!(Arc::ptr_eq(&first.queue, &second.queue));
+
+        drop(first);
+        assert!(queues.queues.lock().contains_key("test"));
+        drop(second);
+        assert!(queues.queues.lock().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! Compact binary encoding of search results with only point ids and scores.
+//!
+//! Clients fetching payloads separately, e.g. rerank pipelines, request it with the
+//! `Accept: application/vnd.qdrant.scores` header. Search is then done without payloads and
+//! vectors, and results are encoded in little-endian binary instead of JSON:
+//!
+//! ```text
+//! results := count: u32, point * count
+//! point   := 0: u8, id: u64, score: f32
+//!          | 1: u8, uuid: [u8; 16], score: f32
+//! ```
+//!
+//! Batch search responds with the number of searches as `u32`, followed by `results` of each.
+
+use actix_web::http::header;
+use actix_web::rt::time::Instant;
+use actix_web::{HttpRequest, HttpResponse};
+use collection::operations::types::CoreSearchRequest;
+use segment::types::{ExtendedPointId, ScoredPoint, WithPayloadInterface, WithVector};
+use storage::content_manager::errors::StorageError;
+
+use crate::actix::helpers::process_response;
+
+pub const CONTENT_TYPE: &str = "application/vnd.qdrant.scores";
+
+/// Size of the largest encoded point, with UUID
+const MAX_POINT_SIZE: usize = 1 + 16 + 4;
+
+/// Whether the client accepts the compact encoding
+pub fn is_accepted(req: &HttpRequest) -> bool {
+    req.headers()
+        .get_all(header::ACCEPT)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_type| media_type.split(';').next().unwrap_or_default().trim() == CONTENT_TYPE)
+}
+
+/// Don't retrieve payloads and vectors, they are not part of the compact response
+pub fn strip_request(request: &mut CoreSearchRequest) {
+    request.with_payload = Some(WithPayloadInterface::Bool(false));
+    request.with_vector = Some(WithVector::Bool(false));
+}
+
+pub fn process_search_response(
+    response: Result<Vec<ScoredPoint>, StorageError>,
+    timing: Instant,
+) -> HttpResponse {
+    match response {
+        Ok(points) => {
+            let mut body = Vec::with_capacity(4 + points.len() * MAX_POINT_SIZE);
+            encode_results(&mut body, &points);
+            compact_response(body)
+        }
+        Err(err) => process_response::<()>(Err(err), timing),
+    }
+}
+
+pub fn process_batch_search_response(
+    response: Result<Vec<Vec<ScoredPoint>>, StorageError>,
+    timing: Instant,
+) -> HttpResponse {
+    match response {
+        Ok(batch) => {
+            let points_count: usize = batch.iter().map(Vec::len).sum();
+            let mut body = Vec::with_capacity(4 + batch.len() * 4 + points_count * MAX_POINT_SIZE);
+            body.extend_from_slice(&(batch.len() as u32).to_le_bytes());
+            for points in &batch {
+                encode_results(&mut body, points);
+            }
+            compact_response(body)
+        }
+        Err(err) => process_response::<()>(Err(err), timing),
+    }
+}
+
+fn compact_response(body: Vec<u8>) -> HttpResponse {
+    HttpResponse::Ok().content_type(CONTENT_TYPE).body(body)
+}
+
+fn encode_results(buf: &mut Vec<u8>, points: &[ScoredPoint]) {
+    buf.extend_from_slice(&(points.len() as u32).to_le_bytes());
+    for point in points {
+        match point.id {
+            ExtendedPointId::NumId(id) => {
+                buf.push(0);
+                buf.extend_from_slice(&id.to_le_bytes());
+            }
+            ExtendedPointId::Uuid(uuid) => {
+                buf.push(1);
+                buf.extend_from_slice(uuid.as_bytes());
+            }
+        }
+        buf.extend_from_slice(&point.score.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn scored_point(id: ExtendedPointId, score: f32) -> ScoredPoint {
+        ScoredPoint {
+            id,
+            version: 0,
+            score,
+            payload: None,
+            vector: None,
+            shard_key: None,
+        }
+    }
+
+    #[test]
+    fn test_encode_results() {
+        let uuid = Uuid::new_v4();
+        let points = [
+            scored_point(ExtendedPointId::NumId(7), 0.5),
+            scored_point(ExtendedPointId::Uuid(uuid), 0.25),
+        ];
+
+        let mut buf = vec![];
+        encode_results(&mut buf, &points);
+
+        let mut expected = vec![];
+        expected.extend_from_slice(&2u32.to_le_bytes());
+        expected.push(0);
+        expected.extend_from_slice(&7u64.to_le_bytes());
+        expected.extend_from_slice(&0.5f32.to_le_bytes());
+        expected.push(1);
+        expected.extend_from_slice(uuid.as_bytes());
+        expected.extend_from_slice(&0.25f32.to_le_bytes());
+
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_is_accepted() {
+        let accepts = |accept: &str| {
+            is_accepted(
+                &TestRequest::default()
+                    .insert_header((header::ACCEPT, accept))
+                    .to_http_request(),
+            )
+        };
+
+        assert!(accepts("application/vnd.qdrant.scores"));
+        assert!(accepts(
+            "application/json;q=0.5, application/vnd.qdrant.scores;q=1"
+        ));
+        assert!(!accepts("application/json"));
+        assert!(!accepts("*/*"));
+    }
+}
@@ -1,28 +1,43 @@
+mod access_log;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod actix_telemetry;
 pub mod api;
 mod api_key;
 mod certificate_helpers;
+mod collection_write_queue;
+mod compact_scores;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod helpers;
+mod optimizer_yield;
+mod read_only;
+mod request_deadline;
+mod request_id;
+mod request_recorder;
+mod request_span;
+mod response_streaming;
+mod runtime_settings;
+mod tenancy;
 
 use std::io;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use ::api::grpc::models::{ApiResponse, ApiStatus, VersionInfo};
 use actix_cors::Cors;
 use actix_multipart::form::tempfile::TempFileConfig;
 use actix_multipart::form::MultipartFormConfig;
+use actix_web::http::KeepAlive;
 use actix_web::middleware::{Compress, Condition, Logger};
 use actix_web::{error, get, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
 use collection::operations::validation;
 use lambda_web::{is_running_on_lambda, run_actix_on_lambda};
 use storage::dispatcher::Dispatcher;
 
+use crate::actix::access_log::AccessLogTransform;
 use crate::actix::api::cluster_api::config_cluster_api;
 use crate::actix::api::collections_api::config_collections_api;
-use crate::actix::api::count_api::count_points;
+use crate::actix::api::count_api::{count_point_groups, count_points};
 use crate::actix::api::discovery_api::config_discovery_api;
 use crate::actix::api::recommend_api::config_recommend_api;
 use crate::actix::api::retrieve_api::{get_point, get_points, scroll_points};
@@ -32,11 +47,23 @@ use crate::actix::api::shards_api::config_shards_api;
 use crate::actix::api::snapshot_api::config_snapshots_api;
 use crate::actix::api::update_api::config_update_api;
 use crate::actix::api_key::{ApiKey, WhitelistItem};
+use crate::actix::collection_write_queue::{CollectionWriteQueueTransform, CollectionWriteQueues};
+use crate::actix::optimizer_yield::OptimizerYieldTransform;
+use crate::actix::read_only::ReadOnly;
+use crate::actix::request_deadline::RequestDeadline;
+use crate::actix::request_id::RequestIdTransform;
+use crate::actix::request_recorder::RequestRecorderTransform;
+use crate::actix::request_span::RequestSpanTransform;
+use crate::actix::response_streaming::ResponseStreaming;
+use crate::actix::runtime_settings::RuntimeSettingsHandle;
+use crate::actix::tenancy::Tenancy;
 use crate::common::auth::AuthKeys;
 use crate::common::health;
 use crate::common::http_client::HttpClient;
+use crate::common::request_recorder::RequestRecorder;
 use crate::common::telemetry::TelemetryCollector;
 use crate::settings::{max_web_workers, Settings};
+use crate::tracing::LoggerHandle;
 
 const DEFAULT_STATIC_DIR: &str = "./static";
 const WEB_UI_PATH: &str = "/dashboard";
@@ -51,6 +78,7 @@ pub async fn init_lambda(
     dispatcher: Arc<Dispatcher>,
     telemetry_collector: Arc<tokio::sync::Mutex<TelemetryCollector>>,
     ready: Option<Arc<health::HealthChecker>>,
+    logger_handle: Arc<LoggerHandle>,
     settings: Settings,
 ) -> io::Result<()> {
     let toc_data = web::Data::from(dispatcher.toc().clone());
@@ -63,6 +91,7 @@ pub async fn init_lambda(
     let telemetry_collector_data = web::Data::from(telemetry_collector);
     let http_client = web::Data::new(HttpClient::from_settings(&settings)?);
     let ready = web::Data::new(ready);
+    let logger_handle = web::Data::from(logger_handle);
     let auth_keys = AuthKeys::try_create(&settings.service);
     let static_folder = settings
         .service
@@ -102,6 +131,23 @@ pub async fn init_lambda(
 
     let upload_dir = dispatcher_data.upload_dir().unwrap();
 
+    let request_recorder = settings
+        .service
+        .request_recorder
+        .as_ref()
+        .map(|config| RequestRecorder::open(config).map(Arc::new))
+        .transpose()?;
+
+    let response_streaming =
+        web::Data::new(ResponseStreaming::new(settings.service.response_streaming));
+    let runtime_settings = web::Data::new(RuntimeSettingsHandle::new(response_streaming.clone()));
+
+    let write_queues = settings
+        .service
+        .collection_write_queue
+        .clone()
+        .map(|config| Arc::new(CollectionWriteQueues::new(config)));
+
     let factory = move || {
         let cors = Cors::default()
             .allow_any_origin()
@@ -117,21 +163,38 @@ pub async fn init_lambda(
 
         let mut app = App::new()
             .wrap(Compress::default()) // Reads the `Accept-Encoding` header to negotiate which compression codec to use.
+            .wrap(RequestRecorderTransform::new(request_recorder.clone()))
+            .wrap(RequestSpanTransform)
+            .wrap(OptimizerYieldTransform)
+            .wrap(CollectionWriteQueueTransform::new(
+                write_queues.clone(),
+                toc_data.clone().into_inner(),
+            ))
+            .wrap(RequestDeadline::new(
+                settings.service.request_deadline.clone(),
+            ))
+            .wrap(ReadOnly::new(settings.service.read_only))
             // api_key middleware
             // note: the last call to `wrap()` or `wrap_fn()` is executed first
             .wrap(Condition::new(
                 auth_keys.is_some(),
                 ApiKey::new(auth_keys.clone(), api_key_whitelist.clone()),
             ))
+            .wrap(Tenancy::new(settings.service.tenancy.clone()))
             .wrap(Condition::new(settings.service.enable_cors, cors))
             .wrap(Logger::default().exclude("/")) // Avoid logging healthcheck requests
+            .wrap(AccessLogTransform::new(auth_keys.clone()))
             .wrap(actix_telemetry::ActixTelemetryTransform::new(
                 actix_telemetry_collector.clone(),
             ))
+            .wrap(RequestIdTransform)
             .app_data(dispatcher_data.clone())
             .app_data(toc_data.clone())
             .app_data(telemetry_collector_data.clone())
             .app_data(http_client.clone())
+            .app_data(response_streaming.clone())
+            .app_data(runtime_settings.clone())
+            .app_data(logger_handle.clone())
             .app_data(ready.clone())
             .app_data(validate_path_config)
             .app_data(validate_query_config)
@@ -151,7 +214,8 @@ pub async fn init_lambda(
             .service(get_point)
             .service(get_points)
             .service(scroll_points)
-            .service(count_points);
+            .service(count_points)
+            .service(count_point_groups);
 
         if web_ui_available {
             app = app.service(
@@ -169,11 +233,18 @@ pub async fn init_lambda(
         run_actix_on_lambda(factory).await.unwrap();
     } else {
         // Local server
-        HttpServer::new(factory)
-            .bind(bind_addr)?
+        let mut server = HttpServer::new(factory)
             .workers(max_web_workers(&settings))
-            .run()
-            .await?;
+            .keep_alive(keep_alive(&settings));
+        if let Some(max_connections) = settings.service.http_max_connections {
+            server = server.max_connections(max_connections);
+        }
+        server = if settings.service.enable_h2c {
+            server.bind_auto_h2c(bind_addr)?
+        } else {
+            server.bind(bind_addr)?
+        };
+        server.run().await?;
     }
     Ok(())
 }
@@ -183,6 +254,7 @@ pub fn init(
     dispatcher: Arc<Dispatcher>,
     telemetry_collector: Arc<tokio::sync::Mutex<TelemetryCollector>>,
     health_checker: Option<Arc<health::HealthChecker>>,
+    logger_handle: Arc<LoggerHandle>,
     settings: Settings,
 ) -> io::Result<()> {
     actix_web::rt::System::new().block_on(async {
@@ -196,6 +268,7 @@ pub fn init(
         let telemetry_collector_data = web::Data::from(telemetry_collector);
         let http_client = web::Data::new(HttpClient::from_settings(&settings)?);
         let health_checker = web::Data::new(health_checker);
+        let logger_handle = web::Data::from(logger_handle);
         let auth_keys = AuthKeys::try_create(&settings.service);
         let static_folder = settings
             .service
@@ -235,6 +308,24 @@ pub fn init(
 
         let upload_dir = dispatcher_data.upload_dir().unwrap();
 
+        let request_recorder = settings
+            .service
+            .request_recorder
+            .as_ref()
+            .map(|config| RequestRecorder::open(config).map(Arc::new))
+            .transpose()?;
+
+        let response_streaming =
+            web::Data::new(ResponseStreaming::new(settings.service.response_streaming));
+        let runtime_settings =
+            web::Data::new(RuntimeSettingsHandle::new(response_streaming.clone()));
+
+        let write_queues = settings
+            .service
+            .collection_write_queue
+            .clone()
+            .map(|config| Arc::new(CollectionWriteQueues::new(config)));
+
         let mut server = HttpServer::new(move || {
             let cors = Cors::default()
                 .allow_any_origin()
@@ -250,21 +341,38 @@ pub fn init(
 
             let mut app = App::new()
                 .wrap(Compress::default()) // Reads the `Accept-Encoding` header to negotiate which compression codec to use.
+                .wrap(RequestRecorderTransform::new(request_recorder.clone()))
+                .wrap(RequestSpanTransform)
+                .wrap(OptimizerYieldTransform)
+                .wrap(CollectionWriteQueueTransform::new(
+                    write_queues.clone(),
+                    toc_data.clone().into_inner(),
+                ))
+                .wrap(RequestDeadline::new(
+                    settings.service.request_deadline.clone(),
+                ))
+                .wrap(ReadOnly::new(settings.service.read_only))
                 // api_key middleware
                 // note: the last call to `wrap()` or `wrap_fn()` is executed first
                 .wrap(Condition::new(
                     auth_keys.is_some(),
                     ApiKey::new(auth_keys.clone(), api_key_whitelist.clone()),
                 ))
+                .wrap(Tenancy::new(settings.service.tenancy.clone()))
                 .wrap(Condition::new(settings.service.enable_cors, cors))
                 .wrap(Logger::default().exclude("/")) // Avoid logging healthcheck requests
+                .wrap(AccessLogTransform::new(auth_keys.clone()))
                 .wrap(actix_telemetry::ActixTelemetryTransform::new(
                     actix_telemetry_collector.clone(),
                 ))
+                .wrap(RequestIdTransform)
                 .app_data(dispatcher_data.clone())
                 .app_data(toc_data.clone())
                 .app_data(telemetry_collector_data.clone())
                 .app_data(http_client.clone())
+                .app_data(response_streaming.clone())
+                .app_data(runtime_settings.clone())
+                .app_data(logger_handle.clone())
                 .app_data(health_checker.clone())
                 .app_data(validate_path_config)
                 .app_data(validate_query_config)
@@ -284,7 +392,8 @@ pub fn init(
                 .service(get_point)
                 .service(get_points)
                 .service(scroll_points)
-                .service(count_points);
+                .service(count_points)
+                .service(count_point_groups);
 
             if web_ui_available {
                 app = app.service(
@@ -293,7 +402,11 @@ pub fn init(
             }
             app
         })
-        .workers(max_web_workers(&settings));
+        .workers(max_web_workers(&settings))
+        .keep_alive(keep_alive(&settings));
+        if let Some(max_connections) = settings.service.http_max_connections {
+            server = server.max_connections(max_connections);
+        }
 
         let port = settings.service.http_port;
         let bind_addr = format!("{}:{}", settings.service.host, port);
@@ -316,7 +429,11 @@ pub fn init(
         } else {
             log::info!("TLS disabled for REST API");
 
-            server.bind(bind_addr)?
+            if settings.service.enable_h2c {
+                server.bind_auto_h2c(bind_addr)?
+            } else {
+                server.bind(bind_addr)?
+            }
         };
 
         log::info!("Qdrant HTTP listening on {}", port);
@@ -324,6 +441,14 @@ pub fn init(
     })
 }
 
+fn keep_alive(settings: &Settings) -> KeepAlive {
+    match settings.service.http_keep_alive_sec {
+        None => KeepAlive::default(),
+        Some(0) => KeepAlive::Disabled,
+        Some(secs) => KeepAlive::Timeout(Duration::from_secs(secs)),
+    }
+}
+
 fn validation_error_handler(
     name: &str,
     err: actix_web_validator::Error,
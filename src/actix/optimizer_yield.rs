@@ -0,0 +1,60 @@
+use std::future::{ready, Ready};
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures_util::future::LocalBoxFuture;
+use segment::common::yielding::{yields_to_requests, RequestGuard};
+
+/// Pauses optimizations while the request is being served.
+///
+/// See [`segment::common::yielding`] for how optimizations yield to requests. Enabled with
+/// [`segment::common::yielding::set_yield_to_requests`].
+pub struct OptimizerYieldTransform;
+
+impl<S, B> Transform<S, ServiceRequest> for OptimizerYieldTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = OptimizerYieldService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(OptimizerYieldService { service }))
+    }
+}
+
+pub struct OptimizerYieldService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for OptimizerYieldService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        if !yields_to_requests() {
+            return Box::pin(self.service.call(request));
+        }
+
+        let guard = RequestGuard::new();
+        let future = self.service.call(request);
+        Box::pin(async move {
+            let response = future.await;
+            drop(guard);
+            response
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use std::future::{ready, Ready};
+
+use actix_web::body::{BoxBody, EitherBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpResponse};
+use futures_util::future::LocalBoxFuture;
+
+use crate::actix::api_key::is_read_only;
+
+/// Rejects every request which may change the storage with `403 Forbidden`.
+///
+/// Requests are classified the same way as for the read-only API key.
+/// Keeps the response type of the wrapped service, so it can be stacked with [`ApiKey`].
+///
+/// [`ApiKey`]: crate::actix::api_key::ApiKey
+pub struct ReadOnly {
+    enabled: bool,
+}
+
+impl ReadOnly {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ReadOnly
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<EitherBody<B, BoxBody>>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ReadOnlyMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ReadOnlyMiddleware {
+            enabled: self.enabled,
+            service,
+        }))
+    }
+}
+
+pub struct ReadOnlyMiddleware<S> {
+    enabled: bool,
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ReadOnlyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<EitherBody<B, BoxBody>>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !self.enabled || is_read_only(&req) {
+            return Box::pin(self.service.call(req));
+        }
+
+        Box::pin(async {
+            Ok(req
+                .into_response(HttpResponse::Forbidden().body("Service is in read-only mode"))
+                .map_into_right_body())
+        })
+    }
+}
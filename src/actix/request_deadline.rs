@@ -0,0 +1,169 @@
+use std::future::{ready, Ready};
+use std::time::Duration;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::HeaderMap;
+use actix_web::rt::time::{timeout, Instant};
+use actix_web::Error;
+use chrono::{DateTime, Utc};
+use futures_util::future::LocalBoxFuture;
+use serde::Deserialize;
+use storage::content_manager::errors::StorageError;
+
+use crate::actix::helpers::storage_into_actix_error;
+use crate::common::clock;
+use crate::settings::RequestDeadlineConfig;
+
+/// Header set by Lambda Web Adapter, with the context of the Lambda invocation
+const LAMBDA_CONTEXT_HEADER: &str = "x-amzn-lambda-context";
+
+/// Cancels the request before the deadline of the invocation serving it, and responds with
+/// `408 Request Timeout` instead.
+///
+/// Search is stopped once cancelled. Updates which are already submitted are still applied.
+/// Requests without a known deadline are not limited. The deadline of the invocation is compared
+/// with [`clock::now`], so the estimated offset of the host clock applies as well.
+pub struct RequestDeadline {
+    config: Option<RequestDeadlineConfig>,
+}
+
+impl RequestDeadline {
+    pub fn new(config: Option<RequestDeadlineConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestDeadline
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestDeadlineMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestDeadlineMiddleware {
+            config: self.config.clone(),
+            service,
+        }))
+    }
+}
+
+pub struct RequestDeadlineMiddleware<S> {
+    config: Option<RequestDeadlineConfig>,
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestDeadlineMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let remaining = self.config.as_ref().and_then(|config| {
+            Some((config, remaining_time(req.headers(), config, clock::now())?))
+        });
+
+        let Some((config, remaining)) = remaining else {
+            return Box::pin(self.service.call(req));
+        };
+
+        let limit = remaining.saturating_sub(Duration::from_millis(config.margin_ms));
+        let timing = Instant::now();
+        let future = self.service.call(req);
+
+        Box::pin(async move {
+            timeout(limit, future).await.unwrap_or_else(|_| {
+                Err(storage_into_actix_error(StorageError::Timeout {
+                    description: format!(
+                        "Request cancelled after {:.3}s, before the invocation deadline",
+                        timing.elapsed().as_secs_f64(),
+                    ),
+                }))
+            })
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct LambdaContext {
+    /// Deadline of the invocation, in milliseconds since the Unix epoch
+    deadline: u64,
+}
+
+/// Remaining time of the request at `now`, from the configured header or the Lambda invocation
+/// context
+fn remaining_time(
+    headers: &HeaderMap,
+    config: &RequestDeadlineConfig,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    let from_header = config
+        .remaining_time_header
+        .as_ref()
+        .and_then(|header| headers.get(header.as_str()))
+        .and_then(|value| value.to_str().ok()?.trim().parse().ok())
+        .map(Duration::from_millis);
+
+    from_header.or_else(|| {
+        let context = headers.get(LAMBDA_CONTEXT_HEADER)?;
+        let context: LambdaContext = serde_json::from_slice(context.as_bytes()).ok()?;
+        let remaining_ms = i64::try_from(context.deadline).ok()? - now.timestamp_millis();
+        Some(Duration::from_millis(
+            u64::try_from(remaining_ms).unwrap_or(0),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    fn config(remaining_time_header: Option<&str>) -> RequestDeadlineConfig {
+        RequestDeadlineConfig {
+            remaining_time_header: remaining_time_header.map(str::to_string),
+            margin_ms: 500,
+        }
+    }
+
+    #[test]
+    fn test_remaining_time() {
+        let now = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let deadline = (now + chrono::Duration::seconds(10)).timestamp_millis();
+        let lambda_context = format!(r#"{{"request_id":"abc","deadline":{deadline}}}"#);
+
+        let req = TestRequest::default()
+            .insert_header((LAMBDA_CONTEXT_HEADER, lambda_context))
+            .insert_header(("x-remaining-time-ms", "2500"))
+            .to_http_request();
+
+        let remaining = remaining_time(req.headers(), &config(None), now);
+        assert_eq!(remaining, Some(Duration::from_secs(10)));
+
+        // Past the deadline
+        let later = now + chrono::Duration::seconds(11);
+        let remaining = remaining_time(req.headers(), &config(None), later);
+        assert_eq!(remaining, Some(Duration::ZERO));
+
+        let remaining = remaining_time(req.headers(), &config(Some("x-remaining-time-ms")), now);
+        assert_eq!(remaining, Some(Duration::from_millis(2500)));
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(remaining_time(req.headers(), &config(None), now), None);
+    }
+}
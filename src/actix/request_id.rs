@@ -0,0 +1,72 @@
+use std::future::{ready, Ready};
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::InternalError;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::Error;
+use api::grpc::request_id::{self, REQUEST_ID_HEADER};
+use futures_util::future::LocalBoxFuture;
+
+/// Assigns an id to every request, see [`api::grpc::request_id`].
+///
+/// The id is set as the `x-request-id` header of the request, so the access log and the
+/// request span see it, and is returned in the same header of the response. Runs before the
+/// other middleware, responses rejected by them carry the id too.
+pub struct RequestIdTransform;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdService { service }))
+    }
+}
+
+pub struct RequestIdService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut request: ServiceRequest) -> Self::Future {
+        let header = HeaderName::from_static(REQUEST_ID_HEADER);
+        let id = request_id::from_header(request.headers().get(&header).map(|id| id.as_bytes()));
+        // Valid ids are visible ASCII, which is always a valid header value
+        let value = HeaderValue::from_str(&id).unwrap();
+        request.headers_mut().insert(header.clone(), value.clone());
+
+        let future = request_id::scope(id, self.service.call(request));
+
+        Box::pin(async move {
+            let mut response = match future.await {
+                Ok(response) => response,
+                Err(error) => {
+                    let mut response = error.error_response();
+                    response.headers_mut().insert(header, value);
+                    return Err(InternalError::from_response(error, response).into());
+                }
+            };
+            response.headers_mut().insert(header, value);
+            Ok(response)
+        })
+    }
+}
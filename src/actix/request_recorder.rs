@@ -0,0 +1,125 @@
+use std::future::{ready, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::PayloadError;
+use actix_web::http::header;
+use actix_web::{web, Error};
+use futures::{stream, Stream, StreamExt};
+use futures_util::future::LocalBoxFuture;
+
+use crate::common::clock;
+use crate::common::request_recorder::{RecordedRequest, RequestRecorder};
+
+pub struct RequestRecorderTransform {
+    recorder: Option<Arc<RequestRecorder>>,
+}
+
+impl RequestRecorderTransform {
+    pub fn new(recorder: Option<Arc<RequestRecorder>>) -> Self {
+        Self { recorder }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestRecorderTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestRecorderService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestRecorderService {
+            service: Rc::new(service),
+            recorder: self.recorder.clone(),
+        }))
+    }
+}
+
+pub struct RequestRecorderService<S> {
+    service: Rc<S>,
+    recorder: Option<Arc<RequestRecorder>>,
+}
+
+/// Records every request together with its JSON body and response status.
+///
+/// The body has to be buffered to be recorded, it is then handed over to the actual handler.
+impl<S, B> Service<ServiceRequest> for RequestRecorderService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut request: ServiceRequest) -> Self::Future {
+        let Some(recorder) = self.recorder.clone() else {
+            return Box::pin(self.service.call(request));
+        };
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let method = request.method().to_string();
+            let uri = request.uri().to_string();
+            let is_json = request
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok())
+                .map_or(false, |content_type| {
+                    content_type.starts_with("application/json")
+                });
+
+            let body = if is_json {
+                let mut payload = request.parts_mut().1.take();
+                let mut body = web::BytesMut::new();
+                while let Some(chunk) = payload.next().await {
+                    body.extend_from_slice(&chunk?);
+                }
+                let body = body.freeze();
+
+                let stream: Pin<Box<dyn Stream<Item = Result<web::Bytes, PayloadError>>>> =
+                    Box::pin(stream::once(ready(Ok(body.clone()))));
+                request.set_payload(Payload::from(stream));
+
+                recorder
+                    .keeps_body(body.len())
+                    .then(|| serde_json::from_slice(&body).ok())
+                    .flatten()
+            } else {
+                None
+            };
+
+            let response = service.call(request).await?;
+
+            let record = RecordedRequest {
+                timestamp: clock::now(),
+                method,
+                uri,
+                body,
+                status: response.status().as_u16(),
+            };
+            tokio::task::spawn_blocking(move || {
+                if let Err(err) = recorder.record(&record) {
+                    log::warn!(
+                        "Failed to record request {} {}: {err}",
+                        record.method,
+                        record.uri
+                    );
+                }
+            });
+
+            Ok(response)
+        })
+    }
+}
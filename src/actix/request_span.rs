@@ -0,0 +1,70 @@
+use std::future::{ready, Ready};
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use api::grpc::request_id::REQUEST_ID_HEADER;
+use tracing::instrument::Instrumented;
+use tracing::Instrument as _;
+
+/// Runs every request inside of a `request` span, with the name of the requested collection
+/// in the `collection` field and the id of the request in the `request_id` field.
+///
+/// Allows to change log level for a single collection with span filter directives,
+/// e.g. `INFO,[{collection=noisy}]=trace`.
+pub struct RequestSpanTransform;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestSpanTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestSpanService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestSpanService { service }))
+    }
+}
+
+pub struct RequestSpanService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestSpanService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Instrumented<S::Future>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        // Recorded as `Display`, so filter directives match the name without quotes
+        let collection = collection_name(request.path()).map(tracing::field::display);
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|request_id| request_id.to_str().ok());
+        let span = tracing::debug_span!("request", collection, request_id);
+
+        let future = span.in_scope(|| self.service.call(request));
+        future.instrument(span)
+    }
+}
+
+/// Get collection name from paths like `/collections/{name}/points`
+fn collection_name(path: &str) -> Option<&str> {
+    let mut segments = path.trim_start_matches('/').split('/');
+    if segments.next() != Some("collections") {
+        return None;
+    }
+    segments.next().filter(|name| !name.is_empty())
+}
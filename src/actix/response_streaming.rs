@@ -0,0 +1,122 @@
+//! Incremental serialization of large responses.
+//!
+//! Scroll and search results can hold many points with payloads and vectors, and their JSON
+//! body is many times larger than the results themselves. With streaming enabled, the body is
+//! serialized in chunks on a blocking thread and sent with chunked transfer encoding as it is
+//! produced, so the full body is never held in memory. Serialization waits while the client
+//! is slower than it, and stops once the client is gone.
+//!
+//! On Lambda, chunks are passed on to the client only with response streaming enabled for the
+//! function, e.g. with `AWS_LWA_INVOKE_MODE=response_stream` of Lambda Web Adapter.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use actix_web::http::header::ContentType;
+use actix_web::rt::time::Instant;
+use actix_web::web::Bytes;
+use actix_web::HttpResponse;
+use api::grpc::models::{ApiResponse, ApiStatus};
+use futures::stream;
+use serde::Serialize;
+use storage::content_manager::errors::StorageError;
+use tokio::sync::mpsc;
+
+use crate::actix::helpers::process_response;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Number of serialized chunks waiting to be sent, before serialization waits for the client
+const MAX_PENDING_CHUNKS: usize = 4;
+
+/// Whether large responses are streamed, shared with handlers as app data
+#[derive(Debug)]
+pub struct ResponseStreaming {
+    enabled: AtomicBool,
+}
+
+impl ResponseStreaming {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Applies to responses produced afterwards, responses being sent are not affected
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+}
+
+/// Same as [`process_response`], but with the body serialized incrementally if `streaming`
+/// is enabled
+pub fn process_streamed_response<D>(
+    response: Result<D, StorageError>,
+    timing: Instant,
+    streaming: &ResponseStreaming,
+) -> HttpResponse
+where
+    D: Serialize + Send + 'static,
+{
+    let result = match response {
+        Ok(result) if streaming.is_enabled() => result,
+        response => return process_response(response, timing),
+    };
+
+    let (sender, mut receiver) = mpsc::channel(MAX_PENDING_CHUNKS);
+    let response = ApiResponse {
+        result: Some(result),
+        status: ApiStatus::Ok,
+        time: timing.elapsed().as_secs_f64(),
+    };
+    tokio::task::spawn_blocking(move || {
+        let mut writer = ChunkWriter {
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            sender,
+        };
+        // Fails only once the client is gone, there is nobody to report it to
+        if serde_json::to_writer(&mut writer, &response).is_ok() {
+            let _ = writer.send_buffer();
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .streaming(stream::poll_fn(move |cx| receiver.poll_recv(cx)))
+}
+
+/// Collects serialized bytes into chunks, and sends them to the response body
+struct ChunkWriter {
+    buffer: Vec<u8>,
+    sender: mpsc::Sender<io::Result<Bytes>>,
+}
+
+impl ChunkWriter {
+    fn send_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(CHUNK_SIZE));
+        self.sender
+            .blocking_send(Ok(Bytes::from(chunk)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "response body dropped"))
+    }
+}
+
+impl io::Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.send_buffer()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffer()
+    }
+}
@@ -0,0 +1,189 @@
+//! Settings which can be changed while the service is running, with `PATCH /settings`.
+//!
+//! Changing other settings requires a new Lambda deployment, or a restart with edited config
+//! files. Changes are not persisted, a restarted service starts from the configuration again.
+
+use actix_web::web;
+use memory::madvise::{self, AdviceConfig};
+use memory::mmap_registry;
+use serde::{Deserialize, Serialize};
+use storage::content_manager::errors::StorageError;
+use tokio::sync::Mutex;
+
+use crate::actix::response_streaming::ResponseStreaming;
+use crate::common::telemetry_reporting;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RuntimeSettings {
+    /// See `storage.performance.optimizers_yield_to_requests`
+    pub optimizers_yield_to_requests: bool,
+    /// See `storage.async_scorer`
+    pub async_scorer: bool,
+    /// See `storage.mmap_advice`, also applied to open memory maps of vectors, HNSW links and
+    /// quantized vectors when changed
+    pub mmap_advice: AdviceConfig,
+    /// See `service.response_streaming`
+    pub response_streaming: bool,
+    /// Report anonymized telemetry, can't be enabled if it was disabled on startup
+    pub telemetry_reporting: bool,
+}
+
+/// Partial runtime settings, only the fields that are set are applied.
+/// Settings which can't be changed at runtime are rejected.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeSettingsDiff {
+    pub optimizers_yield_to_requests: Option<bool>,
+    pub async_scorer: Option<bool>,
+    pub mmap_advice: Option<AdviceConfig>,
+    pub response_streaming: Option<bool>,
+    pub telemetry_reporting: Option<bool>,
+}
+
+impl RuntimeSettings {
+    pub fn merge(&mut self, diff: RuntimeSettingsDiff) {
+        let RuntimeSettingsDiff {
+            optimizers_yield_to_requests,
+            async_scorer,
+            mmap_advice,
+            response_streaming,
+            telemetry_reporting,
+        } = diff;
+
+        merge_value(
+            &mut self.optimizers_yield_to_requests,
+            optimizers_yield_to_requests,
+        );
+        merge_value(&mut self.async_scorer, async_scorer);
+        merge_value(&mut self.mmap_advice, mmap_advice);
+        merge_value(&mut self.response_streaming, response_streaming);
+        merge_value(&mut self.telemetry_reporting, telemetry_reporting);
+    }
+
+    fn validate(&self) -> Result<(), StorageError> {
+        if self.telemetry_reporting && !telemetry_reporting::is_reporter_started() {
+            return Err(StorageError::bad_input(
+                "Telemetry reporting is disabled on startup, it can't be enabled at runtime",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Handle to the settings of the running service, shared with handlers as app data
+pub struct RuntimeSettingsHandle {
+    response_streaming: web::Data<ResponseStreaming>,
+    /// Updates are applied one at a time, so concurrent diffs don't interleave
+    update_lock: Mutex<()>,
+}
+
+impl RuntimeSettingsHandle {
+    pub fn new(response_streaming: web::Data<ResponseStreaming>) -> Self {
+        Self {
+            response_streaming,
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    /// Settings currently in effect
+    pub fn get(&self) -> RuntimeSettings {
+        RuntimeSettings {
+            optimizers_yield_to_requests: segment::common::yielding::yields_to_requests(),
+            async_scorer: segment::vector_storage::common::get_async_scorer(),
+            mmap_advice: madvise::get_global_config(),
+            response_streaming: self.response_streaming.is_enabled(),
+            telemetry_reporting: telemetry_reporting::is_reporting_enabled(),
+        }
+    }
+
+    /// Apply `diff` to the current settings, nothing is changed if the result is invalid
+    pub async fn update(&self, diff: RuntimeSettingsDiff) -> Result<RuntimeSettings, StorageError> {
+        let _update_guard = self.update_lock.lock().await;
+
+        let current = self.get();
+        let mut merged = current.clone();
+        merged.merge(diff);
+        merged.validate()?;
+
+        if merged.optimizers_yield_to_requests != current.optimizers_yield_to_requests {
+            segment::common::yielding::set_yield_to_requests(merged.optimizers_yield_to_requests);
+        }
+        if merged.async_scorer != current.async_scorer {
+            segment::vector_storage::common::set_async_scorer(merged.async_scorer);
+        }
+        if merged.mmap_advice != current.mmap_advice {
+            madvise::set_global(merged.mmap_advice);
+            let advised = mmap_registry::readvise_all();
+            log::debug!(
+                "Advised {advised} open memory maps with {:?}",
+                merged.mmap_advice
+            );
+        }
+        if merged.response_streaming != current.response_streaming {
+            self.response_streaming
+                .set_enabled(merged.response_streaming);
+        }
+        if merged.telemetry_reporting != current.telemetry_reporting {
+            telemetry_reporting::set_reporting_enabled(merged.telemetry_reporting);
+        }
+
+        if merged != current {
+            log::info!("Runtime settings changed: {merged:?}");
+        }
+        Ok(merged)
+    }
+}
+
+/// Replace `value` with `diff`, if `diff` is set
+fn merge_value<T>(value: &mut T, diff: Option<T>) {
+    if let Some(diff) = diff {
+        *value = diff;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use memory::madvise::{Advice, MmapKind};
+
+    use super::*;
+
+    #[test]
+    fn test_merge_runtime_settings() {
+        let mut settings = RuntimeSettings {
+            optimizers_yield_to_requests: false,
+            async_scorer: false,
+            mmap_advice: AdviceConfig::all(Advice::Random),
+            response_streaming: false,
+            telemetry_reporting: true,
+        };
+
+        let diff: RuntimeSettingsDiff =
+            serde_json::from_str(r#"{"mmap_advice": "normal", "response_streaming": true}"#)
+                .unwrap();
+        settings.merge(diff);
+        assert_eq!(settings.mmap_advice, AdviceConfig::all(Advice::Normal));
+        assert!(settings.response_streaming);
+        assert!(settings.telemetry_reporting);
+        assert!(!settings.async_scorer);
+
+        let diff: RuntimeSettingsDiff =
+            serde_json::from_str(r#"{"mmap_advice": {"vectors": "sequential"}}"#).unwrap();
+        settings.merge(diff);
+        assert_eq!(
+            settings.mmap_advice.get(MmapKind::Vectors),
+            Advice::Sequential,
+        );
+        assert_eq!(
+            settings.mmap_advice.get(MmapKind::HnswLinks),
+            Advice::Random
+        );
+
+        // Only access patterns can be used as advice
+        assert!(
+            serde_json::from_str::<RuntimeSettingsDiff>(r#"{"mmap_advice": "dont_need"}"#).is_err(),
+        );
+
+        // Settings which can't be changed at runtime are rejected
+        assert!(serde_json::from_str::<RuntimeSettingsDiff>(r#"{"read_only": true}"#).is_err());
+    }
+}
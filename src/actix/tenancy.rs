@@ -0,0 +1,483 @@
+use std::future::{ready, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use actix_web::dev::{
+    forward_ready, Decompress, Payload, Service, ServiceRequest, ServiceResponse, Transform,
+};
+use actix_web::error::PayloadError;
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::http::{Method, Uri};
+use actix_web::{error, web, Error, HttpMessage};
+use futures::{stream, Stream, StreamExt};
+use futures_util::future::LocalBoxFuture;
+use serde_json::Value;
+use storage::content_manager::toc::tenant_directories::TENANT_SEPARATOR;
+
+use crate::actix::api_key::request_api_key;
+use crate::common::strings::ct_eq;
+use crate::settings::TenancyConfig;
+
+/// Tenant the request is served for, stored in the request extensions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tenant {
+    pub id: String,
+    /// Authenticated by the API key of the tenant, rather than named by a header
+    pub authenticated: bool,
+}
+
+impl Tenant {
+    /// Name of the collection of the tenant, as stored in the table of content
+    pub fn namespaced(&self, collection_name: &str) -> String {
+        format!("{}{TENANT_SEPARATOR}{collection_name}", self.id)
+    }
+
+    /// Name of the collection as seen by the tenant, `None` if it belongs to another tenant
+    pub fn strip_namespace<'a>(&self, namespaced_name: &'a str) -> Option<&'a str> {
+        namespaced_name
+            .strip_prefix(self.id.as_str())?
+            .strip_prefix(TENANT_SEPARATOR)
+    }
+}
+
+/// Serves each tenant from its own namespace of collections.
+///
+/// The tenant is identified by its API key, or named by the configured header, e.g. set by
+/// a gateway in front of the service. Collection names in the path are prefixed with the
+/// tenant id, and collections of other tenants are hidden from the list of collections.
+/// Names of other collections in request bodies, such as the collection to look vectors up in,
+/// are prefixed as well. Requests of tenants to anything but their collections are rejected
+/// with `403 Forbidden`, as are recovery of snapshots and requests to shards of their
+/// collections. Requests without a tenant are served as is.
+pub struct Tenancy {
+    config: Option<TenancyConfig>,
+}
+
+impl Tenancy {
+    pub fn new(config: Option<TenancyConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Tenancy
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = TenancyMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TenancyMiddleware {
+            config: self.config.clone(),
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct TenancyMiddleware<S> {
+    config: Option<TenancyConfig>,
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TenancyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let Some(config) = &self.config else {
+            return Box::pin(self.service.call(req));
+        };
+
+        let tenant = match resolve_tenant(&req, config) {
+            Ok(Some(tenant)) => tenant,
+            Ok(None) => return Box::pin(self.service.call(req)),
+            Err(err) => return Box::pin(ready(Err(error::ErrorBadRequest(err)))),
+        };
+
+        let Some(path) = namespaced_path(req.method(), req.path(), &tenant) else {
+            return Box::pin(ready(Err(error::ErrorForbidden(
+                "Not available to tenants",
+            ))));
+        };
+        let has_references = may_reference_collections(req.method(), req.path());
+
+        let query = req.query_string();
+        let path_and_query = if query.is_empty() {
+            path
+        } else {
+            format!("{path}?{query}")
+        };
+        let mut parts = req.uri().clone().into_parts();
+        parts.path_and_query = path_and_query.parse().ok();
+        let Ok(uri) = Uri::from_parts(parts) else {
+            return Box::pin(ready(Err(error::ErrorBadRequest(
+                "Invalid collection name",
+            ))));
+        };
+        req.match_info_mut().get_mut().update(&uri);
+        req.head_mut().uri = uri;
+
+        if !has_references {
+            req.extensions_mut().insert(tenant);
+            return Box::pin(self.service.call(req));
+        }
+
+        let service = self.service.clone();
+        Box::pin(async move {
+            namespace_body(&mut req, &tenant).await?;
+            req.extensions_mut().insert(tenant);
+            service.call(req).await
+        })
+    }
+}
+
+/// Whether the body of the request may name other collections, see [`namespace_references`]
+fn may_reference_collections(method: &Method, path: &str) -> bool {
+    if method == Method::GET || method == Method::DELETE {
+        return false;
+    }
+    let Some(rest) = path.strip_prefix("/collections/") else {
+        return false;
+    };
+    let rest = rest
+        .split_once('/')
+        .map_or("", |(_, rest)| rest)
+        .trim_end_matches('/');
+    rest.is_empty()
+        || rest.starts_with("points/recommend")
+        || rest.starts_with("points/discover")
+        || rest == "points/search/groups"
+}
+
+/// Buffer the body of the request to prefix the collections it names with the namespace of the
+/// tenant. The body is handed over to the handler decompressed, and as is if it is not JSON.
+async fn namespace_body(req: &mut ServiceRequest, tenant: &Tenant) -> Result<(), Error> {
+    let mut payload = Decompress::from_headers(req.take_payload(), req.headers());
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        body.extend_from_slice(&chunk?);
+    }
+    let mut body = body.freeze();
+
+    if let Ok(mut value) = serde_json::from_slice::<Value>(&body) {
+        if namespace_references(&mut value, tenant) {
+            body = serde_json::to_vec(&value)?.into();
+        }
+    }
+
+    let headers = req.headers_mut();
+    headers.remove(header::CONTENT_ENCODING);
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+
+    let stream: Pin<Box<dyn Stream<Item = Result<web::Bytes, PayloadError>>>> =
+        Box::pin(stream::once(ready(Ok(body))));
+    req.set_payload(Payload::from(stream));
+    Ok(())
+}
+
+/// Prefix names of other collections in a request `body` with the namespace of the tenant: the
+/// collection to initialize a collection from, to look vectors up in, and to look group ids up
+/// in. Returns whether the body names any.
+fn namespace_references(body: &mut Value, tenant: &Tenant) -> bool {
+    // Batches of recommend and discover requests
+    let requests: Vec<&mut Value> = if body.get("searches").map_or(false, Value::is_array) {
+        body["searches"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .collect()
+    } else {
+        vec![body]
+    };
+
+    let mut found = false;
+    for request in requests {
+        for field in ["init_from", "lookup_from"] {
+            let collection = request
+                .get_mut(field)
+                .and_then(|location| location.get_mut("collection"));
+            found |= namespace_name(collection, tenant);
+        }
+
+        match request.get_mut("with_lookup") {
+            Some(Value::Array(lookups)) => {
+                for lookup in lookups {
+                    found |= namespace_name(lookup.get_mut("collection"), tenant);
+                }
+            }
+            Some(lookup @ Value::String(_)) => found |= namespace_name(Some(lookup), tenant),
+            Some(lookup) => found |= namespace_name(lookup.get_mut("collection"), tenant),
+            None => {}
+        }
+    }
+    found
+}
+
+fn namespace_name(name: Option<&mut Value>, tenant: &Tenant) -> bool {
+    match name {
+        Some(Value::String(name)) => {
+            *name = tenant.namespaced(name);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Tenant of the request, by its API key or the tenant header
+fn resolve_tenant(
+    req: &ServiceRequest,
+    config: &TenancyConfig,
+) -> Result<Option<Tenant>, &'static str> {
+    let by_api_key = request_api_key(req).and_then(|key| {
+        config
+            .api_keys
+            .iter()
+            .find(|(_, tenant_key)| ct_eq(tenant_key, &key))
+            .map(|(id, _)| id.clone())
+    });
+
+    let tenant = match by_api_key {
+        Some(id) => Tenant {
+            id,
+            authenticated: true,
+        },
+        None => {
+            let header = config
+                .header
+                .as_ref()
+                .and_then(|header| req.headers().get(header.as_str()));
+            let Some(id) = header else {
+                return Ok(None);
+            };
+            Tenant {
+                id: id.to_str().map_err(|_| "Invalid tenant id")?.to_string(),
+                authenticated: false,
+            }
+        }
+    };
+
+    // Also checked for configured ids, a separator in the id would break the isolation
+    if !is_valid_tenant_id(&tenant.id) {
+        return Err("Invalid tenant id");
+    }
+    Ok(Some(tenant))
+}
+
+fn is_valid_tenant_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Path of the request in the namespace of the tenant, `None` if tenants can't access it
+fn namespaced_path(method: &Method, path: &str, tenant: &Tenant) -> Option<String> {
+    if path == "/collections" || path == "/collections/" {
+        // Listed collections are filtered by the handler
+        return (method == Method::GET).then(|| path.to_string());
+    }
+
+    let rest = path.strip_prefix("/collections/")?;
+    let (name, rest) = rest.split_once('/').unwrap_or((rest, ""));
+    if name.is_empty() || name == "aliases" || !is_tenant_route(rest) {
+        return None;
+    }
+
+    let mut path = format!("/collections/{}", tenant.namespaced(name));
+    if !rest.is_empty() {
+        path.push('/');
+        path.push_str(rest);
+    }
+    Some(path)
+}
+
+/// Whether tenants may access `rest` of the path of their collection.
+///
+/// Snapshots are recovered from any location readable by the service, including snapshots of
+/// other tenants, and shards are managed by the operator of the service.
+fn is_tenant_route(rest: &str) -> bool {
+    let rest = rest.trim_end_matches('/');
+    let first = rest.split('/').next().unwrap_or_default();
+    first != "shards" && rest != "snapshots/recover" && rest != "snapshots/upload"
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, App, HttpResponse};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_namespaced_path() {
+        let tenant = Tenant {
+            id: "acme".to_string(),
+            authenticated: true,
+        };
+
+        assert_eq!(
+            namespaced_path(&Method::PUT, "/collections/test/points", &tenant).as_deref(),
+            Some("/collections/acme.test/points"),
+        );
+        assert_eq!(
+            namespaced_path(&Method::DELETE, "/collections/test", &tenant).as_deref(),
+            Some("/collections/acme.test"),
+        );
+        assert_eq!(
+            namespaced_path(&Method::GET, "/collections", &tenant).as_deref(),
+            Some("/collections"),
+        );
+        assert_eq!(
+            namespaced_path(&Method::POST, "/collections", &tenant),
+            None
+        );
+        assert_eq!(
+            namespaced_path(&Method::POST, "/collections/aliases", &tenant),
+            None,
+        );
+        assert_eq!(namespaced_path(&Method::POST, "/snapshots", &tenant), None);
+        assert_eq!(
+            namespaced_path(&Method::POST, "/collections/test/snapshots", &tenant).as_deref(),
+            Some("/collections/acme.test/snapshots"),
+        );
+
+        assert_eq!(tenant.strip_namespace("acme.test"), Some("test"));
+        assert_eq!(tenant.strip_namespace("acme2.test"), None);
+        assert_eq!(tenant.strip_namespace("test"), None);
+
+        assert!(is_valid_tenant_id("tenant_1-a"));
+        assert!(!is_valid_tenant_id("acme.test"));
+        assert!(!is_valid_tenant_id(""));
+    }
+
+    #[test]
+    fn test_tenant_cannot_recover_snapshots() {
+        let tenant = Tenant {
+            id: "acme".to_string(),
+            authenticated: true,
+        };
+
+        // Snapshots of other tenants must not be recovered into the collection of the tenant
+        for path in [
+            "/collections/test/snapshots/recover",
+            "/collections/test/snapshots/recover/",
+            "/collections/test/snapshots/upload",
+            "/collections/test/shards",
+            "/collections/test/shards/0/snapshots/recover",
+            "/collections/test/shards/0/snapshots/upload",
+            "/collections/test/shards/0/snapshots",
+        ] {
+            assert_eq!(namespaced_path(&Method::PUT, path, &tenant), None, "{path}");
+            assert_eq!(
+                namespaced_path(&Method::POST, path, &tenant),
+                None,
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_namespace_references() {
+        let tenant = Tenant {
+            id: "acme".to_string(),
+            authenticated: true,
+        };
+        let namespaced = |mut body: Value| {
+            let found = namespace_references(&mut body, &tenant);
+            (found, body)
+        };
+
+        assert_eq!(
+            namespaced(json!({ "init_from": { "collection": "docs" } })),
+            (true, json!({ "init_from": { "collection": "acme.docs" } })),
+        );
+        assert_eq!(
+            namespaced(json!({ "searches": [
+                { "positive": [1], "lookup_from": { "collection": "docs" } },
+                { "positive": [2] },
+            ] })),
+            (
+                true,
+                json!({ "searches": [
+                    { "positive": [1], "lookup_from": { "collection": "acme.docs" } },
+                    { "positive": [2] },
+                ] }),
+            ),
+        );
+        assert_eq!(
+            namespaced(json!({ "with_lookup": "docs" })),
+            (true, json!({ "with_lookup": "acme.docs" })),
+        );
+        assert_eq!(
+            namespaced(json!({ "with_lookup": [{ "collection": "a" }, { "collection": "b" }] })),
+            (
+                true,
+                json!({ "with_lookup": [{ "collection": "acme.a" }, { "collection": "acme.b" }] }),
+            ),
+        );
+        assert_eq!(
+            namespaced(json!({ "positive": [1], "limit": 3 })),
+            (false, json!({ "positive": [1], "limit": 3 })),
+        );
+
+        assert!(may_reference_collections(&Method::PUT, "/collections/docs"));
+        assert!(may_reference_collections(
+            &Method::POST,
+            "/collections/docs/points/recommend/batch"
+        ));
+        assert!(!may_reference_collections(
+            &Method::PUT,
+            "/collections/docs/points"
+        ));
+        assert!(!may_reference_collections(
+            &Method::GET,
+            "/collections/docs"
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_tenant_cannot_reference_collections_of_other_tenants() {
+        let config = TenancyConfig {
+            header: Some("x-tenant".to_string()),
+            api_keys: Default::default(),
+        };
+        let app = test::init_service(App::new().wrap(Tenancy::new(Some(config))).route(
+            "/collections/{name}/points/recommend",
+            web::post().to(
+                |name: web::Path<String>, body: web::Json<Value>| async move {
+                    HttpResponse::Ok().json(json!({ "collection": *name, "body": *body }))
+                },
+            ),
+        ))
+        .await;
+
+        // Collection `docs` of tenant `globex`, named as if the namespace was not applied
+        let request = test::TestRequest::post()
+            .uri("/collections/docs/points/recommend")
+            .insert_header(("x-tenant", "acme"))
+            .set_json(json!({ "positive": [1], "lookup_from": { "collection": "globex.docs" } }))
+            .to_request();
+        let response: Value = test::call_and_read_body_json(&app, request).await;
+
+        assert_eq!(
+            response,
+            json!({
+                "collection": "acme.docs",
+                "body": { "positive": [1], "lookup_from": { "collection": "acme.globex.docs" } },
+            }),
+        );
+    }
+}
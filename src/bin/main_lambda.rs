@@ -1,21 +1,36 @@
 #![allow(deprecated)]
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use ::tonic::transport::Uri;
 use clap::Parser;
 use collection::shards::channel_service::ChannelService;
+use qdrant::common::crash_report::{self, CrashReporter};
 use qdrant::common::helpers::{
-    create_general_purpose_runtime, create_search_runtime, create_update_runtime,
+    create_general_purpose_runtime, create_idle_update_runtime, create_search_runtime,
+    create_update_runtime,
 };
+use qdrant::common::io_pacing::spawn_io_probe;
 use qdrant::common::telemetry::TelemetryCollector;
+use qdrant::common::telemetry_ops::startup_telemetry;
 use qdrant::common::telemetry_reporting::TelemetryReporter;
+use qdrant::common::warmup::{read_warmup_queries, run_warmup_queries};
+use qdrant::common::{clock, memory_arenas, memory_pressure, secrets};
 use qdrant::greeting::welcome;
 use qdrant::settings::Settings;
 use qdrant::startup::{
-    remove_started_file_indicator, setup_panic_hook, touch_started_file_indicator,
+    check_clean_shutdown_indicator, remove_started_file_indicator, setup_panic_hook,
+    take_clean_shutdown_indicator, touch_clean_shutdown_indicator, touch_started_file_indicator,
 };
 use storage::content_manager::consensus::persistent::Persistent;
+use storage::content_manager::toc::deployment_manifest::verify_deployment;
+use storage::content_manager::toc::mmap_preload::preload_hot_files;
+use storage::content_manager::toc::pre_migration_backup::backup_before_migration;
+use storage::content_manager::toc::storage_verification::{
+    verify_storage, StorageVerificationReport,
+};
 use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
 #[cfg(not(target_env = "msvc"))]
@@ -81,13 +96,35 @@ struct Args {
     #[arg(long, action, default_value_t = false)]
     disable_telemetry: bool,
 
+    /// Reject all operations which may change the storage.
+    /// Overrides `service.read_only` from the config.
+    #[arg(long, action, default_value_t = false)]
+    read_only: bool,
+
     /// Run stacktrace collector. Used for debugging.
     #[arg(long, action, default_value_t = false)]
     stacktrace: bool,
+
+    /// Role of this instance.
+    /// `reader` only serves reads, and doesn't write into the storage on its own.
+    #[arg(long, value_enum, default_value_t = Role::Writer)]
+    role: Role,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+enum Role {
+    /// Reject all operations which may change the storage, don't run update workers
+    /// on more than a single thread and don't flush the storage on shutdown.
+    /// Memory maps are read ahead and kept cached, for repeated reads of the same storage.
+    Reader,
+    /// Serve all operations
+    Writer,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    startup_telemetry::init();
+
     let args = Args::parse();
 
     // Run backtrace collector, expected to used by `rstack` crate
@@ -101,18 +138,75 @@ async fn main() -> anyhow::Result<()> {
 
     remove_started_file_indicator();
 
-    let settings = Settings::new(args.config_path)?;
+    let mut settings =
+        startup_telemetry::phase("load_settings", || Settings::new(args.config_path))?;
+    let is_reader = args.role == Role::Reader;
+    if args.read_only || is_reader {
+        settings.service.read_only = true;
+    }
+
+    if let Some(secret_id) = &settings.service.api_keys_secret {
+        let keys =
+            startup_telemetry::phase_async("fetch_api_keys", secrets::fetch_api_keys(secret_id))
+                .await
+                .map_err(|err| {
+                    anyhow::anyhow!("Failed to read API keys from {secret_id}: {err}")
+                })?;
+        if keys.api_key.is_some() {
+            settings.service.api_key = keys.api_key;
+        }
+        if keys.read_only_api_key.is_some() {
+            settings.service.read_only_api_key = keys.read_only_api_key;
+        }
+    }
 
     let reporting_enabled = !settings.telemetry_disabled && !args.disable_telemetry;
 
     let reporting_id = TelemetryCollector::generate_id();
 
-    qdrant::tracing::setup(&settings.log_level)?;
-
-    setup_panic_hook(reporting_enabled, reporting_id.to_string());
+    let logger_handle = Arc::new(qdrant::tracing::setup(
+        settings
+            .logger
+            .clone()
+            .with_top_level_directive(settings.log_level.clone()),
+    )?);
+    qdrant::tracing::redact::set_secrets(settings.service.api_keys().cloned());
+    if settings.service.api_key.is_none()
+        && settings.service.read_only_api_key.is_none()
+        && settings.service.iam_auth.is_none()
+    {
+        log::warn!("No API key is configured, anyone who can reach the function may use the API");
+    }
 
-    memory::madvise::set_global(settings.storage.mmap_advice);
+    let crash_reporter = settings
+        .service
+        .crash_report
+        .as_ref()
+        .map(|config| CrashReporter::new(config, logger_handle.tail_buffer()));
+    setup_panic_hook(reporting_enabled, reporting_id.to_string(), crash_reporter);
+
+    let mmap_advice = if is_reader {
+        let reader_advice = memory::madvise::AdviceConfig::all(memory::madvise::Advice::Normal);
+        if settings.storage.mmap_advice != reader_advice {
+            log::info!(
+                "Reader uses normal advice for all memory maps instead of storage.mmap_advice {:?}",
+                settings.storage.mmap_advice,
+            );
+        }
+        reader_advice
+    } else {
+        settings.storage.mmap_advice
+    };
+    memory::madvise::set_global(mmap_advice);
+    memory::mlock::set_global(settings.storage.mlock);
     segment::vector_storage::common::set_async_scorer(settings.storage.async_scorer);
+    segment::vector_storage::common::set_async_scorer_direct_io(
+        settings.storage.async_scorer_direct_io,
+    );
+    segment::common::yielding::set_yield_to_requests(
+        settings.storage.performance.optimizers_yield_to_requests,
+    );
+    memory::direct_io::set_global(settings.storage.performance.optimizers_direct_io);
 
     welcome(&settings);
 
@@ -123,42 +217,192 @@ async fn main() -> anyhow::Result<()> {
         );
     }
 
+    if settings.service.read_only {
+        log::info!("Qdrant is started in read-only mode, all write operations are rejected");
+    }
+
     // Validate as soon as possible, but we must initialize logging first
     settings.validate_and_warn();
 
     // Saved state of the consensus.
-    let persistent_consensus_state =
-        Persistent::load_or_init(&settings.storage.storage_path, args.bootstrap.is_none())?;
+    let persistent_consensus_state = startup_telemetry::phase("load_consensus_state", || {
+        Persistent::load_or_init(&settings.storage.storage_path, args.bootstrap.is_none())
+    })?;
+
+    if let Some(memory_arenas_config) = &settings.service.memory_arenas {
+        memory_arenas::init(memory_arenas_config)?;
+        log::info!("Runtimes allocate memory in separate arenas");
+    }
 
     // Create and own search runtime out of the scope of async context to ensure correct
     // destruction of it
     let search_runtime = create_search_runtime(settings.storage.performance.max_search_threads)
         .expect("Can't search create runtime.");
 
-    let update_runtime =
+    // Reader never receives updates, its update workers only wait for them
+    let update_runtime = if is_reader {
+        create_idle_update_runtime()
+    } else {
         create_update_runtime(settings.storage.performance.max_optimization_threads)
-            .expect("Can't optimizer create runtime.");
+    }
+    .expect("Can't optimizer create runtime.");
 
     let general_runtime =
         create_general_purpose_runtime().expect("Can't optimizer general purpose runtime.");
     let runtime_handle = general_runtime.handle().clone();
 
+    runtime_handle.spawn(memory_arenas::enforce_soft_limits());
+
+    if let Some(clock_reference_url) = &settings.service.clock_reference_url {
+        runtime_handle.spawn(clock::sync_offset(clock_reference_url.clone()));
+    }
+
+    let storage_path = Path::new(&settings.storage.storage_path);
+
+    // Storage might have been restored from a remote copy, check it before loading collections.
+    // Storage flushed on shutdown by this version doesn't need it.
+    let verification_report = startup_telemetry::phase("verify_storage", || {
+        // Only the writer consumes the indicator, readers leave it to the writer
+        let is_clean_shutdown = if is_reader {
+            check_clean_shutdown_indicator(storage_path)
+        } else {
+            take_clean_shutdown_indicator(storage_path)
+        };
+        let mut verification_report = if is_clean_shutdown {
+            log::info!("Storage was flushed on previous shutdown, skipping verification");
+            StorageVerificationReport::default()
+        } else {
+            verify_storage(storage_path)
+        };
+        verification_report
+            .issues
+            .extend(verify_deployment(storage_path, env!("CARGO_PKG_VERSION")));
+        verification_report
+    });
+    if !verification_report.is_ok() {
+        for issue in &verification_report.issues {
+            log::error!("Storage verification: {issue}");
+        }
+        anyhow::bail!(
+            "Storage verification failed with {} issues: {}",
+            verification_report.issues.len(),
+            serde_json::to_string(&verification_report)?,
+        );
+    }
+    for segment_path in &verification_report.skipped {
+        log::warn!(
+            "Segment is deleted or was not fully saved, it will be skipped: {}",
+            segment_path.display(),
+        );
+    }
+    log::debug!(
+        "Storage verified: {} collections, {} shards, {} segments",
+        verification_report.collections,
+        verification_report.shards,
+        verification_report.segments,
+    );
+
+    // Keep files rewritten by migration, so the previous version can be started again.
+    // Readers don't write to the storage, the writer backs it up.
+    if !verification_report.migrations.is_empty() && !is_reader {
+        let backup_path = backup_before_migration(storage_path, &verification_report.migrations)?;
+        log::info!(
+            "Storage of {} collections and segments will be migrated, previous state is backed up to {}",
+            verification_report.migrations.len(),
+            backup_path.display(),
+        );
+    }
+
     // Table of content manages the list of collections.
     // It is a main entry point for the storage.
-    let toc = TableOfContent::new_sync(
-        &settings.storage,
-        search_runtime,
-        update_runtime,
-        general_runtime,
-        ChannelService::new(settings.service.http_port),
-        persistent_consensus_state.this_peer_id(),
-        None,
+    let toc = startup_telemetry::phase_async(
+        "load_collections",
+        TableOfContent::new_sync(
+            &settings.storage,
+            search_runtime,
+            update_runtime,
+            general_runtime,
+            ChannelService::new(settings.service.http_port),
+            persistent_consensus_state.this_peer_id(),
+            None,
+        ),
     )
     .await;
 
-    toc.clear_all_tmp_directories()?;
+    if !is_reader {
+        toc.clear_all_tmp_directories()?;
+    }
+
+    // Lambda doesn't throttle CPU during the init phase, read hot files while it lasts
+    if settings.storage.preload_on_startup {
+        let report =
+            startup_telemetry::phase("preload_hot_files", || preload_hot_files(storage_path));
+        log::info!(
+            "Preloaded {} files, {} MiB in {:?}",
+            report.files,
+            report.bytes / (1024 * 1024),
+            report.elapsed,
+        );
+    }
+
+    if let Some(warmup_queries_path) = &settings.service.warmup_queries_path {
+        match read_warmup_queries(Path::new(warmup_queries_path)) {
+            Ok(queries) => {
+                let report = startup_telemetry::phase_async(
+                    "warmup_queries",
+                    run_warmup_queries(&toc, queries),
+                )
+                .await;
+                log::info!(
+                    "Ran {} warmup queries in {:?}, {} failed",
+                    report.queries,
+                    report.elapsed,
+                    report.failed,
+                );
+            }
+            Err(err) => {
+                log::warn!("Failed to read warmup queries from {warmup_queries_path}: {err}")
+            }
+        }
+    }
 
     let toc_arc = Arc::new(toc);
+    crash_report::set_toc(&toc_arc);
+
+    #[cfg(unix)]
+    if !is_reader {
+        runtime_handle.spawn(flush_on_sigterm(
+            toc_arc.clone(),
+            PathBuf::from(&settings.storage.storage_path),
+        ));
+    }
+
+    // Readers don't write to the storage
+    if let (Some(latency_ms), false) =
+        (settings.storage.performance.io_pacing_latency_ms, is_reader)
+    {
+        if let Err(err) = spawn_io_probe(storage_path, Duration::from_millis(latency_ms)) {
+            log::warn!("Failed to start probing storage latency, IO is not paced: {err}");
+        }
+    }
+
+    if let (Some(idle_sec), false) = (settings.storage.checkpoint_idle_sec, is_reader) {
+        runtime_handle.spawn(checkpoint_when_idle(
+            toc_arc.clone(),
+            Duration::from_secs(idle_sec),
+        ));
+    }
+
+    if let Some(idle_sec) = settings.storage.release_idle_sec {
+        runtime_handle.spawn(release_memory_when_idle(
+            toc_arc.clone(),
+            Duration::from_secs(idle_sec),
+        ));
+    }
+
+    if let Some(config) = settings.storage.memory_pressure {
+        runtime_handle.spawn(memory_pressure::monitor(toc_arc.clone(), config));
+    }
 
     // Router for external queries.
     // It decides if query should go directly to the ToC or through the consensus.
@@ -184,7 +428,7 @@ async fn main() -> anyhow::Result<()> {
     if reporting_enabled {
         log::info!("Telemetry reporting enabled, id: {}", reporting_id);
 
-        runtime_handle.spawn(TelemetryReporter::run(telemetry_collector.clone()));
+        runtime_handle.spawn(TelemetryReporter::run_lambda(telemetry_collector.clone()));
     } else {
         log::info!("Telemetry reporting disabled");
     }
@@ -196,11 +440,17 @@ async fn main() -> anyhow::Result<()> {
     #[cfg(feature = "web")]
     {
         touch_started_file_indicator();
+        startup_telemetry::ready();
         let dispatcher_arc = dispatcher_arc.clone();
         let settings = settings.clone();
-        let _ =
-            qdrant::actix::init_lambda(dispatcher_arc.clone(), telemetry_collector, None, settings)
-                .await;
+        let _ = qdrant::actix::init_lambda(
+            dispatcher_arc.clone(),
+            telemetry_collector,
+            None,
+            logger_handle,
+            settings,
+        )
+        .await;
     }
 
     //
@@ -219,3 +469,84 @@ async fn main() -> anyhow::Result<()> {
     drop(settings);
     Ok(())
 }
+
+/// Checkpoint all collections once no points were updated for `idle`, so they are loaded
+/// without replaying their WAL if the execution environment is reclaimed while idle.
+///
+/// The execution environment is frozen between invocations, a checkpoint due while frozen
+/// is made once it is thawed.
+async fn checkpoint_when_idle(toc: Arc<TableOfContent>, idle: Duration) {
+    let mut checkpointed_update = None;
+    loop {
+        let last_update = toc.last_update();
+        let wait = match last_update {
+            Some(updated) if last_update != checkpointed_update => {
+                idle.saturating_sub(updated.elapsed())
+            }
+            _ => idle,
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+            continue;
+        }
+
+        let timing = Instant::now();
+        match toc.checkpoint_all_collections().await {
+            Ok(()) => log::debug!("Checkpointed collections in {:?}", timing.elapsed()),
+            Err(err) => log::warn!("Failed to checkpoint collections: {err}"),
+        }
+        checkpointed_update = last_update;
+    }
+}
+
+/// Release memory of collections once they were not used for `idle`, see
+/// [`storage::content_manager::toc::idle_release`].
+async fn release_memory_when_idle(toc: Arc<TableOfContent>, idle: Duration) {
+    loop {
+        let collections = toc.all_collections().await;
+        let (idle_collections, wait) = toc.idle_tracker().take_idle(&collections, idle);
+
+        for collection_name in idle_collections {
+            match toc.release_collection_memory(&collection_name).await {
+                Ok(files) => log::debug!(
+                    "Released memory of {files} files of idle collection {collection_name}",
+                ),
+                Err(err) => log::warn!(
+                    "Failed to release memory of idle collection {collection_name}: {err}",
+                ),
+            }
+        }
+
+        // Don't spin if collections are released as soon as they are idle
+        tokio::time::sleep(wait.max(Duration::from_secs(1))).await;
+    }
+}
+
+/// Lambda sends SIGTERM before shutting the execution environment down.
+/// Flush the storage and mark it as such, so the next cold start can skip verifying it.
+#[cfg(unix)]
+async fn flush_on_sigterm(toc: Arc<TableOfContent>, storage_path: PathBuf) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(sigterm) => sigterm,
+        Err(err) => {
+            log::warn!("Failed to listen for SIGTERM, storage is not flushed on shutdown: {err}");
+            return;
+        }
+    };
+    sigterm.recv().await;
+
+    log::info!("Received SIGTERM, flushing storage");
+    match toc.checkpoint_all_collections().await {
+        Ok(()) => {
+            if let Err(err) = toc.save_runtime_state().await {
+                log::warn!("Failed to save runtime state on shutdown: {err}");
+            }
+            touch_clean_shutdown_indicator(&storage_path);
+        }
+        Err(err) => log::error!("Failed to flush storage on shutdown: {err}"),
+    }
+
+    std::process::exit(0);
+}
@@ -1,3 +1,11 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use storage::rbac::Access;
+
+use super::iam_auth::IamAuth;
 use super::strings::ct_eq;
 use crate::settings::ServiceConfig;
 
@@ -9,6 +17,18 @@ pub struct AuthKeys {
 
     /// A key allowing Read operations
     read_only: Option<String>,
+
+    /// Accept JWTs signed with the read-write key, granting the access of their claims
+    jwt_rbac: bool,
+
+    /// Authentication with AWS IAM credentials
+    iam_auth: Option<Arc<IamAuth>>,
+}
+
+/// Claims of a JWT, besides the optional `exp` validated by the decoder
+#[derive(Deserialize)]
+struct Claims {
+    access: Access,
 }
 
 impl AuthKeys {
@@ -16,18 +36,32 @@ impl AuthKeys {
     ///
     /// Returns None if no scheme is specified.
     pub fn try_create(service_config: &ServiceConfig) -> Option<Self> {
+        let iam_auth = service_config
+            .iam_auth
+            .clone()
+            .map(|config| Arc::new(IamAuth::new(config)));
         match (
             service_config.api_key.clone(),
             service_config.read_only_api_key.clone(),
         ) {
-            (None, None) => None,
+            (None, None) if iam_auth.is_none() => None,
             (read_write, read_only) => Some(Self {
                 read_write,
                 read_only,
+                jwt_rbac: service_config.jwt_rbac,
+                iam_auth,
             }),
         }
     }
 
+    /// Authentication with AWS IAM credentials, if enabled and `token` is meant for it
+    pub fn iam_auth_for(&self, token: &str) -> Option<Arc<IamAuth>> {
+        self.iam_auth
+            .as_ref()
+            .filter(|_| IamAuth::is_token(token))
+            .cloned()
+    }
+
     /// Check if a key is allowed to read
     #[inline]
     pub fn can_read(&self, key: &str) -> bool {
@@ -45,4 +79,85 @@ impl AuthKeys {
             .map(|rw_key| ct_eq(rw_key, key))
             .unwrap_or_default()
     }
+
+    /// Access granted by a JWT, if JWTs are accepted and the token is signed with the read-write
+    /// key and not expired
+    pub fn validate_jwt(&self, token: &str) -> Option<Access> {
+        if !self.jwt_rbac {
+            return None;
+        }
+        let secret = self.read_write.as_ref()?;
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        // Tokens without `exp` don't expire
+        validation.required_spec_claims = HashSet::new();
+        jsonwebtoken::decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            &validation,
+        )
+        .ok()
+        .map(|data| data.claims.access)
+    }
+
+    /// Name of the configured key matching the given one, identifies the caller in logs
+    pub fn key_id(&self, key: &str) -> Option<&'static str> {
+        if self.can_write(key) {
+            Some("read_write")
+        } else if self
+            .read_only
+            .as_ref()
+            .map_or(false, |ro_key| ct_eq(ro_key, key))
+        {
+            Some("read_only")
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+    use storage::rbac::AccessMode;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_jwt() {
+        let auth_keys = AuthKeys {
+            read_write: Some("secret".to_string()),
+            read_only: None,
+            jwt_rbac: true,
+            iam_auth: None,
+        };
+        let token = |claims: serde_json::Value, secret: &str| {
+            jsonwebtoken::encode(
+                &Header::default(),
+                &claims,
+                &EncodingKey::from_secret(secret.as_bytes()),
+            )
+            .unwrap()
+        };
+
+        let access = auth_keys.validate_jwt(&token(json!({"access": "r"}), "secret"));
+        assert_eq!(access, Some(Access::Global(AccessMode::Read)));
+
+        let claims = json!({"access": [{"collection": "books", "access": "rw"}]});
+        assert!(matches!(
+            auth_keys.validate_jwt(&token(claims, "secret")),
+            Some(Access::Collections(_)),
+        ));
+
+        assert_eq!(
+            auth_keys.validate_jwt(&token(json!({"access": "m"}), "other")),
+            None,
+        );
+        assert_eq!(
+            auth_keys.validate_jwt(&token(json!({"access": "m", "exp": 1}), "secret")),
+            None,
+        );
+        assert_eq!(auth_keys.validate_jwt("secret"), None);
+    }
 }
@@ -0,0 +1,140 @@
+//! Wall-clock time derived from the monotonic clock.
+//!
+//! System time of serverless hosts may be skewed, and may jump while the process is running,
+//! e.g. when the execution environment is thawed or the host clock is corrected. Time reported by
+//! [`now`] is anchored to the system time once, advances with the monotonic clock afterwards and
+//! never goes backwards.
+//!
+//! Optionally, offset of the host clock is estimated from the `Date` header of a reference
+//! endpoint, e.g. the S3 endpoint of the region, and applied on top.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+/// `Date` header has a resolution of a second, smaller offsets can't be told apart from noise
+const MIN_OFFSET: Duration = Duration::from_secs(1);
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+static ANCHOR: OnceLock<(Instant, DateTime<Utc>)> = OnceLock::new();
+
+/// Offset of the reference clock from the host clock, in milliseconds
+static OFFSET_MS: AtomicI64 = AtomicI64::new(0);
+
+/// Current time, corrected by the estimated offset of the host clock
+pub fn now() -> DateTime<Utc> {
+    at(Instant::now())
+}
+
+/// Wall-clock time of the `instant`
+pub fn at(instant: Instant) -> DateTime<Utc> {
+    let (anchor_instant, anchor_time) = *ANCHOR.get_or_init(|| (Instant::now(), Utc::now()));
+    let elapsed = instant.saturating_duration_since(anchor_instant);
+    anchor_time
+        + chrono::Duration::from_std(elapsed).unwrap_or_else(|_| chrono::Duration::zero())
+        + offset()
+}
+
+/// Estimated offset of the reference clock from the host clock
+pub fn offset() -> chrono::Duration {
+    chrono::Duration::milliseconds(OFFSET_MS.load(Ordering::Relaxed))
+}
+
+/// Estimate offset of the reference clock, from the `reference` time received in a response to
+/// a request sent at `sent` and received at `received`, as of the clock without an offset.
+///
+/// Like NTP, assumes that the reference time was taken halfway through the round trip.
+fn estimate_offset(
+    reference: DateTime<Utc>,
+    sent: DateTime<Utc>,
+    received: DateTime<Utc>,
+) -> chrono::Duration {
+    // `Date` header is truncated to a second, its expected value is half a second later
+    let reference = reference + chrono::Duration::milliseconds(500);
+    let midpoint = sent + (received - sent) / 2;
+    reference - midpoint
+}
+
+/// Periodically estimate offset of the host clock from the `Date` header of `reference_url`
+pub async fn sync_offset(reference_url: String) {
+    let client = reqwest::Client::new();
+    let mut interval = tokio::time::interval(SYNC_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        match request_offset(&client, &reference_url).await {
+            Ok(offset) => {
+                let offset = if offset.abs().to_std().unwrap_or_default() < MIN_OFFSET {
+                    chrono::Duration::zero()
+                } else {
+                    offset
+                };
+                if offset != self::offset() {
+                    log::info!(
+                        "Host clock is off by {}ms from {reference_url}",
+                        offset.num_milliseconds(),
+                    );
+                }
+                OFFSET_MS.store(offset.num_milliseconds(), Ordering::Relaxed);
+            }
+            Err(err) => log::debug!("Failed to estimate clock offset from {reference_url}: {err}"),
+        }
+    }
+}
+
+async fn request_offset(
+    client: &reqwest::Client,
+    reference_url: &str,
+) -> anyhow::Result<chrono::Duration> {
+    let sent = Instant::now();
+    let response = client.head(reference_url).send().await?;
+    let received = Instant::now();
+
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .ok_or_else(|| anyhow::anyhow!("response has no Date header"))?
+        .to_str()?;
+    let reference = DateTime::parse_from_rfc2822(date)?.with_timezone(&Utc);
+
+    Ok(estimate_offset(
+        reference,
+        at(sent) - offset(),
+        at(received) - offset(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_now_is_monotonic() {
+        let mut previous = now();
+        for _ in 0..1000 {
+            let current = now();
+            assert!(current >= previous);
+            previous = current;
+        }
+    }
+
+    #[test]
+    fn test_estimate_offset() {
+        let sent = DateTime::parse_from_rfc3339("2024-01-01T00:00:00.200Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let received = sent + chrono::Duration::milliseconds(200);
+
+        // Local time halfway through the round trip is 00:00:00.300, reference time somewhere
+        // within 00:00:05
+        let reference = DateTime::parse_from_rfc2822("Mon, 01 Jan 2024 00:00:05 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let offset = estimate_offset(reference, sent, received);
+        assert_eq!(offset.num_milliseconds(), 5200);
+    }
+}
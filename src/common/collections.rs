@@ -23,6 +23,7 @@ use storage::content_manager::collection_meta_ops::{
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
+use storage::rbac::{self, AccessMode};
 
 pub async fn do_get_collection(
     toc: &TableOfContent,
@@ -44,6 +45,8 @@ pub async fn do_list_collections(toc: &TableOfContent) -> CollectionsResponse {
         .all_collections()
         .await
         .into_iter()
+        // Requests restricted to some collections only see those
+        .filter(|name| rbac::check_collection(name, name, AccessMode::Read).is_ok())
         .map(|name| CollectionDescription { name })
         .collect_vec();
 
@@ -126,6 +129,9 @@ pub async fn do_create_snapshot(
     collection_name: &str,
     wait: bool,
 ) -> Result<SnapshotDescription, StorageError> {
+    // Access of the request is not passed to the spawned task
+    let real_collection_name = dispatcher.toc().resolve_alias(collection_name).await;
+    rbac::check_collection(collection_name, &real_collection_name, AccessMode::Manage)?;
     let collection = collection_name.to_string();
     let dispatcher = dispatcher.clone();
     let snapshot = tokio::spawn(async move { dispatcher.create_snapshot(&collection).await });
@@ -0,0 +1,96 @@
+//! Crash reports written by the panic hook.
+//!
+//! A report is a JSON file with the panic message and location, backtrace, build info,
+//! collections loaded at the moment and the latest records of the log tail, so a crash of an
+//! instance can be investigated after its logs are gone.
+
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, Weak};
+use std::{fs, io, panic};
+
+use serde::Serialize;
+use storage::content_manager::toc::TableOfContent;
+
+use crate::common::clock;
+use crate::settings::CrashReportConfig;
+use crate::tracing::tail::{LogRecord, TailBuffer};
+
+/// Set once the storage is loaded, to list the collections
+static TOC: OnceLock<Weak<TableOfContent>> = OnceLock::new();
+
+/// Include the collections of `toc` in crash reports
+pub fn set_toc(toc: &Arc<TableOfContent>) {
+    let _ = TOC.set(Arc::downgrade(toc));
+}
+
+pub struct CrashReporter {
+    dir: PathBuf,
+    log_lines: usize,
+    tail_buffer: Arc<TailBuffer>,
+}
+
+#[derive(Serialize)]
+struct CrashReport<'a> {
+    timestamp: String,
+    reporting_id: &'a str,
+    version: &'static str,
+    debug: bool,
+    thread: Option<&'a str>,
+    message: &'a str,
+    location: Option<String>,
+    backtrace: &'a str,
+    /// `None` if the collections couldn't be listed without blocking
+    collections: Option<Vec<String>>,
+    /// `None` if the log tail couldn't be read without blocking
+    log_lines: Option<Vec<LogRecord>>,
+}
+
+impl CrashReporter {
+    pub fn new(config: &CrashReportConfig, tail_buffer: Arc<TailBuffer>) -> Self {
+        Self {
+            dir: PathBuf::from(&config.path),
+            log_lines: config.log_lines,
+            tail_buffer,
+        }
+    }
+
+    /// Write a report of the panic, returns path of the report
+    pub fn write(
+        &self,
+        reporting_id: &str,
+        message: &str,
+        location: Option<&panic::Location>,
+        backtrace: &str,
+    ) -> io::Result<PathBuf> {
+        let collections = TOC
+            .get()
+            .and_then(Weak::upgrade)
+            .and_then(|toc| toc.try_all_collections());
+
+        let thread = std::thread::current();
+        let now = clock::now();
+        let report = CrashReport {
+            timestamp: now.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            reporting_id,
+            version: env!("CARGO_PKG_VERSION"),
+            debug: cfg!(debug_assertions),
+            thread: thread.name(),
+            message,
+            location: location.map(|location| format!("{}:{}", location.file(), location.line())),
+            backtrace,
+            collections,
+            log_lines: self.tail_buffer.try_tail(self.log_lines),
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!(
+            "crash-{}-{}.json",
+            now.format("%Y%m%d-%H%M%S-%6f"),
+            std::process::id(),
+        ));
+        let report = serde_json::to_vec_pretty(&report).map_err(io::Error::from)?;
+        fs::write(&path, report)?;
+
+        Ok(path)
+    }
+}
@@ -10,6 +10,7 @@ use tokio::runtime::Runtime;
 use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
 use validator::Validate;
 
+use crate::common::memory_arenas::{self, Arena};
 use crate::settings::{Settings, TlsConfig};
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate)]
@@ -47,6 +48,7 @@ pub fn create_search_runtime(max_search_threads: usize) -> io::Result<Runtime> {
             let id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
             format!("search-{id}")
         })
+        .on_thread_start(|| memory_arenas::bind_current_thread(Arena::Search))
         .build()
 }
 
@@ -59,7 +61,8 @@ pub fn create_update_runtime(max_optimization_threads: usize) -> io::Result<Runt
             static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
             let update_id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
             format!("update-{update_id}")
-        });
+        })
+        .on_thread_start(|| memory_arenas::bind_current_thread(Arena::Update));
 
     if max_optimization_threads > 0 {
         // panics if val is not larger than 0.
@@ -68,6 +71,17 @@ pub fn create_update_runtime(max_optimization_threads: usize) -> io::Result<Runt
     update_runtime_builder.build()
 }
 
+/// Update runtime of an instance which never updates the storage, with a single thread
+pub fn create_idle_update_runtime() -> io::Result<Runtime> {
+    runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .max_blocking_threads(1)
+        .enable_time()
+        .thread_name("update-idle")
+        .on_thread_start(|| memory_arenas::bind_current_thread(Arena::Update))
+        .build()
+}
+
 pub fn create_general_purpose_runtime() -> io::Result<Runtime> {
     runtime::Builder::new_multi_thread()
         .enable_time()
@@ -78,6 +92,7 @@ pub fn create_general_purpose_runtime() -> io::Result<Runtime> {
             let general_id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
             format!("general-{general_id}")
         })
+        .on_thread_start(|| memory_arenas::bind_current_thread(Arena::General))
         .build()
 }
 
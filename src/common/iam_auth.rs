@@ -0,0 +1,303 @@
+//! Authentication of requests with AWS IAM credentials, see `service.iam_auth` of the settings.
+//!
+//! Callers sign a `GetCallerIdentity` request of AWS STS with SigV4 and send its presigned URL
+//! as the token of the request, like the IAM authenticator of EKS does. Only AWS can verify the
+//! signature, so the presigned request is sent to STS, which answers with the ARN of the caller.
+//! The ARN is matched against the principals of the settings, which grant the access of the
+//! request, see [`storage::rbac`]. Callers are cached until their presigned requests expire, so
+//! STS is not called for every request.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, ensure, Context as _};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use chrono::{NaiveDateTime, TimeZone as _, Utc};
+use reqwest::header::ACCEPT;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use storage::rbac::Access;
+
+use crate::settings::IamAuthConfig;
+
+/// Prefix of tokens with a presigned request, followed by its URL in unpadded base64url
+pub const TOKEN_PREFIX: &str = "aws-sigv4.";
+
+/// Header signed by callers along with the request, with the `server_id` of the settings
+const SERVER_ID_HEADER: &str = "x-qdrant-server-id";
+
+/// Callers are verified again after this time, even if their presigned requests are still valid
+const MAX_CACHE_TIME: Duration = Duration::from_secs(15 * 60);
+
+const MAX_CACHED_CALLERS: usize = 1024;
+
+const STS_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub struct IamAuth {
+    config: IamAuthConfig,
+    client: Client,
+    /// ARN of the caller and the expiration of the presigned request, by token
+    callers: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetCallerIdentityResponse {
+    get_caller_identity_response: GetCallerIdentityOutput,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetCallerIdentityOutput {
+    get_caller_identity_result: CallerIdentity,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CallerIdentity {
+    arn: String,
+}
+
+impl IamAuth {
+    pub fn new(config: IamAuthConfig) -> Self {
+        let client = Client::builder()
+            .timeout(STS_TIMEOUT)
+            .build()
+            .expect("failed to create HTTP client for STS");
+        Self {
+            config,
+            client,
+            callers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_token(token: &str) -> bool {
+        token.starts_with(TOKEN_PREFIX)
+    }
+
+    /// Access granted to the caller of the presigned request in `token`, `None` if the request
+    /// is not valid or the caller is none of the principals
+    pub async fn authenticate(&self, token: &str) -> Option<Access> {
+        let arn = match self.cached_caller(token) {
+            Some(arn) => arn,
+            None => match self.verify(token).await {
+                Ok(arn) => arn,
+                Err(err) => {
+                    log::debug!("Rejected AWS SigV4 token: {err:#}");
+                    return None;
+                }
+            },
+        };
+
+        let principal = self
+            .config
+            .principals
+            .iter()
+            .find(|principal| matches_arn(&principal.arn, &arn));
+        if principal.is_none() {
+            log::debug!("{arn} is not an IAM principal allowed to call the service");
+        }
+        principal.map(|principal| principal.access.clone())
+    }
+
+    fn callers(&self) -> MutexGuard<'_, HashMap<String, (String, Instant)>> {
+        match self.callers.lock() {
+            Ok(callers) => callers,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn cached_caller(&self, token: &str) -> Option<String> {
+        let callers = self.callers();
+        let (arn, expires) = callers.get(token)?;
+        (*expires > Instant::now()).then(|| arn.clone())
+    }
+
+    /// Send the presigned request to STS, returns the ARN of the caller
+    async fn verify(&self, token: &str) -> anyhow::Result<String> {
+        let url = presigned_url(token, self.config.server_id.is_some())?;
+        let valid_for = valid_for(&url)?;
+
+        let mut request = self.client.get(url).header(ACCEPT, "application/json");
+        if let Some(server_id) = &self.config.server_id {
+            request = request.header(SERVER_ID_HEADER, server_id);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        ensure!(status.is_success(), "STS responded with {status}");
+
+        let body = response.bytes().await?;
+        let response: GetCallerIdentityResponse = serde_json::from_slice(&body)?;
+        let arn = response
+            .get_caller_identity_response
+            .get_caller_identity_result
+            .arn;
+
+        let now = Instant::now();
+        let mut callers = self.callers();
+        if callers.len() >= MAX_CACHED_CALLERS {
+            callers.retain(|_, (_, expires)| *expires > now);
+            if callers.len() >= MAX_CACHED_CALLERS {
+                callers.clear();
+            }
+        }
+        callers.insert(
+            token.to_string(),
+            (arn.clone(), now + valid_for.min(MAX_CACHE_TIME)),
+        );
+
+        Ok(arn)
+    }
+}
+
+/// URL of the presigned request in `token`, if it is a `GetCallerIdentity` request of STS
+/// signed with SigV4
+fn presigned_url(token: &str, server_id_signed: bool) -> anyhow::Result<Url> {
+    let encoded = token
+        .strip_prefix(TOKEN_PREFIX)
+        .context("not an AWS SigV4 token")?;
+    let url = Url::parse(&String::from_utf8(URL_SAFE_NO_PAD.decode(encoded)?)?)?;
+
+    // The request is sent as is, it must not go anywhere but STS
+    let host = url.host_str().unwrap_or_default();
+    let is_sts = host == "sts.amazonaws.com"
+        || host
+            .strip_prefix("sts.")
+            .and_then(|host| host.strip_suffix(".amazonaws.com"))
+            .map_or(false, |region| !region.is_empty() && !region.contains('.'));
+    ensure!(
+        url.scheme() == "https"
+            && is_sts
+            && url.port().is_none()
+            && url.username().is_empty()
+            && url.password().is_none()
+            && url.path() == "/",
+        "not a request to STS",
+    );
+
+    let pairs = url.query_pairs().count();
+    let query: HashMap<_, _> = url.query_pairs().collect();
+    ensure!(query.len() == pairs, "duplicate query parameters");
+    let param = |name: &str| query.get(name).map(|value| value.as_ref());
+    ensure!(
+        param("Action") == Some("GetCallerIdentity"),
+        "not a GetCallerIdentity request",
+    );
+    ensure!(
+        param("X-Amz-Algorithm") == Some("AWS4-HMAC-SHA256"),
+        "not signed with SigV4",
+    );
+    if server_id_signed {
+        let signed = param("X-Amz-SignedHeaders")
+            .unwrap_or_default()
+            .split(';')
+            .any(|header| header == SERVER_ID_HEADER);
+        ensure!(signed, "{SERVER_ID_HEADER} header is not signed");
+    }
+
+    Ok(url)
+}
+
+/// Time until the presigned request expires
+fn valid_for(url: &Url) -> anyhow::Result<Duration> {
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| anyhow!("{name} is missing"))
+    };
+    let signed_at = NaiveDateTime::parse_from_str(&param("X-Amz-Date")?, "%Y%m%dT%H%M%SZ")?;
+    let expires_sec: i64 = param("X-Amz-Expires")?.parse()?;
+
+    let expires = Utc.from_utc_datetime(&signed_at) + chrono::Duration::seconds(expires_sec);
+    (expires - crate::common::clock::now())
+        .to_std()
+        .map_err(|_| anyhow!("presigned request expired"))
+}
+
+/// Whether `arn` matches `pattern`, in which `*` matches any characters
+fn matches_arn(pattern: &str, arn: &str) -> bool {
+    let mut parts: Vec<&str> = pattern.split('*').collect();
+    let Some(rest) = arn.strip_prefix(parts.remove(0)) else {
+        return false;
+    };
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+
+    let mut rest = rest;
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(url: &str) -> String {
+        format!("{TOKEN_PREFIX}{}", URL_SAFE_NO_PAD.encode(url))
+    }
+
+    #[test]
+    fn test_presigned_url() {
+        let query = "Action=GetCallerIdentity&Version=2011-06-15&X-Amz-Algorithm=AWS4-HMAC-SHA256\
+            &X-Amz-Date=20240101T000000Z&X-Amz-Expires=900\
+            &X-Amz-SignedHeaders=host%3Bx-qdrant-server-id&X-Amz-Signature=abc";
+
+        for host in ["sts.amazonaws.com", "sts.eu-west-1.amazonaws.com"] {
+            let url = format!("https://{host}/?{query}");
+            assert!(presigned_url(&token(&url), true).is_ok(), "{url}");
+        }
+        for url in [
+            format!("https://example.com/?{query}"),
+            format!("https://sts.amazonaws.com.example.com/?{query}"),
+            format!("https://sts.eu-west-1.example.com/.amazonaws.com/?{query}"),
+            format!("http://sts.amazonaws.com/?{query}"),
+            format!("https://sts.amazonaws.com:8443/?{query}"),
+            format!("https://sts.amazonaws.com/?{query}&Action=AssumeRole"),
+            format!(
+                "https://sts.amazonaws.com/?{}",
+                query.replace("GetCallerIdentity", "X")
+            ),
+        ] {
+            assert!(presigned_url(&token(&url), false).is_err(), "{url}");
+        }
+
+        let url = format!(
+            "https://sts.amazonaws.com/?{}",
+            query.replace("%3Bx-qdrant-server-id", ""),
+        );
+        assert!(presigned_url(&token(&url), false).is_ok());
+        assert!(presigned_url(&token(&url), true).is_err());
+        assert!(presigned_url("secret", false).is_err());
+    }
+
+    #[test]
+    fn test_matches_arn() {
+        let arn = "arn:aws:sts::123456789012:assumed-role/indexer/session";
+        assert!(matches_arn(arn, arn));
+        assert!(matches_arn(
+            "arn:aws:sts::123456789012:assumed-role/indexer/*",
+            arn
+        ));
+        assert!(matches_arn("arn:aws:sts::*:assumed-role/*/session", arn));
+        assert!(matches_arn("*", arn));
+        assert!(!matches_arn(
+            "arn:aws:sts::123456789012:assumed-role/indexer",
+            arn
+        ));
+        assert!(!matches_arn(
+            "arn:aws:sts::123456789012:assumed-role/search/*",
+            arn
+        ));
+        assert!(!matches_arn("*/indexer/*/session", arn));
+    }
+}
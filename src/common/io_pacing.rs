@@ -0,0 +1,140 @@
+//! Pacing of optimizations by latency of the storage.
+//!
+//! Network filesystems like EFS throttle throughput once burst credits run out, which shows as
+//! growing latency of every operation. A probe measures latency of a small synced write, and
+//! marks IO as congested while it is above the limit, so optimizations pause and leave the
+//! remaining throughput to searches. See [`segment::common::yielding`].
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Seek, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use segment::common::yielding::set_io_congested;
+
+const PROBE_FILE: &str = ".io_probe";
+const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+const PROBE_SIZE: usize = 4096;
+
+/// Weight of the latest probe in the smoothed latency
+const SMOOTHING: f64 = 0.3;
+
+/// Congestion is cleared once latency drops below this fraction of the limit, so pacing
+/// doesn't flap around the limit
+const RECOVERY_FRACTION: f64 = 0.5;
+
+/// Start probing latency of the storage at `storage_path` in the background
+pub fn spawn_io_probe(storage_path: &Path, latency_limit: Duration) -> io::Result<()> {
+    let mut probe = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(storage_path.join(PROBE_FILE))?;
+
+    thread::Builder::new()
+        .name("io-probe".to_string())
+        .spawn(move || {
+            let mut detector = CongestionDetector::new(latency_limit);
+            loop {
+                thread::sleep(PROBE_INTERVAL);
+                let latency = match probe_latency(&mut probe) {
+                    Ok(latency) => latency,
+                    Err(err) => {
+                        log::warn!("Failed to probe storage latency: {err}");
+                        continue;
+                    }
+                };
+
+                let was_congested = detector.congested;
+                let congested = detector.observe(latency);
+                if congested != was_congested {
+                    log::info!(
+                        "Storage IO is {}, latency {:?}",
+                        if congested {
+                            "congested, pausing optimizations"
+                        } else {
+                            "no longer congested"
+                        },
+                        detector.latency(),
+                    );
+                    set_io_congested(congested);
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+fn probe_latency(probe: &mut File) -> io::Result<Duration> {
+    let started = Instant::now();
+    probe.rewind()?;
+    probe.write_all(&[0; PROBE_SIZE])?;
+    probe.sync_data()?;
+    Ok(started.elapsed())
+}
+
+struct CongestionDetector {
+    limit: Duration,
+    /// Smoothed latency in seconds
+    smoothed: Option<f64>,
+    congested: bool,
+}
+
+impl CongestionDetector {
+    fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            smoothed: None,
+            congested: false,
+        }
+    }
+
+    fn latency(&self) -> Duration {
+        Duration::from_secs_f64(self.smoothed.unwrap_or_default())
+    }
+
+    /// Record latency of a probe, returns whether IO is congested
+    fn observe(&mut self, latency: Duration) -> bool {
+        let latency = latency.as_secs_f64();
+        let smoothed = match self.smoothed {
+            Some(smoothed) => smoothed + SMOOTHING * (latency - smoothed),
+            None => latency,
+        };
+        self.smoothed = Some(smoothed);
+
+        let limit = self.limit.as_secs_f64();
+        if smoothed > limit {
+            self.congested = true;
+        } else if smoothed < limit * RECOVERY_FRACTION {
+            self.congested = false;
+        }
+        self.congested
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_congestion_detector() {
+        let mut detector = CongestionDetector::new(Duration::from_millis(50));
+        let ms = Duration::from_millis;
+
+        assert!(!detector.observe(ms(5)));
+        // A single slow probe is smoothed out
+        assert!(!detector.observe(ms(100)));
+        assert!(detector.observe(ms(200)));
+        // Still congested between the recovery threshold and the limit
+        for _ in 0..3 {
+            detector.observe(ms(10));
+        }
+        assert!(detector.latency() > ms(25));
+        assert!(detector.observe(ms(10)));
+        for _ in 0..5 {
+            detector.observe(ms(10));
+        }
+        assert!(!detector.observe(ms(10)));
+    }
+}
@@ -0,0 +1,103 @@
+//! Client of the Lambda Extensions API.
+//!
+//! The execution environment is frozen once the response of the invocation is sent, and every
+//! registered extension asked for the next event. Work started while an invocation is running
+//! is therefore only finished if an extension holds the invocation open until it is done.
+
+use reqwest::Client;
+use serde::Deserialize;
+
+/// Environment variable with the address of the Lambda Runtime and Extensions API
+const RUNTIME_API_ENV: &str = "AWS_LAMBDA_RUNTIME_API";
+
+const EXTENSION_API_VERSION: &str = "2020-01-01";
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "eventType", rename_all = "UPPERCASE")]
+pub enum ExtensionEvent {
+    Invoke {
+        #[serde(rename = "requestId")]
+        request_id: String,
+    },
+}
+
+/// Extension registered in the execution environment of the process.
+///
+/// Registered as an internal extension, which only receives `INVOKE` events.
+pub struct LambdaExtension {
+    client: Client,
+    base_url: String,
+    extension_id: String,
+}
+
+impl LambdaExtension {
+    /// Register an extension named `name`, `None` if not running on Lambda.
+    ///
+    /// Has to be called during the init phase of the execution environment.
+    pub async fn register(name: &str) -> Option<anyhow::Result<Self>> {
+        let runtime_api = std::env::var(RUNTIME_API_ENV).ok()?;
+        let base_url = format!("http://{runtime_api}/{EXTENSION_API_VERSION}/extension");
+        Some(Self::register_at(base_url, name).await)
+    }
+
+    async fn register_at(base_url: String, name: &str) -> anyhow::Result<Self> {
+        let client = Client::new();
+        let response = client
+            .post(format!("{base_url}/register"))
+            .header("Lambda-Extension-Name", name)
+            .body(r#"{"events":["INVOKE"]}"#)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let extension_id = response
+            .headers()
+            .get("Lambda-Extension-Identifier")
+            .and_then(|id| id.to_str().ok())
+            .ok_or_else(|| anyhow::anyhow!("Lambda-Extension-Identifier header is missing"))?
+            .to_string();
+
+        Ok(Self {
+            client,
+            base_url,
+            extension_id,
+        })
+    }
+
+    /// Signal that the extension is done with the current invocation, and wait for the next
+    /// event. The execution environment may be frozen while waiting.
+    pub async fn next_event(&self) -> anyhow::Result<ExtensionEvent> {
+        let body = self
+            .client
+            .get(format!("{}/event/next", self.base_url))
+            .header("Lambda-Extension-Identifier", &self.extension_id)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        let event = r#"{
+            "eventType": "INVOKE",
+            "deadlineMs": 1696000000000,
+            "requestId": "3da1f2dc-3222-475e-9205-e2e6c6318895",
+            "invokedFunctionArn": "arn:aws:lambda:us-east-1:123456789012:function:qdrant",
+            "tracing": {"type": "X-Amzn-Trace-Id", "value": "Root=1-5f35ae12-0c0fec141ab77a00bc047aa2"}
+        }"#;
+        let event: ExtensionEvent = serde_json::from_str(event).unwrap();
+        assert!(matches!(
+            event,
+            ExtensionEvent::Invoke { request_id } if request_id == "3da1f2dc-3222-475e-9205-e2e6c6318895"
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+//! Separate jemalloc arenas for the runtimes of the service.
+//!
+//! Threads of the search, update and general runtimes allocate from arenas of their own, so
+//! memory held by queries, optimizations and the rest of the service (including loading of
+//! segments) is reported separately instead of as a single RSS number.
+//!
+//! An arena above its soft limit returns unused pages to the OS immediately instead of after
+//! the decay time. Allocations are never refused. Huge allocations (8 MiB and above by default)
+//! are served from a shared arena of jemalloc and are not attributed to any of these.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::MemoryArenasConfig;
+
+const SOFT_LIMITS_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+static ARENAS: OnceLock<Vec<ArenaState>> = OnceLock::new();
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Arena {
+    Search,
+    Update,
+    General,
+}
+
+impl Arena {
+    const ALL: [Arena; 3] = [Arena::Search, Arena::Update, Arena::General];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Arena::Search => "search",
+            Arena::Update => "update",
+            Arena::General => "general",
+        }
+    }
+
+    fn soft_limit(self, config: &MemoryArenasConfig) -> Option<usize> {
+        let soft_limit_mb = match self {
+            Arena::Search => config.search_soft_limit_mb,
+            Arena::Update => config.update_soft_limit_mb,
+            Arena::General => config.general_soft_limit_mb,
+        };
+        soft_limit_mb.map(|mb| mb * 1024 * 1024)
+    }
+}
+
+struct ArenaState {
+    arena: Arena,
+    index: u32,
+    soft_limit: Option<usize>,
+    /// Decay time configured for the arena on creation, restored once it is below its soft limit
+    decay_ms: isize,
+    over_soft_limit: AtomicBool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct ArenaUsage {
+    pub name: String,
+    /// Bytes in use by live allocations
+    pub allocated: usize,
+    /// Bytes of physical memory held by the arena, including unused pages not returned yet
+    pub resident: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub soft_limit: Option<usize>,
+}
+
+/// Create an arena for every runtime and allocate from the general one on the current thread.
+///
+/// Must be called before runtimes are created, their threads are bound to arenas on start.
+pub fn init(config: &MemoryArenasConfig) -> anyhow::Result<()> {
+    let mut arenas = Vec::with_capacity(Arena::ALL.len());
+    for arena in Arena::ALL {
+        let index = jemalloc::create_arena()?;
+        arenas.push(ArenaState {
+            arena,
+            index,
+            soft_limit: arena.soft_limit(config),
+            decay_ms: jemalloc::decay_ms(index)?,
+            over_soft_limit: AtomicBool::new(false),
+        });
+    }
+
+    if ARENAS.set(arenas).is_err() {
+        anyhow::bail!("memory arenas are already initialized");
+    }
+
+    bind_current_thread(Arena::General);
+    Ok(())
+}
+
+/// Allocate from the `arena` on the current thread, no-op if arenas are not initialized
+pub fn bind_current_thread(arena: Arena) {
+    let Some(state) = ARENAS
+        .get()
+        .and_then(|arenas| arenas.iter().find(|state| state.arena == arena))
+    else {
+        return;
+    };
+
+    if let Err(err) = jemalloc::bind_thread(state.index) {
+        log::warn!(
+            "Failed to allocate from {} memory arena: {err}",
+            arena.name()
+        );
+    }
+}
+
+/// Memory usage of every arena, empty if arenas are not initialized
+pub fn usage() -> Vec<ArenaUsage> {
+    let Some(arenas) = ARENAS.get() else {
+        return vec![];
+    };
+
+    if let Err(err) = jemalloc::refresh_stats() {
+        log::debug!("Failed to refresh memory arena stats: {err}");
+        return vec![];
+    }
+
+    arenas
+        .iter()
+        .filter_map(|state| {
+            let (allocated, resident) = jemalloc::arena_stats(state.index).ok()?;
+            Some(ArenaUsage {
+                name: state.arena.name().to_string(),
+                allocated,
+                resident,
+                soft_limit: state.soft_limit,
+            })
+        })
+        .collect()
+}
+
+/// Periodically purge unused pages of arenas above their soft limit
+pub async fn enforce_soft_limits() {
+    let Some(arenas) = ARENAS.get() else {
+        return;
+    };
+    if arenas.iter().all(|state| state.soft_limit.is_none()) {
+        return;
+    }
+
+    let mut interval = tokio::time::interval(SOFT_LIMITS_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = jemalloc::refresh_stats() {
+            log::debug!("Failed to refresh memory arena stats: {err}");
+            continue;
+        }
+
+        for state in arenas {
+            if let Err(err) = check_soft_limit(state) {
+                log::warn!(
+                    "Failed to check soft limit of {} memory arena: {err}",
+                    state.arena.name(),
+                );
+            }
+        }
+    }
+}
+
+fn check_soft_limit(state: &ArenaState) -> anyhow::Result<()> {
+    let Some(soft_limit) = state.soft_limit else {
+        return Ok(());
+    };
+
+    let (_, resident) = jemalloc::arena_stats(state.index)?;
+    let over_soft_limit = resident > soft_limit;
+    if over_soft_limit == state.over_soft_limit.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    if over_soft_limit {
+        log::warn!(
+            "Memory arena {} holds {resident} bytes, above its soft limit of {soft_limit} bytes",
+            state.arena.name(),
+        );
+        jemalloc::set_decay_ms(state.index, 0)?;
+    } else {
+        jemalloc::set_decay_ms(state.index, state.decay_ms)?;
+    }
+
+    state
+        .over_soft_limit
+        .store(over_soft_limit, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(not(target_env = "msvc"))]
+mod jemalloc {
+    use tikv_jemalloc_ctl::{epoch, raw, Result};
+
+    pub fn create_arena() -> Result<u32> {
+        unsafe { raw::read(b"arenas.create\0") }
+    }
+
+    pub fn bind_thread(index: u32) -> Result<()> {
+        unsafe { raw::write(b"thread.arena\0", index) }
+    }
+
+    pub fn refresh_stats() -> Result<()> {
+        epoch::advance().map(|_| ())
+    }
+
+    /// Allocated and resident bytes of the arena, as of the last stats refresh
+    pub fn arena_stats(index: u32) -> Result<(usize, usize)> {
+        let small = read_usize(&format!("stats.arenas.{index}.small.allocated\0"))?;
+        let large = read_usize(&format!("stats.arenas.{index}.large.allocated\0"))?;
+        let resident = read_usize(&format!("stats.arenas.{index}.resident\0"))?;
+        Ok((small + large, resident))
+    }
+
+    pub fn decay_ms(index: u32) -> Result<isize> {
+        unsafe { raw::read(format!("arena.{index}.dirty_decay_ms\0").as_bytes()) }
+    }
+
+    /// Set decay time of both dirty and muzzy pages, `0` returns them to the OS immediately
+    pub fn set_decay_ms(index: u32, decay_ms: isize) -> Result<()> {
+        unsafe {
+            raw::write(
+                format!("arena.{index}.dirty_decay_ms\0").as_bytes(),
+                decay_ms,
+            )?;
+            raw::write(
+                format!("arena.{index}.muzzy_decay_ms\0").as_bytes(),
+                decay_ms,
+            )
+        }
+    }
+
+    fn read_usize(name: &str) -> Result<usize> {
+        unsafe { raw::read(name.as_bytes()) }
+    }
+}
+
+#[cfg(target_env = "msvc")]
+mod jemalloc {
+    use anyhow::{anyhow, Result};
+
+    pub fn create_arena() -> Result<u32> {
+        Err(anyhow!("memory arenas require jemalloc"))
+    }
+
+    pub fn bind_thread(_index: u32) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn refresh_stats() -> Result<()> {
+        Ok(())
+    }
+
+    pub fn arena_stats(_index: u32) -> Result<(usize, usize)> {
+        Err(anyhow!("memory arenas require jemalloc"))
+    }
+
+    pub fn decay_ms(_index: u32) -> Result<isize> {
+        Err(anyhow!("memory arenas require jemalloc"))
+    }
+
+    pub fn set_decay_ms(_index: u32, _decay_ms: isize) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+//! Monitor of memory pressure, which gives memory back before the process runs out of it.
+//!
+//! Pages of memory maps count towards the memory limit of a Lambda function. Once the limit is
+//! close, the OS evicts pages which searches read from the disk again right away, showing as a
+//! growing rate of major page faults. The monitor samples available memory and major page
+//! faults, and under pressure disables readahead of memory maps and releases memory of
+//! collections which are not used. Its decisions are reported in telemetry, see [`telemetry`].
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use memory::madvise::{self, Advice, AdviceConfig};
+use memory::mmap_registry;
+use schemars::JsonSchema;
+use segment::common::anonymize::Anonymize;
+use serde::{Deserialize, Serialize};
+use storage::content_manager::toc::TableOfContent;
+use storage::types::MemoryPressureConfig;
+
+use crate::common::telemetry_ops::resources_telemetry::{memory_limit, resident_memory};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of the latest decisions reported in telemetry
+const MAX_DECISIONS: usize = 32;
+
+/// Pressure is gone once available memory is above the minimum by this factor, and major page
+/// faults are below the maximum by this fraction, so decisions don't flap around the thresholds
+const RECOVERY_FACTOR: f64 = 1.5;
+const RECOVERY_FRACTION: f64 = 0.5;
+
+/// State of the running monitor, `None` if it is not running
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+#[derive(Default)]
+struct State {
+    under_pressure: bool,
+    available_bytes: usize,
+    major_faults_per_sec: f64,
+    decisions: VecDeque<PressureDecision>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct MemoryPressureTelemetry {
+    pub under_pressure: bool,
+    /// Memory available to the process at the last sample
+    pub available_bytes: usize,
+    /// Major page faults per second at the last sample
+    pub major_faults_per_sec: f64,
+    /// Latest decisions of the monitor, from the oldest one
+    pub decisions: Vec<PressureDecision>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct PressureDecision {
+    pub timestamp: DateTime<Utc>,
+    pub action: PressureAction,
+    /// Collection whose memory was released
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub collection: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PressureAction {
+    /// Readahead of memory maps was disabled
+    DemoteAdvice,
+    /// Advice of memory maps was restored once the pressure was gone
+    RestoreAdvice,
+    /// Memory of a collection which was not used was released
+    ReleaseCollection,
+}
+
+/// State of the monitor for telemetry, `None` if it is not running
+pub fn telemetry() -> Option<MemoryPressureTelemetry> {
+    let state = state();
+    let state = state.as_ref()?;
+    Some(MemoryPressureTelemetry {
+        under_pressure: state.under_pressure,
+        available_bytes: state.available_bytes,
+        major_faults_per_sec: state.major_faults_per_sec,
+        decisions: state.decisions.iter().cloned().collect(),
+    })
+}
+
+fn state() -> MutexGuard<'static, Option<State>> {
+    match STATE.lock() {
+        Ok(state) => state,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn record(action: PressureAction, collection: Option<String>) {
+    if let Some(state) = state().as_mut() {
+        if state.decisions.len() >= MAX_DECISIONS {
+            state.decisions.pop_front();
+        }
+        state.decisions.push_back(PressureDecision {
+            timestamp: crate::common::clock::now(),
+            action,
+            collection,
+        });
+    }
+}
+
+/// Monitor memory pressure until the process exits
+pub async fn monitor(toc: Arc<TableOfContent>, config: MemoryPressureConfig) {
+    let Some(mut previous) = Sample::take() else {
+        log::warn!("Memory can't be sampled on this platform, memory pressure is not monitored");
+        return;
+    };
+    *state() = Some(State::default());
+
+    let mut detector = PressureDetector::new(&config);
+    let release_cold = Duration::from_secs(config.release_cold_sec);
+    // Advice before it was demoted, to restore it once the pressure is gone
+    let mut demoted_from = None;
+
+    loop {
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+        let Some(sample) = Sample::take() else {
+            continue;
+        };
+        let elapsed = sample.at.duration_since(previous.at).as_secs_f64();
+        let major_faults_per_sec =
+            sample.major_faults.saturating_sub(previous.major_faults) as f64 / elapsed;
+        let available = sample.available;
+        previous = sample;
+
+        let was_under_pressure = detector.under_pressure;
+        let under_pressure = detector.observe(available, major_faults_per_sec);
+        if under_pressure != was_under_pressure {
+            log::info!(
+                "Memory is {}, {} MiB available, {major_faults_per_sec:.0} major page faults per second",
+                if under_pressure {
+                    "under pressure"
+                } else {
+                    "no longer under pressure"
+                },
+                available / 1024 / 1024,
+            );
+        }
+        if let Some(state) = state().as_mut() {
+            state.under_pressure = under_pressure;
+            state.available_bytes = available;
+            state.major_faults_per_sec = major_faults_per_sec;
+        }
+
+        if under_pressure {
+            if demoted_from.is_none() {
+                let current = madvise::get_global_config();
+                let demoted = demote(current);
+                if demoted != current {
+                    madvise::set_global(demoted);
+                    mmap_registry::readvise_all();
+                    record(PressureAction::DemoteAdvice, None);
+                    demoted_from = Some(current);
+                }
+            }
+            release_cold_collections(&toc, release_cold).await;
+        } else if let Some(original) = demoted_from.take() {
+            // Keep the advice if it was changed since, e.g. with runtime settings
+            if madvise::get_global_config() == demote(original) {
+                madvise::set_global(original);
+                mmap_registry::readvise_all();
+                record(PressureAction::RestoreAdvice, None);
+            }
+        }
+    }
+}
+
+async fn release_cold_collections(toc: &TableOfContent, cold: Duration) {
+    let collections = toc.all_collections().await;
+    let (cold_collections, _) = toc.idle_tracker().take_idle(&collections, cold);
+
+    for collection_name in cold_collections {
+        match toc.release_collection_memory(&collection_name).await {
+            Ok(files) => {
+                log::debug!(
+                    "Released memory of {files} files of collection {collection_name} under memory pressure",
+                );
+                record(PressureAction::ReleaseCollection, Some(collection_name));
+            }
+            Err(err) => log::warn!(
+                "Failed to release memory of collection {collection_name} under memory pressure: {err}",
+            ),
+        }
+    }
+}
+
+/// Advice without readahead, which brings pages into memory before they are needed
+fn demote(config: AdviceConfig) -> AdviceConfig {
+    let demote_advice = |advice| match advice {
+        Advice::Normal | Advice::Sequential => Advice::Random,
+        advice => advice,
+    };
+    AdviceConfig {
+        default: demote_advice(config.default),
+        vectors: config.vectors.map(demote_advice),
+        hnsw_links: config.hnsw_links.map(demote_advice),
+        quantized: config.quantized.map(demote_advice),
+    }
+}
+
+struct Sample {
+    at: Instant,
+    major_faults: u64,
+    /// Bytes of memory available to the process
+    available: usize,
+}
+
+impl Sample {
+    fn take() -> Option<Self> {
+        let below_limit = memory_limit()?.saturating_sub(resident_memory()?);
+        // Other processes take memory of the host as well
+        let available = match sys_info::mem_info() {
+            Ok(mem_info) => below_limit.min(mem_info.avail as usize * 1024),
+            Err(_) => below_limit,
+        };
+
+        Some(Self {
+            at: Instant::now(),
+            major_faults: major_faults()?,
+            available,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn major_faults() -> Option<u64> {
+    // Name of the executable in the second field may contain spaces, `majflt` is the 10th field
+    // after it
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(9)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn major_faults() -> Option<u64> {
+    None
+}
+
+struct PressureDetector {
+    min_available: usize,
+    max_major_faults_per_sec: Option<f64>,
+    under_pressure: bool,
+}
+
+impl PressureDetector {
+    fn new(config: &MemoryPressureConfig) -> Self {
+        Self {
+            min_available: config.min_available_mb * 1024 * 1024,
+            max_major_faults_per_sec: config.max_major_faults_per_sec.map(|max| max as f64),
+            under_pressure: false,
+        }
+    }
+
+    /// Record a sample, returns whether memory is under pressure
+    fn observe(&mut self, available: usize, major_faults_per_sec: f64) -> bool {
+        let faulting = |fraction: f64| {
+            self.max_major_faults_per_sec
+                .is_some_and(|max| major_faults_per_sec > max * fraction)
+        };
+
+        if available < self.min_available || faulting(1.0) {
+            self.under_pressure = true;
+        } else if available as f64 > self.min_available as f64 * RECOVERY_FACTOR
+            && !faulting(RECOVERY_FRACTION)
+        {
+            self.under_pressure = false;
+        }
+        self.under_pressure
+    }
+}
+
+impl Anonymize for MemoryPressureTelemetry {
+    fn anonymize(&self) -> Self {
+        MemoryPressureTelemetry {
+            under_pressure: self.under_pressure,
+            available_bytes: self.available_bytes.anonymize(),
+            major_faults_per_sec: self.major_faults_per_sec,
+            decisions: self.decisions.anonymize(),
+        }
+    }
+}
+
+impl Anonymize for PressureDecision {
+    fn anonymize(&self) -> Self {
+        PressureDecision {
+            timestamp: self.timestamp.anonymize(),
+            action: self.action,
+            collection: self.collection.anonymize(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use memory::madvise::MmapKind;
+
+    use super::*;
+
+    #[test]
+    fn test_pressure_detector() {
+        let mut detector = PressureDetector::new(&MemoryPressureConfig {
+            min_available_mb: 100,
+            max_major_faults_per_sec: Some(100),
+            release_cold_sec: 60,
+        });
+        let mb = |mb: usize| mb * 1024 * 1024;
+
+        assert!(!detector.observe(mb(500), 10.0));
+        assert!(detector.observe(mb(50), 10.0));
+        // Still under pressure until there is enough memory and few faults
+        assert!(detector.observe(mb(120), 10.0));
+        assert!(detector.observe(mb(500), 80.0));
+        assert!(!detector.observe(mb(500), 10.0));
+        assert!(detector.observe(mb(500), 200.0));
+    }
+
+    #[test]
+    fn test_demote() {
+        let config = AdviceConfig {
+            default: Advice::Normal,
+            vectors: Some(Advice::Sequential),
+            hnsw_links: Some(Advice::Random),
+            quantized: None,
+        };
+        let demoted = demote(config);
+        for kind in [MmapKind::Vectors, MmapKind::HnswLinks, MmapKind::Quantized] {
+            assert_eq!(demoted.get(kind), Advice::Random);
+        }
+        assert_eq!(demote(demoted), demoted);
+    }
+}
@@ -1,6 +1,9 @@
-use prometheus::proto::{Counter, Gauge, LabelPair, Metric, MetricFamily, MetricType};
+use prometheus::proto::{
+    Bucket, Counter, Gauge, Histogram, LabelPair, Metric, MetricFamily, MetricType,
+};
 use prometheus::TextEncoder;
 
+use crate::common::memory_arenas::ArenaUsage;
 use crate::common::telemetry::TelemetryData;
 use crate::common::telemetry_ops::app_telemetry::{AppBuildTelemetry, AppFeaturesTelemetry};
 use crate::common::telemetry_ops::cluster_telemetry::{ClusterStatusTelemetry, ClusterTelemetry};
@@ -10,6 +13,9 @@ use crate::common::telemetry_ops::collections_telemetry::{
 use crate::common::telemetry_ops::requests_telemetry::{
     GrpcTelemetry, RequestsTelemetry, WebApiTelemetry,
 };
+use crate::common::telemetry_ops::resources_telemetry::{ResourceUsage, ResourcesTelemetry};
+use crate::tracing::sink::LogSinkHealth;
+use crate::tracing::span_timings::SpanTimings;
 
 /// Whitelist for REST endpoints in metrics output.
 ///
@@ -74,6 +80,15 @@ impl MetricsProvider for TelemetryData {
         self.collections.add_metrics(metrics);
         self.cluster.add_metrics(metrics);
         self.requests.add_metrics(metrics);
+        if let Some(resources) = &self.resources {
+            resources.add_metrics(metrics);
+        }
+        if !self.log_sinks.is_empty() {
+            self.log_sinks.add_metrics(metrics);
+        }
+        if !self.span_timings.is_empty() {
+            self.span_timings.add_metrics(metrics);
+        }
     }
 }
 
@@ -343,6 +358,159 @@ impl MetricsProvider for GrpcTelemetry {
     }
 }
 
+impl MetricsProvider for ResourcesTelemetry {
+    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>) {
+        let resources = [
+            ("storage", "_bytes", "size of the storage", &self.storage),
+            (
+                "tmp",
+                "_bytes",
+                "used space of the temporary files filesystem",
+                &self.tmp,
+            ),
+            (
+                "memory",
+                "_bytes",
+                "resident memory of the process",
+                &self.memory,
+            ),
+            ("open_files", "", "open file descriptors", &self.open_files),
+        ];
+
+        for (name, unit, help, usage) in resources {
+            if let Some(usage) = usage {
+                add_usage_metrics(metrics, name, unit, help, usage);
+            }
+        }
+
+        if !self.memory_arenas.is_empty() {
+            self.memory_arenas.add_metrics(metrics);
+        }
+    }
+}
+
+impl MetricsProvider for Vec<ArenaUsage> {
+    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>) {
+        let (mut allocated, mut resident, mut soft_limit, mut ratio) =
+            (vec![], vec![], vec![], vec![]);
+        for arena in self {
+            let labels = [("arena", arena.name.as_str())];
+            allocated.push(gauge(arena.allocated as f64, &labels));
+            resident.push(gauge(arena.resident as f64, &labels));
+            if let Some(limit) = arena.soft_limit.filter(|limit| *limit > 0) {
+                soft_limit.push(gauge(limit as f64, &labels));
+                ratio.push(gauge(arena.resident as f64 / limit as f64, &labels));
+            }
+        }
+
+        metrics.push(metric_family(
+            "memory_arena_allocated_bytes",
+            "memory in use by live allocations of the arena",
+            MetricType::GAUGE,
+            allocated,
+        ));
+        metrics.push(metric_family(
+            "memory_arena_used_bytes",
+            "resident memory held by the arena",
+            MetricType::GAUGE,
+            resident,
+        ));
+        if !soft_limit.is_empty() {
+            metrics.push(metric_family(
+                "memory_arena_limit_bytes",
+                "soft limit of resident memory held by the arena",
+                MetricType::GAUGE,
+                soft_limit,
+            ));
+            metrics.push(metric_family(
+                "memory_arena_usage_ratio",
+                "resident memory held by the arena relative to its soft limit, from 0 to 1",
+                MetricType::GAUGE,
+                ratio,
+            ));
+        }
+    }
+}
+
+impl MetricsProvider for Vec<LogSinkHealth> {
+    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>) {
+        let (mut healthy, mut fallback_writes) = (vec![], vec![]);
+        for sink in self {
+            let labels = [("sink", sink.name.as_str())];
+            healthy.push(gauge(if sink.healthy { 1.0 } else { 0.0 }, &labels));
+            fallback_writes.push(counter(sink.fallback_writes as f64, &labels));
+        }
+
+        metrics.push(metric_family(
+            "log_sink_healthy",
+            "whether the last write into the log file has succeeded",
+            MetricType::GAUGE,
+            healthy,
+        ));
+        metrics.push(metric_family(
+            "log_sink_fallback_writes_total",
+            "log writes redirected to stdout while the log file was unwritable",
+            MetricType::COUNTER,
+            fallback_writes,
+        ));
+    }
+}
+
+impl MetricsProvider for Vec<SpanTimings> {
+    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>) {
+        metrics.push(metric_family(
+            "span_duration_seconds",
+            "duration of key operations, by span",
+            MetricType::HISTOGRAM,
+            self.iter()
+                .map(|timings| {
+                    let buckets = timings
+                        .buckets
+                        .iter()
+                        .map(|bucket| (bucket.le, bucket.count));
+                    histogram(
+                        timings.count,
+                        timings.sum_sec,
+                        buckets,
+                        &[("span", &timings.span)],
+                    )
+                })
+                .collect(),
+        ));
+    }
+}
+
+/// Report usage of a limited resource as `{name}_used`, `{name}_limit` and `{name}_usage_ratio`
+/// gauges, so alarms can be put on the ratio without any math on the consumer side.
+fn add_usage_metrics(
+    metrics: &mut Vec<MetricFamily>,
+    name: &str,
+    unit: &str,
+    help: &str,
+    usage: &ResourceUsage,
+) {
+    metrics.push(metric_family(
+        &format!("{name}_used{unit}"),
+        help,
+        MetricType::GAUGE,
+        vec![gauge(usage.used as f64, &[])],
+    ));
+    metrics.push(metric_family(
+        &format!("{name}_limit{unit}"),
+        &format!("limit of {help}"),
+        MetricType::GAUGE,
+        vec![gauge(usage.limit as f64, &[])],
+    ));
+    if let Some(ratio) = usage.ratio() {
+        metrics.push(metric_family(
+            &format!("{name}_usage_ratio"),
+            &format!("{help} relative to its limit, from 0 to 1"),
+            MetricType::GAUGE,
+            vec![gauge(ratio, &[])],
+        ));
+    }
+}
+
 fn metric_family(name: &str, help: &str, r#type: MetricType, metrics: Vec<Metric>) -> MetricFamily {
     let mut metric_family = MetricFamily::default();
     metric_family.set_name(name.into());
@@ -374,6 +542,36 @@ fn gauge(value: f64, labels: &[(&str, &str)]) -> Metric {
     metric
 }
 
+/// Histogram of `count` samples summing up to `sum`, with cumulative counts of `buckets` by their
+/// upper bound
+fn histogram(
+    count: u64,
+    sum: f64,
+    buckets: impl IntoIterator<Item = (f64, u64)>,
+    labels: &[(&str, &str)],
+) -> Metric {
+    let mut metric = Metric::default();
+    metric.set_label(labels.iter().map(|(n, v)| label_pair(n, v)).collect());
+    metric.set_histogram({
+        let mut histogram = Histogram::default();
+        histogram.set_sample_count(count);
+        histogram.set_sample_sum(sum);
+        histogram.set_bucket(
+            buckets
+                .into_iter()
+                .map(|(upper_bound, cumulative_count)| {
+                    let mut bucket = Bucket::default();
+                    bucket.set_upper_bound(upper_bound);
+                    bucket.set_cumulative_count(cumulative_count);
+                    bucket
+                })
+                .collect(),
+        );
+        histogram
+    });
+    metric
+}
+
 fn label_pair(name: &str, value: &str) -> LabelPair {
     let mut label = LabelPair::default();
     label.set_name(name.into());
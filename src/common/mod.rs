@@ -1,5 +1,8 @@
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod clock;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod collections;
+pub mod crash_report;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod error_reporting;
 #[allow(dead_code)]
@@ -7,9 +10,19 @@ pub mod health;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod helpers;
 pub mod http_client;
+pub mod iam_auth;
+pub mod io_pacing;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod lambda_extension;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod memory_arenas;
+pub mod memory_pressure;
 pub mod metrics;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod points;
+pub mod request_recorder;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod secrets;
 pub mod snapshots;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod stacktrace;
@@ -18,6 +31,8 @@ pub mod telemetry;
 pub mod telemetry_ops;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod telemetry_reporting;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod warmup;
 
 pub mod auth;
 
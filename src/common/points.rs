@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use collection::common::batching::batch_requests;
 use collection::operations::consistency_params::ReadConsistency;
@@ -13,9 +13,9 @@ use collection::operations::shard_key_selector::ShardKeySelector;
 use collection::operations::shard_selector_internal::ShardSelectorInternal;
 use collection::operations::types::{
     CoreSearchRequest, CoreSearchRequestBatch, CountRequestInternal, CountResult,
-    DiscoverRequestBatch, DiscoverRequestInternal, GroupsResult, PointRequestInternal,
-    RecommendGroupsRequestInternal, Record, ScrollRequestInternal, ScrollResult,
-    SearchGroupsRequestInternal, UpdateResult,
+    DiscoverRequestBatch, DiscoverRequestInternal, GroupsCountRequestInternal, GroupsCountResult,
+    GroupsResult, PointRequestInternal, RecommendGroupsRequestInternal, Record,
+    ScrollRequestInternal, ScrollResult, SearchGroupsRequestInternal, UpdateResult,
 };
 use collection::operations::vector_ops::{
     DeleteVectors, UpdateVectors, UpdateVectorsOp, VectorOperations,
@@ -33,6 +33,8 @@ use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
 use validator::Validate;
 
+use crate::tracing::slow_query_log::{self, SlowQueryEntry};
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate)]
 pub struct CreateFieldIndex {
     pub field_name: PayloadKeyType,
@@ -153,6 +155,81 @@ fn get_shard_selector_for_update(
     }
 }
 
+/// Apply the update, and report it into the slow query log if it took too long
+async fn update(
+    toc: &TableOfContent,
+    collection_name: &str,
+    operation: CollectionUpdateOperations,
+    wait: bool,
+    ordering: WriteOrdering,
+    shard_selector: ShardSelectorInternal,
+) -> Result<UpdateResult, StorageError> {
+    let started = Instant::now();
+    let result = toc
+        .update(collection_name, operation, wait, ordering, shard_selector)
+        .await;
+
+    let duration = started.elapsed();
+    if slow_query_log::is_slow(duration) {
+        SlowQueryEntry {
+            operation: "update",
+            collection: collection_name,
+            duration,
+            requests: 1,
+            limit: None,
+            filters: Vec::new(),
+        }
+        .log();
+    }
+
+    result
+}
+
+/// Collect details of the searches for the slow query log before they are consumed, if it is
+/// enabled
+fn slow_search_entry<'a, 'r>(
+    collection_name: &'a str,
+    requests: impl IntoIterator<Item = &'r CoreSearchRequest>,
+) -> Option<SlowQueryEntry<'a>> {
+    if !slow_query_log::is_enabled() {
+        return None;
+    }
+
+    let mut entry = SlowQueryEntry {
+        operation: "search",
+        collection: collection_name,
+        duration: Duration::ZERO,
+        requests: 0,
+        limit: None,
+        filters: Vec::new(),
+    };
+
+    for request in requests {
+        entry.requests += 1;
+        entry.limit = entry.limit.max(Some(request.limit));
+
+        if let Some(filter) = &request.filter {
+            let summary = slow_query_log::filter_summary(filter);
+            if !entry.filters.contains(&summary) {
+                entry.filters.push(summary);
+            }
+        }
+    }
+
+    Some(entry)
+}
+
+fn log_if_slow(entry: Option<SlowQueryEntry>, started: Instant) {
+    let Some(mut entry) = entry else {
+        return;
+    };
+
+    entry.duration = started.elapsed();
+    if slow_query_log::is_slow(entry.duration) {
+        entry.log();
+    }
+}
+
 pub async fn do_upsert_points(
     toc: &TableOfContent,
     collection_name: &str,
@@ -167,7 +244,8 @@ pub async fn do_upsert_points(
 
     let shard_selector = get_shard_selector_for_update(shard_selection, shard_key);
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -196,7 +274,8 @@ pub async fn do_delete_points(
     let collection_operation = CollectionUpdateOperations::PointOperation(point_operation);
     let shard_selector = get_shard_selector_for_update(shard_selection, shard_key);
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -222,7 +301,8 @@ pub async fn do_update_vectors(
 
     let shard_selector = get_shard_selector_for_update(shard_selection, shard_key);
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -258,7 +338,8 @@ pub async fn do_delete_vectors(
             VectorOperations::DeleteVectorsByFilter(filter, vector_names.clone());
         let collection_operation = CollectionUpdateOperations::VectorOperation(vectors_operation);
         result = Some(
-            toc.update(
+            update(
+                toc,
                 collection_name,
                 collection_operation,
                 wait,
@@ -273,7 +354,8 @@ pub async fn do_delete_vectors(
         let vectors_operation = VectorOperations::DeleteVectors(points.into(), vector_names);
         let collection_operation = CollectionUpdateOperations::VectorOperation(vectors_operation);
         result = Some(
-            toc.update(
+            update(
+                toc,
                 collection_name,
                 collection_operation,
                 wait,
@@ -311,7 +393,8 @@ pub async fn do_set_payload(
 
     let shard_selector = get_shard_selector_for_update(shard_selection, shard_key);
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -345,7 +428,8 @@ pub async fn do_overwrite_payload(
 
     let shard_selector = get_shard_selector_for_update(shard_selection, shard_key);
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -379,7 +463,8 @@ pub async fn do_delete_payload(
 
     let shard_selector = get_shard_selector_for_update(shard_selection, shard_key);
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -410,7 +495,8 @@ pub async fn do_clear_payload(
 
     let shard_selector = get_shard_selector_for_update(shard_selection, shard_key);
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -547,7 +633,8 @@ pub async fn do_create_index_internal(
         ShardSelectorInternal::All
     };
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -618,7 +705,8 @@ pub async fn do_delete_index_internal(
         ShardSelectorInternal::All
     };
 
-    toc.update(
+    update(
+        toc,
         collection_name,
         collection_operation,
         wait,
@@ -691,6 +779,10 @@ pub async fn do_search_batch_points(
     read_consistency: Option<ReadConsistency>,
     timeout: Option<Duration>,
 ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
+    let slow_query_entry =
+        slow_search_entry(collection_name, requests.iter().map(|(request, _)| request));
+    let started = Instant::now();
+
     let requests = batch_requests::<
         (CoreSearchRequest, ShardSelectorInternal),
         ShardSelectorInternal,
@@ -724,8 +816,10 @@ pub async fn do_search_batch_points(
         },
     )?;
 
-    let results = futures::future::try_join_all(requests).await?;
-    let flatten_results: Vec<Vec<_>> = results.into_iter().flatten().collect();
+    let results = futures::future::try_join_all(requests).await;
+    log_if_slow(slow_query_entry, started);
+
+    let flatten_results: Vec<Vec<_>> = results?.into_iter().flatten().collect();
     Ok(flatten_results)
 }
 
@@ -737,14 +831,21 @@ pub async fn do_core_search_batch_points(
     shard_selection: ShardSelectorInternal,
     timeout: Option<Duration>,
 ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
-    toc.core_search_batch(
-        collection_name,
-        request,
-        read_consistency,
-        shard_selection,
-        timeout,
-    )
-    .await
+    let slow_query_entry = slow_search_entry(collection_name, &request.searches);
+    let started = Instant::now();
+
+    let result = toc
+        .core_search_batch(
+            collection_name,
+            request,
+            read_consistency,
+            shard_selection,
+            timeout,
+        )
+        .await;
+
+    log_if_slow(slow_query_entry, started);
+    result
 }
 
 pub async fn do_search_point_groups(
@@ -836,6 +937,17 @@ pub async fn do_count_points(
         .await
 }
 
+pub async fn do_count_point_groups(
+    toc: &TableOfContent,
+    collection_name: &str,
+    request: GroupsCountRequestInternal,
+    read_consistency: Option<ReadConsistency>,
+    shard_selection: ShardSelectorInternal,
+) -> Result<GroupsCountResult, StorageError> {
+    toc.count_groups(collection_name, request, read_consistency, shard_selection)
+        .await
+}
+
 pub async fn do_get_points(
     toc: &TableOfContent,
     collection_name: &str,
@@ -0,0 +1,243 @@
+//! On-disk ring buffer of recorded API requests, used to reproduce issues offline.
+//!
+//! Each request is stored as a separate JSON file named by its sequence number.
+//! Once `capacity` records are stored, the oldest record is removed for every new one.
+//! Only the method, URI, JSON body and response status are kept: headers
+//! (including API keys) are never recorded, and values of the configured fields of the body
+//! and query parameters of the URI are redacted before the record is written.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::{fs, io};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::RequestRecorderConfig;
+
+const RECORD_EXTENSION: &str = "json";
+
+const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+    pub status: u16,
+}
+
+pub struct RequestRecorder {
+    dir: PathBuf,
+    capacity: u64,
+    max_body_size: usize,
+    redacted_fields: HashSet<String>,
+    next_seq: AtomicU64,
+}
+
+impl RequestRecorder {
+    pub fn open(config: &RequestRecorderConfig) -> io::Result<Self> {
+        let dir = PathBuf::from(&config.path);
+        fs::create_dir_all(&dir)?;
+
+        // Continue numbering after records left by previous runs
+        let next_seq = list_records(&dir)?.last().map_or(0, |(seq, _)| seq + 1);
+
+        Ok(Self {
+            dir,
+            capacity: config.capacity as u64,
+            max_body_size: config.max_body_kb * 1024,
+            redacted_fields: config.redacted_fields.iter().cloned().collect(),
+            next_seq: AtomicU64::new(next_seq),
+        })
+    }
+
+    /// Whether a body of the given size should be kept in the record.
+    pub fn keeps_body(&self, size: usize) -> bool {
+        size <= self.max_body_size
+    }
+
+    pub fn record(&self, request: &RecordedRequest) -> io::Result<()> {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let record = self.redact(request);
+        fs::write(self.record_path(seq), serde_json::to_vec(&record)?)?;
+
+        if seq >= self.capacity {
+            match fs::remove_file(self.record_path(seq - self.capacity)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn record_path(&self, seq: u64) -> PathBuf {
+        self.dir.join(format!("{seq:020}.{RECORD_EXTENSION}"))
+    }
+
+    fn redact(&self, request: &RecordedRequest) -> RecordedRequest {
+        let uri = match request.uri.split_once('?') {
+            Some((path, query)) => {
+                let query = query
+                    .split('&')
+                    .map(|param| match param.split_once('=') {
+                        Some((name, _)) if self.redacted_fields.contains(name) => {
+                            format!("{name}={REDACTED}")
+                        }
+                        _ => param.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("&");
+                format!("{path}?{query}")
+            }
+            None => request.uri.clone(),
+        };
+
+        let mut body = request.body.clone();
+        if let Some(body) = &mut body {
+            self.redact_value(body);
+        }
+
+        RecordedRequest {
+            uri,
+            body,
+            ..request.clone()
+        }
+    }
+
+    fn redact_value(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (name, value) in fields {
+                    if self.redacted_fields.contains(name) {
+                        *value = serde_json::Value::from(REDACTED);
+                    } else {
+                        self.redact_value(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => {
+                values.iter_mut().for_each(|value| self.redact_value(value));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Read all records stored in `dir`, oldest first.
+pub fn read_records(dir: &Path) -> io::Result<Vec<(u64, RecordedRequest)>> {
+    list_records(dir)?
+        .into_iter()
+        .map(|(seq, path)| {
+            let record = serde_json::from_slice(&fs::read(path)?)?;
+            Ok((seq, record))
+        })
+        .collect()
+}
+
+fn list_records(dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut records = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(RECORD_EXTENSION) {
+            continue;
+        }
+        let seq = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u64>().ok());
+        if let Some(seq) = seq {
+            records.push((seq, path));
+        }
+    }
+    records.sort_unstable_by_key(|(seq, _)| *seq);
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str) -> RecordedRequest {
+        RecordedRequest {
+            timestamp: Utc::now(),
+            method: "POST".to_string(),
+            uri: uri.to_string(),
+            body: Some(serde_json::json!({ "limit": 3 })),
+            status: 200,
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_latest_records() {
+        let dir = tempfile::Builder::new()
+            .prefix("recorder")
+            .tempdir()
+            .unwrap();
+        let config = RequestRecorderConfig {
+            path: dir.path().to_string_lossy().into_owned(),
+            capacity: 3,
+            max_body_kb: 1,
+            redacted_fields: vec![],
+        };
+
+        let recorder = RequestRecorder::open(&config).unwrap();
+        for i in 0..5 {
+            recorder.record(&request(&format!("/r/{i}"))).unwrap();
+        }
+
+        let records = read_records(dir.path()).unwrap();
+        let uris: Vec<_> = records.iter().map(|(_, r)| r.uri.as_str()).collect();
+        assert_eq!(uris, ["/r/2", "/r/3", "/r/4"]);
+
+        // Reopening continues the sequence instead of overwriting
+        let recorder = RequestRecorder::open(&config).unwrap();
+        recorder.record(&request("/r/5")).unwrap();
+        let records = read_records(dir.path()).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records.last().unwrap().1.uri, "/r/5");
+    }
+
+    #[test]
+    fn test_sensitive_values_are_redacted() {
+        let dir = tempfile::Builder::new()
+            .prefix("recorder")
+            .tempdir()
+            .unwrap();
+        let config = RequestRecorderConfig {
+            path: dir.path().to_string_lossy().into_owned(),
+            capacity: 3,
+            max_body_kb: 1,
+            redacted_fields: vec!["payload".to_string(), "value".to_string()],
+        };
+
+        let recorder = RequestRecorder::open(&config).unwrap();
+        recorder
+            .record(&RecordedRequest {
+                body: Some(serde_json::json!({
+                    "points": [{ "id": 1, "payload": { "email": "a@b.c" } }],
+                    "filter": { "must": [{ "key": "email", "match": { "value": "a@b.c" } }] },
+                    "limit": 3,
+                })),
+                ..request("/collections/test/points?value=secret&wait=true")
+            })
+            .unwrap();
+
+        let (_, record) = read_records(dir.path()).unwrap().pop().unwrap();
+        assert_eq!(
+            record.uri,
+            "/collections/test/points?value=<redacted>&wait=true"
+        );
+        assert_eq!(
+            record.body.unwrap(),
+            serde_json::json!({
+                "points": [{ "id": 1, "payload": "<redacted>" }],
+                "filter": { "must": [{ "key": "email", "match": { "value": "<redacted>" } }] },
+                "limit": 3,
+            })
+        );
+    }
+}
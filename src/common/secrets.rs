@@ -0,0 +1,87 @@
+//! Client of the AWS Parameters and Secrets Lambda Extension, see `service.api_keys_secret` of
+//! the settings.
+//!
+//! The extension is a layer of the function, serving secrets of Secrets Manager over HTTP on
+//! localhost and caching them. It authenticates requests with the session token of the function,
+//! so API keys don't have to be put into the configuration or the environment of the function.
+
+use reqwest::Client;
+use serde::Deserialize;
+
+/// Environment variable with the port of the extension, if not the default one
+const EXTENSION_PORT_ENV: &str = "PARAMETERS_SECRETS_EXTENSION_HTTP_PORT";
+
+const DEFAULT_EXTENSION_PORT: u16 = 2773;
+
+/// Environment variable with the session token of the function
+const SESSION_TOKEN_ENV: &str = "AWS_SESSION_TOKEN";
+
+/// API keys stored in the secret, a JSON object with the same fields as the settings
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct ApiKeysSecret {
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub read_only_api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GetSecretValueResponse {
+    #[serde(rename = "SecretString")]
+    secret_string: Option<String>,
+}
+
+/// Fetch the API keys stored in the secret `secret_id`, a name or an ARN of the secret
+pub async fn fetch_api_keys(secret_id: &str) -> anyhow::Result<ApiKeysSecret> {
+    let port = match std::env::var(EXTENSION_PORT_ENV) {
+        Ok(port) => port.parse()?,
+        Err(_) => DEFAULT_EXTENSION_PORT,
+    };
+    let session_token = std::env::var(SESSION_TOKEN_ENV)
+        .map_err(|_| anyhow::anyhow!("{SESSION_TOKEN_ENV} is not set, not running on Lambda?"))?;
+
+    let body = Client::new()
+        .get(format!("http://localhost:{port}/secretsmanager/get"))
+        .query(&[("secretId", secret_id)])
+        .header("X-Aws-Parameters-Secrets-Token", session_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    parse_api_keys(&body)
+}
+
+fn parse_api_keys(body: &[u8]) -> anyhow::Result<ApiKeysSecret> {
+    let response: GetSecretValueResponse = serde_json::from_slice(body)?;
+    let secret_string = response
+        .secret_string
+        .ok_or_else(|| anyhow::anyhow!("secret has no string value"))?;
+    Ok(serde_json::from_str(&secret_string)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_api_keys() {
+        let body = br#"{
+            "ARN": "arn:aws:secretsmanager:us-east-1:123456789012:secret:qdrant-api-keys-a1b2c3",
+            "Name": "qdrant-api-keys",
+            "SecretString": "{\"api_key\":\"rw-key\",\"read_only_api_key\":\"ro-key\"}",
+            "VersionId": "a1b2c3d4-5678-90ab-cdef-EXAMPLE11111"
+        }"#;
+        assert_eq!(
+            parse_api_keys(body).unwrap(),
+            ApiKeysSecret {
+                api_key: Some("rw-key".to_string()),
+                read_only_api_key: Some("ro-key".to_string()),
+            },
+        );
+
+        let binary = br#"{"Name": "qdrant-api-keys", "SecretBinary": "AAEC"}"#;
+        assert!(parse_api_keys(binary).is_err());
+    }
+}
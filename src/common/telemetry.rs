@@ -13,7 +13,10 @@ use crate::common::telemetry_ops::collections_telemetry::CollectionsTelemetry;
 use crate::common::telemetry_ops::requests_telemetry::{
     ActixTelemetryCollector, RequestsTelemetry, TonicTelemetryCollector,
 };
+use crate::common::telemetry_ops::resources_telemetry::ResourcesTelemetry;
 use crate::settings::Settings;
+use crate::tracing::sink::{self, LogSinkHealth};
+use crate::tracing::span_timings::{self, SpanTimings};
 
 pub struct TelemetryCollector {
     process_id: Uuid,
@@ -32,6 +35,17 @@ pub struct TelemetryData {
     pub(crate) collections: CollectionsTelemetry,
     pub(crate) cluster: ClusterTelemetry,
     pub(crate) requests: RequestsTelemetry,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub(crate) resources: Option<ResourcesTelemetry>,
+    /// Health of the log files, if logging into files
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub(crate) log_sinks: Vec<LogSinkHealth>,
+    /// Latency histograms of key spans, if any were recorded
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub(crate) span_timings: Vec<SpanTimings>,
 }
 
 impl Anonymize for TelemetryData {
@@ -42,6 +56,22 @@ impl Anonymize for TelemetryData {
             collections: self.collections.anonymize(),
             cluster: self.cluster.anonymize(),
             requests: self.requests.anonymize(),
+            resources: self.resources.anonymize(),
+            log_sinks: self.log_sinks.anonymize(),
+            span_timings: self.span_timings.clone(),
+        }
+    }
+}
+
+impl Anonymize for LogSinkHealth {
+    fn anonymize(&self) -> Self {
+        LogSinkHealth {
+            name: self.name.clone(),
+            log_file: self.log_file.anonymize(),
+            healthy: self.healthy,
+            failing_since: self.failing_since,
+            last_error: None,
+            fallback_writes: self.fallback_writes,
         }
     }
 }
@@ -80,6 +110,13 @@ impl TelemetryCollector {
                 &self.actix_telemetry_collector.lock(),
                 &self.tonic_telemetry_collector.lock(),
             ),
+            resources: if level > 0 {
+                Some(ResourcesTelemetry::collect(&self.settings))
+            } else {
+                None
+            },
+            log_sinks: sink::health(),
+            span_timings: span_timings::timings(),
         }
     }
 }
@@ -5,6 +5,7 @@ use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use serde::{Deserialize, Serialize};
 
+use crate::common::clock;
 use crate::settings::Settings;
 
 pub struct AppBuildTelemetryCollector {
@@ -14,7 +15,7 @@ pub struct AppBuildTelemetryCollector {
 impl AppBuildTelemetryCollector {
     pub fn new() -> Self {
         AppBuildTelemetryCollector {
-            startup: Utc::now().round_subsecs(2),
+            startup: clock::now().round_subsecs(2),
         }
     }
 }
@@ -2,3 +2,6 @@ pub mod app_telemetry;
 pub mod cluster_telemetry;
 pub mod collections_telemetry;
 pub mod requests_telemetry;
+pub mod resources_telemetry;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod startup_telemetry;
@@ -0,0 +1,242 @@
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use segment::common::anonymize::Anonymize;
+use serde::{Deserialize, Serialize};
+
+use crate::common::memory_arenas::{self, ArenaUsage};
+use crate::common::memory_pressure::{self, MemoryPressureTelemetry};
+use crate::settings::Settings;
+
+/// Memory size assigned to the Lambda function, in MegaBytes
+const LAMBDA_MEMORY_SIZE_ENV: &str = "AWS_LAMBDA_FUNCTION_MEMORY_SIZE";
+
+/// Usage of a limited resource, together with its limit
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct ResourceUsage {
+    pub used: usize,
+    pub limit: usize,
+}
+
+impl ResourceUsage {
+    pub fn ratio(&self) -> Option<f64> {
+        (self.limit > 0).then(|| self.used as f64 / self.limit as f64)
+    }
+}
+
+/// Usage of the resources which cause failures when exhausted.
+///
+/// Every value is reported together with its limit, so alarms can be set on the ratio directly.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct ResourcesTelemetry {
+    /// Size of the storage directory, against the configured quota or the filesystem capacity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub storage: Option<ResourceUsage>,
+    /// Used space of the filesystem with temporary files, e.g. `/tmp` in Lambda
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub tmp: Option<ResourceUsage>,
+    /// Resident memory of the process, against the memory limit of the function or container
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub memory: Option<ResourceUsage>,
+    /// Open file descriptors, against the soft limit of the process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub open_files: Option<ResourceUsage>,
+    /// Memory of the separate jemalloc arenas, if enabled
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub memory_arenas: Vec<ArenaUsage>,
+    /// State and latest decisions of the memory pressure monitor, if enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub memory_pressure: Option<MemoryPressureTelemetry>,
+}
+
+impl ResourcesTelemetry {
+    pub fn collect(settings: &Settings) -> Self {
+        ResourcesTelemetry {
+            storage: get_storage_usage(settings),
+            tmp: get_tmp_usage(),
+            memory: get_memory_usage(),
+            open_files: get_open_files_usage(),
+            memory_arenas: memory_arenas::usage(),
+            memory_pressure: memory_pressure::telemetry(),
+        }
+    }
+}
+
+fn get_storage_usage(settings: &Settings) -> Option<ResourceUsage> {
+    let storage_path = Path::new(&settings.storage.storage_path);
+    let used = dir_size(storage_path).ok()?;
+    let limit = match settings.service.storage_quota_mb {
+        Some(quota_mb) => quota_mb * 1024 * 1024,
+        None => filesystem_space(storage_path)?.0,
+    };
+    Some(ResourceUsage { used, limit })
+}
+
+fn get_tmp_usage() -> Option<ResourceUsage> {
+    let (total, free) = filesystem_space(&std::env::temp_dir())?;
+    Some(ResourceUsage {
+        used: total.saturating_sub(free),
+        limit: total,
+    })
+}
+
+fn get_memory_usage() -> Option<ResourceUsage> {
+    Some(ResourceUsage {
+        used: resident_memory()?,
+        limit: memory_limit()?,
+    })
+}
+
+fn get_open_files_usage() -> Option<ResourceUsage> {
+    Some(ResourceUsage {
+        used: open_files()?,
+        limit: open_files_limit()?,
+    })
+}
+
+/// Total size of all files in the directory, recursively
+fn dir_size(path: &Path) -> std::io::Result<usize> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else if file_type.is_file() {
+            size += entry.metadata()?.len() as usize;
+        }
+    }
+    Ok(size)
+}
+
+/// Total and free space of the filesystem containing `path`
+#[cfg(unix)]
+fn filesystem_space(path: &Path) -> Option<(usize, usize)> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt as _;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    let fragment_size = stat.f_frsize as usize;
+    Some((
+        stat.f_blocks as usize * fragment_size,
+        stat.f_bavail as usize * fragment_size,
+    ))
+}
+
+#[cfg(not(unix))]
+fn filesystem_space(_path: &Path) -> Option<(usize, usize)> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn resident_memory() -> Option<usize> {
+    // Second field is the number of resident pages
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: usize = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    (page_size > 0).then(|| pages * page_size as usize)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn resident_memory() -> Option<usize> {
+    None
+}
+
+/// Memory available to the process: Lambda function memory size, cgroup limit or total RAM
+pub(crate) fn memory_limit() -> Option<usize> {
+    if let Some(memory_mb) = std::env::var(LAMBDA_MEMORY_SIZE_ENV)
+        .ok()
+        .and_then(|memory_mb| memory_mb.parse::<usize>().ok())
+    {
+        return Some(memory_mb * 1024 * 1024);
+    }
+
+    let total = sys_info::mem_info()
+        .ok()
+        .map(|mem_info| mem_info.total as usize * 1024);
+
+    let cgroup_limit = [
+        "/sys/fs/cgroup/memory.max",
+        "/sys/fs/cgroup/memory/memory.limit_in_bytes",
+    ]
+    .into_iter()
+    .find_map(|path| fs::read_to_string(path).ok()?.trim().parse::<usize>().ok());
+
+    match (cgroup_limit, total) {
+        // cgroup v1 reports a huge number if there is no limit
+        (Some(cgroup_limit), Some(total)) => Some(cgroup_limit.min(total)),
+        (cgroup_limit, total) => cgroup_limit.or(total),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn open_files() -> Option<usize> {
+    Some(fs::read_dir("/proc/self/fd").ok()?.count())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_files() -> Option<usize> {
+    None
+}
+
+#[cfg(unix)]
+fn open_files_limit() -> Option<usize> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return None;
+    }
+    (limit.rlim_cur != libc::RLIM_INFINITY).then_some(limit.rlim_cur as usize)
+}
+
+#[cfg(not(unix))]
+fn open_files_limit() -> Option<usize> {
+    None
+}
+
+impl Anonymize for ResourceUsage {
+    fn anonymize(&self) -> Self {
+        ResourceUsage {
+            used: self.used.anonymize(),
+            limit: self.limit.anonymize(),
+        }
+    }
+}
+
+impl Anonymize for ResourcesTelemetry {
+    fn anonymize(&self) -> Self {
+        ResourcesTelemetry {
+            storage: self.storage.anonymize(),
+            tmp: self.tmp.anonymize(),
+            memory: self.memory.anonymize(),
+            open_files: self.open_files.anonymize(),
+            memory_arenas: self.memory_arenas.anonymize(),
+            memory_pressure: self.memory_pressure.anonymize(),
+        }
+    }
+}
+
+impl Anonymize for ArenaUsage {
+    fn anonymize(&self) -> Self {
+        ArenaUsage {
+            name: self.name.clone(),
+            allocated: self.allocated.anonymize(),
+            resident: self.resident.anonymize(),
+            soft_limit: self.soft_limit.anonymize(),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Durations of the startup phases, to find what a cold start is spent on.
+//!
+//! Phases are recorded by the binary once, in order of execution. Every phase also runs inside of
+//! a `startup` span, so its logs can be told apart and its duration shows up in traces.
+
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use segment::common::anonymize::Anonymize;
+use serde::{Deserialize, Serialize};
+use tracing::Instrument as _;
+
+static PROCESS_START: OnceLock<Instant> = OnceLock::new();
+static PHASES: Mutex<Vec<StartupPhaseTelemetry>> = Mutex::new(Vec::new());
+static READY: OnceLock<Duration> = OnceLock::new();
+static FIRST_REQUEST: OnceLock<Duration> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct StartupPhaseTelemetry {
+    pub name: String,
+    /// Time since the process start when the phase has started
+    pub start_ms: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct CollectionStartupTelemetry {
+    pub id: String,
+    /// Time it took to load the collection, part of the `load_collections` phase
+    pub load_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct StartupTelemetry {
+    /// Startup phases in order of execution
+    pub phases: Vec<StartupPhaseTelemetry>,
+    pub collections: Vec<CollectionStartupTelemetry>,
+    /// Time since the process start until the service has been ready to accept requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub ready_ms: Option<u64>,
+    /// Time since the process start until the first request has been served
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub first_request_ms: Option<u64>,
+}
+
+impl StartupTelemetry {
+    pub fn collect(collections: Vec<CollectionStartupTelemetry>) -> Self {
+        StartupTelemetry {
+            phases: PHASES.lock().clone(),
+            collections,
+            ready_ms: READY.get().map(as_millis),
+            first_request_ms: FIRST_REQUEST.get().map(as_millis),
+        }
+    }
+}
+
+/// Start measuring startup, must be called first thing in `main`
+pub fn init() {
+    PROCESS_START.get_or_init(Instant::now);
+}
+
+/// Run `f` as a startup phase called `name`
+pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = tracing::info_span!("startup", phase = name).in_scope(f);
+    record_phase(name, start);
+    result
+}
+
+/// Run `future` as a startup phase called `name`
+pub async fn phase_async<T>(name: &'static str, future: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let result = future
+        .instrument(tracing::info_span!("startup", phase = name))
+        .await;
+    record_phase(name, start);
+    result
+}
+
+/// Mark the service as ready to accept requests
+pub fn ready() {
+    READY.get_or_init(since_process_start);
+}
+
+/// Mark the first request as served, no-op for later requests
+pub fn request_served() {
+    if FIRST_REQUEST.get().is_none() {
+        FIRST_REQUEST.get_or_init(since_process_start);
+    }
+}
+
+fn record_phase(name: &'static str, start: Instant) {
+    let process_start = *PROCESS_START.get_or_init(|| start);
+    PHASES.lock().push(StartupPhaseTelemetry {
+        name: name.to_string(),
+        start_ms: as_millis(&start.saturating_duration_since(process_start)),
+        duration_ms: as_millis(&start.elapsed()),
+    });
+}
+
+fn since_process_start() -> Duration {
+    PROCESS_START.get_or_init(Instant::now).elapsed()
+}
+
+fn as_millis(duration: &Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+impl Anonymize for StartupTelemetry {
+    fn anonymize(&self) -> Self {
+        StartupTelemetry {
+            phases: self.phases.clone(),
+            collections: self.collections.anonymize(),
+            ready_ms: self.ready_ms,
+            first_request_ms: self.first_request_ms,
+        }
+    }
+}
+
+impl Anonymize for CollectionStartupTelemetry {
+    fn anonymize(&self) -> Self {
+        CollectionStartupTelemetry {
+            id: self.id.anonymize(),
+            load_ms: self.load_ms,
+        }
+    }
+}
@@ -1,13 +1,37 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use segment::common::anonymize::Anonymize;
 use tokio::sync::Mutex;
 
+use crate::common::lambda_extension::{ExtensionEvent, LambdaExtension};
 use crate::common::telemetry::TelemetryCollector;
 
 const DETAIL_LEVEL: usize = 5;
 const REPORTING_INTERVAL: Duration = Duration::from_secs(60 * 60); // One hour
+/// Reports within an invocation hold it open, don't let a slow endpoint hold it for long
+const LAMBDA_REPORTING_TIMEOUT: Duration = Duration::from_secs(5);
+const LAMBDA_EXTENSION_NAME: &str = "qdrant-telemetry";
+
+/// Reporter was started, reporting can be paused and resumed at runtime only if it was
+static REPORTER_STARTED: AtomicBool = AtomicBool::new(false);
+
+static REPORTING_PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_reporter_started() -> bool {
+    REPORTER_STARTED.load(Ordering::Relaxed)
+}
+
+/// Whether telemetry is being reported, `false` if the reporter was never started
+pub fn is_reporting_enabled() -> bool {
+    is_reporter_started() && !REPORTING_PAUSED.load(Ordering::Relaxed)
+}
+
+/// Pause or resume reporting of the running reporter
+pub fn set_reporting_enabled(enabled: bool) {
+    REPORTING_PAUSED.store(!enabled, Ordering::Relaxed);
+}
 
 pub struct TelemetryReporter {
     telemetry_url: String,
@@ -16,6 +40,7 @@ pub struct TelemetryReporter {
 
 impl TelemetryReporter {
     fn new(telemetry: Arc<Mutex<TelemetryCollector>>) -> Self {
+        REPORTER_STARTED.store(true, Ordering::Relaxed);
         let telemetry_url = if cfg!(debug_assertions) {
             "https://staging-telemetry.qdrant.io".to_string()
         } else {
@@ -29,6 +54,10 @@ impl TelemetryReporter {
     }
 
     async fn report(&self) {
+        if REPORTING_PAUSED.load(Ordering::Relaxed) {
+            return;
+        }
+
         let data = self
             .telemetry
             .lock()
@@ -53,4 +82,40 @@ impl TelemetryReporter {
             tokio::time::sleep(REPORTING_INTERVAL).await;
         }
     }
+
+    /// Report telemetry from within invocations when running on Lambda, where the process is
+    /// frozen between invocations and a report in the background would never be sent.
+    ///
+    /// The reporter registers as a Lambda extension, so the invocation isn't finished until the
+    /// report is sent. Reports at most once per reporting interval, on the first invocation after
+    /// it elapses. Falls back to [`Self::run`] when not running on Lambda.
+    pub async fn run_lambda(telemetry: Arc<Mutex<TelemetryCollector>>) {
+        let extension = match LambdaExtension::register(LAMBDA_EXTENSION_NAME).await {
+            Some(Ok(extension)) => extension,
+            Some(Err(err)) => {
+                log::warn!("Failed to register telemetry reporter as Lambda extension: {err}");
+                return Self::run(telemetry).await;
+            }
+            None => return Self::run(telemetry).await,
+        };
+
+        let reporter = Self::new(telemetry);
+        let mut last_report: Option<Instant> = None;
+        loop {
+            // Invocations wait for the extension, keep asking for events even if it fails
+            match extension.next_event().await {
+                Ok(ExtensionEvent::Invoke { .. }) => {}
+                Err(err) => {
+                    log::warn!("Failed to receive event of Lambda extension: {err}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            }
+
+            if last_report.map_or(true, |reported| reported.elapsed() >= REPORTING_INTERVAL) {
+                let _ = tokio::time::timeout(LAMBDA_REPORTING_TIMEOUT, reporter.report()).await;
+                last_report = Some(Instant::now());
+            }
+        }
+    }
 }
@@ -0,0 +1,109 @@
+//! Representative searches run on startup, before the first request.
+//!
+//! Searches read HNSW graphs, vectors and payload indexes from disk on first use. Running
+//! typical searches during init loads the parts real requests need, while the CPU is not
+//! throttled yet. Queries are read from a JSON array or a file with a JSON object per line:
+//!
+//! ```json
+//! {"collection": "products", "request": {"vector": [0.2, 0.1, 0.9], "limit": 10}}
+//! ```
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+
+use collection::operations::shard_selector_internal::ShardSelectorInternal;
+use collection::operations::types::SearchRequest;
+use serde::Deserialize;
+use storage::content_manager::toc::TableOfContent;
+
+use crate::common::points::do_core_search_points;
+
+#[derive(Debug, Deserialize)]
+pub struct WarmupQuery {
+    pub collection: String,
+    pub request: SearchRequest,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WarmupReport {
+    pub queries: usize,
+    pub failed: usize,
+    pub elapsed: Duration,
+}
+
+/// Read warmup queries from `path`, either a JSON array or newline delimited JSON
+pub fn read_warmup_queries(path: &Path) -> io::Result<Vec<WarmupQuery>> {
+    let content = fs::read_to_string(path)?;
+    if content.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(&content)?);
+    }
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(io::Error::from))
+        .collect()
+}
+
+/// Run all queries one by one, failed queries are logged and skipped
+pub async fn run_warmup_queries(toc: &TableOfContent, queries: Vec<WarmupQuery>) -> WarmupReport {
+    let started = Instant::now();
+    let mut report = WarmupReport::default();
+
+    for WarmupQuery {
+        collection,
+        request,
+    } in queries
+    {
+        let SearchRequest {
+            search_request,
+            shard_key,
+        } = request;
+        let shard_selection = match shard_key {
+            None => ShardSelectorInternal::All,
+            Some(shard_keys) => shard_keys.into(),
+        };
+
+        let result = do_core_search_points(
+            toc,
+            &collection,
+            search_request.into(),
+            None,
+            shard_selection,
+            None,
+        )
+        .await;
+
+        report.queries += 1;
+        if let Err(err) = result {
+            log::warn!("Warmup query of collection {collection} failed: {err}");
+            report.failed += 1;
+        }
+    }
+
+    report.elapsed = started.elapsed();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_warmup_queries() {
+        let dir = tempfile::Builder::new().tempdir().unwrap();
+        let query = r#"{"collection": "test", "request": {"vector": [0.1, 0.2], "limit": 3}}"#;
+
+        let ndjson = dir.path().join("queries.ndjson");
+        fs::write(&ndjson, format!("{query}\n\n{query}\n")).unwrap();
+        let queries = read_warmup_queries(&ndjson).unwrap();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].collection, "test");
+        assert_eq!(queries[0].request.search_request.limit, 3);
+
+        let array = dir.path().join("queries.json");
+        fs::write(&array, format!("[{query}]")).unwrap();
+        assert_eq!(read_warmup_queries(&array).unwrap().len(), 1);
+    }
+}
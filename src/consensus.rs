@@ -1239,6 +1239,7 @@ mod tests {
                             init_from: None,
                             quantization_config: None,
                             sharding_method: None,
+                            search_defaults: None,
                         },
                     )),
                     None,
@@ -10,6 +10,7 @@ use ::tonic::transport::Uri;
 use api::grpc::transport_channel_pool::TransportChannelPool;
 use clap::Parser;
 use collection::shards::channel_service::ChannelService;
+use qdrant::common::crash_report::{self, CrashReporter};
 use qdrant::common::helpers::{
     create_general_purpose_runtime, create_search_runtime, create_update_runtime,
     load_tls_client_config,
@@ -119,12 +120,31 @@ fn main() -> anyhow::Result<()> {
 
     let reporting_id = TelemetryCollector::generate_id();
 
-    qdrant::tracing::setup(&settings.log_level)?;
-
-    setup_panic_hook(reporting_enabled, reporting_id.to_string());
+    let logger_handle = Arc::new(qdrant::tracing::setup(
+        settings
+            .logger
+            .clone()
+            .with_top_level_directive(settings.log_level.clone()),
+    )?);
+    qdrant::tracing::redact::set_secrets(settings.service.api_keys().cloned());
+
+    let crash_reporter = settings
+        .service
+        .crash_report
+        .as_ref()
+        .map(|config| CrashReporter::new(config, logger_handle.tail_buffer()));
+    setup_panic_hook(reporting_enabled, reporting_id.to_string(), crash_reporter);
 
     memory::madvise::set_global(settings.storage.mmap_advice);
+    memory::mlock::set_global(settings.storage.mlock);
     segment::vector_storage::common::set_async_scorer(settings.storage.async_scorer);
+    segment::vector_storage::common::set_async_scorer_direct_io(
+        settings.storage.async_scorer_direct_io,
+    );
+    segment::common::yielding::set_yield_to_requests(
+        settings.storage.performance.optimizers_yield_to_requests,
+    );
+    memory::direct_io::set_global(settings.storage.performance.optimizers_direct_io);
 
     welcome(&settings);
 
@@ -236,6 +256,7 @@ fn main() -> anyhow::Result<()> {
     });
 
     let toc_arc = Arc::new(toc);
+    crash_report::set_toc(&toc_arc);
     let storage_path = toc_arc.storage_path();
 
     // Holder for all actively running threads of the service: web, gPRC, consensus, etc.
@@ -374,6 +395,7 @@ fn main() -> anyhow::Result<()> {
     #[cfg(feature = "web")]
     {
         let dispatcher_arc = dispatcher_arc.clone();
+        let logger_handle = logger_handle.clone();
         let settings = settings.clone();
         let handle = thread::Builder::new()
             .name("web".to_string())
@@ -384,6 +406,7 @@ fn main() -> anyhow::Result<()> {
                         dispatcher_arc.clone(),
                         telemetry_collector,
                         health_checker,
+                        logger_handle,
                         settings,
                     ),
                 )
@@ -406,6 +429,7 @@ fn main() -> anyhow::Result<()> {
                     qdrant::tonic::init(
                         dispatcher_arc,
                         tonic_telemetry_collector,
+                        logger_handle,
                         settings,
                         grpc_port,
                         runtime_handle,
@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use qdrant::common::request_recorder::read_records;
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Method;
+
+/// Replay requests captured by the request recorder against a Qdrant instance.
+///
+/// Typical use is to copy the storage of the affected deployment locally, start Qdrant on it
+/// and replay the recorded requests to reproduce the issue:
+/// `cargo run --bin replay -- --records ./recorded_requests --url http://localhost:6333`
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Directory with recorded requests, `service.request_recorder.path` of the recording instance.
+    #[arg(long, value_name = "PATH")]
+    records: PathBuf,
+
+    /// Base URL of the Qdrant REST API to replay requests against.
+    #[arg(long, value_name = "URL", default_value = "http://localhost:6333")]
+    url: String,
+
+    /// API key to send with every replayed request.
+    #[arg(long)]
+    api_key: Option<String>,
+
+    /// Stop at the first request whose response status differs from the recorded one.
+    #[arg(long, action, default_value_t = false)]
+    fail_fast: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let client = Client::new();
+    let base_url = args.url.trim_end_matches('/');
+
+    let records = read_records(&args.records)?;
+    println!("Replaying {} requests", records.len());
+
+    let mut mismatches = 0;
+    for (seq, record) in records {
+        let method = Method::from_bytes(record.method.as_bytes())?;
+        let mut request = client.request(method, format!("{base_url}{}", record.uri));
+        if let Some(body) = &record.body {
+            request = request
+                .header(CONTENT_TYPE, "application/json")
+                .body(serde_json::to_vec(body)?);
+        }
+        if let Some(api_key) = &args.api_key {
+            request = request.header("api-key", api_key);
+        }
+
+        let response = request.send()?;
+        let status = response.status().as_u16();
+        let marker = if status == record.status {
+            "ok"
+        } else {
+            mismatches += 1;
+            "MISMATCH"
+        };
+        println!(
+            "{seq:>8} {} {} recorded={} replayed={status} {marker}",
+            record.method, record.uri, record.status,
+        );
+        if status != record.status {
+            println!("         {}", response.text()?);
+            if args.fail_fast {
+                break;
+            }
+        }
+    }
+
+    if mismatches > 0 {
+        anyhow::bail!("{mismatches} replayed requests returned a different status");
+    }
+    Ok(())
+}
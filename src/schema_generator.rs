@@ -8,10 +8,10 @@ use collection::operations::snapshot_ops::{
 };
 use collection::operations::types::{
     AliasDescription, CollectionClusterInfo, CollectionInfo, CollectionsAliasesResponse,
-    CountRequest, CountResult, DiscoverRequest, DiscoverRequestBatch, GroupsResult, PointGroup,
-    PointRequest, RecommendGroupsRequest, RecommendRequest, RecommendRequestBatch, Record,
-    ScrollRequest, ScrollResult, SearchGroupsRequest, SearchRequest, SearchRequestBatch,
-    UpdateResult,
+    CountRequest, CountResult, DiscoverRequest, DiscoverRequestBatch, GroupsCountRequest,
+    GroupsCountResult, GroupsResult, PointGroup, PointRequest, RecommendGroupsRequest,
+    RecommendRequest, RecommendRequestBatch, Record, ScrollRequest, ScrollResult,
+    SearchGroupsRequest, SearchRequest, SearchRequestBatch, UpdateResult,
 };
 use collection::operations::vector_ops::{DeleteVectors, UpdateVectors};
 use schemars::gen::SchemaSettings;
@@ -26,10 +26,13 @@ use storage::types::ClusterStatus;
 use crate::common::helpers::LocksOption;
 use crate::common::points::{CreateFieldIndex, UpdateOperations};
 use crate::common::telemetry::TelemetryData;
+use crate::common::telemetry_ops::startup_telemetry::StartupTelemetry;
 
 mod actix;
 mod common;
 mod settings;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+mod tracing;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
 struct AllDefinitions {
@@ -78,6 +81,9 @@ struct AllDefinitions {
     b9: ShardSnapshotRecover,
     ba: DiscoverRequest,
     bb: DiscoverRequestBatch,
+    bc: StartupTelemetry,
+    bd: GroupsCountRequest,
+    be: GroupsCountResult,
 }
 
 fn save_schema<T: JsonSchema>() {
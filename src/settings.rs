@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::{env, io};
 
 use api::grpc::transport_channel_pool::{
@@ -7,9 +8,12 @@ use collection::operations::validation;
 use config::{Config, ConfigError, Environment, File, FileFormat, Source};
 use segment::common::cpu::get_num_cpus;
 use serde::Deserialize;
+use storage::rbac::Access;
 use storage::types::StorageConfig;
 use validator::Validate;
 
+use crate::tracing::LoggerConfig;
+
 const DEFAULT_CONFIG: &str = include_str!("../config/config.yaml");
 
 #[derive(Debug, Deserialize, Validate, Clone)]
@@ -20,6 +24,16 @@ pub struct ServiceConfig {
     pub grpc_port: Option<u16>, // None means that gRPC is disabled
     pub max_request_size_mb: usize,
     pub max_workers: Option<usize>,
+    /// Keep idle HTTP connections open for this many seconds, `0` disables keep-alive.
+    /// Should be longer than the idle timeout of a load balancer in front of the service.
+    #[serde(default)]
+    pub http_keep_alive_sec: Option<u64>,
+    /// Maximum number of concurrent HTTP connections per worker.
+    #[serde(default)]
+    pub http_max_connections: Option<usize>,
+    /// Accept HTTP/2 without TLS (h2c) next to HTTP/1.x. Has no effect if TLS is enabled.
+    #[serde(default)]
+    pub enable_h2c: bool,
     #[serde(default = "default_cors")]
     pub enable_cors: bool,
     #[serde(default)]
@@ -28,6 +42,22 @@ pub struct ServiceConfig {
     pub verify_https_client_certificate: bool,
     pub api_key: Option<String>,
     pub read_only_api_key: Option<String>,
+    /// Secret of Secrets Manager with `api_key` and `read_only_api_key`, read through the
+    /// Parameters and Secrets Lambda Extension on startup. Keys of the secret replace the ones
+    /// above. Only used by the Lambda binary.
+    #[serde(default)]
+    pub api_keys_secret: Option<String>,
+    /// Accept JWTs signed with `api_key` in place of the keys, restricted to the access granted
+    /// by their claims.
+    #[serde(default)]
+    pub jwt_rbac: bool,
+    /// Authenticate requests with AWS IAM credentials, in addition to the keys. Disabled if not
+    /// set.
+    #[serde(default)]
+    pub iam_auth: Option<IamAuthConfig>,
+    /// Reject all operations which may change the storage, e.g. for readers sharing the storage.
+    #[serde(default)]
+    pub read_only: bool,
 
     /// Directory where static files are served from.
     /// For example, the Web-UI should be placed here.
@@ -38,6 +68,163 @@ pub struct ServiceConfig {
     /// This includes the Web-UI. True by default.
     #[serde(default)]
     pub enable_static_content: Option<bool>,
+
+    /// Record incoming REST requests to disk, to replay them later with the `replay` tool.
+    /// Disabled if not set.
+    #[serde(default)]
+    #[validate]
+    pub request_recorder: Option<RequestRecorderConfig>,
+
+    /// Storage quota to report storage usage against, e.g. for EFS which has no fixed capacity.
+    /// Capacity of the filesystem the storage is on, if not set.
+    #[serde(default)]
+    pub storage_quota_mb: Option<usize>,
+
+    /// Allocate memory of the search, update and general runtimes in separate jemalloc arenas.
+    /// Disabled if not set.
+    #[serde(default)]
+    pub memory_arenas: Option<MemoryArenasConfig>,
+
+    /// URL to estimate offset of the host clock from, by the `Date` header of its responses.
+    /// Timestamps are taken from the host clock, if not set.
+    #[serde(default)]
+    pub clock_reference_url: Option<String>,
+
+    /// Cancel requests shortly before the deadline of the invocation serving them.
+    /// Disabled if not set.
+    #[serde(default)]
+    pub request_deadline: Option<RequestDeadlineConfig>,
+
+    /// Apply updates of the same collection one at a time, queueing the rest.
+    /// Disabled if not set.
+    #[serde(default)]
+    pub collection_write_queue: Option<CollectionWriteQueueConfig>,
+
+    /// Serve tenants from separate namespaces of collections. Disabled if not set.
+    #[serde(default)]
+    pub tenancy: Option<TenancyConfig>,
+
+    /// Serialize scroll and search responses incrementally, sending the body in chunks.
+    #[serde(default)]
+    pub response_streaming: bool,
+
+    /// File with searches to run on startup, before the first request. Either a JSON array or
+    /// a JSON object per line, each with a `collection` and a search `request`.
+    #[serde(default)]
+    pub warmup_queries_path: Option<String>,
+
+    /// Write a crash report when the service panics. Disabled if not set.
+    #[serde(default)]
+    #[validate]
+    pub crash_report: Option<CrashReportConfig>,
+}
+
+impl ServiceConfig {
+    /// API keys of the service and of the tenants, which must never be logged
+    pub fn api_keys(&self) -> impl Iterator<Item = &String> {
+        let tenant_keys = self
+            .tenancy
+            .iter()
+            .flat_map(|tenancy| tenancy.api_keys.values());
+        self.api_key
+            .iter()
+            .chain(&self.read_only_api_key)
+            .chain(tenant_keys)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct MemoryArenasConfig {
+    /// Soft limit of memory held by the search runtime, in MegaBytes.
+    #[serde(default)]
+    pub search_soft_limit_mb: Option<usize>,
+    /// Soft limit of memory held by the update runtime, in MegaBytes.
+    #[serde(default)]
+    pub update_soft_limit_mb: Option<usize>,
+    /// Soft limit of memory held by the rest of the service, in MegaBytes.
+    #[serde(default)]
+    pub general_soft_limit_mb: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RequestDeadlineConfig {
+    /// Header with the remaining time of the request in milliseconds, e.g. set by API Gateway.
+    /// If not set or missing, the deadline of the Lambda invocation is read from the
+    /// `x-amzn-lambda-context` header of Lambda Web Adapter.
+    #[serde(default)]
+    pub remaining_time_header: Option<String>,
+    /// Time left for responding after the request is cancelled, in milliseconds.
+    #[serde(default = "default_deadline_margin_ms")]
+    pub margin_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TenancyConfig {
+    /// Header naming the tenant of the request, e.g. set by a gateway in front of the service.
+    /// Requests are still authenticated with the service API keys.
+    #[serde(default)]
+    pub header: Option<String>,
+    /// API keys of tenants by tenant id. A tenant key grants full access to the collections of
+    /// the tenant, and nothing else.
+    #[serde(default)]
+    pub api_keys: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct IamAuthConfig {
+    /// Value of the `x-qdrant-server-id` header, which callers must sign along with their
+    /// requests, so tokens for other services are rejected. Not required if not set.
+    #[serde(default)]
+    pub server_id: Option<String>,
+    /// IAM principals allowed to call the service, the first one matching the caller applies.
+    #[serde(default)]
+    pub principals: Vec<IamPrincipalConfig>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct IamPrincipalConfig {
+    /// ARN of the caller returned by STS `GetCallerIdentity`, `*` matches any characters.
+    /// For example `arn:aws:sts::123456789012:assumed-role/indexer/*`.
+    pub arn: String,
+    /// Access granted to the principal, same as the `access` claim of JWTs.
+    pub access: Access,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CollectionWriteQueueConfig {
+    /// Maximum number of updates waiting for their turn per collection, more are rejected.
+    #[serde(default = "default_write_queue_max_queued")]
+    pub max_queued: usize,
+    /// Time an update may wait for its turn, in milliseconds.
+    #[serde(default = "default_write_queue_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+pub struct RequestRecorderConfig {
+    /// Directory to store recorded requests in.
+    #[validate(length(min = 1))]
+    pub path: String,
+    /// Maximum number of recorded requests to keep. Oldest requests are removed first.
+    #[serde(default = "default_recorder_capacity")]
+    #[validate(range(min = 1))]
+    pub capacity: usize,
+    /// Requests with a larger body are recorded without it.
+    #[serde(default = "default_recorder_max_body_kb")]
+    pub max_body_kb: usize,
+    /// Values of these JSON body fields, at any depth, and query parameters are not recorded.
+    #[serde(default = "default_recorder_redacted_fields")]
+    pub redacted_fields: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+pub struct CrashReportConfig {
+    /// Directory to write crash reports into, one JSON file per crash.
+    #[validate(length(min = 1))]
+    pub path: String,
+    /// Number of the latest log records to include, taken from the log tail.
+    #[serde(default = "default_crash_report_log_lines")]
+    pub log_lines: usize,
 }
 
 #[derive(Debug, Deserialize, Clone, Default, Validate)]
@@ -116,8 +303,10 @@ pub struct TlsConfig {
 
 #[derive(Debug, Deserialize, Clone, Validate)]
 pub struct Settings {
-    #[serde(default = "default_log_level")]
-    pub log_level: String,
+    #[serde(default)]
+    pub log_level: Option<String>,
+    #[serde(default)]
+    pub logger: LoggerConfig,
     #[validate]
     pub storage: StorageConfig,
     #[validate]
@@ -184,10 +373,6 @@ const fn default_cors() -> bool {
     true
 }
 
-fn default_log_level() -> String {
-    "INFO".to_string()
-}
-
 const fn default_timeout_ms() -> u64 {
     DEFAULT_GRPC_TIMEOUT.as_millis() as u64
 }
@@ -217,6 +402,40 @@ const fn default_message_timeout_tics() -> u64 {
     10
 }
 
+const fn default_deadline_margin_ms() -> u64 {
+    500
+}
+
+const fn default_write_queue_max_queued() -> usize {
+    64
+}
+
+const fn default_write_queue_timeout_ms() -> u64 {
+    10_000
+}
+
+const fn default_recorder_capacity() -> usize {
+    1000
+}
+
+const fn default_recorder_max_body_kb() -> usize {
+    1024
+}
+
+fn default_recorder_redacted_fields() -> Vec<String> {
+    // Payloads and the values filters match them against, as well as credentials
+    [
+        "payload", "value", "text", "any", "except", "api_key", "password", "secret", "token",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+const fn default_crash_report_log_lines() -> usize {
+    100
+}
+
 const fn default_tls_cert_ttl() -> Option<u64> {
     // Default one hour
     Some(3600)
@@ -273,6 +492,26 @@ impl Settings {
         // Build and merge config and deserialize into Settings, attach any load errors we had
         let mut settings: Settings = config.build()?.try_deserialize()?;
         settings.load_errors.extend(load_errors);
+
+        // Updates of a collection are applied one at a time with the write queue, there would be
+        // nothing to batch, only the window to wait for
+        if settings.service.collection_write_queue.is_some()
+            && settings
+                .storage
+                .performance
+                .update_batch_window_ms
+                .take()
+                .is_some()
+        {
+            settings.load_errors.push(LogMsg::Warn(
+                "Update batching is disabled, as updates are queued by collection_write_queue"
+                    .to_string(),
+            ));
+        }
+
+        // Keep the storage of each tenant in a directory of its own
+        settings.storage.tenant_directories |= settings.service.tenancy.is_some();
+
         Ok(settings)
     }
 }
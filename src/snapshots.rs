@@ -6,6 +6,7 @@ use collection::shards::shard::PeerId;
 use log::info;
 use storage::content_manager::alias_mapping::AliasPersistence;
 use storage::content_manager::snapshots::SnapshotConfig;
+use storage::content_manager::toc::runtime_state::RuntimeState;
 use storage::content_manager::toc::{ALIASES_PATH, COLLECTIONS_DIR};
 
 /// Recover snapshots from the given arguments
@@ -26,6 +27,9 @@ pub fn recover_snapshots(
     this_peer_id: PeerId,
     is_distributed: bool,
 ) -> Vec<String> {
+    // Recovered collections may be missing from the state saved on shutdown
+    RuntimeState::discard(Path::new(storage_dir));
+
     let collection_dir_path = Path::new(storage_dir).join(COLLECTIONS_DIR);
     let mut recovered_collections: Vec<String> = vec![];
 
@@ -88,6 +92,9 @@ pub fn recover_full_snapshot(
     this_peer_id: PeerId,
     is_distributed: bool,
 ) -> Vec<String> {
+    // Recovered collections may be missing from the state saved on shutdown
+    RuntimeState::discard(Path::new(storage_dir));
+
     let snapshot_temp_path = temp_dir
         .map(PathBuf::from)
         .unwrap_or_else(|| Path::new(storage_dir).join("snapshots_recovery_tmp"));
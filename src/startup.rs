@@ -2,11 +2,13 @@
 
 use std::backtrace::Backtrace;
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::common::crash_report::CrashReporter;
 use crate::common::error_reporting::ErrorReporter;
 
 const DEFAULT_INITIALIZED_FILE: &str = ".qdrant-initialized";
+const CLEAN_SHUTDOWN_FILE: &str = ".qdrant-clean-shutdown";
 
 fn get_init_file_path() -> PathBuf {
     std::env::var("QDRANT_INIT_FILE_PATH")
@@ -14,7 +16,11 @@ fn get_init_file_path() -> PathBuf {
         .unwrap_or_else(|_| DEFAULT_INITIALIZED_FILE.into())
 }
 
-pub fn setup_panic_hook(reporting_enabled: bool, reporting_id: String) {
+pub fn setup_panic_hook(
+    reporting_enabled: bool,
+    reporting_id: String,
+    crash_reporter: Option<CrashReporter>,
+) {
     panic::set_hook(Box::new(move |panic_info| {
         let backtrace = Backtrace::force_capture().to_string();
         let loc = if let Some(loc) = panic_info.location() {
@@ -33,6 +39,14 @@ pub fn setup_panic_hook(reporting_enabled: bool, reporting_id: String) {
         log::error!("Panic backtrace: \n{}", backtrace);
         log::error!("Panic occurred{loc}: {message}");
 
+        if let Some(crash_reporter) = &crash_reporter {
+            let location = panic_info.location();
+            match crash_reporter.write(&reporting_id, message, location, &backtrace) {
+                Ok(path) => log::error!("Crash report written to {}", path.display()),
+                Err(err) => log::error!("Failed to write crash report: {err}"),
+            }
+        }
+
         if reporting_enabled {
             ErrorReporter::report(message, &reporting_id, Some(&loc));
         }
@@ -57,3 +71,36 @@ pub fn remove_started_file_indicator() {
         }
     }
 }
+
+/// Creates a file in the storage that indicates that all data has been flushed on shutdown.
+/// Contains the version of Qdrant, as storage may be started by a different version next time.
+pub fn touch_clean_shutdown_indicator(storage_path: &Path) {
+    let path = storage_path.join(CLEAN_SHUTDOWN_FILE);
+    if let Err(err) = std::fs::write(path, env!("CARGO_PKG_VERSION")) {
+        log::warn!("Failed to create clean shutdown indicator: {}", err);
+    }
+}
+
+/// Removes the clean shutdown indicator from the storage.
+/// Returns `true` if the storage has been flushed on shutdown by the same version of Qdrant.
+///
+/// Use before loading the storage, so a crash of this run is not mistaken for a clean shutdown.
+pub fn take_clean_shutdown_indicator(storage_path: &Path) -> bool {
+    let is_clean = check_clean_shutdown_indicator(storage_path);
+    let path = storage_path.join(CLEAN_SHUTDOWN_FILE);
+    if path.exists() {
+        if let Err(err) = std::fs::remove_file(&path) {
+            log::warn!("Failed to remove clean shutdown indicator: {}", err);
+            // Indicator left in place would be stale after the next crash
+            return false;
+        }
+    }
+    is_clean
+}
+
+/// Returns `true` if the storage has been flushed on shutdown by the same version of Qdrant,
+/// leaving the indicator in place. For instances which don't write to the storage.
+pub fn check_clean_shutdown_indicator(storage_path: &Path) -> bool {
+    let path = storage_path.join(CLEAN_SHUTDOWN_FILE);
+    std::fs::read_to_string(path).map_or(false, |version| version == env!("CARGO_PKG_VERSION"))
+}
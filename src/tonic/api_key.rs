@@ -4,6 +4,7 @@ use actix_web_httpauth::headers::authorization::{Bearer, Scheme};
 use futures_util::future::BoxFuture;
 use reqwest::header::HeaderValue;
 use reqwest::StatusCode;
+use storage::rbac::{self, Access, AccessMode};
 use tonic::body::BoxBody;
 use tonic::Code;
 use tower::Service;
@@ -12,7 +13,7 @@ use tower_layer::Layer;
 use crate::common::auth::AuthKeys;
 use crate::common::strings::ct_eq;
 
-const READ_ONLY_RPC_PATHS: [&str; 13] = [
+const READ_ONLY_RPC_PATHS: [&str; 14] = [
     "/qdrant.Collections/List",
     "/qdrant.Collections/Get",
     "/qdrant.Points/Scroll",
@@ -26,6 +27,14 @@ const READ_ONLY_RPC_PATHS: [&str; 13] = [
     "/qdrant.Points/RecommendBatch",
     "/qdrant.Points/Discover",
     "/qdrant.Points/DiscoverBatch",
+    "/qdrant.Qdrant/GetLogger",
+];
+
+/// Services operating on collections, checked against the access of a JWT by the storage
+const COLLECTION_RPC_PREFIXES: [&str; 3] = [
+    "/qdrant.Collections/",
+    "/qdrant.Points/",
+    "/qdrant.Snapshots/",
 ];
 
 #[derive(Clone)]
@@ -42,9 +51,11 @@ pub struct ApiKeyMiddlewareLayer {
 impl<S> Service<tonic::codegen::http::Request<tonic::transport::Body>> for ApiKeyMiddleware<S>
 where
     S: Service<
-        tonic::codegen::http::Request<tonic::transport::Body>,
-        Response = tonic::codegen::http::Response<tonic::body::BoxBody>,
-    >,
+            tonic::codegen::http::Request<tonic::transport::Body>,
+            Response = tonic::codegen::http::Response<tonic::body::BoxBody>,
+        > + Clone
+        + Send
+        + 'static,
     S::Future: Send + 'static,
 {
     type Response = tonic::codegen::http::Response<tonic::body::BoxBody>;
@@ -59,40 +70,50 @@ where
         &mut self,
         request: tonic::codegen::http::Request<tonic::transport::Body>,
     ) -> Self::Future {
-        // Grab API key from request
-        let key =
-            // Request header
-            request.headers().get("api-key").and_then(|key| key.to_str().ok()).map(|key| key.to_string())
-                // Fall back to authentication header with bearer token
-                .or_else(|| {
-                    request.headers().get("authorization")
-                        .and_then(|auth| {
-                            Bearer::parse(auth).ok().map(|bearer| bearer.token().into())
-                        })
-                });
-
-        if let Some(key) = key {
+        if let Some(key) = request_api_key(&request) {
             let is_allowed = self.auth_keys.can_write(&key)
                 || (is_read_only(&request) && self.auth_keys.can_read(&key));
             if is_allowed {
                 return Box::pin(self.service.call(request));
             }
+
+            if let Some(access) = self.auth_keys.validate_jwt(&key) {
+                if is_allowed_with_access(&request, &access) {
+                    return Box::pin(rbac::scope(access, self.service.call(request)));
+                }
+            }
+
+            if let Some(iam_auth) = self.auth_keys.iam_auth_for(&key) {
+                let clone = self.service.clone();
+                let mut service = std::mem::replace(&mut self.service, clone);
+                return Box::pin(async move {
+                    match iam_auth.authenticate(&key).await {
+                        Some(access) if is_allowed_with_access(&request, &access) => {
+                            rbac::scope(access, service.call(request)).await
+                        }
+                        _ => Ok(forbidden()),
+                    }
+                });
+            }
         }
 
-        let mut response = Self::Response::new(BoxBody::default());
-        *response.status_mut() = StatusCode::FORBIDDEN;
-        response.headers_mut().append(
-            "grpc-status",
-            HeaderValue::from(Code::PermissionDenied as i32),
-        );
-        response
-            .headers_mut()
-            .append("grpc-message", HeaderValue::from_static("Invalid api-key"));
-
-        Box::pin(async move { Ok(response) })
+        Box::pin(async move { Ok(forbidden()) })
     }
 }
 
+fn forbidden() -> tonic::codegen::http::Response<BoxBody> {
+    let mut response = tonic::codegen::http::Response::new(BoxBody::default());
+    *response.status_mut() = StatusCode::FORBIDDEN;
+    response.headers_mut().append(
+        "grpc-status",
+        HeaderValue::from(Code::PermissionDenied as i32),
+    );
+    response
+        .headers_mut()
+        .append("grpc-message", HeaderValue::from_static("Invalid api-key"));
+    response
+}
+
 impl ApiKeyMiddlewareLayer {
     pub fn new(auth_keys: AuthKeys) -> Self {
         Self { auth_keys }
@@ -110,7 +131,39 @@ impl<S> Layer<S> for ApiKeyMiddlewareLayer {
     }
 }
 
-fn is_read_only<R>(req: &tonic::codegen::http::Request<R>) -> bool {
+/// Grab API key from request
+pub fn request_api_key<R>(req: &tonic::codegen::http::Request<R>) -> Option<String> {
+    // Request header
+    req.headers()
+        .get("api-key")
+        .and_then(|key| key.to_str().ok())
+        .map(|key| key.to_string())
+        // Fall back to authentication header with bearer token
+        .or_else(|| {
+            req.headers()
+                .get("authorization")
+                .and_then(|auth| Bearer::parse(auth).ok().map(|bearer| bearer.token().into()))
+        })
+}
+
+/// Whether a request with the access of a JWT may reach the method, other methods than the ones
+/// operating on collections require global access
+fn is_allowed_with_access<R>(req: &tonic::codegen::http::Request<R>, access: &Access) -> bool {
+    let uri_path = req.uri().path();
+    // Aliases of all collections are listed
+    let is_collection_method = uri_path != "/qdrant.Collections/ListAliases"
+        && COLLECTION_RPC_PREFIXES
+            .iter()
+            .any(|prefix| uri_path.starts_with(prefix));
+
+    match access {
+        Access::Global(AccessMode::Manage) => true,
+        Access::Global(_) => is_collection_method || is_read_only(req),
+        Access::Collections(_) => is_collection_method,
+    }
+}
+
+pub fn is_read_only<R>(req: &tonic::codegen::http::Request<R>) -> bool {
     let uri_path = req.uri().path();
     READ_ONLY_RPC_PATHS
         .iter()
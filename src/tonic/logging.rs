@@ -1,23 +1,45 @@
 use std::task::{Context, Poll};
 
+use api::grpc::request_id::{self, REQUEST_ID_HEADER};
 use futures_util::future::BoxFuture;
 use tonic::body::BoxBody;
+use tonic::codegen::http::header::HeaderValue;
 use tonic::codegen::http::Response;
 use tonic::Code;
 use tower::Service;
 use tower_layer::Layer;
+use tracing::Instrument as _;
+
+use crate::common::auth::AuthKeys;
+use crate::tonic::api_key::request_api_key;
+use crate::tracing::access_log::AccessLogEntry;
 
 #[derive(Clone)]
 pub struct LoggingMiddleware<T> {
     inner: T,
+    access_log: Option<AccessLogConfig>,
 }
 
 #[derive(Clone)]
-pub struct LoggingMiddlewareLayer;
+pub struct LoggingMiddlewareLayer {
+    access_log: Option<AccessLogConfig>,
+}
+
+#[derive(Clone)]
+struct AccessLogConfig {
+    auth_keys: Option<AuthKeys>,
+}
 
 impl LoggingMiddlewareLayer {
     pub fn new() -> Self {
-        Self {}
+        Self { access_log: None }
+    }
+
+    /// Also report every request into the access log
+    pub fn with_access_log(auth_keys: Option<AuthKeys>) -> Self {
+        Self {
+            access_log: Some(AccessLogConfig { auth_keys }),
+        }
     }
 }
 
@@ -37,17 +59,63 @@ where
 
     fn call(
         &mut self,
-        request: tonic::codegen::http::Request<tonic::transport::Body>,
+        mut request: tonic::codegen::http::Request<tonic::transport::Body>,
     ) -> Self::Future {
         let clone = self.inner.clone();
         let mut inner = std::mem::replace(&mut self.inner, clone);
 
+        // Internal requests carry the id of the API request they are made for
+        let id = request_id::from_header(
+            request
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .map(|request_id| request_id.as_bytes()),
+        );
+        // Valid ids are visible ASCII, which is always a valid header value
+        let request_id_header = HeaderValue::from_str(&id).unwrap();
+        request
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, request_id_header.clone());
+
         let method_name = request.uri().path().to_string();
+        let access_log = self.access_log.as_ref().map(|config| {
+            let http_method = request.method().to_string();
+            let api_key = config.auth_keys.as_ref().and_then(|auth_keys| {
+                request_api_key(&request).and_then(|key| auth_keys.key_id(&key))
+            });
+            (http_method, id.clone(), api_key)
+        });
         let instant = std::time::Instant::now();
-        let future = inner.call(request);
+        let span = tracing::debug_span!("request", request_id = id.as_str());
+        let future = request_id::scope(id, inner.call(request)).instrument(span);
         Box::pin(async move {
-            let response = future.await;
+            let mut response = future.await;
+            if let Ok(response) = &mut response {
+                response
+                    .headers_mut()
+                    .insert(REQUEST_ID_HEADER, request_id_header);
+            }
             let elapsed_sec = instant.elapsed().as_secs_f32();
+
+            if let Some((http_method, request_id, api_key)) = &access_log {
+                let code = match &response {
+                    Ok(response) => tonic::Status::from_header_map(response.headers())
+                        .map_or(Code::Ok, |status| status.code()),
+                    Err(_) => Code::Internal,
+                };
+                AccessLogEntry {
+                    protocol: "grpc",
+                    method: http_method,
+                    path: &method_name,
+                    status: code as u16,
+                    duration: instant.elapsed(),
+                    bytes: None,
+                    api_key: *api_key,
+                    request_id: Some(request_id),
+                }
+                .log();
+            }
+
             match response {
                 Err(error) => {
                     log::error!("gGRPC request error {}", method_name);
@@ -108,6 +176,9 @@ impl<S> Layer<S> for LoggingMiddlewareLayer {
     type Service = LoggingMiddleware<S>;
 
     fn layer(&self, service: S) -> Self::Service {
-        LoggingMiddleware { inner: service }
+        LoggingMiddleware {
+            inner: service,
+            access_log: self.access_log.clone(),
+        }
     }
 }
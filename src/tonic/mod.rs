@@ -1,12 +1,14 @@
-mod api;
+pub mod api;
 mod api_key;
 mod logging;
+mod read_only;
+mod tenancy;
 mod tonic_telemetry;
 
 use std::io;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use ::api::grpc::grpc_health_v1::health_check_response::ServingStatus;
 use ::api::grpc::grpc_health_v1::health_server::{Health, HealthServer};
@@ -24,8 +26,9 @@ use ::api::grpc::qdrant::qdrant_server::{Qdrant, QdrantServer};
 use ::api::grpc::qdrant::shard_snapshots_server::ShardSnapshotsServer;
 use ::api::grpc::qdrant::snapshots_server::SnapshotsServer;
 use ::api::grpc::qdrant::{
-    GetConsensusCommitRequest, GetConsensusCommitResponse, HealthCheckReply, HealthCheckRequest,
-    WaitOnConsensusCommitRequest, WaitOnConsensusCommitResponse,
+    GetConsensusCommitRequest, GetConsensusCommitResponse, GetLoggerRequest, HealthCheckReply,
+    HealthCheckRequest, LoggerReply, UpdateLoggerRequest, WaitOnConsensusCommitRequest,
+    WaitOnConsensusCommitResponse,
 };
 use ::api::grpc::QDRANT_DESCRIPTOR_SET;
 use storage::content_manager::consensus_manager::ConsensusStateRef;
@@ -47,9 +50,27 @@ use crate::tonic::api::collections_internal_api::CollectionsInternalService;
 use crate::tonic::api::points_api::PointsService;
 use crate::tonic::api::points_internal_api::PointsInternalService;
 use crate::tonic::api::snapshots_api::{ShardSnapshotsService, SnapshotsService};
+use crate::tracing::{LoggerConfig, LoggerConfigDiff, LoggerHandle};
 
 #[derive(Default)]
-pub struct QdrantService {}
+pub struct QdrantService {
+    /// Not available on the internal API
+    logger_handle: Option<Arc<LoggerHandle>>,
+}
+
+impl QdrantService {
+    pub fn new(logger_handle: Arc<LoggerHandle>) -> Self {
+        Self {
+            logger_handle: Some(logger_handle),
+        }
+    }
+
+    fn logger_handle(&self) -> Result<&LoggerHandle, Status> {
+        self.logger_handle
+            .as_deref()
+            .ok_or_else(|| Status::unimplemented("Logger configuration is not available"))
+    }
+}
 
 #[tonic::async_trait]
 impl Qdrant for QdrantService {
@@ -59,6 +80,41 @@ impl Qdrant for QdrantService {
     ) -> Result<Response<HealthCheckReply>, Status> {
         Ok(Response::new(VersionInfo::default().into()))
     }
+
+    async fn get_logger(
+        &self,
+        _request: Request<GetLoggerRequest>,
+    ) -> Result<Response<LoggerReply>, Status> {
+        let timing = Instant::now();
+        let config = self.logger_handle()?.get_config().await;
+        logger_reply(&config, timing)
+    }
+
+    async fn update_logger(
+        &self,
+        request: Request<UpdateLoggerRequest>,
+    ) -> Result<Response<LoggerReply>, Status> {
+        let timing = Instant::now();
+        let logger_handle = self.logger_handle()?;
+
+        let diff: LoggerConfigDiff = serde_json::from_str(&request.into_inner().config)
+            .map_err(|err| Status::invalid_argument(format!("Invalid logger config: {err}")))?;
+        let config = logger_handle.update_config(diff).await.map_err(|err| {
+            Status::invalid_argument(format!("Failed to update logger config: {err:#}"))
+        })?;
+
+        logger_reply(&config, timing)
+    }
+}
+
+fn logger_reply(config: &LoggerConfig, timing: Instant) -> Result<Response<LoggerReply>, Status> {
+    let config = serde_json::to_string(config)
+        .map_err(|err| Status::internal(format!("Failed to serialize logger config: {err}")))?;
+
+    Ok(Response::new(LoggerReply {
+        config,
+        time: timing.elapsed().as_secs_f64(),
+    }))
 }
 
 // Additional health check service that follows gRPC health check protocol as described in #2614
@@ -145,6 +201,7 @@ async fn wait_stop_signal(for_what: &str) {
 pub fn init(
     dispatcher: Arc<Dispatcher>,
     telemetry_collector: Arc<parking_lot::Mutex<TonicTelemetryCollector>>,
+    logger_handle: Arc<LoggerHandle>,
     settings: Settings,
     grpc_port: u16,
     runtime: Handle,
@@ -153,7 +210,7 @@ pub fn init(
         let socket =
             SocketAddr::from((settings.service.host.parse::<IpAddr>().unwrap(), grpc_port));
 
-        let qdrant_service = QdrantService::default();
+        let qdrant_service = QdrantService::new(logger_handle);
         let health_service = HealthService::default();
         let collections_service = CollectionsService::new(dispatcher.clone());
         let points_service = PointsService::new(dispatcher.clone());
@@ -189,13 +246,28 @@ pub fn init(
 
         // The stack of middleware that our service will be wrapped in
         let middleware_layer = tower::ServiceBuilder::new()
-            .layer(logging::LoggingMiddlewareLayer::new())
+            .layer(logging::LoggingMiddlewareLayer::with_access_log(
+                AuthKeys::try_create(&settings.service),
+            ))
             .layer(tonic_telemetry::TonicTelemetryLayer::new(
                 telemetry_collector,
             ))
+            .option_layer(
+                settings
+                    .service
+                    .tenancy
+                    .clone()
+                    .map(tenancy::TenancyMiddlewareLayer::new),
+            )
             .option_layer({
                 AuthKeys::try_create(&settings.service).map(api_key::ApiKeyMiddlewareLayer::new)
             })
+            .option_layer(
+                settings
+                    .service
+                    .read_only
+                    .then_some(read_only::ReadOnlyMiddlewareLayer),
+            )
             .into_inner();
 
         server
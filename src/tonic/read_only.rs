@@ -0,0 +1,79 @@
+use std::task::{Context, Poll};
+
+use futures_util::future::BoxFuture;
+use reqwest::header::HeaderValue;
+use reqwest::StatusCode;
+use tonic::body::BoxBody;
+use tonic::Code;
+use tower::Service;
+use tower_layer::Layer;
+
+use crate::tonic::api_key::is_read_only;
+
+/// Services which never change the storage, next to the read-only RPCs of the API key
+const READ_ONLY_SERVICE_PREFIXES: [&str; 3] = [
+    "/grpc.health.v1.Health/",
+    "/grpc.reflection.v1alpha.ServerReflection/",
+    "/qdrant.Qdrant/",
+];
+
+/// Rejects every RPC which may change the storage with `PermissionDenied`
+#[derive(Clone)]
+pub struct ReadOnlyMiddleware<T> {
+    service: T,
+}
+
+#[derive(Clone)]
+pub struct ReadOnlyMiddlewareLayer;
+
+impl<S> Service<tonic::codegen::http::Request<tonic::transport::Body>> for ReadOnlyMiddleware<S>
+where
+    S: Service<
+        tonic::codegen::http::Request<tonic::transport::Body>,
+        Response = tonic::codegen::http::Response<tonic::body::BoxBody>,
+    >,
+    S::Future: Send + 'static,
+{
+    type Response = tonic::codegen::http::Response<tonic::body::BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(
+        &mut self,
+        request: tonic::codegen::http::Request<tonic::transport::Body>,
+    ) -> Self::Future {
+        let path = request.uri().path();
+        let is_allowed = is_read_only(&request)
+            || READ_ONLY_SERVICE_PREFIXES
+                .iter()
+                .any(|prefix| path.starts_with(prefix));
+        if is_allowed {
+            return Box::pin(self.service.call(request));
+        }
+
+        let mut response = Self::Response::new(BoxBody::default());
+        *response.status_mut() = StatusCode::FORBIDDEN;
+        response.headers_mut().append(
+            "grpc-status",
+            HeaderValue::from(Code::PermissionDenied as i32),
+        );
+        response.headers_mut().append(
+            "grpc-message",
+            HeaderValue::from_static("Service is in read-only mode"),
+        );
+
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+impl<S> Layer<S> for ReadOnlyMiddlewareLayer {
+    type Service = ReadOnlyMiddleware<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        ReadOnlyMiddleware { service }
+    }
+}
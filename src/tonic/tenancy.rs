@@ -0,0 +1,141 @@
+use std::task::{Context, Poll};
+
+use futures_util::future::BoxFuture;
+use reqwest::header::HeaderValue;
+use reqwest::StatusCode;
+use tonic::body::BoxBody;
+use tonic::Code;
+use tower::Service;
+use tower_layer::Layer;
+
+use crate::common::strings::ct_eq;
+use crate::settings::TenancyConfig;
+use crate::tonic::api_key::request_api_key;
+
+/// Rejects RPCs of tenants with `PermissionDenied`.
+///
+/// Collections are named within the messages of RPCs, so they can't be namespaced the way
+/// collections in REST paths are, see `crate::actix::tenancy::Tenancy`. Tenants are served over
+/// REST only, and requests with the API key of a tenant or naming a tenant in the tenant header
+/// are rejected, rather than served with access to all collections.
+#[derive(Clone)]
+pub struct TenancyMiddleware<T> {
+    service: T,
+    config: TenancyConfig,
+}
+
+#[derive(Clone)]
+pub struct TenancyMiddlewareLayer {
+    config: TenancyConfig,
+}
+
+impl<S> Service<tonic::codegen::http::Request<tonic::transport::Body>> for TenancyMiddleware<S>
+where
+    S: Service<
+        tonic::codegen::http::Request<tonic::transport::Body>,
+        Response = tonic::codegen::http::Response<tonic::body::BoxBody>,
+    >,
+    S::Future: Send + 'static,
+{
+    type Response = tonic::codegen::http::Response<tonic::body::BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(
+        &mut self,
+        request: tonic::codegen::http::Request<tonic::transport::Body>,
+    ) -> Self::Future {
+        if !is_tenant_request(&request, &self.config) {
+            return Box::pin(self.service.call(request));
+        }
+
+        let mut response = Self::Response::new(BoxBody::default());
+        *response.status_mut() = StatusCode::FORBIDDEN;
+        response.headers_mut().append(
+            "grpc-status",
+            HeaderValue::from(Code::PermissionDenied as i32),
+        );
+        response.headers_mut().append(
+            "grpc-message",
+            HeaderValue::from_static("Tenants are served over REST only"),
+        );
+
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+impl TenancyMiddlewareLayer {
+    pub fn new(config: TenancyConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S> Layer<S> for TenancyMiddlewareLayer {
+    type Service = TenancyMiddleware<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        TenancyMiddleware {
+            service,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Whether the request has the API key of a tenant, or names a tenant in the tenant header
+fn is_tenant_request<R>(
+    request: &tonic::codegen::http::Request<R>,
+    config: &TenancyConfig,
+) -> bool {
+    let has_tenant_header = config.header.as_ref().map_or(false, |header| {
+        request.headers().contains_key(header.as_str())
+    });
+    let has_tenant_key = request_api_key(request).map_or(false, |key| {
+        config
+            .api_keys
+            .values()
+            .any(|tenant_key| ct_eq(tenant_key, &key))
+    });
+    has_tenant_header || has_tenant_key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_tenant_request() {
+        let config = TenancyConfig {
+            header: Some("x-tenant-id".to_string()),
+            api_keys: [("acme".to_string(), "acme-key".to_string())].into(),
+        };
+        let request = |headers: &[(&str, &str)]| {
+            let mut request = tonic::codegen::http::Request::builder().uri("/qdrant.Points/Search");
+            for (name, value) in headers {
+                request = request.header(*name, *value);
+            }
+            request.body(()).unwrap()
+        };
+
+        assert!(!is_tenant_request(&request(&[]), &config));
+        assert!(!is_tenant_request(
+            &request(&[("api-key", "service-key")]),
+            &config
+        ));
+        assert!(is_tenant_request(
+            &request(&[("api-key", "acme-key")]),
+            &config
+        ));
+        assert!(is_tenant_request(
+            &request(&[("authorization", "Bearer acme-key")]),
+            &config
+        ));
+        assert!(is_tenant_request(
+            &request(&[("x-tenant-id", "acme")]),
+            &config
+        ));
+    }
+}
@@ -0,0 +1,229 @@
+//! Access log of the REST and gRPC APIs.
+//!
+//! Every served request is reported as an event with [`ACCESS_LOG_TARGET`] target.
+//! These events are excluded from the other outputs and written into a separate sink,
+//! either as `key=value` text lines or as JSON lines.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::time::Duration;
+use std::{fmt, io, sync};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, layer, registry};
+
+use super::config::merge_option;
+pub use super::config::Format;
+use super::sink::{self, FallbackWriter};
+use super::Logger;
+
+pub const ACCESS_LOG_TARGET: &str = "access_log";
+
+const SINK_NAME: &str = "access_log";
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Write access log into this file instead of stdout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    /// `key=value` pairs or JSON object, one request per line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Format>,
+}
+
+impl Config {
+    pub fn merge(&mut self, diff: Self) {
+        let Self {
+            enabled,
+            log_file,
+            format,
+        } = diff;
+
+        merge_option(&mut self.enabled, enabled);
+        merge_option(&mut self.log_file, log_file);
+        merge_option(&mut self.format, format);
+    }
+}
+
+/// Single served request, reported into the access log.
+#[derive(Debug)]
+pub struct AccessLogEntry<'a> {
+    pub protocol: &'static str,
+    pub method: &'a str,
+    /// Request path or full gRPC method name
+    pub path: &'a str,
+    /// HTTP status or gRPC code
+    pub status: u16,
+    pub duration: Duration,
+    /// Size of the response body, if known
+    pub bytes: Option<u64>,
+    /// Which of the configured API keys the request was made with
+    pub api_key: Option<&'static str>,
+    pub request_id: Option<&'a str>,
+}
+
+impl AccessLogEntry<'_> {
+    pub fn log(&self) {
+        tracing::info!(
+            target: ACCESS_LOG_TARGET,
+            protocol = self.protocol,
+            method = self.method,
+            path = self.path,
+            status = self.status,
+            duration_us = self.duration.as_micros() as u64,
+            bytes = self.bytes,
+            api_key = self.api_key,
+            request_id = self.request_id,
+        );
+    }
+}
+
+pub fn new_logger<S>(config: &mut Config) -> Logger<S>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    let layer = match new_layer(config) {
+        Ok(layer) => layer,
+        Err(err) => {
+            eprintln!(
+                "failed to enable access log into {} log file: {err}",
+                config.log_file.as_deref().unwrap_or_default(),
+            );
+
+            config.enabled = Some(false);
+            None
+        }
+    };
+
+    layer.with_filter(new_filter(config))
+}
+
+pub fn new_layer<S>(
+    config: &Config,
+) -> anyhow::Result<Option<Box<dyn tracing_subscriber::Layer<S> + Send + Sync>>>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    if !config.enabled.unwrap_or_default() {
+        sink::unregister(SINK_NAME);
+        return Ok(None);
+    }
+
+    let writer: Box<dyn io::Write + Send> = match &config.log_file {
+        Some(log_file) => Box::new(
+            FallbackWriter::open(SINK_NAME, log_file)
+                .with_context(|| format!("failed to open {log_file} log file"))?,
+        ),
+        None => {
+            sink::unregister(SINK_NAME);
+            Box::new(io::stdout())
+        }
+    };
+
+    let layer = AccessLogLayer {
+        format: config.format.unwrap_or_default(),
+        writer: sync::Mutex::new(writer),
+    };
+
+    Ok(Some(layer.boxed()))
+}
+
+pub fn new_filter(config: &Config) -> filter::EnvFilter {
+    let directives = if config.enabled.unwrap_or_default() {
+        format!("off,{ACCESS_LOG_TARGET}=info")
+    } else {
+        "off".to_string()
+    };
+
+    filter::EnvFilter::builder()
+        .with_regex(false)
+        .parse_lossy(directives)
+}
+
+struct AccessLogLayer {
+    format: Format,
+    writer: sync::Mutex<Box<dyn io::Write + Send>>,
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for AccessLogLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: layer::Context<'_, S>) {
+        let mut fields = FieldsVisitor::default();
+        event.record(&mut fields);
+
+        let timestamp =
+            crate::common::clock::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+
+        let line = match self.format {
+            Format::Text => {
+                let mut line = timestamp;
+                for (key, value) in &fields.0 {
+                    match value {
+                        // Keep simple strings unquoted, everything else is written as JSON
+                        Value::String(string) if !string.is_empty() && !string.contains(' ') => {
+                            write!(line, " {key}={string}")
+                        }
+                        value => write!(line, " {key}={value}"),
+                    }
+                    .unwrap(); // Writing into `String` never fails
+                }
+                line
+            }
+            Format::Json => {
+                let mut object = Map::with_capacity(fields.0.len() + 1);
+                object.insert("timestamp".to_string(), Value::String(timestamp));
+                object.extend(
+                    fields
+                        .0
+                        .into_iter()
+                        .map(|(key, value)| (key.to_string(), value)),
+                );
+                Value::Object(object).to_string()
+            }
+        };
+
+        let mut writer = match self.writer.lock() {
+            Ok(writer) => writer,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // Nowhere to report failures of the logger itself
+        let _ = writeln!(writer, "{line}");
+    }
+}
+
+/// Collects event fields in the order they are recorded
+#[derive(Default)]
+pub(super) struct FieldsVisitor(pub(super) Vec<(&'static str, Value)>);
+
+impl Visit for FieldsVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .push((field.name(), Value::from(format!("{value:?}"))));
+    }
+}
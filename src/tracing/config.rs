@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::fmt;
+
+use super::{access_log, default, on_disk, rate_limit, slow_query_log, tail};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LoggerConfig {
+    #[serde(flatten)]
+    pub default: default::Config,
+    /// Names of fields whose values are masked in the log output, see [`super::redact`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redact: Option<Vec<String>>,
+    pub on_disk: on_disk::Config,
+    pub access_log: access_log::Config,
+    pub slow_query_log: slow_query_log::Config,
+    pub tail: tail::Config,
+    pub rate_limit: rate_limit::Config,
+}
+
+/// Partial logger configuration, only the fields that are set are applied to the running logger.
+pub type LoggerConfigDiff = LoggerConfig;
+
+impl LoggerConfig {
+    /// Use top-level `log_level` of the settings, if log level of the default logger is not set
+    pub fn with_top_level_directive(mut self, log_level: Option<String>) -> Self {
+        if self.default.log_level.is_none() {
+            self.default.log_level = log_level;
+        } else if log_level.is_some() {
+            eprintln!(
+                "Both top-level `log_level` and `logger.log_level` config directives are used. \
+                 `logger.log_level` takes priority, so top-level `log_level` will be ignored."
+            );
+        }
+
+        self
+    }
+
+    pub fn merge(&mut self, diff: LoggerConfigDiff) {
+        self.default.merge(diff.default);
+        merge_option(&mut self.redact, diff.redact);
+        self.on_disk.merge(diff.on_disk);
+        self.access_log.merge(diff.access_log);
+        self.slow_query_log.merge(diff.slow_query_log);
+        self.tail.merge(diff.tail);
+        self.rate_limit.merge(diff.rate_limit);
+    }
+}
+
+/// Format of lines written by a logger output
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    #[default]
+    Text,
+    /// JSON object per line, e.g. to query logs with CloudWatch Logs Insights
+    Json,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanEvent {
+    New,
+    Enter,
+    Exit,
+    Close,
+}
+
+impl SpanEvent {
+    pub fn unwrap_or_default_config(events: &Option<HashSet<Self>>) -> fmt::format::FmtSpan {
+        match events {
+            Some(events) => Self::into_fmt_span(events.iter().copied()),
+            None => fmt::format::FmtSpan::NEW,
+        }
+    }
+
+    pub fn into_fmt_span(events: impl IntoIterator<Item = Self>) -> fmt::format::FmtSpan {
+        events
+            .into_iter()
+            .fold(fmt::format::FmtSpan::NONE, |events, event| {
+                events | event.into()
+            })
+    }
+}
+
+impl From<SpanEvent> for fmt::format::FmtSpan {
+    fn from(event: SpanEvent) -> Self {
+        match event {
+            SpanEvent::New => fmt::format::FmtSpan::NEW,
+            SpanEvent::Enter => fmt::format::FmtSpan::ENTER,
+            SpanEvent::Exit => fmt::format::FmtSpan::EXIT,
+            SpanEvent::Close => fmt::format::FmtSpan::CLOSE,
+        }
+    }
+}
+
+/// Replace `value` with `diff`, if `diff` is set
+pub(super) fn merge_option<T>(value: &mut Option<T>, diff: Option<T>) {
+    if diff.is_some() {
+        *value = diff;
+    }
+}
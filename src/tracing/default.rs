@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+use std::io;
+
+use colored::control::ShouldColorize;
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::fmt::writer::{BoxMakeWriter, MakeWriterExt as _};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, fmt, registry};
+
+use super::config::{merge_option, Format, SpanEvent};
+use super::json::JsonLayer;
+use super::redact::RedactingWriter;
+use super::Logger;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Directives on top of `log_level`, e.g. `collection::*=trace`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Vec<String>>,
+    /// Match field values of the directives as regular expressions, disabled by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_regex: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span_events: Option<HashSet<SpanEvent>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Format>,
+    /// Write warnings and errors to stderr and other lines to stdout, disabled by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_stderr: Option<bool>,
+}
+
+impl Config {
+    pub fn merge(&mut self, diff: Self) {
+        let Self {
+            log_level,
+            filters,
+            filter_regex,
+            span_events,
+            format,
+            split_stderr,
+        } = diff;
+
+        merge_option(&mut self.log_level, log_level);
+        merge_option(&mut self.filters, filters);
+        merge_option(&mut self.filter_regex, filter_regex);
+        merge_option(&mut self.span_events, span_events);
+        merge_option(&mut self.format, format);
+        merge_option(&mut self.split_stderr, split_stderr);
+    }
+}
+
+pub fn new_logger<S>(config: &Config) -> Logger<S>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    Some(new_layer(config)).with_filter(new_filter(config))
+}
+
+pub fn new_layer<S>(config: &Config) -> Box<dyn tracing_subscriber::Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    let writer = RedactingWriter::new(new_writer(config));
+
+    if config.format.unwrap_or_default() == Format::Json {
+        return JsonLayer::new(writer).boxed();
+    }
+
+    fmt::Layer::default()
+        .with_writer(writer)
+        // Only use ANSI if we should colorize
+        .with_ansi(ShouldColorize::from_env().should_colorize())
+        .with_span_events(SpanEvent::unwrap_or_default_config(&config.span_events))
+        .boxed()
+}
+
+/// Stdout, or stderr for warnings and errors if `split_stderr` is enabled
+fn new_writer(config: &Config) -> BoxMakeWriter {
+    if config.split_stderr.unwrap_or_default() {
+        let writer = io::stderr
+            .with_max_level(tracing::Level::WARN)
+            .or_else(io::stdout);
+        return BoxMakeWriter::new(writer);
+    }
+
+    BoxMakeWriter::new(io::stdout)
+}
+
+pub fn new_filter(config: &Config) -> filter::EnvFilter {
+    super::filter(
+        config.log_level.as_deref().unwrap_or(""),
+        config.filters.as_deref().unwrap_or_default(),
+        config.filter_regex.unwrap_or_default(),
+    )
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing_subscriber::{layer, reload, Registry};
+
+use super::config::{LoggerConfig, LoggerConfigDiff};
+use super::rate_limit::RateLimitLayer;
+use super::tail::{LogRecord, TailBuffer};
+use super::{access_log, default, on_disk, redact, slow_query_log, tail, Logger};
+
+type DefaultLoggerSubscriber = Registry;
+type DefaultLoggerLayer = reload::Layer<Logger<DefaultLoggerSubscriber>, DefaultLoggerSubscriber>;
+type DefaultLoggerReloadHandle =
+    reload::Handle<Logger<DefaultLoggerSubscriber>, DefaultLoggerSubscriber>;
+
+type OnDiskLoggerSubscriber = layer::Layered<DefaultLoggerLayer, DefaultLoggerSubscriber>;
+type OnDiskLoggerLayer = reload::Layer<Logger<OnDiskLoggerSubscriber>, OnDiskLoggerSubscriber>;
+type OnDiskLoggerReloadHandle =
+    reload::Handle<Logger<OnDiskLoggerSubscriber>, OnDiskLoggerSubscriber>;
+
+type AccessLoggerSubscriber = layer::Layered<OnDiskLoggerLayer, OnDiskLoggerSubscriber>;
+type AccessLoggerLayer = reload::Layer<Logger<AccessLoggerSubscriber>, AccessLoggerSubscriber>;
+type AccessLoggerReloadHandle =
+    reload::Handle<Logger<AccessLoggerSubscriber>, AccessLoggerSubscriber>;
+
+type SlowQueryLoggerSubscriber = layer::Layered<AccessLoggerLayer, AccessLoggerSubscriber>;
+type SlowQueryLoggerLayer =
+    reload::Layer<Logger<SlowQueryLoggerSubscriber>, SlowQueryLoggerSubscriber>;
+type SlowQueryLoggerReloadHandle =
+    reload::Handle<Logger<SlowQueryLoggerSubscriber>, SlowQueryLoggerSubscriber>;
+
+type TailLoggerSubscriber = layer::Layered<SlowQueryLoggerLayer, SlowQueryLoggerSubscriber>;
+type TailLoggerReloadHandle = reload::Handle<Logger<TailLoggerSubscriber>, TailLoggerSubscriber>;
+
+/// Handle to the running logger, allows to change its configuration at runtime
+pub struct LoggerHandle {
+    config: RwLock<LoggerConfig>,
+    default: DefaultLoggerReloadHandle,
+    on_disk: OnDiskLoggerReloadHandle,
+    access_log: AccessLoggerReloadHandle,
+    slow_query_log: SlowQueryLoggerReloadHandle,
+    tail: TailLoggerReloadHandle,
+    tail_buffer: Arc<TailBuffer>,
+    rate_limit: RateLimitLayer,
+}
+
+impl LoggerHandle {
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn new(
+        config: LoggerConfig,
+        default: DefaultLoggerReloadHandle,
+        on_disk: OnDiskLoggerReloadHandle,
+        access_log: AccessLoggerReloadHandle,
+        slow_query_log: SlowQueryLoggerReloadHandle,
+        tail: TailLoggerReloadHandle,
+        tail_buffer: Arc<TailBuffer>,
+        rate_limit: RateLimitLayer,
+    ) -> Self {
+        Self {
+            config: RwLock::new(config),
+            default,
+            on_disk,
+            access_log,
+            slow_query_log,
+            tail,
+            tail_buffer,
+            rate_limit,
+        }
+    }
+
+    pub async fn get_config(&self) -> LoggerConfig {
+        self.config.read().await.clone()
+    }
+
+    /// Apply `diff` to the current configuration and reload the outputs it changes, returns the
+    /// applied configuration
+    pub async fn update_config(&self, diff: LoggerConfigDiff) -> anyhow::Result<LoggerConfig> {
+        let mut config = self.config.write().await;
+
+        let mut merged = config.clone();
+        merged.merge(diff);
+
+        if merged.redact != config.redact {
+            redact::set_fields(merged.redact.as_deref().unwrap_or_default());
+        }
+
+        if merged.default != config.default {
+            let layer = default::new_layer(&merged.default);
+            let filter = default::new_filter(&merged.default);
+
+            self.default.modify(move |logger| {
+                *logger.inner_mut() = Some(layer);
+                *logger.filter_mut() = filter;
+            })?;
+        }
+
+        if merged.on_disk != config.on_disk {
+            let layer = on_disk::new_layer(&merged.on_disk)?;
+            let filter = on_disk::new_filter(&merged.on_disk);
+
+            self.on_disk.modify(move |logger| {
+                *logger.inner_mut() = layer;
+                *logger.filter_mut() = filter;
+            })?;
+        }
+
+        if merged.access_log != config.access_log {
+            let layer = access_log::new_layer(&merged.access_log)?;
+            let filter = access_log::new_filter(&merged.access_log);
+
+            self.access_log.modify(move |logger| {
+                *logger.inner_mut() = layer;
+                *logger.filter_mut() = filter;
+            })?;
+        }
+
+        if merged.slow_query_log != config.slow_query_log {
+            let layer = slow_query_log::new_layer(&merged.slow_query_log)?;
+            let filter = slow_query_log::new_filter(&merged.slow_query_log);
+
+            self.slow_query_log.modify(move |logger| {
+                *logger.inner_mut() = layer;
+                *logger.filter_mut() = filter;
+            })?;
+        }
+
+        if merged.tail != config.tail {
+            let layer = tail::new_layer(&merged.tail, &self.tail_buffer);
+            let filter = tail::new_filter(&merged.tail);
+
+            self.tail.modify(move |logger| {
+                *logger.inner_mut() = layer;
+                *logger.filter_mut() = filter;
+            })?;
+        }
+
+        if merged.rate_limit != config.rate_limit {
+            self.rate_limit.update(&merged.rate_limit);
+        }
+
+        *config = merged.clone();
+
+        Ok(merged)
+    }
+
+    pub fn tail_buffer(&self) -> Arc<TailBuffer> {
+        self.tail_buffer.clone()
+    }
+
+    /// Last `lines` records of the in-memory log tail, at `level` or more severe
+    pub async fn tail(
+        &self,
+        lines: usize,
+        level: Option<tracing::Level>,
+    ) -> anyhow::Result<Vec<LogRecord>> {
+        if !self.config.read().await.tail.is_enabled() {
+            anyhow::bail!("log tail is disabled, enable it with `logger.tail.enabled`");
+        }
+
+        Ok(self.tail_buffer.tail(lines, level))
+    }
+}
@@ -0,0 +1,132 @@
+//! JSON lines output of the default and on-disk loggers.
+//!
+//! Every event is written as a JSON object with `timestamp`, `level`, `target` and `fields` of
+//! the event, and `span` and `spans` with names and fields of the spans the event is in, from
+//! the outermost one, and `request_id` of the API request being served. Span events are not
+//! written.
+
+use std::io::Write as _;
+
+use api::grpc::request_id;
+use serde_json::{Map, Value};
+use tracing::span;
+use tracing_log::NormalizeEvent as _;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::{layer, registry};
+
+use super::access_log::FieldsVisitor;
+
+pub struct JsonLayer<W> {
+    make_writer: W,
+}
+
+impl<W> JsonLayer<W> {
+    pub fn new(make_writer: W) -> Self {
+        Self { make_writer }
+    }
+}
+
+/// Fields of a span, stored in its extensions
+struct SpanFields(Map<String, Value>);
+
+impl<S, W> tracing_subscriber::Layer<S> for JsonLayer<W>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+    W: for<'writer> MakeWriter<'writer> + 'static,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut extensions = span.extensions_mut();
+        // Already recorded, if both the default and on-disk loggers write JSON
+        if extensions.get_mut::<SpanFields>().is_none() {
+            let mut fields = FieldsVisitor::default();
+            attrs.record(&mut fields);
+            extensions.insert(SpanFields(into_map(fields)));
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut fields = FieldsVisitor::default();
+        values.record(&mut fields);
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(span_fields)) = extensions.get_mut::<SpanFields>() {
+            span_fields.extend(into_map(fields));
+        }
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: layer::Context<'_, S>) {
+        // Events of the `log` crate carry their metadata in `log.*` fields
+        let normalized_metadata = event.normalized_metadata();
+        let metadata = normalized_metadata
+            .as_ref()
+            .unwrap_or_else(|| event.metadata());
+
+        let mut fields = FieldsVisitor::default();
+        event.record(&mut fields);
+        fields.0.retain(|(key, _)| !key.starts_with("log."));
+
+        let mut object = Map::with_capacity(6);
+        object.insert(
+            "timestamp".to_string(),
+            Value::String(
+                crate::common::clock::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            ),
+        );
+        object.insert(
+            "level".to_string(),
+            Value::String(metadata.level().to_string()),
+        );
+        object.insert(
+            "target".to_string(),
+            Value::String(metadata.target().to_string()),
+        );
+        object.insert("fields".to_string(), Value::Object(into_map(fields)));
+        // Also set while spans of the request are disabled by the log level
+        if let Some(request_id) = request_id::current() {
+            object.insert("request_id".to_string(), Value::String(request_id));
+        }
+
+        if let Some(scope) = ctx.event_scope(event) {
+            let spans: Vec<_> = scope
+                .from_root()
+                .map(|span| {
+                    let mut object = span
+                        .extensions()
+                        .get::<SpanFields>()
+                        .map(|SpanFields(fields)| fields.clone())
+                        .unwrap_or_default();
+                    object.insert("name".to_string(), Value::String(span.name().to_string()));
+                    Value::Object(object)
+                })
+                .collect();
+
+            if let Some(span) = spans.last() {
+                object.insert("span".to_string(), span.clone());
+            }
+            object.insert("spans".to_string(), Value::Array(spans));
+        }
+
+        let mut line = Value::Object(object).to_string();
+        line.push('\n');
+        // Nowhere to report failures of the logger itself
+        let _ = self
+            .make_writer
+            .make_writer_for(metadata)
+            .write_all(line.as_bytes());
+    }
+}
+
+fn into_map(fields: FieldsVisitor) -> Map<String, Value> {
+    fields
+        .0
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+}
@@ -0,0 +1,184 @@
+pub mod access_log;
+pub mod config;
+pub mod default;
+pub mod handle;
+mod json;
+pub mod on_disk;
+pub mod rate_limit;
+pub mod redact;
+pub mod sink;
+pub mod slow_query_log;
+pub mod span_timings;
+pub mod tail;
+
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::str::FromStr as _;
+
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, reload};
+
+pub use self::config::{LoggerConfig, LoggerConfigDiff};
+pub use self::handle::LoggerHandle;
+
+const DEFAULT_LOG_LEVEL: log::LevelFilter = log::LevelFilter::Info;
+
+const DEFAULT_FILTERS: &[(&str, log::LevelFilter)] = &[
+    ("hyper", log::LevelFilter::Info),
+    ("h2", log::LevelFilter::Error),
+    ("tower", log::LevelFilter::Warn),
+    ("rustls", log::LevelFilter::Info),
+    ("wal", log::LevelFilter::Warn),
+    ("raft", log::LevelFilter::Warn),
+];
+
+/// Output of the logger, which can be reconfigured at runtime
+pub type Logger<S> = filter::Filtered<
+    Option<Box<dyn tracing_subscriber::Layer<S> + Send + Sync>>,
+    filter::EnvFilter,
+    S,
+>;
+
+pub fn setup(mut config: LoggerConfig) -> anyhow::Result<LoggerHandle> {
+    tracing_log::LogTracer::init()?;
+
+    redact::set_fields(config.redact.as_deref().unwrap_or_default());
+
+    let default_logger = default::new_logger(&config.default);
+    let (default_logger, default_logger_handle) = reload::Layer::new(default_logger);
+    let reg = tracing_subscriber::registry().with(default_logger);
+
+    let on_disk_logger = on_disk::new_logger(&mut config.on_disk);
+    let (on_disk_logger, on_disk_logger_handle) = reload::Layer::new(on_disk_logger);
+    let reg = reg.with(on_disk_logger);
+
+    let access_logger = access_log::new_logger(&mut config.access_log);
+    let (access_logger, access_logger_handle) = reload::Layer::new(access_logger);
+    let reg = reg.with(access_logger);
+
+    let slow_query_logger = slow_query_log::new_logger(&mut config.slow_query_log);
+    let (slow_query_logger, slow_query_logger_handle) = reload::Layer::new(slow_query_logger);
+    let reg = reg.with(slow_query_logger);
+
+    let tail_buffer = tail::TailBuffer::new();
+    let tail_logger = tail::new_logger(&config.tail, &tail_buffer);
+    let (tail_logger, tail_logger_handle) = reload::Layer::new(tail_logger);
+    let reg = reg.with(tail_logger);
+
+    let reg = reg.with(span_timings::new_layer());
+
+    let rate_limit = rate_limit::RateLimitLayer::new(&config.rate_limit);
+    let reg = reg.with(rate_limit.clone());
+
+    // Use `console` or `console-subscriber` feature to enable `console-subscriber`
+    //
+    // Note, that `console-subscriber` requires manually enabling
+    // `--cfg tokio_unstable` rust flags during compilation!
+    //
+    // Otherwise `console_subscriber::spawn` call panics!
+    //
+    // See https://docs.rs/tokio/latest/tokio/#unstable-features
+    #[cfg(all(feature = "console-subscriber", tokio_unstable))]
+    let reg = reg.with(console_subscriber::spawn());
+
+    #[cfg(all(feature = "console-subscriber", not(tokio_unstable)))]
+    eprintln!(
+        "`console-subscriber` requires manually enabling \
+         `--cfg tokio_unstable` rust flags during compilation!"
+    );
+
+    // Use `tracy` or `tracing-tracy` feature to enable `tracing-tracy`
+    #[cfg(feature = "tracing-tracy")]
+    let reg = reg.with(tracing_tracy::TracyLayer::new().with_filter(
+        tracing_subscriber::filter::filter_fn(|metadata| metadata.is_span()),
+    ));
+
+    tracing::subscriber::set_global_default(reg)?;
+
+    let logger_handle = LoggerHandle::new(
+        config,
+        default_logger_handle,
+        on_disk_logger_handle,
+        access_logger_handle,
+        slow_query_logger_handle,
+        tail_logger_handle,
+        tail_buffer,
+        rate_limit,
+    );
+
+    Ok(logger_handle)
+}
+
+/// Build filter of a logger output from user directives, on top of the default ones
+///
+/// `filters` are applied after `log_level`, so an output can log a single module more verbosely.
+/// Targets of directives ending with `*`, e.g. `collection::*=trace`, match any target starting
+/// with the rest. With `regex`, field values of directives are matched as regular expressions,
+/// e.g. `[request{collection=logs_.*}]=debug`.
+///
+/// Access log and slow query log events are always excluded, they only go to their own outputs.
+pub fn filter(log_level: &str, user_filters: &[String], regex: bool) -> filter::EnvFilter {
+    let mut filters = DEFAULT_LOG_LEVEL.to_string();
+
+    let user_log_level = log_level
+        .rsplit(',')
+        .find_map(|dir| log::LevelFilter::from_str(dir).ok());
+
+    for (target, log_level) in DEFAULT_FILTERS.iter().copied() {
+        if user_log_level.unwrap_or(DEFAULT_LOG_LEVEL) > log_level {
+            write!(&mut filters, ",{target}={log_level}").unwrap(); // Writing into `String` never fails
+        }
+    }
+
+    let user_filters = log_level
+        .split(',')
+        .chain(user_filters.iter().flat_map(|filters| filters.split(',')))
+        .filter(|directive| !directive.is_empty());
+    for directive in user_filters {
+        write!(&mut filters, ",{}", strip_target_glob(directive)).unwrap(); // Writing into `String` never fails
+    }
+
+    write!(&mut filters, ",{}=off", access_log::ACCESS_LOG_TARGET).unwrap(); // Writing into `String` never fails
+    write!(
+        &mut filters,
+        ",{}=off",
+        slow_query_log::SLOW_QUERY_LOG_TARGET
+    )
+    .unwrap(); // Writing into `String` never fails
+
+    filter::EnvFilter::builder()
+        .with_regex(regex)
+        .parse_lossy(filters)
+}
+
+/// Directive with `*` removed from the end of its target, targets are matched by prefix anyway
+fn strip_target_glob(directive: &str) -> Cow<'_, str> {
+    let target_end = directive.find(['[', '=']).unwrap_or(directive.len());
+    let (target, rest) = directive.split_at(target_end);
+    match target.strip_suffix('*') {
+        Some(target) => Cow::Owned(format!("{target}{rest}")),
+        None => Cow::Borrowed(directive),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_target_glob() {
+        assert_eq!(
+            strip_target_glob("collection::*=trace"),
+            "collection::=trace"
+        );
+        assert_eq!(
+            strip_target_glob("collection*[search]=debug"),
+            "collection[search]=debug"
+        );
+        assert_eq!(
+            strip_target_glob("[request{path=/.*}]=debug"),
+            "[request{path=/.*}]=debug"
+        );
+        assert_eq!(strip_target_glob("debug"), "debug");
+    }
+}
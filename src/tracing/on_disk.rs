@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use std::sync;
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, fmt, registry};
+
+use super::config::{merge_option, Format, SpanEvent};
+use super::json::JsonLayer;
+use super::redact::RedactingWriter;
+use super::sink::{self, Compression, FallbackWriter, Retention, Rotation};
+use super::Logger;
+
+const SINK_NAME: &str = "on_disk";
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Directives on top of `log_level`, e.g. `collection::*=trace`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Vec<String>>,
+    /// Match field values of the directives as regular expressions, disabled by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_regex: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span_events: Option<HashSet<SpanEvent>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Format>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Rotation>,
+    /// Number of rotated log files to keep, all are kept if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+    /// Total size of rotated log files to keep in bytes, unlimited if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_total_size: Option<u64>,
+    /// Compression of rotated log files, `none` by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<Compression>,
+}
+
+impl Config {
+    pub fn merge(&mut self, diff: Self) {
+        let Self {
+            enabled,
+            log_file,
+            log_level,
+            filters,
+            filter_regex,
+            span_events,
+            format,
+            rotation,
+            max_files,
+            max_total_size,
+            compression,
+        } = diff;
+
+        merge_option(&mut self.enabled, enabled);
+        merge_option(&mut self.log_file, log_file);
+        merge_option(&mut self.log_level, log_level);
+        merge_option(&mut self.filters, filters);
+        merge_option(&mut self.filter_regex, filter_regex);
+        merge_option(&mut self.span_events, span_events);
+        merge_option(&mut self.format, format);
+        merge_option(&mut self.rotation, rotation);
+        merge_option(&mut self.max_files, max_files);
+        merge_option(&mut self.max_total_size, max_total_size);
+        merge_option(&mut self.compression, compression);
+    }
+}
+
+pub fn new_logger<S>(config: &mut Config) -> Logger<S>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    let layer = match new_layer(config) {
+        Ok(layer) => layer,
+        Err(err) => {
+            eprintln!(
+                "failed to enable logging into {} log file: {err}",
+                config.log_file.as_deref().unwrap_or_default(),
+            );
+
+            config.enabled = Some(false);
+            None
+        }
+    };
+
+    layer.with_filter(new_filter(config))
+}
+
+pub fn new_layer<S>(
+    config: &Config,
+) -> anyhow::Result<Option<Box<dyn tracing_subscriber::Layer<S> + Send + Sync>>>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    if !config.enabled.unwrap_or_default() {
+        sink::unregister(SINK_NAME);
+        return Ok(None);
+    }
+
+    let Some(log_file) = &config.log_file else {
+        anyhow::bail!("log file is not specified");
+    };
+
+    let retention = Retention {
+        max_files: config.max_files,
+        max_total_size: config.max_total_size,
+        compression: config.compression.unwrap_or_default(),
+    };
+    let writer = FallbackWriter::open(SINK_NAME, log_file)
+        .with_context(|| format!("failed to open {log_file} log file"))?
+        .with_rotation(config.rotation.unwrap_or_default(), retention);
+
+    if config.format.unwrap_or_default() == Format::Json {
+        let writer = RedactingWriter::new(sync::Mutex::new(writer));
+        return Ok(Some(JsonLayer::new(writer).boxed()));
+    }
+
+    let layer = fmt::Layer::default()
+        .with_writer(RedactingWriter::new(sync::Mutex::new(writer)))
+        .with_span_events(SpanEvent::unwrap_or_default_config(&config.span_events))
+        .with_ansi(false);
+
+    Ok(Some(layer.boxed()))
+}
+
+pub fn new_filter(config: &Config) -> filter::EnvFilter {
+    super::filter(
+        config.log_level.as_deref().unwrap_or(""),
+        config.filters.as_deref().unwrap_or_default(),
+        config.filter_regex.unwrap_or_default(),
+    )
+}
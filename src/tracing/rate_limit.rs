@@ -0,0 +1,205 @@
+//! Rate limiting of log lines per target.
+//!
+//! Applies to all outputs but the access and slow query logs. Lines of a target above the limit
+//! within a second are dropped, and the number of dropped lines is logged once the target logs
+//! again in a later second. Keeps a hot loop logging warnings from flooding CloudWatch.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing_log::NormalizeEvent as _;
+use tracing_subscriber::layer;
+
+use super::access_log::ACCESS_LOG_TARGET;
+use super::config::merge_option;
+use super::slow_query_log::SLOW_QUERY_LOG_TARGET;
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Target of the summaries of dropped lines, never limited
+const SUMMARY_TARGET: &str = module_path!();
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Maximum number of lines per second of each target, unlimited if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_lines_per_sec: Option<u32>,
+}
+
+impl Config {
+    pub fn merge(&mut self, diff: Self) {
+        let Self { max_lines_per_sec } = diff;
+
+        merge_option(&mut self.max_lines_per_sec, max_lines_per_sec);
+    }
+}
+
+/// Drops events above the limit for all outputs, shared with the logger handle so the limit
+/// can be changed at runtime
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    inner: Arc<RateLimiter>,
+}
+
+struct RateLimiter {
+    /// `0` if unlimited
+    max_lines_per_sec: AtomicU32,
+    targets: Mutex<HashMap<String, TargetWindow>>,
+    /// Summaries of dropped lines, logged along with the next line passing the limit
+    summaries: Mutex<Vec<Summary>>,
+}
+
+struct Summary {
+    target: String,
+    dropped: u64,
+    limit: u32,
+}
+
+struct TargetWindow {
+    started: Instant,
+    lines: u32,
+    dropped: u64,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: &Config) -> Self {
+        let layer = Self {
+            inner: Arc::new(RateLimiter {
+                max_lines_per_sec: AtomicU32::new(0),
+                targets: Mutex::new(HashMap::new()),
+                summaries: Mutex::new(Vec::new()),
+            }),
+        };
+        layer.update(config);
+        layer
+    }
+
+    pub fn update(&self, config: &Config) {
+        self.inner
+            .max_lines_per_sec
+            .store(config.max_lines_per_sec.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Count a line of `target`, returns whether it is within the limit, and the number of lines
+    /// dropped within the previous window if it just ended
+    fn record(&self, target: &str, limit: u32, now: Instant) -> (bool, u64) {
+        let mut targets = match self.inner.targets.lock() {
+            Ok(targets) => targets,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // Don't allocate the key for every line
+        if !targets.contains_key(target) {
+            let window = TargetWindow {
+                started: now,
+                lines: 0,
+                dropped: 0,
+            };
+            targets.insert(target.to_string(), window);
+        }
+        let window = targets.get_mut(target).unwrap(); // Inserted above
+
+        let mut dropped = 0;
+        if now.duration_since(window.started) >= WINDOW {
+            dropped = std::mem::take(&mut window.dropped);
+            window.started = now;
+            window.lines = 0;
+        }
+
+        if window.lines >= limit {
+            window.dropped += 1;
+            return (false, dropped);
+        }
+        window.lines += 1;
+        (true, dropped)
+    }
+
+    fn summaries(&self) -> MutexGuard<'_, Vec<Summary>> {
+        match self.inner.summaries.lock() {
+            Ok(summaries) => summaries,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for RateLimitLayer {
+    fn event_enabled(&self, event: &tracing::Event<'_>, _ctx: layer::Context<'_, S>) -> bool {
+        let limit = self.inner.max_lines_per_sec.load(Ordering::Relaxed);
+        if limit == 0 {
+            return true;
+        }
+
+        // Events of the `log` crate carry their target in the `log.target` field
+        let normalized_metadata = event.normalized_metadata();
+        let metadata = normalized_metadata
+            .as_ref()
+            .unwrap_or_else(|| event.metadata());
+        let target = metadata.target();
+        if target == ACCESS_LOG_TARGET
+            || target == SLOW_QUERY_LOG_TARGET
+            || target == SUMMARY_TARGET
+        {
+            return true;
+        }
+
+        let (enabled, dropped) = self.record(target, limit, Instant::now());
+        if dropped > 0 {
+            // Logging from the filter would dispatch an event while the filters of this one are
+            // still evaluated, the summary is logged once this one is
+            self.summaries().push(Summary {
+                target: target.to_string(),
+                dropped,
+                limit,
+            });
+        }
+        enabled
+    }
+
+    fn on_event(&self, _event: &tracing::Event<'_>, _ctx: layer::Context<'_, S>) {
+        if self.inner.max_lines_per_sec.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+
+        // Not holding the lock, the summaries pass through this layer as well
+        let summaries = std::mem::take(&mut *self.summaries());
+        for Summary {
+            target,
+            dropped,
+            limit,
+        } in summaries
+        {
+            tracing::warn!(
+                target: SUMMARY_TARGET,
+                "Dropped {dropped} log lines of {target}, above the limit of {limit} lines per second",
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit() {
+        let layer = RateLimitLayer::new(&Config {
+            max_lines_per_sec: Some(2),
+        });
+        let started = Instant::now();
+
+        assert_eq!(layer.record("a", 2, started), (true, 0));
+        assert_eq!(layer.record("a", 2, started), (true, 0));
+        assert_eq!(layer.record("a", 2, started), (false, 0));
+        assert_eq!(layer.record("a", 2, started), (false, 0));
+        // Other targets are limited separately
+        assert_eq!(layer.record("b", 2, started), (true, 0));
+
+        // Dropped lines are reported once the target logs in the next window
+        let next_window = started + WINDOW;
+        assert_eq!(layer.record("a", 2, next_window), (true, 2));
+        assert_eq!(layer.record("a", 2, next_window), (true, 0));
+    }
+}
@@ -0,0 +1,350 @@
+//! Redaction of sensitive data in log output.
+//!
+//! Lines written by the default and on-disk loggers, and records of the log tail, are scanned
+//! for:
+//! - values of fields named in `logger.redact`, as `name=value` fields of events, `"name": value`
+//!   entries of JSON objects and payloads, and values of filter conditions with `key: "name"`
+//! - configured API keys, anywhere in the line
+//!
+//! Found values are replaced with `"***"`, so debug logging can be enabled in production without
+//! leaking user data.
+
+use std::borrow::Cow;
+use std::io;
+use std::sync::RwLock;
+
+use tracing_subscriber::fmt::MakeWriter;
+
+pub const MASK: &str = "***";
+
+static REDACTOR: RwLock<Redactor> = RwLock::new(Redactor {
+    fields: Vec::new(),
+    secrets: Vec::new(),
+});
+
+struct Redactor {
+    fields: Vec<String>,
+    secrets: Vec<String>,
+}
+
+/// Set names of the fields to redact, from `logger.redact`
+pub fn set_fields(fields: &[String]) {
+    write_redactor().fields = fields
+        .iter()
+        .filter(|field| !field.is_empty())
+        .cloned()
+        .collect();
+}
+
+/// Set secrets, e.g. API keys, which must never be logged
+pub fn set_secrets(secrets: impl IntoIterator<Item = String>) {
+    write_redactor().secrets = secrets
+        .into_iter()
+        .filter(|secret| !secret.is_empty())
+        .collect();
+}
+
+fn write_redactor() -> std::sync::RwLockWriteGuard<'static, Redactor> {
+    match REDACTOR.write() {
+        Ok(redactor) => redactor,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Whether values of the field are redacted as a whole
+pub fn is_redacted_field(name: &str) -> bool {
+    let redactor = match REDACTOR.read() {
+        Ok(redactor) => redactor,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    redactor.fields.iter().any(|field| field == name)
+}
+
+/// Mask sensitive values in `line`
+pub fn redact(line: &str) -> Cow<'_, str> {
+    let redactor = match REDACTOR.read() {
+        Ok(redactor) => redactor,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if redactor.fields.is_empty() && redactor.secrets.is_empty() {
+        return Cow::Borrowed(line);
+    }
+
+    let mut line = Cow::Borrowed(line);
+    for secret in &redactor.secrets {
+        if line.contains(secret.as_str()) {
+            line = Cow::Owned(line.replace(secret.as_str(), MASK));
+        }
+    }
+    for field in &redactor.fields {
+        if line.contains(field.as_str()) {
+            line = Cow::Owned(redact_field(&line, field));
+        }
+    }
+    line
+}
+
+fn redact_field(line: &str, field: &str) -> String {
+    let mut redacted = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(found) = rest.find(field) {
+        let (before, after) = rest.split_at(found);
+        let after_name = &after[field.len()..];
+        redacted.push_str(before);
+        redacted.push_str(field);
+        rest = after_name;
+
+        let escaped = before.ends_with("\\\"");
+        let quote = if escaped { "\\\"" } else { "\"" };
+        let quoted = before.ends_with(quote);
+        let preceded_by_word = trim_ansi_end(before)
+            .chars()
+            .next_back()
+            .map_or(false, |c| c.is_alphanumeric() || c == '_' || c == '.');
+
+        if quoted {
+            let Some(after_quote) = after_name.strip_prefix(quote) else {
+                continue;
+            };
+
+            if let Some(value) = after_quote.strip_prefix(':') {
+                // `"name": value` of JSON objects and payloads
+                let value = value.trim_start_matches(' ');
+                redacted.push_str(&after_name[..after_name.len() - value.len()]);
+                redacted.push_str(quote);
+                redacted.push_str(MASK);
+                redacted.push_str(quote);
+                rest = &value[value_end(value, escaped)..];
+            } else if before[..before.len() - quote.len()].ends_with("key: ") {
+                // `key: "name"` of a filter condition, mask all values of the condition
+                let end = block_end(after_quote, escaped);
+                redacted.push_str(quote);
+                redacted.push_str(&mask_literals(&after_quote[..end], escaped));
+                rest = &after_quote[end..];
+            }
+        } else if !preceded_by_word {
+            // `name=value` fields of events, `=` may be colored
+            let Some(value) = skip_ansi(after_name).strip_prefix('=') else {
+                continue;
+            };
+            let value = skip_ansi(value);
+            redacted.push_str(&after_name[..after_name.len() - value.len()]);
+            redacted.push_str("\"***\"");
+            rest = &value[value_end(value, false)..];
+        }
+    }
+
+    redacted.push_str(rest);
+    redacted
+}
+
+/// `text` without trailing ANSI escape sequences of colored output
+fn trim_ansi_end(mut text: &str) -> &str {
+    while let Some(start) = text.rfind("\x1b[") {
+        let sequence = &text[start + 2..];
+        let is_sequence = sequence.strip_suffix('m').map_or(false, |codes| {
+            codes.bytes().all(|b| b.is_ascii_digit() || b == b';')
+        });
+        if !is_sequence {
+            break;
+        }
+        text = &text[..start];
+    }
+    text
+}
+
+/// Skip ANSI escape sequences of colored output
+fn skip_ansi(mut text: &str) -> &str {
+    while let Some(sequence) = text.strip_prefix("\x1b[") {
+        match sequence.find('m') {
+            Some(end) => text = &sequence[end + 1..],
+            None => break,
+        }
+    }
+    text
+}
+
+/// End of the value at the start of `text`: a quoted string, an object, an array, a tuple
+/// variant like `String("value")`, or anything up to a delimiter
+fn value_end(text: &str, escaped: bool) -> usize {
+    let quote = if escaped { "\\\"" } else { "\"" };
+    if let Some(string) = text.strip_prefix(quote) {
+        return quote.len() + quoted_end(string, quote);
+    }
+
+    let bytes = text.as_bytes();
+    let ident_end = bytes
+        .iter()
+        .position(|b| !(b.is_ascii_alphanumeric() || *b == b'_'))
+        .unwrap_or(bytes.len());
+
+    match bytes.get(ident_end) {
+        Some(b'{' | b'[' | b'(') => ident_end + block_end(&text[ident_end + 1..], escaped) + 2,
+        _ if ident_end > 0 || bytes.first() == Some(&b'-') => bytes
+            .iter()
+            .position(|b| matches!(b, b',' | b'}' | b']' | b')' | b' ' | b'\x1b' | b'\n' | b'"'))
+            .unwrap_or(bytes.len()),
+        _ => 0,
+    }
+    .min(text.len())
+}
+
+/// Length of a quoted string without the opening quote, including the closing one
+fn quoted_end(string: &str, quote: &str) -> usize {
+    let mut offset = 0;
+    while let Some(found) = string[offset..].find(quote) {
+        let end = offset + found;
+        // An escaped quote within the string
+        if quote == "\"" && string[..end].ends_with('\\') && !string[..end].ends_with("\\\\") {
+            offset = end + 1;
+            continue;
+        }
+        return end + quote.len();
+    }
+    string.len()
+}
+
+/// Position of the bracket closing the block `text` is in, or the end of the text
+fn block_end(text: &str, escaped: bool) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    for (i, b) in bytes.iter().enumerate() {
+        let after_backslash = i > 0 && bytes[i - 1] == b'\\';
+        match b {
+            // Quotes within escaped JSON strings are all preceded by a backslash
+            b'"' if after_backslash == escaped => in_string = !in_string,
+            b'{' | b'[' | b'(' if !in_string => depth += 1,
+            b'}' | b']' | b')' if !in_string => {
+                if depth == 0 {
+                    return i;
+                }
+                depth -= 1;
+            }
+            _ => (),
+        }
+    }
+    text.len()
+}
+
+/// Replace contents of quoted strings and numbers in `text`
+fn mask_literals(text: &str, escaped: bool) -> String {
+    let quote = if escaped { "\\\"" } else { "\"" };
+    let mut masked = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(string) = rest.strip_prefix(quote) {
+            let end = quoted_end(string, quote);
+            masked.push_str(quote);
+            masked.push_str(MASK);
+            masked.push_str(quote);
+            rest = &string[end..];
+        } else if c.is_ascii_digit() && !masked.ends_with(|c: char| c.is_alphanumeric() || c == '_')
+        {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == 'e'))
+                .unwrap_or(rest.len());
+            masked.push_str(MASK);
+            rest = &rest[end..];
+        } else {
+            masked.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    masked
+}
+
+/// Writer redacting everything written through it, see [`redact`].
+///
+/// Loggers write each event with a single call, so values are never split between writes.
+pub struct RedactingWriter<M> {
+    make_writer: M,
+}
+
+impl<M> RedactingWriter<M> {
+    pub fn new(make_writer: M) -> Self {
+        Self { make_writer }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingWriter<M> {
+    type Writer = Redacting<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        Redacting(self.make_writer.make_writer())
+    }
+
+    fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> Self::Writer {
+        Redacting(self.make_writer.make_writer_for(meta))
+    }
+}
+
+pub struct Redacting<W>(W);
+
+impl<W: io::Write> io::Write for Redacting<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match std::str::from_utf8(buf).map(redact) {
+            Ok(Cow::Owned(line)) => self.0.write_all(line.as_bytes())?,
+            _ => self.0.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_field() {
+        let field = "email";
+
+        assert_eq!(
+            redact_field("upsert email=a@b.c id=1", field),
+            "upsert email=\"***\" id=1",
+        );
+        assert_eq!(
+            redact_field("upsert email=\"a b\" id=1", field),
+            "upsert email=\"***\" id=1",
+        );
+        // Other fields containing the name are kept
+        assert_eq!(redact_field("user_email=a@b.c", field), "user_email=a@b.c");
+        assert_eq!(
+            redact_field(r#"{"email":"a@b.c","id":1}"#, field),
+            r#"{"email":"***","id":1}"#,
+        );
+        assert_eq!(
+            redact_field(
+                r#"Payload({"email": String("a@b.c"), "id": Number(1)})"#,
+                field
+            ),
+            r#"Payload({"email": "***", "id": Number(1)})"#,
+        );
+        assert_eq!(
+            redact_field(
+                r#"{"message":"payload {\"email\": String(\"a@b.c\")}"}"#,
+                field
+            ),
+            r#"{"message":"payload {\"email\": \"***\"}"}"#,
+        );
+        assert_eq!(
+            redact_field(
+                r#"FieldCondition { key: "email", match: Some(Value(MatchValue { value: Keyword("a@b.c") })), range: None }, FieldCondition { key: "id" }"#,
+                field,
+            ),
+            r#"FieldCondition { key: "email", match: Some(Value(MatchValue { value: Keyword("***") })), range: None }, FieldCondition { key: "id" }"#,
+        );
+        // Colored output
+        assert_eq!(
+            redact_field("\x1b[3memail\x1b[0m\x1b[2m=\x1b[0ma@b.c", field),
+            "\x1b[3memail\x1b[0m\x1b[2m=\x1b[0m\"***\"",
+        );
+    }
+}
@@ -0,0 +1,551 @@
+//! Health and rotation of the log files.
+//!
+//! Log file may become unwritable while the service is running, e.g. when EFS is unmounted or
+//! runs out of space. Logs are written to stdout instead, and the file is re-opened periodically
+//! until writes succeed again. Health of every log file is reported in `/livez` and telemetry.
+//!
+//! Log files may be rotated by time or size, see [`Rotation`]. The current file is renamed with
+//! the time of the rotation as suffix, e.g. `qdrant.log.20240131-235959-000123`, and a new file
+//! is started. Rotated files may be compressed in a background thread, and the oldest ones are
+//! removed above the configured number or total size of files, see [`Retention`].
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{fs, io, thread};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::common::clock;
+
+const REOPEN_INTERVAL: Duration = Duration::from_secs(30);
+
+static SINKS: Mutex<Vec<Arc<Sink>>> = Mutex::new(Vec::new());
+
+/// Held while rotated files are compressed and removed, so background threads don't race
+static RETENTION_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct LogSinkHealth {
+    /// Logger output the file belongs to, e.g. `on_disk` or `access_log`
+    pub name: String,
+    pub log_file: String,
+    /// Whether the last write into the file has succeeded
+    pub healthy: bool,
+    /// Time of the first failed write, if the file is not healthy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub failing_since: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Writes redirected to stdout since the start of the service
+    pub fallback_writes: u64,
+}
+
+/// When to rotate a log file
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    #[default]
+    Never,
+    /// On the first write of every hour, in UTC
+    Hourly,
+    /// On the first write of every day, in UTC
+    Daily,
+    /// Before the file grows larger than this number of bytes
+    Size(u64),
+}
+
+impl Rotation {
+    /// Period of `time` for time based rotation, the file is rotated once it changes
+    fn period(self, time: DateTime<Utc>) -> i64 {
+        match self {
+            Rotation::Never | Rotation::Size(_) => 0,
+            Rotation::Hourly => time.timestamp().div_euclid(60 * 60),
+            Rotation::Daily => time.timestamp().div_euclid(24 * 60 * 60),
+        }
+    }
+}
+
+/// Compression of rotated log files
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Extension appended to names of the compressed files
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gz"),
+            Compression::Zstd => Some("zst"),
+        }
+    }
+
+    /// Whether the rotated file is compressed already, by any method
+    fn is_compressed(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| extension == "gz" || extension == "zst")
+    }
+}
+
+/// Which rotated files to keep, and how
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Retention {
+    /// Number of rotated files to keep, all are kept if not set
+    pub max_files: Option<usize>,
+    /// Total size of rotated files to keep in bytes, unlimited if not set
+    pub max_total_size: Option<u64>,
+    pub compression: Compression,
+}
+
+struct Sink {
+    name: &'static str,
+    log_file: String,
+    state: Mutex<SinkState>,
+}
+
+#[derive(Default)]
+struct SinkState {
+    failing_since: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    fallback_writes: u64,
+}
+
+/// Health of every log file in use
+pub fn health() -> Vec<LogSinkHealth> {
+    SINKS
+        .lock()
+        .iter()
+        .map(|sink| {
+            let state = sink.state.lock();
+            LogSinkHealth {
+                name: sink.name.to_string(),
+                log_file: sink.log_file.clone(),
+                healthy: state.failing_since.is_none(),
+                failing_since: state.failing_since,
+                last_error: state.last_error.clone(),
+                fallback_writes: state.fallback_writes,
+            }
+        })
+        .collect()
+}
+
+/// Stop reporting health of the `name` output, when it doesn't write into a file anymore
+pub fn unregister(name: &str) {
+    SINKS.lock().retain(|sink| sink.name != name);
+}
+
+/// Writer into a log file, which falls back to stdout while the file is unwritable
+pub struct FallbackWriter {
+    sink: Arc<Sink>,
+    file: Option<fs::File>,
+    reopen_at: Instant,
+    rotation: Rotation,
+    retention: Retention,
+    /// Size of the current file
+    size: u64,
+    /// Rotation period the current file was last written in
+    period: i64,
+}
+
+impl FallbackWriter {
+    /// Open `log_file` for the `name` output, replacing the previous file of the output
+    pub fn open(name: &'static str, log_file: &str) -> io::Result<Self> {
+        let (file, size, modified) = open(log_file)?;
+
+        let sink = Arc::new(Sink {
+            name,
+            log_file: log_file.to_string(),
+            state: Default::default(),
+        });
+
+        let mut sinks = SINKS.lock();
+        sinks.retain(|sink| sink.name != name);
+        sinks.push(sink.clone());
+
+        Ok(Self {
+            sink,
+            file: Some(file),
+            reopen_at: Instant::now(),
+            rotation: Rotation::Never,
+            retention: Retention::default(),
+            size,
+            period: Rotation::Never.period(modified),
+        })
+    }
+
+    /// Rotate the log file by `rotation`, and keep rotated files by `retention`
+    pub fn with_rotation(mut self, rotation: Rotation, retention: Retention) -> Self {
+        self.rotation = rotation;
+        self.retention = retention;
+        // File left by a previous run is rotated on the first write of a new period
+        if let Some(file) = &self.file {
+            let modified = file
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .map_or_else(|_| clock::now(), DateTime::from);
+            self.period = rotation.period(modified);
+        }
+        self
+    }
+
+    fn fail(&mut self, err: io::Error) {
+        let mut state = self.sink.state.lock();
+        if state.failing_since.is_none() {
+            state.failing_since = Some(clock::now());
+            eprintln!(
+                "failed to write into {} log file, logging to stdout: {err}",
+                self.sink.log_file,
+            );
+        }
+        state.last_error = Some(err.to_string());
+
+        self.file = None;
+        self.reopen_at = Instant::now() + REOPEN_INTERVAL;
+    }
+
+    fn reopen_if_due(&mut self) {
+        if self.file.is_some() || Instant::now() < self.reopen_at {
+            return;
+        }
+
+        match open(&self.sink.log_file) {
+            Ok((file, size, _)) => {
+                self.file = Some(file);
+                self.size = size;
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    fn rotate_if_due(&mut self, write_size: usize) {
+        if self.file.is_none() {
+            return;
+        }
+
+        let now = clock::now();
+        let due = match self.rotation {
+            Rotation::Never => false,
+            Rotation::Hourly | Rotation::Daily => self.rotation.period(now) != self.period,
+            Rotation::Size(max_size) => self.size > 0 && self.size + write_size as u64 > max_size,
+        };
+        if !due {
+            return;
+        }
+
+        if let Err(err) = self.rotate(now) {
+            self.fail(err);
+        }
+    }
+
+    fn rotate(&mut self, now: DateTime<Utc>) -> io::Result<()> {
+        let log_file = &self.sink.log_file;
+        let mut rotated_file = format!("{log_file}.{}", now.format("%Y%m%d-%H%M%S-%6f"));
+        // Never overwrite a file rotated at the same time
+        let mut attempt = 0;
+        while Path::new(&rotated_file).exists() {
+            attempt += 1;
+            rotated_file = format!("{log_file}.{}.{attempt}", now.format("%Y%m%d-%H%M%S-%6f"));
+        }
+
+        self.file = None;
+        fs::rename(log_file, rotated_file)?;
+        let (file, size, _) = open(log_file)?;
+        self.file = Some(file);
+        self.size = size;
+        self.period = self.rotation.period(now);
+
+        if self.retention.compression == Compression::None {
+            retain_rotated_files(Path::new(log_file), self.retention)?;
+            return Ok(());
+        }
+
+        // Compression takes a while, don't block the thread which is logging
+        let log_file = PathBuf::from(log_file);
+        let retention = self.retention;
+        thread::Builder::new()
+            .name("log-compression".to_string())
+            .spawn(move || {
+                if let Err(err) = retain_rotated_files(&log_file, retention) {
+                    eprintln!(
+                        "failed to compress rotated {} log files: {err}",
+                        log_file.display(),
+                    );
+                }
+            })?;
+        Ok(())
+    }
+}
+
+impl io::Write for FallbackWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.reopen_if_due();
+        self.rotate_if_due(buf.len());
+
+        if let Some(file) = &mut self.file {
+            match file.write_all(buf) {
+                Ok(()) => {
+                    self.size += buf.len() as u64;
+                    let mut state = self.sink.state.lock();
+                    if state.failing_since.take().is_some() {
+                        eprintln!("writing into {} log file again", self.sink.log_file);
+                    }
+                    return Ok(buf.len());
+                }
+                Err(err) => self.fail(err),
+            }
+        }
+
+        self.sink.state.lock().fallback_writes += 1;
+        io::stdout().write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => io::stdout().flush(),
+        }
+    }
+}
+
+/// Open `log_file` for appending, with its size and modification time
+fn open(log_file: &str) -> io::Result<(fs::File, u64, DateTime<Utc>)> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file)?;
+    let metadata = file.metadata()?;
+    let modified = metadata
+        .modified()
+        .map_or_else(|_| clock::now(), DateTime::from);
+    Ok((file, metadata.len(), modified))
+}
+
+/// Compress rotated files of `log_file`, and remove the oldest ones above the limits of
+/// `retention`
+fn retain_rotated_files(log_file: &Path, retention: Retention) -> io::Result<()> {
+    let _guard = RETENTION_LOCK.lock();
+
+    let mut rotated_files = rotated_files(log_file)?;
+
+    // Files left uncompressed, e.g. by a crash, are compressed as well
+    if retention.compression != Compression::None {
+        for rotated_file in &mut rotated_files {
+            if !Compression::is_compressed(rotated_file) {
+                *rotated_file = compress(rotated_file, retention.compression)?;
+            }
+        }
+        // A file may have been compressed partially before, and is listed twice now
+        rotated_files.sort();
+        rotated_files.dedup();
+    }
+
+    let mut files = 0;
+    let mut total_size = 0;
+    for rotated_file in rotated_files.iter().rev() {
+        files += 1;
+        total_size += fs::metadata(rotated_file)?.len();
+
+        let above_max_files = retention.max_files.is_some_and(|max| files > max);
+        let above_max_size = retention.max_total_size.is_some_and(|max| total_size > max);
+        if above_max_files || above_max_size {
+            fs::remove_file(rotated_file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Compress `path` into a file with the extension of `compression`, which replaces it
+fn compress(path: &Path, compression: Compression) -> io::Result<PathBuf> {
+    let Some(extension) = compression.extension() else {
+        return Ok(path.to_path_buf());
+    };
+
+    let mut compressed_path = path.as_os_str().to_owned();
+    compressed_path.push(".");
+    compressed_path.push(extension);
+    let compressed_path = PathBuf::from(compressed_path);
+
+    let mut input = fs::File::open(path)?;
+    let output = fs::File::create(&compressed_path)?;
+
+    let output = match compression {
+        Compression::None => unreachable!(), // No extension
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::Encoder::new(output, 0)?;
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?
+        }
+    };
+    // Never remove the original before the compressed file is persisted
+    output.sync_all()?;
+    fs::remove_file(path)?;
+
+    Ok(compressed_path)
+}
+
+/// Rotated files of `log_file`, from the oldest
+fn rotated_files(log_file: &Path) -> io::Result<Vec<PathBuf>> {
+    let (Some(dir), Some(name)) = (log_file.parent(), log_file.file_name()) else {
+        return Ok(Vec::new());
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let prefix = format!("{}.", name.to_string_lossy());
+
+    // Suffixes start with the time of the rotation, so names sort from the oldest
+    let mut rotated_files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix(&prefix))
+                .is_some_and(|suffix| suffix.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .map(|entry| entry.path())
+        .collect();
+    rotated_files.sort();
+    Ok(rotated_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::*;
+
+    #[test]
+    fn test_fallback_writer_reopens_log_file() {
+        let dir = tempfile::Builder::new().tempdir().unwrap();
+        let log_file = dir.path().join("qdrant.log");
+        let log_file = log_file.to_str().unwrap();
+
+        let mut writer = FallbackWriter::open("test_fallback", log_file).unwrap();
+        writer.write_all(b"first\n").unwrap();
+
+        writer.fail(io::Error::other("unmounted"));
+        writer.write_all(b"fallback\n").unwrap();
+
+        let sink_health = || {
+            health()
+                .into_iter()
+                .find(|sink| sink.name == "test_fallback")
+                .unwrap()
+        };
+
+        let unhealthy = sink_health();
+        assert!(!unhealthy.healthy);
+        assert_eq!(unhealthy.last_error.as_deref(), Some("unmounted"));
+        assert_eq!(unhealthy.fallback_writes, 1);
+
+        writer.reopen_at = Instant::now();
+        writer.write_all(b"second\n").unwrap();
+
+        assert!(sink_health().healthy);
+        assert_eq!(fs::read_to_string(log_file).unwrap(), "first\nsecond\n");
+
+        unregister("test_fallback");
+    }
+
+    #[test]
+    fn test_rotate_log_file_by_size() {
+        let dir = tempfile::Builder::new().tempdir().unwrap();
+        let log_file = dir.path().join("qdrant.log");
+        let log_file = log_file.to_str().unwrap();
+
+        let mut writer = FallbackWriter::open("test_rotation", log_file)
+            .unwrap()
+            .with_rotation(
+                Rotation::Size(10),
+                Retention {
+                    max_files: Some(2),
+                    ..Default::default()
+                },
+            );
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+
+        let mut rotated: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_str() != Some(log_file))
+            .collect();
+        rotated.sort();
+
+        // Oldest rotated file is removed
+        assert_eq!(rotated.len(), 2);
+        assert_eq!(fs::read_to_string(&rotated[0]).unwrap(), "second\n");
+        assert_eq!(fs::read_to_string(&rotated[1]).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(log_file).unwrap(), "fourth\n");
+
+        unregister("test_rotation");
+    }
+
+    #[test]
+    fn test_retain_rotated_log_files() {
+        let dir = tempfile::Builder::new().tempdir().unwrap();
+        let log_file = dir.path().join("qdrant.log");
+        for (suffix, content) in [
+            ("", "current\n"),
+            (".20240101-000000-000000", "oldest\n"),
+            (".20240102-000000-000000", "older\n"),
+            (".20240103-000000-000000", "newest\n"),
+        ] {
+            fs::write(format!("{}{suffix}", log_file.display()), content).unwrap();
+        }
+
+        let retention = Retention {
+            max_files: Some(2),
+            max_total_size: None,
+            compression: Compression::Gzip,
+        };
+        retain_rotated_files(&log_file, retention).unwrap();
+
+        let rotated = rotated_files(&log_file).unwrap();
+        assert_eq!(rotated.len(), 2);
+        let decompressed: Vec<_> = rotated
+            .iter()
+            .map(|path| {
+                assert_eq!(path.extension().unwrap(), "gz");
+                let mut decoder = flate2::read::GzDecoder::new(fs::File::open(path).unwrap());
+                let mut content = String::new();
+                io::Read::read_to_string(&mut decoder, &mut content).unwrap();
+                content
+            })
+            .collect();
+        assert_eq!(decompressed, ["older\n", "newest\n"]);
+        // Current file is never touched
+        assert_eq!(fs::read_to_string(&log_file).unwrap(), "current\n");
+
+        // Only the newest file fits into the total size
+        let newest_size = fs::metadata(&rotated[1]).unwrap().len();
+        let retention = Retention {
+            max_files: None,
+            max_total_size: Some(newest_size),
+            compression: Compression::Zstd,
+        };
+        retain_rotated_files(&log_file, retention).unwrap();
+        assert_eq!(rotated_files(&log_file).unwrap(), &rotated[1..]);
+    }
+}
@@ -0,0 +1,223 @@
+//! Log of slow searches and updates.
+//!
+//! Operations taking longer than the threshold are reported as events with
+//! [`SLOW_QUERY_LOG_TARGET`] target, with the collection, timing, limit and a summary of the
+//! filter. These events are excluded from the other outputs, and written as JSON lines into a
+//! separate file regardless of the log level.
+
+use std::sync;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use segment::types::{Condition, Filter};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, registry};
+
+use super::config::merge_option;
+use super::json::JsonLayer;
+use super::sink::{self, FallbackWriter};
+use super::Logger;
+
+pub const SLOW_QUERY_LOG_TARGET: &str = "slow_queries";
+
+const SINK_NAME: &str = "slow_query_log";
+
+const DEFAULT_LOG_FILE: &str = "./slow_queries.log";
+
+const DEFAULT_THRESHOLD_MS: u64 = 1000;
+
+/// Threshold of the enabled log in milliseconds, `0` if disabled
+static THRESHOLD_MS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Defaults to `./slow_queries.log`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    /// Operations taking at least this long are logged, defaults to 1000 ms
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold_ms: Option<u64>,
+}
+
+impl Config {
+    pub fn merge(&mut self, diff: Self) {
+        let Self {
+            enabled,
+            log_file,
+            threshold_ms,
+        } = diff;
+
+        merge_option(&mut self.enabled, enabled);
+        merge_option(&mut self.log_file, log_file);
+        merge_option(&mut self.threshold_ms, threshold_ms);
+    }
+}
+
+/// Search or update of a collection, reported if it took longer than the threshold
+#[derive(Debug)]
+pub struct SlowQueryEntry<'a> {
+    pub operation: &'static str,
+    pub collection: &'a str,
+    pub duration: Duration,
+    /// Number of requests in a batch
+    pub requests: usize,
+    /// Largest limit of the requests
+    pub limit: Option<usize>,
+    /// Summaries of distinct filters of the requests, see [`filter_summary`]
+    pub filters: Vec<String>,
+}
+
+impl SlowQueryEntry<'_> {
+    pub fn log(&self) {
+        let filters = (!self.filters.is_empty()).then(|| self.filters.join(" | "));
+        tracing::warn!(
+            target: SLOW_QUERY_LOG_TARGET,
+            operation = self.operation,
+            collection = self.collection,
+            duration_ms = self.duration.as_millis() as u64,
+            requests = self.requests,
+            limit = self.limit,
+            filter = filters,
+        );
+    }
+}
+
+/// Whether the log is enabled, check before collecting details of an operation
+pub fn is_enabled() -> bool {
+    THRESHOLD_MS.load(Ordering::Relaxed) > 0
+}
+
+/// Whether an operation that took `duration` should be reported, check before building the entry
+pub fn is_slow(duration: Duration) -> bool {
+    let threshold_ms = THRESHOLD_MS.load(Ordering::Relaxed);
+    threshold_ms > 0 && duration.as_millis() >= u128::from(threshold_ms)
+}
+
+/// Keys the conditions of `filter` are on, without values, e.g. `must(city, price) should(tags)`
+pub fn filter_summary(filter: &Filter) -> String {
+    let Filter {
+        should,
+        must,
+        must_not,
+    } = filter;
+
+    [("must", must), ("should", should), ("must_not", must_not)]
+        .into_iter()
+        .filter_map(|(clause, conditions)| {
+            let conditions = conditions.as_ref()?;
+            let conditions: Vec<_> = conditions.iter().map(condition_summary).collect();
+            Some(format!("{clause}({})", conditions.join(", ")))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn condition_summary(condition: &Condition) -> String {
+    match condition {
+        Condition::Field(condition) => condition.key.clone(),
+        Condition::IsEmpty(condition) => format!("is_empty({})", condition.is_empty.key),
+        Condition::IsNull(condition) => format!("is_null({})", condition.is_null.key),
+        Condition::HasId(condition) => format!("has_id[{}]", condition.has_id.len()),
+        Condition::Nested(condition) => format!(
+            "nested({}: {})",
+            condition.nested.key,
+            filter_summary(&condition.nested.filter),
+        ),
+        Condition::Filter(filter) => format!("[{}]", filter_summary(filter)),
+    }
+}
+
+pub fn new_logger<S>(config: &mut Config) -> Logger<S>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    let layer = match new_layer(config) {
+        Ok(layer) => layer,
+        Err(err) => {
+            eprintln!(
+                "failed to enable slow query log into {} log file: {err}",
+                config.log_file.as_deref().unwrap_or(DEFAULT_LOG_FILE),
+            );
+
+            config.enabled = Some(false);
+            None
+        }
+    };
+
+    layer.with_filter(new_filter(config))
+}
+
+pub fn new_layer<S>(
+    config: &Config,
+) -> anyhow::Result<Option<Box<dyn tracing_subscriber::Layer<S> + Send + Sync>>>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    if !config.enabled.unwrap_or_default() {
+        THRESHOLD_MS.store(0, Ordering::Relaxed);
+        sink::unregister(SINK_NAME);
+        return Ok(None);
+    }
+
+    let log_file = config.log_file.as_deref().unwrap_or(DEFAULT_LOG_FILE);
+    let writer = FallbackWriter::open(SINK_NAME, log_file)
+        .with_context(|| format!("failed to open {log_file} log file"))?;
+
+    let threshold_ms = config.threshold_ms.unwrap_or(DEFAULT_THRESHOLD_MS);
+    // Zero would disable the log
+    THRESHOLD_MS.store(threshold_ms.max(1), Ordering::Relaxed);
+
+    Ok(Some(JsonLayer::new(sync::Mutex::new(writer)).boxed()))
+}
+
+pub fn new_filter(config: &Config) -> filter::EnvFilter {
+    let directives = if config.enabled.unwrap_or_default() {
+        format!("off,{SLOW_QUERY_LOG_TARGET}=trace")
+    } else {
+        "off".to_string()
+    };
+
+    filter::EnvFilter::builder()
+        .with_regex(false)
+        .parse_lossy(directives)
+}
+
+#[cfg(test)]
+mod tests {
+    use segment::types::{FieldCondition, HasIdCondition, IsEmptyCondition, PayloadField};
+
+    use super::*;
+
+    #[test]
+    fn test_filter_summary() {
+        let is_empty = Condition::IsEmpty(IsEmptyCondition {
+            is_empty: PayloadField {
+                key: "name".to_string(),
+            },
+        });
+        let filter = Filter {
+            must: Some(vec![
+                Condition::Field(FieldCondition::new_match(
+                    "city",
+                    "Berlin".to_string().into(),
+                )),
+                Condition::new_nested("tags", Filter::new_must(is_empty)),
+            ]),
+            should: None,
+            must_not: Some(vec![Condition::HasId(HasIdCondition::from(
+                Default::default(),
+            ))]),
+        };
+
+        // Values are never included
+        assert_eq!(
+            filter_summary(&filter),
+            "must(city, nested(tags: must(is_empty(name)))) must_not(has_id[0])",
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Latency histograms of key spans.
+//!
+//! Durations of the spans listed in [`TIMED_SPANS`] are recorded when they close, and exported
+//! in telemetry and as `span_duration_seconds` histograms in metrics. This gives a breakdown of
+//! request latency without an external APM. Libraries emit these spans only when built with the
+//! `tracing` feature.
+
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, layer, registry};
+
+/// Names of the timed spans
+pub const TIMED_SPANS: &[&str] = &[
+    // Search in a single segment
+    "segment_search",
+    // Update operation from the WAL applied to segments
+    "wal_apply",
+];
+
+/// Upper bounds of the histogram buckets in seconds
+const BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static HISTOGRAMS: Mutex<Vec<Histogram>> = Mutex::new(Vec::new());
+
+struct Histogram {
+    span: &'static str,
+    /// Number of spans within each bucket, not cumulative
+    counts: [u64; BUCKETS.len()],
+    count: u64,
+    sum: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct SpanTimings {
+    pub span: String,
+    pub count: u64,
+    /// Total duration of the spans in seconds
+    pub sum_sec: f64,
+    pub buckets: Vec<SpanTimingsBucket>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct SpanTimingsBucket {
+    /// Upper bound of the bucket in seconds
+    pub le: f64,
+    /// Number of spans which took `le` seconds or less
+    pub count: u64,
+}
+
+/// Timings of every span closed since the start of the service
+pub fn timings() -> Vec<SpanTimings> {
+    HISTOGRAMS
+        .lock()
+        .iter()
+        .map(|histogram| {
+            let buckets = BUCKETS
+                .iter()
+                .zip(&histogram.counts)
+                .scan(0, |cumulative, (&le, &count)| {
+                    *cumulative += count;
+                    Some(SpanTimingsBucket {
+                        le,
+                        count: *cumulative,
+                    })
+                })
+                .collect();
+
+            SpanTimings {
+                span: histogram.span.to_string(),
+                count: histogram.count,
+                sum_sec: histogram.sum.as_secs_f64(),
+                buckets,
+            }
+        })
+        .collect()
+}
+
+fn record(span: &'static str, duration: Duration) {
+    let mut histograms = HISTOGRAMS.lock();
+    let index = match histograms
+        .iter()
+        .position(|histogram| histogram.span == span)
+    {
+        Some(index) => index,
+        None => {
+            histograms.push(Histogram {
+                span,
+                counts: [0; BUCKETS.len()],
+                count: 0,
+                sum: Duration::ZERO,
+            });
+            histograms.len() - 1
+        }
+    };
+    let histogram = &mut histograms[index];
+
+    let seconds = duration.as_secs_f64();
+    if let Some(bucket) = BUCKETS.iter().position(|&le| seconds <= le) {
+        histogram.counts[bucket] += 1;
+    }
+    histogram.count += 1;
+    histogram.sum += duration;
+}
+
+/// Time the span was created at, kept in its extensions
+struct Started(Instant);
+
+struct SpanTimingsLayer;
+
+impl<S> tracing_subscriber::Layer<S> for SpanTimingsLayer
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    fn on_new_span(
+        &self,
+        _attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        ctx: layer::Context<'_, S>,
+    ) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Started(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: tracing::span::Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let started = span.extensions().get::<Started>().map(|started| started.0);
+        if let Some(started) = started {
+            record(span.name(), started.elapsed());
+        }
+    }
+}
+
+/// Layer recording durations of [`TIMED_SPANS`], all other spans and events are ignored
+pub fn new_layer<S>() -> impl tracing_subscriber::Layer<S>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    // Timed spans are all at debug level or above, don't enable anything more verbose
+    let filter =
+        filter::filter_fn(|metadata| metadata.is_span() && TIMED_SPANS.contains(&metadata.name()))
+            .with_max_level_hint(filter::LevelFilter::DEBUG);
+
+    SpanTimingsLayer.with_filter(filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_timings() {
+        let subscriber = tracing_subscriber::registry().with(new_layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug_span!("segment_search").in_scope(|| ());
+            tracing::debug_span!("segment_search").in_scope(|| ());
+            tracing::debug_span!("not_timed").in_scope(|| ());
+        });
+        record("wal_apply", Duration::from_millis(30));
+        record("wal_apply", Duration::from_secs(60));
+
+        let timings = timings();
+        let timings = |span: &str| timings.iter().find(|timings| timings.span == span).cloned();
+
+        assert_eq!(timings("segment_search").unwrap().count, 2);
+        assert!(timings("not_timed").is_none());
+
+        let wal_apply = timings("wal_apply").unwrap();
+        assert_eq!(wal_apply.count, 2);
+        assert_eq!(wal_apply.sum_sec, 60.03);
+        let count_up_to = |le: f64| {
+            let bucket = wal_apply.buckets.iter().find(|bucket| bucket.le == le);
+            bucket.unwrap().count
+        };
+        assert_eq!(count_up_to(0.025), 0);
+        assert_eq!(count_up_to(0.05), 1);
+        // Longer than the last bucket, only counted in total
+        assert_eq!(count_up_to(10.0), 1);
+    }
+}
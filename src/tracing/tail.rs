@@ -0,0 +1,271 @@
+//! In-memory buffer of the latest log records.
+//!
+//! Keeps the last `capacity` records in a ring buffer, which can be read with `GET /logs/tail`.
+//! There is no shell on a Lambda to read the log files from, so this is the quickest way to see
+//! what a running instance logged recently.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, TryLockError};
+
+use api::grpc::request_id;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing_log::NormalizeEvent as _;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, layer, registry};
+
+use super::access_log::FieldsVisitor;
+use super::config::merge_option;
+use super::{redact, Logger};
+
+const DEFAULT_CAPACITY: usize = 1000;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Enabled by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Directives on top of `log_level`, e.g. `collection::*=trace`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Vec<String>>,
+    /// Match field values of the directives as regular expressions, disabled by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_regex: Option<bool>,
+    /// Number of the latest records to keep, defaults to 1000
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<usize>,
+}
+
+impl Config {
+    pub fn merge(&mut self, diff: Self) {
+        let Self {
+            enabled,
+            log_level,
+            filters,
+            filter_regex,
+            capacity,
+        } = diff;
+
+        merge_option(&mut self.enabled, enabled);
+        merge_option(&mut self.log_level, log_level);
+        merge_option(&mut self.filters, filters);
+        merge_option(&mut self.filter_regex, filter_regex);
+        merge_option(&mut self.capacity, capacity);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LogRecord {
+    pub timestamp: String,
+    #[serde(serialize_with = "serialize_level")]
+    pub level: tracing::Level,
+    pub target: String,
+    pub message: String,
+    /// Other fields of the event
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+fn serialize_level<S: serde::Serializer>(
+    level: &tracing::Level,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(level)
+}
+
+/// Ring buffer of the latest records, shared between the layer and the logger handle, so the
+/// records survive reloads of the layer
+pub struct TailBuffer {
+    records: Mutex<VecDeque<LogRecord>>,
+}
+
+impl TailBuffer {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            records: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    fn records(&self) -> std::sync::MutexGuard<'_, VecDeque<LogRecord>> {
+        match self.records.lock() {
+            Ok(records) => records,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn push(&self, record: LogRecord, capacity: usize) {
+        let mut records = self.records();
+        while records.len() >= capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Drop the oldest records above `capacity`
+    fn truncate(&self, capacity: usize) {
+        let mut records = self.records();
+        let excess = records.len().saturating_sub(capacity);
+        records.drain(..excess);
+    }
+
+    /// Last `lines` records, `None` if the buffer is locked at the moment, e.g. by a panicking
+    /// thread which was logging
+    pub fn try_tail(&self, lines: usize) -> Option<Vec<LogRecord>> {
+        let records = match self.records.try_lock() {
+            Ok(records) => records,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        let skip = records.len().saturating_sub(lines);
+        Some(records.iter().skip(skip).cloned().collect())
+    }
+
+    /// Last `lines` records at `level` or more severe, from the oldest one
+    pub fn tail(&self, lines: usize, level: Option<tracing::Level>) -> Vec<LogRecord> {
+        let records = self.records();
+        let mut tail: Vec<_> = records
+            .iter()
+            .rev()
+            .filter(|record| level.map_or(true, |level| record.level <= level))
+            .take(lines)
+            .cloned()
+            .collect();
+        tail.reverse();
+        tail
+    }
+}
+
+struct TailLayer {
+    buffer: Arc<TailBuffer>,
+    capacity: usize,
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for TailLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: layer::Context<'_, S>) {
+        // Events of the `log` crate carry their metadata in `log.*` fields
+        let normalized_metadata = event.normalized_metadata();
+        let metadata = normalized_metadata
+            .as_ref()
+            .unwrap_or_else(|| event.metadata());
+
+        let mut visitor = FieldsVisitor::default();
+        event.record(&mut visitor);
+
+        let mut message = String::new();
+        let mut fields = Map::new();
+        for (key, value) in visitor.0 {
+            match (key, value) {
+                ("message", Value::String(value)) => message = redact::redact(&value).into_owned(),
+                (key, _) if key.starts_with("log.") => (),
+                (key, _) if redact::is_redacted_field(key) => {
+                    fields.insert(key.to_string(), Value::from(redact::MASK));
+                }
+                (key, Value::String(value)) => {
+                    fields.insert(key.to_string(), Value::from(redact::redact(&value)));
+                }
+                (key, value) => {
+                    fields.insert(key.to_string(), value);
+                }
+            }
+        }
+
+        let record = LogRecord {
+            timestamp: crate::common::clock::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message,
+            fields,
+            request_id: request_id::current(),
+        };
+
+        self.buffer.push(record, self.capacity);
+    }
+}
+
+pub fn new_logger<S>(config: &Config, buffer: &Arc<TailBuffer>) -> Logger<S>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    new_layer(config, buffer).with_filter(new_filter(config))
+}
+
+pub fn new_layer<S>(
+    config: &Config,
+    buffer: &Arc<TailBuffer>,
+) -> Option<Box<dyn tracing_subscriber::Layer<S> + Send + Sync>>
+where
+    S: tracing::Subscriber + for<'span> registry::LookupSpan<'span>,
+{
+    if !config.is_enabled() {
+        buffer.truncate(0);
+        return None;
+    }
+
+    let capacity = config.capacity.unwrap_or(DEFAULT_CAPACITY).max(1);
+    buffer.truncate(capacity);
+
+    let layer = TailLayer {
+        buffer: buffer.clone(),
+        capacity,
+    };
+
+    Some(layer.boxed())
+}
+
+pub fn new_filter(config: &Config) -> filter::EnvFilter {
+    super::filter(
+        config.log_level.as_deref().unwrap_or(""),
+        config.filters.as_deref().unwrap_or_default(),
+        config.filter_regex.unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: tracing::Level, message: &str) -> LogRecord {
+        LogRecord {
+            timestamp: String::new(),
+            level,
+            target: "test".to_string(),
+            message: message.to_string(),
+            fields: Map::new(),
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_tail_buffer() {
+        let buffer = TailBuffer::new();
+        buffer.push(record(tracing::Level::INFO, "a"), 3);
+        buffer.push(record(tracing::Level::WARN, "b"), 3);
+        buffer.push(record(tracing::Level::DEBUG, "c"), 3);
+        buffer.push(record(tracing::Level::ERROR, "d"), 3);
+
+        let messages = |records: Vec<LogRecord>| -> Vec<_> {
+            records.into_iter().map(|record| record.message).collect()
+        };
+
+        // The oldest record is dropped above the capacity
+        assert_eq!(messages(buffer.tail(10, None)), ["b", "c", "d"]);
+        assert_eq!(messages(buffer.tail(2, None)), ["c", "d"]);
+        assert_eq!(
+            messages(buffer.tail(10, Some(tracing::Level::WARN))),
+            ["b", "d"],
+        );
+
+        buffer.truncate(1);
+        assert_eq!(messages(buffer.tail(10, None)), ["d"]);
+    }
+}